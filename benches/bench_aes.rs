@@ -1,8 +1,11 @@
 use criterion::{black_box, criterion_group, criterion_main, Criterion, BenchmarkId};
 use rand::Rng;
-use tfhe::core_crypto::prelude::*;
+use tfhe::core_crypto::{
+    prelude::*,
+    commons::math::random::Seed,
+};
 use patching_wwlp::{
-    automorphism::gen_all_auto_keys, byte_array_to_mat, generate_scheme_switching_key, get_he_state_error, he_add_round_key, he_mix_columns, he_shift_rows, he_sub_bytes_by_patched_wwlp_cbs, keygen_pbs_with_glwe_ds, keyswitch_lwe_ciphertext_by_glwe_keyswitch, Aes128Ref, aes_instances::*, BLOCKSIZE_IN_BIT, BLOCKSIZE_IN_BYTE, BYTESIZE, NUM_ROUNDS
+    byte_array_to_mat, decrypt_aes_state, encrypt_aes_round_keys, get_he_state_error, he_add_round_key, he_mix_columns, he_shift_rows, he_sub_bytes_by_patched_wwlp_cbs, keyswitch_lwe_ciphertext_by_glwe_keyswitch, trivially_encrypt_aes_state, AesClientKey, AesKeySize, AesRef, HeAes, aes_instances::*, BLOCKSIZE_IN_BIT, BLOCKSIZE_IN_BYTE, NUM_ROUNDS
 };
 
 criterion_group!(
@@ -10,6 +13,7 @@ criterion_group!(
     config = Criterion::default().sample_size(1000);
     targets =
         criterion_benchmark_aes,
+        criterion_benchmark_aes_block,
 );
 criterion_main!(benches);
 
@@ -23,87 +27,27 @@ fn criterion_benchmark_aes(c: &mut Criterion) {
     ];
 
     for (param, id) in param_list.iter() {
-        let lwe_dimension = param.lwe_dimension();
-        let lwe_modular_std_dev = param.lwe_modular_std_dev();
-        let glwe_dimension = param.glwe_dimension();
-        let polynomial_size = param.polynomial_size();
-        let glwe_modular_std_dev = param.glwe_modular_std_dev();
-        let pbs_base_log = param.pbs_base_log();
-        let pbs_level = param.pbs_level();
-        let glwe_ds_base_log = param.glwe_ds_base_log();
-        let glwe_ds_level = param.glwe_ds_level();
-        let common_polynomial_size = param.common_polynomial_size();
-        let fft_type_ds = param.fft_type_ds();
-        let auto_base_log = param.auto_base_log();
-        let auto_level = param.auto_level();
-        let fft_type_auto = param.fft_type_auto();
-        let ss_base_log = param.ss_base_log();
-        let ss_level = param.ss_level();
-        let cbs_base_log = param.cbs_base_log();
-        let cbs_level = param.cbs_level();
-        let log_lut_count = param.log_lut_count();
-        let ciphertext_modulus = param.ciphertext_modulus();
-
-        // Set random generators and buffers
+        // Generate keys
         let mut boxed_seeder = new_seeder();
         let seeder = boxed_seeder.as_mut();
-
-        let mut secret_generator = SecretRandomGenerator::<ActivatedRandomGenerator>::new(seeder.seed());
         let mut encryption_generator = EncryptionRandomGenerator::<ActivatedRandomGenerator>::new(seeder.seed(), seeder);
 
-        // Generate keys
-        let (
-            lwe_sk,
-            glwe_sk,
-            lwe_sk_after_ks,
-            fourier_bsk,
-            fourier_ksk,
-        ) = keygen_pbs_with_glwe_ds(
-            lwe_dimension,
-            glwe_dimension,
-            polynomial_size,
-            lwe_modular_std_dev,
-            glwe_modular_std_dev,
-            pbs_base_log,
-            pbs_level,
-            glwe_ds_base_log,
-            glwe_ds_level,
-            common_polynomial_size,
-            fft_type_ds,
-            ciphertext_modulus,
-            &mut secret_generator,
-            &mut encryption_generator,
-        );
-        let fourier_bsk = fourier_bsk.as_view();
-
-        let ss_key = generate_scheme_switching_key(
-            &glwe_sk,
-            ss_base_log,
-            ss_level,
-            glwe_modular_std_dev,
-            ciphertext_modulus,
-            &mut encryption_generator,
-        );
-        let ss_key = ss_key.as_view();
-
-        let auto_keys = gen_all_auto_keys(
-            auto_base_log,
-            auto_level,
-            fft_type_auto,
-            &glwe_sk,
-            glwe_modular_std_dev,
-            &mut encryption_generator,
-        );
+        let mut rng = rand::thread_rng();
+        let (client_key, server_key) = AesClientKey::generate(param, Seed(rng.gen::<u128>()));
+        let lwe_sk = client_key.lwe_secret_key();
+        let fourier_bsk = server_key.fourier_bsk();
+        let fourier_ksk = server_key.glwe_ksk();
+        let auto_keys = server_key.auto_keys();
+        let ss_key = server_key.ss_key();
+        let cbs_acc = server_key.cbs_acc();
 
         // ======== Plain ========
-        let mut rng = rand::thread_rng();
         let mut key = [0u8; BLOCKSIZE_IN_BYTE];
         for i in 0..BLOCKSIZE_IN_BYTE {
             key[i] = rng.gen_range(0..=u8::MAX);
         }
 
-        let aes = Aes128Ref::new(&key);
-        let round_keys = aes.get_round_keys();
+        let aes = AesRef::new(&key);
 
         let mut message = [0u8; BLOCKSIZE_IN_BYTE];
         for i in 0..16 {
@@ -112,54 +56,27 @@ fn criterion_benchmark_aes(c: &mut Criterion) {
         let correct_output = byte_array_to_mat(aes.encrypt_block(message));
 
         // ======== HE ========
-        let mut he_round_keys = Vec::<LweCiphertextListOwned<u64>>::with_capacity(NUM_ROUNDS + 1);
-        for r in 0..=NUM_ROUNDS {
-            let mut lwe_list_rk = LweCiphertextList::new(
-                0u64,
-                fourier_bsk.output_lwe_dimension().to_lwe_size(),
-                LweCiphertextCount(BLOCKSIZE_IN_BIT),
-                ciphertext_modulus,
-            );
-
-            let rk = PlaintextList::from_container((0..BLOCKSIZE_IN_BIT).map(|i| {
-                let byte_idx = i / BYTESIZE;
-                let bit_idx = i % BYTESIZE;
-                let round_key_byte = round_keys[r][byte_idx];
-                let round_key_bit = (round_key_byte & (1 << bit_idx)) >> bit_idx;
-                (round_key_bit as u64) << 63
-            }).collect::<Vec<u64>>());
-            encrypt_lwe_ciphertext_list(
-                &lwe_sk,
-                &mut lwe_list_rk,
-                &rk,
-                glwe_modular_std_dev,
+        let he_aes = HeAes::new(
+            &server_key,
+            encrypt_aes_round_keys(
+                lwe_sk,
+                &key,
+                param.glwe_modular_std_dev(),
+                param.ciphertext_modulus(),
                 &mut encryption_generator,
-            );
-
-            he_round_keys.push(lwe_list_rk);
-        }
-
-        let mut he_state = LweCiphertextList::new(
-            0u64,
-            fourier_bsk.output_lwe_dimension().to_lwe_size(),
-            LweCiphertextCount(BLOCKSIZE_IN_BIT),
-            ciphertext_modulus,
+            ),
         );
+        let he_round_keys = he_aes.he_round_keys();
+
+        let mut he_state = trivially_encrypt_aes_state(message, lwe_sk.lwe_dimension().to_lwe_size(), param.ciphertext_modulus());
         let mut he_state_ks = LweCiphertextList::new(
             0u64,
-            lwe_sk_after_ks.lwe_dimension().to_lwe_size(),
+            client_key.lwe_secret_key_after_ks().lwe_dimension().to_lwe_size(),
             LweCiphertextCount(BLOCKSIZE_IN_BIT),
-            ciphertext_modulus,
+            param.ciphertext_modulus(),
         );
 
         // Bench
-        he_state.as_mut().fill(0u64);
-        for (bit_idx, mut he_bit) in he_state.iter_mut().enumerate() {
-            let byte_idx = bit_idx / 8;
-            let pt = (message[byte_idx] & (1 << bit_idx)) >> bit_idx;
-            *he_bit.get_mut_body().data += (pt as u64) << 63;
-        }
-
         // AddRoundKey
         group.bench_function(
             BenchmarkId::new(
@@ -186,7 +103,7 @@ fn criterion_benchmark_aes(c: &mut Criterion) {
                         keyswitch_lwe_ciphertext_by_glwe_keyswitch(
                             black_box(&lwe),
                             black_box(&mut lwe_ks),
-                            black_box(&fourier_ksk),
+                            black_box(fourier_ksk),
                         );
                     }
                 })
@@ -203,11 +120,9 @@ fn criterion_benchmark_aes(c: &mut Criterion) {
                         black_box(&he_state_ks),
                         black_box(&mut he_state),
                         black_box(fourier_bsk),
-                        black_box(&auto_keys),
+                        black_box(auto_keys),
                         black_box(ss_key),
-                        black_box(cbs_base_log),
-                        black_box(cbs_level),
-                        black_box(log_lut_count),
+                        black_box(cbs_acc),
                     );
                 })
             );
@@ -245,7 +160,7 @@ fn criterion_benchmark_aes(c: &mut Criterion) {
                     keyswitch_lwe_ciphertext_by_glwe_keyswitch(
                         black_box(&lwe),
                         black_box(&mut lwe_ks),
-                        black_box(&fourier_ksk),
+                        black_box(fourier_ksk),
                     );
                 }
             }));
@@ -261,11 +176,9 @@ fn criterion_benchmark_aes(c: &mut Criterion) {
                     black_box(&he_state_ks),
                     black_box(&mut he_state),
                     black_box(fourier_bsk),
-                    black_box(&auto_keys),
+                    black_box(auto_keys),
                     black_box(ss_key),
-                    black_box(cbs_base_log),
-                    black_box(cbs_level),
-                    black_box(log_lut_count),
+                    black_box(cbs_acc),
                 );
             })
         );
@@ -284,18 +197,72 @@ fn criterion_benchmark_aes(c: &mut Criterion) {
             })
         );
 
-        let (_, max_err) = get_he_state_error(&he_state, correct_output, &lwe_sk);
+        let (_, max_err) = get_he_state_error(&he_state, correct_output, lwe_sk);
 
         println!(
             "n: {}, N: {}, k: {}, l_pbs: {}, B_pbs: 2^{}, l_cbs: {}, B_cbs: 2^{}
 B_ds: 2^{}, l_ds: {},
 l_auto: {}, B_auto: 2^{}, l_ss: {}, B_ss: 2^{}, log_lut_count: {},
 max err: {:.2} bits",
-            lwe_dimension.0, polynomial_size.0, glwe_dimension.0, pbs_level.0, pbs_base_log.0, cbs_level.0, cbs_base_log.0,
-            glwe_ds_base_log.0, glwe_ds_level.0,
-            auto_level.0, auto_base_log.0, ss_level.0, ss_base_log.0, log_lut_count.0,
+            param.lwe_dimension().0, param.polynomial_size().0, param.glwe_dimension().0, param.pbs_level().0, param.pbs_base_log().0, param.cbs_level().0, param.cbs_base_log().0,
+            param.glwe_ds_base_log().0, param.glwe_ds_level().0,
+            param.auto_level().0, param.auto_base_log().0, param.ss_level().0, param.ss_base_log().0, param.log_lut_count().0,
             (max_err as f64).log2(),
         );
         println!();
     }
-}
\ No newline at end of file
+}
+
+fn criterion_benchmark_aes_block(c: &mut Criterion) {
+    let mut group = c.benchmark_group("aes block encryption by patched WWL+ circuit bootstrapping");
+    group.sample_size(10);
+
+    let param_list = [
+        (*AES_SET_1, AesKeySize::Aes128, "AES-128 set 1"),
+        (*AES_SET_2, AesKeySize::Aes128, "AES-128 set 2"),
+        (*AES_SET_3, AesKeySize::Aes128, "AES-128 set 3"),
+        (*AES256_SET_1, AesKeySize::Aes256, "AES-256 set 1"),
+        (*AES256_SET_2, AesKeySize::Aes256, "AES-256 set 2"),
+        (*AES256_SET_3, AesKeySize::Aes256, "AES-256 set 3"),
+    ];
+
+    for (param, key_size, id) in param_list.iter() {
+        let mut boxed_seeder = new_seeder();
+        let seeder = boxed_seeder.as_mut();
+        let mut encryption_generator = EncryptionRandomGenerator::<ActivatedRandomGenerator>::new(seeder.seed(), seeder);
+
+        let mut rng = rand::thread_rng();
+        let (client_key, server_key) = AesClientKey::generate(param, Seed(rng.gen::<u128>()));
+        let lwe_sk = client_key.lwe_secret_key();
+
+        let key: Vec<u8> = (0..key_size.key_len_in_byte()).map(|_| rng.gen_range(0..=u8::MAX)).collect();
+        let mut message = [0u8; BLOCKSIZE_IN_BYTE];
+        for i in 0..16 {
+            message[i] = rng.gen_range(0..=255);
+        }
+
+        let he_aes = HeAes::new(
+            &server_key,
+            encrypt_aes_round_keys(
+                lwe_sk,
+                &key,
+                param.glwe_modular_std_dev(),
+                param.ciphertext_modulus(),
+                &mut encryption_generator,
+            ),
+        );
+
+        group.bench_function(
+            BenchmarkId::new("Encrypt block", id),
+            |b| b.iter(|| {
+                let mut he_state = trivially_encrypt_aes_state(message, lwe_sk.lwe_dimension().to_lwe_size(), param.ciphertext_modulus());
+                he_aes.encrypt_block(black_box(&mut he_state));
+            })
+        );
+
+        let mut he_state = trivially_encrypt_aes_state(message, lwe_sk.lwe_dimension().to_lwe_size(), param.ciphertext_modulus());
+        he_aes.encrypt_block(&mut he_state);
+        let output = decrypt_aes_state(lwe_sk, &he_state);
+        println!("{id}: {}", if output == AesRef::new(&key).encrypt_block(message) {"correct"} else {"incorrect"});
+    }
+}
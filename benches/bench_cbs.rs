@@ -2,7 +2,7 @@ use criterion::{black_box, criterion_group, criterion_main, Criterion, Benchmark
 use dyn_stack::ReborrowMut;
 use tfhe::core_crypto::prelude::*;
 use tfhe::core_crypto::fft_impl::fft64::crypto::wop_pbs::{circuit_bootstrap_boolean_scratch, circuit_bootstrap_boolean};
-use patching_wwlp::{allocate_and_generate_new_glwe_keyswitch_key, convert_lwe_to_glwe_by_trace_with_preprocessing, convert_lwe_to_glwe_by_trace_with_preprocessing_high_prec, convert_standard_glwe_keyswitch_key_to_fourier, gen_all_auto_keys, generate_scheme_switching_key, get_max_err_ggsw_bit, keygen_pbs, lwe_msb_bit_refresh, lwe_msb_bit_to_lev, switch_scheme, FourierGlweKeyswitchKey, wwlp_cbs_instance::*};
+use patching_wwlp::{allocate_and_generate_new_glwe_keyswitch_key, convert_lwe_to_glwe_by_trace_with_preprocessing, convert_lwe_to_glwe_by_trace_with_preprocessing_high_prec, convert_standard_glwe_keyswitch_key_to_fourier, gen_all_auto_keys, generate_scheme_switching_key, get_max_err_ggsw_bit, keygen_pbs, keygen_pbs_from_param, lwe_msb_bit_refresh, lwe_msb_bit_to_lev, switch_scheme, CbsAccumulator, FourierGlweKeyswitchKey, FourierGlweKeyswitchKeyOwned, wwlp_cbs_instance::*, F128};
 
 criterion_group!(
     name = benches;
@@ -259,6 +259,7 @@ fn criterion_benchmark_cbs(c: &mut Criterion) {
         let mut fourier_ggsw = FourierGgswCiphertext::new(glwe_size, polynomial_size, cbs_base_log, cbs_level);
 
         // Bench
+        let cbs_acc = CbsAccumulator::new(polynomial_size, glwe_size, cbs_base_log, cbs_level, LutCountLog(0));
         group.bench_function(
             BenchmarkId::new(
                 "step 1",
@@ -274,9 +275,7 @@ fn criterion_benchmark_cbs(c: &mut Criterion) {
                     black_box(&lwe_ks),
                     black_box(&mut lev),
                     black_box(bsk),
-                    black_box(cbs_base_log),
-                    black_box(cbs_level),
-                    black_box(LutCountLog(0)),
+                    black_box(&cbs_acc),
                 );
             })
         );
@@ -368,19 +367,7 @@ fn criterion_benchmark_patched_wwlp_cbs(c: &mut Criterion) {
             lwe_sk_after_ks,
             bsk,
             ksk,
-        ) = keygen_pbs(
-            lwe_dimension,
-            glwe_dimension,
-            polynomial_size,
-            lwe_modular_std_dev,
-            glwe_modular_std_dev,
-            pbs_base_log,
-            pbs_level,
-            ks_base_log,
-            ks_level,
-            &mut secret_generator,
-            &mut encryption_generator,
-        );
+        ) = keygen_pbs_from_param(param, &mut secret_generator, &mut encryption_generator);
         let bsk = bsk.as_view();
 
         let ksk = allocate_and_generate_new_lwe_keyswitch_key(
@@ -480,6 +467,7 @@ fn criterion_benchmark_patched_wwlp_cbs(c: &mut Criterion) {
             })
         );
 
+        let cbs_acc = CbsAccumulator::new(polynomial_size, glwe_size, cbs_base_log, cbs_level, log_lut_count);
         group.bench_function(
             BenchmarkId::new(
                 "step 1-2",
@@ -495,9 +483,7 @@ fn criterion_benchmark_patched_wwlp_cbs(c: &mut Criterion) {
                     black_box(&lwe_ks),
                     black_box(&mut lev),
                     black_box(bsk),
-                    black_box(cbs_base_log),
-                    black_box(cbs_level),
-                    black_box(log_lut_count),
+                    black_box(&cbs_acc),
                 );
             })
         );
@@ -653,7 +639,7 @@ fn criterion_benchmark_patched_high_prec_wwlp_cbs(c: &mut Criterion) {
             ciphertext_modulus,
             &mut encryption_generator,
         );
-        let mut fourier_glwe_dsk_to_large = FourierGlweKeyswitchKey::new(
+        let mut fourier_glwe_dsk_to_large: FourierGlweKeyswitchKeyOwned<F128> = FourierGlweKeyswitchKey::new(
             glwe_size,
             large_glwe_size,
             polynomial_size,
@@ -672,7 +658,7 @@ fn criterion_benchmark_patched_high_prec_wwlp_cbs(c: &mut Criterion) {
             ciphertext_modulus,
             &mut encryption_generator,
         );
-        let mut fourier_glwe_dsk_from_large = FourierGlweKeyswitchKey::new(
+        let mut fourier_glwe_dsk_from_large: FourierGlweKeyswitchKeyOwned<F128> = FourierGlweKeyswitchKey::new(
             large_glwe_size,
             glwe_size,
             polynomial_size,
@@ -735,6 +721,7 @@ fn criterion_benchmark_patched_high_prec_wwlp_cbs(c: &mut Criterion) {
             })
         );
 
+        let cbs_acc = CbsAccumulator::new(polynomial_size, glwe_size, cbs_base_log, cbs_level, log_lut_count);
         group.bench_function(
             BenchmarkId::new(
                 "step 1-2",
@@ -750,9 +737,7 @@ fn criterion_benchmark_patched_high_prec_wwlp_cbs(c: &mut Criterion) {
                     black_box(&lwe_ks),
                     black_box(&mut lev),
                     black_box(bsk),
-                    black_box(cbs_base_log),
-                    black_box(cbs_level),
-                    black_box(log_lut_count),
+                    black_box(&cbs_acc),
                 );
             })
         );
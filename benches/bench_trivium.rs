@@ -0,0 +1,91 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion, BenchmarkId, Throughput};
+use rand::Rng;
+use tfhe::core_crypto::{
+    prelude::*,
+    commons::math::random::Seed,
+};
+use patching_wwlp::{
+    encrypt_aes_round_keys, encrypt_bytes, AesClientKey, HeAes, HeAesCtr, HeKreyvium, HeTrivium, aes_instances::*,
+    BLOCKSIZE_IN_BIT, BLOCKSIZE_IN_BYTE, KREYVIUM_IV_LEN_IN_BYTE, KREYVIUM_KEY_LEN_IN_BYTE, TRIVIUM_IV_LEN_IN_BYTE,
+    TRIVIUM_KEY_LEN_IN_BYTE, TRIVIUM_MAX_PARALLEL_STEPS,
+};
+
+criterion_group!(
+    name = benches;
+    config = Criterion::default().sample_size(10);
+    targets = criterion_benchmark_keystream,
+);
+criterion_main!(benches);
+
+/// Keystream throughput of Trivium and Kreyvium for a batch of parallel steps, against AES-128 in CTR mode.
+fn criterion_benchmark_keystream(c: &mut Criterion) {
+    let mut group = c.benchmark_group("keystream generation by patched WWL+ circuit bootstrapping");
+
+    let param_list = [
+        (*AES_SET_1, "set 1"),
+        (*AES_SET_2, "set 2"),
+        (*AES_SET_3, "set 3"),
+    ];
+
+    for (param, id) in param_list.iter() {
+        let mut boxed_seeder = new_seeder();
+        let seeder = boxed_seeder.as_mut();
+        let mut encryption_generator = EncryptionRandomGenerator::<ActivatedRandomGenerator>::new(seeder.seed(), seeder);
+
+        let mut rng = rand::thread_rng();
+        let (client_key, server_key) = AesClientKey::generate(param, Seed(rng.gen::<u128>()));
+        let lwe_sk = client_key.lwe_secret_key();
+
+        // Trivium
+        let key: Vec<u8> = (0..TRIVIUM_KEY_LEN_IN_BYTE).map(|_| rng.gen_range(0..=u8::MAX)).collect();
+        let iv: Vec<u8> = (0..TRIVIUM_IV_LEN_IN_BYTE).map(|_| rng.gen_range(0..=u8::MAX)).collect();
+        let he_key = encrypt_bytes(lwe_sk, &key, param.glwe_modular_std_dev(), param.ciphertext_modulus(), &mut encryption_generator);
+        let mut he_trivium = HeTrivium::new(&server_key, &he_key, &iv);
+
+        group.throughput(Throughput::Elements(TRIVIUM_MAX_PARALLEL_STEPS as u64));
+        group.bench_function(
+            BenchmarkId::new("Trivium keystream", id),
+            |b| b.iter(|| {
+                black_box(he_trivium.keystream(black_box(TRIVIUM_MAX_PARALLEL_STEPS)));
+            })
+        );
+
+        // Kreyvium
+        let key: Vec<u8> = (0..KREYVIUM_KEY_LEN_IN_BYTE).map(|_| rng.gen_range(0..=u8::MAX)).collect();
+        let iv: Vec<u8> = (0..KREYVIUM_IV_LEN_IN_BYTE).map(|_| rng.gen_range(0..=u8::MAX)).collect();
+        let he_key = encrypt_bytes(lwe_sk, &key, param.glwe_modular_std_dev(), param.ciphertext_modulus(), &mut encryption_generator);
+        let mut he_kreyvium = HeKreyvium::new(&server_key, &he_key, &iv);
+
+        group.bench_function(
+            BenchmarkId::new("Kreyvium keystream", id),
+            |b| b.iter(|| {
+                black_box(he_kreyvium.keystream(black_box(TRIVIUM_MAX_PARALLEL_STEPS)));
+            })
+        );
+
+        // AES-128 in CTR mode
+        let key: Vec<u8> = (0..BLOCKSIZE_IN_BYTE).map(|_| rng.gen_range(0..=u8::MAX)).collect();
+        let mut initial_counter = [0u8; BLOCKSIZE_IN_BYTE];
+        rng.fill(&mut initial_counter);
+        let he_aes_ctr = HeAesCtr::new(HeAes::new(
+            &server_key,
+            encrypt_aes_round_keys(
+                lwe_sk,
+                &key,
+                param.glwe_modular_std_dev(),
+                param.ciphertext_modulus(),
+                &mut encryption_generator,
+            ),
+        ));
+
+        group.throughput(Throughput::Elements(BLOCKSIZE_IN_BIT as u64));
+        let mut block_idx = 0;
+        group.bench_function(
+            BenchmarkId::new("AES-128 CTR keystream", id),
+            |b| b.iter(|| {
+                black_box(he_aes_ctr.keystream_block(initial_counter, black_box(block_idx)));
+                block_idx += 1;
+            })
+        );
+    }
+}
@@ -0,0 +1,82 @@
+//! AES-CTR transciphering: the client encrypts its data by AES-CTR and the server converts the AES
+//! ciphertext into LWE encryptions of the plaintext bits by evaluating the keystream homomorphically.
+use tfhe::core_crypto::prelude::*;
+use crate::{aes_he::*, aes_ref::*, cbs_keys::AesServerKey};
+
+/// Counter block of the keystream block `block_idx`, where the initial counter block is incremented
+/// as a 128-bit big-endian integer, as in SP 800-38A.
+pub fn aes_ctr_counter_block(initial_counter: StateByteArray, block_idx: usize) -> StateByteArray {
+    u128::from_be_bytes(initial_counter).wrapping_add(block_idx as u128).to_be_bytes()
+}
+
+/// Homomorphic AES-CTR keystream generation and transciphering.
+pub struct HeAesCtr<'a, Scalar: UnsignedTorus> {
+    he_aes: HeAes<'a, Scalar>,
+}
+
+impl<'a, Scalar> HeAesCtr<'a, Scalar>
+where
+    Scalar: UnsignedTorus + CastInto<usize> + CastFrom<usize>,
+{
+    pub fn new(he_aes: HeAes<'a, Scalar>) -> Self {
+        HeAesCtr {
+            he_aes,
+        }
+    }
+
+    /// The round keys are expanded from the master key given by [`encrypt_aes_master_key`].
+    pub fn from_he_master_key<Cont>(server_key: &'a AesServerKey<Scalar>, he_master_key: &LweCiphertextList<Cont>) -> Self
+    where
+        Cont: Container<Element=Scalar>,
+    {
+        Self::new(HeAes::from_he_master_key(server_key, he_master_key))
+    }
+
+    pub fn he_aes(&self) -> &HeAes<'a, Scalar> {
+        &self.he_aes
+    }
+
+    /// Encryption of the keystream block `block_idx`, i.e., of the AES encryption of its counter block.
+    pub fn keystream_block(&self, initial_counter: StateByteArray, block_idx: usize) -> LweCiphertextListOwned<Scalar> {
+        let mut he_block = trivially_encrypt_aes_state(
+            aes_ctr_counter_block(initial_counter, block_idx),
+            self.he_aes.server_key().fourier_bsk().output_lwe_dimension().to_lwe_size(),
+            self.he_aes.he_round_keys()[0].ciphertext_modulus(),
+        );
+        self.he_aes.encrypt_block(&mut he_block);
+
+        he_block
+    }
+
+    /// Converts an AES-CTR ciphertext of any length into the LWE encryptions of the plaintext bits,
+    /// where bit i of byte b is at index 8b + i, by adding the trivial encryptions of the ciphertext bits
+    /// to the keystream. The last keystream block is truncated.
+    pub fn transcipher(&self, initial_counter: StateByteArray, ciphertext: &[u8]) -> LweCiphertextListOwned<Scalar> {
+        let lwe_size = self.he_aes.server_key().fourier_bsk().output_lwe_dimension().to_lwe_size();
+        let ciphertext_modulus = self.he_aes.he_round_keys()[0].ciphertext_modulus();
+        let mut he_plaintext = LweCiphertextList::new(
+            Scalar::ZERO,
+            lwe_size,
+            LweCiphertextCount(BYTESIZE * ciphertext.len()),
+            ciphertext_modulus,
+        );
+
+        for (block_idx, (ciphertext_block, mut he_plaintext_block)) in ciphertext.chunks(BLOCKSIZE_IN_BYTE)
+            .zip(he_plaintext.chunks_mut(BLOCKSIZE_IN_BIT))
+            .enumerate()
+        {
+            let he_keystream = self.keystream_block(initial_counter, block_idx);
+            he_plaintext_block.as_mut().copy_from_slice(
+                he_keystream.get_sub(0..BYTESIZE * ciphertext_block.len()).as_ref()
+            );
+
+            for (bit_idx, mut he_bit) in he_plaintext_block.iter_mut().enumerate() {
+                if ciphertext_block[bit_idx / BYTESIZE] & (1 << (bit_idx % BYTESIZE)) != 0 {
+                    lwe_ciphertext_plaintext_add_assign(&mut he_bit, Plaintext(Scalar::ONE << (Scalar::BITS - 1)));
+                }
+            }
+        }
+
+        he_plaintext
+    }
+}
@@ -0,0 +1,267 @@
+//! Homomorphic GHASH and AES-GCM tag computation, so that the data transciphered by the CTR part of GCM
+//! is also authenticated under encryption.
+//!
+//! The product X * H in GF(2^128) is linear in X for a fixed H: it is the sum of x_k * (H x^k) over the
+//! coefficients x_k of X. The coefficients of every H x^k are computed once from refreshed encryptions of the
+//! bits of H and packed into a GLWE ciphertext, so that a multiplication costs one CBS per encrypted bit of X
+//! followed by an external product with the packed H x^k, and nothing for the public bits of X.
+use tfhe::core_crypto::prelude::*;
+use crate::{
+    aes_ctr::*,
+    aes_he::*,
+    aes_ref::*,
+    cbs_keys::AesServerKey,
+    glwe_conv::convert_lwes_to_glwe_by_trace_with_preprocessing,
+    pbs::lwe_msb_bit_refresh,
+    utils::lwe_ciphertext_list_add_assign,
+    keyswitch_lwe_ciphertext_by_glwe_keyswitch,
+};
+
+/// Homomorphic multiplication in GF(2^128) by a fixed encrypted element H, e.g. the GHASH subkey.
+///
+/// The blocks are encrypted bitwise where bit i of byte b is at index 8b + i, with the block order of [`gf128_mul`].
+pub struct HeGf128Mul<'a, Scalar: UnsignedTorus> {
+    server_key: &'a AesServerKey<Scalar>,
+    /// The k-th GLWE ciphertext encrypts the coefficient j of H x^k at the monomial X^(jN/128).
+    glwe_h_powers: GlweCiphertextListOwned<Scalar>,
+}
+
+impl<'a, Scalar> HeGf128Mul<'a, Scalar>
+where
+    Scalar: UnsignedTorus + CastInto<usize> + CastFrom<usize>,
+{
+    /// The bits of H are refreshed by PBS before the packing, so that the products do not depend on the noise of H.
+    pub fn new<Cont>(server_key: &'a AesServerKey<Scalar>, he_h: &LweCiphertextList<Cont>) -> Self
+    where
+        Cont: Container<Element=Scalar>,
+    {
+        assert_eq!(he_h.entity_count(), BLOCKSIZE_IN_BIT);
+
+        let fourier_bsk = server_key.fourier_bsk();
+        let lwe_size = fourier_bsk.output_lwe_dimension().to_lwe_size();
+        let glwe_size = fourier_bsk.glwe_size();
+        let polynomial_size = fourier_bsk.polynomial_size();
+        let ciphertext_modulus = he_h.ciphertext_modulus();
+        assert!(polynomial_size.0 >= BLOCKSIZE_IN_BIT);
+
+        let mut he_h_ks = new_lwe_ciphertext_list_after_ks(server_key, BLOCKSIZE_IN_BIT, ciphertext_modulus);
+        keyswitch_lwe_ciphertext_list(he_h, &mut he_h_ks, server_key);
+        let mut he_h_refreshed = LweCiphertextList::new(Scalar::ZERO, lwe_size, LweCiphertextCount(BLOCKSIZE_IN_BIT), ciphertext_modulus);
+        for (lwe_ks, mut lwe_refreshed) in he_h_ks.iter().zip(he_h_refreshed.iter_mut()) {
+            lwe_msb_bit_refresh(&lwe_ks, &mut lwe_refreshed, fourier_bsk);
+        }
+
+        // The coefficient j of H x^k is the sum of the coefficients l of H such that x^(l + k) has the coefficient j
+        let reduced_monomials = (0..(2 * BLOCKSIZE_IN_BIT - 1)).map(gf128_reduced_monomial).collect::<Vec<u128>>();
+        let mut glwe_h_powers = GlweCiphertextList::new(
+            Scalar::ZERO,
+            glwe_size,
+            polynomial_size,
+            GlweCiphertextCount(BLOCKSIZE_IN_BIT),
+            ciphertext_modulus,
+        );
+        let mut he_h_power = LweCiphertextList::new(Scalar::ZERO, lwe_size, LweCiphertextCount(BLOCKSIZE_IN_BIT), ciphertext_modulus);
+        for (k, mut glwe_h_power) in glwe_h_powers.iter_mut().enumerate() {
+            he_h_power.as_mut().fill(Scalar::ZERO);
+            for l in 0..BLOCKSIZE_IN_BIT {
+                let he_h_coeff = he_h_refreshed.get(gf128_coeff_to_bit_idx(l));
+                let reduced_monomial = reduced_monomials[l + k];
+                for (j, mut he_h_power_coeff) in he_h_power.iter_mut().enumerate() {
+                    if (reduced_monomial >> j) & 1 != 0 {
+                        lwe_ciphertext_add_assign(&mut he_h_power_coeff, &he_h_coeff);
+                    }
+                }
+            }
+
+            convert_lwes_to_glwe_by_trace_with_preprocessing(&he_h_power, &mut glwe_h_power, server_key.auto_keys());
+        }
+
+        HeGf128Mul {
+            server_key,
+            glwe_h_powers,
+        }
+    }
+
+    pub fn server_key(&self) -> &AesServerKey<Scalar> {
+        self.server_key
+    }
+
+    /// Encryption of X * H. The public bits of X, i.e. trivial encryptions, skip the CBS.
+    pub fn mul<Cont>(&self, he_x: &LweCiphertextList<Cont>) -> LweCiphertextListOwned<Scalar>
+    where
+        Cont: Container<Element=Scalar>,
+    {
+        assert_eq!(he_x.entity_count(), BLOCKSIZE_IN_BIT);
+
+        let fourier_bsk = self.server_key.fourier_bsk();
+        let lwe_size = fourier_bsk.output_lwe_dimension().to_lwe_size();
+        let glwe_size = fourier_bsk.glwe_size();
+        let polynomial_size = fourier_bsk.polynomial_size();
+        let ciphertext_modulus = he_x.ciphertext_modulus();
+
+        let public_coeffs = (0..BLOCKSIZE_IN_BIT)
+            .map(|k| decode_public_bit(&he_x.get(gf128_coeff_to_bit_idx(k))))
+            .collect::<Vec<_>>();
+        let encrypted_coeffs = public_coeffs.iter().enumerate()
+            .filter_map(|(k, public_coeff)| public_coeff.is_none().then_some(k))
+            .collect::<Vec<usize>>();
+
+        let mut he_x_ks = new_lwe_ciphertext_list_after_ks(self.server_key, encrypted_coeffs.len(), ciphertext_modulus);
+        for (mut lwe_ks, &k) in he_x_ks.iter_mut().zip(encrypted_coeffs.iter()) {
+            keyswitch_lwe_ciphertext_by_glwe_keyswitch(&he_x.get(gf128_coeff_to_bit_idx(k)), &mut lwe_ks, self.server_key.glwe_ksk());
+        }
+        let fourier_ggsw_x = circuit_bootstrap_bits(
+            &he_x_ks,
+            fourier_bsk,
+            self.server_key.auto_keys(),
+            self.server_key.ss_key(),
+            self.server_key.cbs_acc(),
+        );
+
+        let mut glwe_out = GlweCiphertext::new(Scalar::ZERO, glwe_size, polynomial_size, ciphertext_modulus);
+        for (k, public_coeff) in public_coeffs.iter().enumerate() {
+            if *public_coeff == Some(1) {
+                glwe_ciphertext_add_assign(&mut glwe_out, &self.glwe_h_powers.get(k));
+            }
+        }
+        for (&k, fourier_ggsw) in encrypted_coeffs.iter().zip(fourier_ggsw_x.as_view().into_ggsw_iter()) {
+            add_external_product_assign(&mut glwe_out, &fourier_ggsw, &self.glwe_h_powers.get(k));
+        }
+
+        let mut he_out = LweCiphertextList::new(Scalar::ZERO, lwe_size, LweCiphertextCount(BLOCKSIZE_IN_BIT), ciphertext_modulus);
+        let coeff_stride = polynomial_size.0 / BLOCKSIZE_IN_BIT;
+        for j in 0..BLOCKSIZE_IN_BIT {
+            let mut lwe_out = he_out.get_mut(gf128_coeff_to_bit_idx(j));
+            extract_lwe_sample_from_glwe_ciphertext(&glwe_out, &mut lwe_out, MonomialDegree(j * coeff_stride));
+        }
+
+        he_out
+    }
+
+    /// GHASH with H as the hash subkey on the blocks concatenated in `he_blocks`.
+    ///
+    /// Only the first product can skip the CBS of public bits, e.g. of a public AAD block, since the later
+    /// inputs are added to the encrypted Y_{i-1}.
+    pub fn ghash<Cont>(&self, he_blocks: &LweCiphertextList<Cont>) -> LweCiphertextListOwned<Scalar>
+    where
+        Cont: Container<Element=Scalar>,
+    {
+        assert_eq!(he_blocks.entity_count() % BLOCKSIZE_IN_BIT, 0);
+
+        let mut he_y = LweCiphertextList::new(
+            Scalar::ZERO,
+            he_blocks.lwe_size(),
+            LweCiphertextCount(BLOCKSIZE_IN_BIT),
+            he_blocks.ciphertext_modulus(),
+        );
+        for he_block in he_blocks.chunks_exact(BLOCKSIZE_IN_BIT) {
+            lwe_ciphertext_list_add_assign(&mut he_y, he_block);
+            he_y = self.mul(&he_y);
+        }
+
+        he_y
+    }
+}
+
+/// Encryption of X * Y in GF(2^128), where Y is refreshed and packed as in [`HeGf128Mul::new`].
+pub fn he_gf128_mul<Scalar, XCont, YCont>(
+    he_x: &LweCiphertextList<XCont>,
+    he_y: &LweCiphertextList<YCont>,
+    server_key: &AesServerKey<Scalar>,
+) -> LweCiphertextListOwned<Scalar>
+where
+    Scalar: UnsignedTorus + CastInto<usize> + CastFrom<usize>,
+    XCont: Container<Element=Scalar>,
+    YCont: Container<Element=Scalar>,
+{
+    HeGf128Mul::new(server_key, he_y).mul(he_x)
+}
+
+/// Homomorphic AES-GCM with 96-bit IVs: transciphering by the CTR part and tag computation under the
+/// encrypted key, with the hash subkey H = AES_K(0^128) evaluated homomorphically.
+pub struct HeAesGcm<'a, Scalar: UnsignedTorus> {
+    he_aes_ctr: HeAesCtr<'a, Scalar>,
+    he_mul_h: HeGf128Mul<'a, Scalar>,
+}
+
+impl<'a, Scalar> HeAesGcm<'a, Scalar>
+where
+    Scalar: UnsignedTorus + CastInto<usize> + CastFrom<usize>,
+{
+    pub fn new(he_aes: HeAes<'a, Scalar>) -> Self {
+        let server_key = he_aes.server_key();
+        let he_aes_ctr = HeAesCtr::new(he_aes);
+        let he_h = he_aes_ctr.keystream_block([0u8; BLOCKSIZE_IN_BYTE], 0);
+
+        HeAesGcm {
+            he_aes_ctr,
+            he_mul_h: HeGf128Mul::new(server_key, &he_h),
+        }
+    }
+
+    /// The round keys are expanded from the master key given by [`encrypt_aes_master_key`].
+    pub fn from_he_master_key<Cont>(server_key: &'a AesServerKey<Scalar>, he_master_key: &LweCiphertextList<Cont>) -> Self
+    where
+        Cont: Container<Element=Scalar>,
+    {
+        Self::new(HeAes::from_he_master_key(server_key, he_master_key))
+    }
+
+    pub fn he_aes_ctr(&self) -> &HeAesCtr<'a, Scalar> {
+        &self.he_aes_ctr
+    }
+
+    /// Multiplication by the hash subkey H.
+    pub fn he_mul_h(&self) -> &HeGf128Mul<'a, Scalar> {
+        &self.he_mul_h
+    }
+
+    /// Converts a GCM ciphertext into the LWE encryptions of the plaintext bits as in [`HeAesCtr::transcipher`].
+    ///
+    /// The counter blocks start from inc32(J0). The 128-bit increment of the CTR mode agrees with inc32 since
+    /// the 32-bit counter cannot wrap around within the at most 2^32 - 2 blocks of a GCM plaintext.
+    pub fn transcipher(&self, iv: &GcmIv, ciphertext: &[u8]) -> LweCiphertextListOwned<Scalar> {
+        self.he_aes_ctr.transcipher(gcm_inc32(gcm_initial_counter_block(iv)), ciphertext)
+    }
+
+    /// Encryption of the tag GHASH_H(A, C) + AES_K(J0) of the public AAD and ciphertext.
+    pub fn tag(&self, iv: &GcmIv, aad: &[u8], ciphertext: &[u8]) -> LweCiphertextListOwned<Scalar> {
+        let he_aes = self.he_aes_ctr.he_aes();
+        let lwe_size = he_aes.server_key().fourier_bsk().output_lwe_dimension().to_lwe_size();
+        let ciphertext_modulus = he_aes.he_round_keys()[0].ciphertext_modulus();
+
+        let blocks = gcm_ghash_blocks(aad, ciphertext);
+        let mut he_blocks = LweCiphertextList::new(
+            Scalar::ZERO,
+            lwe_size,
+            LweCiphertextCount(BLOCKSIZE_IN_BIT * blocks.len()),
+            ciphertext_modulus,
+        );
+        for (block, mut he_block) in blocks.iter().zip(he_blocks.chunks_exact_mut(BLOCKSIZE_IN_BIT)) {
+            he_block.as_mut().copy_from_slice(trivially_encrypt_aes_state(*block, lwe_size, ciphertext_modulus).as_ref());
+        }
+
+        let mut he_tag = self.he_mul_h.ghash(&he_blocks);
+        let he_ek_j0 = self.he_aes_ctr.keystream_block(gcm_initial_counter_block(iv), 0);
+        lwe_ciphertext_list_add_assign(&mut he_tag, he_ek_j0.as_view());
+
+        he_tag
+    }
+}
+
+/// Encryption of the XOR of an encrypted tag given by [`HeAesGcm::tag`] and a received tag,
+/// whose bits are all zero iff the received tag is valid.
+pub fn gcm_verify_tag<Scalar, Cont>(he_tag: &LweCiphertextList<Cont>, tag: StateByteArray) -> LweCiphertextListOwned<Scalar>
+where
+    Scalar: UnsignedTorus,
+    Cont: Container<Element=Scalar>,
+{
+    let mut he_diff = LweCiphertextList::from_container(he_tag.as_ref().to_vec(), he_tag.lwe_size(), he_tag.ciphertext_modulus());
+    for (bit_idx, mut he_bit) in he_diff.iter_mut().enumerate() {
+        if tag[bit_idx / BYTESIZE] & (1 << (bit_idx % BYTESIZE)) != 0 {
+            lwe_ciphertext_plaintext_add_assign(&mut he_bit, Plaintext(Scalar::ONE << (Scalar::BITS - 1)));
+        }
+    }
+
+    he_diff
+}
@@ -1,19 +1,18 @@
 use aligned_vec::ABox;
 use tfhe::{
-    shortint::prelude::*,
     core_crypto::{
         prelude::*,
         fft_impl::fft64::{
             c64,
             crypto::{
                 bootstrap::FourierLweBootstrapKeyView,
-                ggsw::FourierGgswCiphertextListView,
+                ggsw::FourierGgswCiphertextList,
             },
         },
     },
 };
-use std::collections::HashMap;
-use crate::{aes_ref::*, ggsw_conv::*, utils::*, AutomorphKey};
+use std::{collections::HashMap, sync::OnceLock};
+use crate::{aes_ref::*, cbs_keys::AesServerKey, ggsw_conv::*, lut_eval::*, pbs::*, utils::*, keyswitch_lwe_ciphertext_by_glwe_keyswitch, AutomorphKey};
 
 #[inline]
 pub fn he_add_round_key<Scalar, StateCont, RkCont>(
@@ -32,29 +31,195 @@ pub fn he_sub_bytes_by_patched_wwlp_cbs<Scalar, InputCont, OutputCont>(
     he_state_output: &mut LweCiphertextList<OutputCont>,
     fourier_bsk: FourierLweBootstrapKeyView,
     auto_keys: &HashMap<usize, AutomorphKey<ABox<[c64]>>>,
-    ss_key: FourierGgswCiphertextListView,
-    ggsw_base_log: DecompositionBaseLog,
-    ggsw_level: DecompositionLevelCount,
-    log_lut_count: LutCountLog,
+    ss_key: FourierSchemeSwitchingKeyView,
+    cbs_acc: &CbsAccumulator<Scalar>,
 ) where
     Scalar: UnsignedTorus + CastInto<usize> + CastFrom<usize>,
     InputCont: Container<Element = Scalar>,
     OutputCont: ContainerMut<Element = Scalar>,
 {
-    for (input_byte, mut output_byte) in he_state_input.chunks_exact(BYTESIZE)
-        .zip(he_state_output.chunks_exact_mut(BYTESIZE))
-    {
-        he_sbox_eval_by_patched_wwlp_cbs(
-            &input_byte,
-            &mut output_byte,
+    he_sub_bytes_by_table(he_state_input, he_state_output, fourier_bsk, auto_keys, ss_key, cbs_acc, &AES128_SBOX);
+}
+
+pub fn he_inv_sub_bytes_by_patched_wwlp_cbs<Scalar, InputCont, OutputCont>(
+    he_state_input: &LweCiphertextList<InputCont>,
+    he_state_output: &mut LweCiphertextList<OutputCont>,
+    fourier_bsk: FourierLweBootstrapKeyView,
+    auto_keys: &HashMap<usize, AutomorphKey<ABox<[c64]>>>,
+    ss_key: FourierSchemeSwitchingKeyView,
+    cbs_acc: &CbsAccumulator<Scalar>,
+) where
+    Scalar: UnsignedTorus + CastInto<usize> + CastFrom<usize>,
+    InputCont: Container<Element = Scalar>,
+    OutputCont: ContainerMut<Element = Scalar>,
+{
+    he_sub_bytes_by_table(he_state_input, he_state_output, fourier_bsk, auto_keys, ss_key, cbs_acc, &AES128_INV_SBOX);
+}
+
+/// Evaluates the S-box table on every chunk of log2(table size) bits of the input, e.g. on every byte for AES.
+pub(crate) fn he_sub_bytes_by_table<Scalar, InputCont, OutputCont>(
+    he_state_input: &LweCiphertextList<InputCont>,
+    he_state_output: &mut LweCiphertextList<OutputCont>,
+    fourier_bsk: FourierLweBootstrapKeyView,
+    auto_keys: &HashMap<usize, AutomorphKey<ABox<[c64]>>>,
+    ss_key: FourierSchemeSwitchingKeyView,
+    cbs_acc: &CbsAccumulator<Scalar>,
+    sbox: &[u8],
+) where
+    Scalar: UnsignedTorus + CastInto<usize> + CastFrom<usize>,
+    InputCont: Container<Element = Scalar>,
+    OutputCont: ContainerMut<Element = Scalar>,
+{
+    // The CBS is skipped for the public bits, which are folded into the tables instead
+    let public_bits = he_state_input.iter().map(|lwe| decode_public_bit(&lwe)).collect::<Vec<_>>();
+    let encrypted_bits = public_bits.iter().enumerate()
+        .filter_map(|(bit_idx, public_bit)| public_bit.is_none().then_some(bit_idx))
+        .collect::<Vec<usize>>();
+
+    let fourier_ggsw_encrypted_bits = if encrypted_bits.len() == public_bits.len() {
+        circuit_bootstrap_bits(he_state_input, fourier_bsk, auto_keys, ss_key, cbs_acc)
+    } else {
+        let mut he_encrypted_bits = LweCiphertextList::new(
+            Scalar::ZERO,
+            he_state_input.lwe_size(),
+            LweCiphertextCount(encrypted_bits.len()),
+            he_state_input.ciphertext_modulus(),
+        );
+        for (mut dst, &bit_idx) in he_encrypted_bits.iter_mut().zip(encrypted_bits.iter()) {
+            dst.as_mut().copy_from_slice(he_state_input.get(bit_idx).as_ref());
+        }
+        circuit_bootstrap_bits(&he_encrypted_bits, fourier_bsk, auto_keys, ss_key, cbs_acc)
+    };
+
+    let mut fourier_ggsw_bits = vec![None; public_bits.len()];
+    for (&bit_idx, fourier_ggsw) in encrypted_bits.iter().zip(fourier_ggsw_encrypted_bits.as_view().into_ggsw_iter()) {
+        fourier_ggsw_bits[bit_idx] = Some(fourier_ggsw);
+    }
+    let offset_bits = public_bits.iter().map(|public_bit| public_bit.unwrap_or(0)).collect::<Vec<u8>>();
+
+    he_eval_sbox_by_cmux_tree(&fourier_ggsw_bits, &offset_bits, sbox, he_state_output);
+}
+
+pub(crate) fn circuit_bootstrap_bits<Scalar, InputCont>(
+    he_bits: &LweCiphertextList<InputCont>,
+    fourier_bsk: FourierLweBootstrapKeyView,
+    auto_keys: &HashMap<usize, AutomorphKey<ABox<[c64]>>>,
+    ss_key: FourierSchemeSwitchingKeyView,
+    cbs_acc: &CbsAccumulator<Scalar>,
+) -> FourierGgswCiphertextList<Vec<c64>>
+where
+    Scalar: UnsignedTorus + CastInto<usize> + CastFrom<usize>,
+    InputCont: Container<Element = Scalar>,
+{
+    let glwe_size = fourier_bsk.glwe_size();
+    let polynomial_size = fourier_bsk.polynomial_size();
+    let ggsw_base_log = cbs_acc.decomposition_base_log();
+    let ggsw_level = cbs_acc.decomposition_level_count();
+    let num_bits = he_bits.entity_count();
+
+    let mut fourier_ggsw_bit_list = FourierGgswCiphertextList::new(
+        vec![c64::default();
+        num_bits * polynomial_size.to_fourier_polynomial_size().0
+            * glwe_size.0
+            * glwe_size.0
+            * ggsw_level.0
+        ],
+        num_bits,
+        glwe_size,
+        polynomial_size,
+        ggsw_base_log,
+        ggsw_level,
+    );
+    if num_bits > 0 {
+        circuit_bootstrap_lwe_ciphertext_list_by_trace_with_preprocessing(
+            he_bits,
+            &mut fourier_ggsw_bit_list,
             fourier_bsk,
             auto_keys,
             ss_key,
-            ggsw_base_log,
-            ggsw_level,
-            log_lut_count,
+            cbs_acc,
         );
     }
+
+    fourier_ggsw_bit_list
+}
+
+/// Evaluates the S-box on every chunk of log2(table size) bits whose bit i is the GGSW bit XOR the offset bit,
+/// or the offset bit alone if there is no GGSW bit. The offsets are folded into the table, which is only indexed
+/// by the GGSW bits.
+fn he_eval_sbox_by_cmux_tree<Scalar, OutputCont>(
+    fourier_ggsw_bits: &[Option<FourierGgswCiphertext<&[c64]>>],
+    offset_bits: &[u8],
+    sbox: &[u8],
+    he_state_output: &mut LweCiphertextList<OutputCont>,
+) where
+    Scalar: UnsignedTorus + CastFrom<usize>,
+    OutputCont: ContainerMut<Element = Scalar>,
+{
+    let sbox_size = sbox.len().ilog2() as usize;
+    assert_eq!(sbox.len(), 1 << sbox_size);
+
+    for ((fourier_ggsw_byte, offset_byte), mut output_byte) in fourier_ggsw_bits.chunks_exact(sbox_size)
+        .zip(offset_bits.chunks_exact(sbox_size))
+        .zip(he_state_output.chunks_exact_mut(sbox_size))
+    {
+        he_eval_lut_by_cmux_tree(fourier_ggsw_byte, offset_byte, sbox, &mut output_byte);
+    }
+}
+
+/// Evaluates a table on the input whose bit i is the GGSW bit XOR the offset bit, or the offset bit alone if
+/// there is no GGSW bit, where the output bit k is the bit k of the table entry.
+pub(crate) fn he_eval_lut_by_cmux_tree<Scalar, OutputCont>(
+    fourier_ggsw_bits: &[Option<FourierGgswCiphertext<&[c64]>>],
+    offset_bits: &[u8],
+    table: &[u8],
+    he_output: &mut LweCiphertextList<OutputCont>,
+) where
+    Scalar: UnsignedTorus + CastFrom<usize>,
+    OutputCont: ContainerMut<Element = Scalar>,
+{
+    let num_inputs = fourier_ggsw_bits.len();
+    assert_eq!(offset_bits.len(), num_inputs);
+    assert_eq!(table.len(), 1 << num_inputs);
+
+    let offset = offset_bits.iter().enumerate()
+        .fold(0usize, |acc, (bit_idx, &bit)| acc | ((bit as usize) << bit_idx));
+    let encrypted_bits = (0..num_inputs).filter(|&bit_idx| fourier_ggsw_bits[bit_idx].is_some()).collect::<Vec<usize>>();
+
+    if encrypted_bits.is_empty() {
+        let val = table[offset];
+        for (bit_idx, mut lwe_out) in he_output.iter_mut().enumerate() {
+            lwe_out.as_mut().fill(Scalar::ZERO);
+            *lwe_out.get_mut_body().data = Scalar::cast_from(((val >> bit_idx) & 1) as usize) << (Scalar::BITS - 1);
+        }
+        return;
+    }
+
+    let folded_table = (0..(1usize << encrypted_bits.len())).map(|idx| {
+        let input = encrypted_bits.iter().enumerate()
+            .fold(offset, |acc, (j, &bit_idx)| acc ^ (((idx >> j) & 1) << bit_idx));
+        table[input] as usize
+    }).collect::<Vec<usize>>();
+    let fourier_ggsw_list = encrypted_bits.iter()
+        .map(|&bit_idx| fourier_ggsw_bits[bit_idx].unwrap())
+        .collect::<Vec<_>>();
+
+    lut_eval_by_cmux_tree(&fourier_ggsw_list, &folded_table, he_output);
+}
+
+/// Value of a public bit, i.e., of a trivial encryption whose mask is zero.
+pub(crate) fn decode_public_bit<Scalar, Cont>(lwe: &LweCiphertext<Cont>) -> Option<u8>
+where
+    Scalar: UnsignedTorus,
+    Cont: Container<Element=Scalar>,
+{
+    if lwe.get_mask().as_ref().iter().any(|&a| a != Scalar::ZERO) {
+        return None;
+    }
+
+    let body = *lwe.get_body().data;
+    let bit = (body.wrapping_add(Scalar::ONE << (Scalar::BITS - 2)) >> (Scalar::BITS - 1)) == Scalar::ONE;
+    Some(bit as u8)
 }
 
 fn get_he_state_byte<Scalar, Cont>(
@@ -141,6 +306,78 @@ where
     }
 }
 
+pub fn he_inv_shift_rows<Scalar, Cont>(he_state: &mut LweCiphertextList<Cont>)
+where
+    Scalar: UnsignedInteger,
+    Cont: ContainerMut<Element=Scalar>,
+{
+    let mut buf = LweCiphertextList::new(
+        Scalar::ZERO,
+        he_state.lwe_size(),
+        LweCiphertextCount(BLOCKSIZE_IN_BIT),
+        he_state.ciphertext_modulus(),
+    );
+    buf.as_mut().clone_from_slice(he_state.as_ref());
+
+    for row in 1..4 {
+        for col in 0..4 {
+            let mut dst = get_he_state_byte_mut(he_state, row, (row + col) % 4);
+            let src = get_he_state_byte(&buf, row, col);
+            dst.as_mut().clone_from_slice(src.as_ref());
+        }
+    }
+}
+
+/// InvMixColumns by the 32x32 matrix over GF(2) of a column, so that each output bit is the sum of
+/// distinct input bits. Expanding 9, 11, 13 and 14 by shift-and-add would instead add some input bits
+/// an even number of times, which cancels mod 2 but not in the noise.
+pub fn he_inv_mix_columns<Scalar, Cont>(he_state: &mut LweCiphertextList<Cont>)
+where
+    Scalar: UnsignedInteger,
+    Cont: ContainerMut<Element=Scalar>,
+{
+    let mut buf = LweCiphertextList::new(Scalar::ZERO, he_state.lwe_size(), LweCiphertextCount(BLOCKSIZE_IN_BIT), he_state.ciphertext_modulus());
+    buf.as_mut().clone_from_slice(he_state.as_ref());
+
+    let inv_mix_columns_mat = inv_mix_columns_bit_matrix();
+    for col in 0..4 {
+        for row in 0..4 {
+            let mut he_byte_out = get_he_state_byte_mut(he_state, row, col);
+            for (bit_idx, mut he_bit_out) in he_byte_out.iter_mut().enumerate() {
+                he_bit_out.as_mut().fill(Scalar::ZERO);
+                for (in_idx, &is_term) in inv_mix_columns_mat[BYTESIZE * row + bit_idx].iter().enumerate() {
+                    if is_term {
+                        let he_byte_in = get_he_state_byte(&buf, in_idx / BYTESIZE, col);
+                        lwe_ciphertext_add_assign(&mut he_bit_out, &he_byte_in.get(in_idx % BYTESIZE));
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Bit matrix of InvMixColumns on a column, indexed by 8 * row + bit from the LSB.
+/// The j-th input bit of row r' is in the i-th output bit of row r iff bit i of c * 2^j is set,
+/// where c in {14, 11, 13, 9} is the coefficient of (r, r').
+fn inv_mix_columns_bit_matrix() -> [[bool; WORDSIZE_IN_BIT]; WORDSIZE_IN_BIT] {
+    const INV_MIX_COLUMNS_COEFFS: [u8; NUM_ROWS] = [14, 11, 13, 9];
+
+    let mut mat = [[false; WORDSIZE_IN_BIT]; WORDSIZE_IN_BIT];
+    for row_out in 0..NUM_ROWS {
+        for row_in in 0..NUM_ROWS {
+            let c = INV_MIX_COLUMNS_COEFFS[(row_in + NUM_ROWS - row_out) % NUM_ROWS];
+            for j in 0..BYTESIZE {
+                let column = mult(c, 1 << j);
+                for i in 0..BYTESIZE {
+                    mat[BYTESIZE * row_out + i][BYTESIZE * row_in + j] = (column >> i) & 1 == 1;
+                }
+            }
+        }
+    }
+
+    mat
+}
+
 fn he_mult_by_two<Scalar, Cont>(he_byte: &LweCiphertextList<Cont>) -> LweCiphertextListOwned<Scalar>
 where
     Scalar: UnsignedInteger,
@@ -179,111 +416,6 @@ where
     he_byte.as_mut().clone_from_slice(buf.as_ref());
 }
 
-fn he_sbox_eval_by_patched_wwlp_cbs<Scalar, InCont, OutCont>(
-    input: &LweCiphertextList<InCont>,
-    output: &mut LweCiphertextList<OutCont>,
-    fourier_bsk: FourierLweBootstrapKeyView,
-    auto_keys: &HashMap<usize, AutomorphKey<ABox<[c64]>>>,
-    ss_key: FourierGgswCiphertextListView,
-    ggsw_base_log: DecompositionBaseLog,
-    ggsw_level: DecompositionLevelCount,
-    log_lut_count: LutCountLog,
-) where
-    Scalar: UnsignedTorus + CastInto<usize> + CastFrom<usize>,
-    InCont: Container<Element=Scalar>,
-    OutCont: ContainerMut<Element=Scalar>,
-{
-    let glwe_size = fourier_bsk.glwe_size();
-    let polynomial_size = fourier_bsk.polynomial_size();
-    let ciphertext_modulus = output.ciphertext_modulus();
-
-    let mut vec_glev = vec![
-        GlweCiphertextList::new(
-            Scalar::ZERO,
-            glwe_size,
-            polynomial_size,
-            GlweCiphertextCount(ggsw_level.0),
-            ciphertext_modulus,
-        ); BYTESIZE];
-    for (input_bit, glev) in input.iter().zip(vec_glev.iter_mut()) {
-        let glev_mut_view = GlweCiphertextListMutView::from_container(
-            glev.as_mut(),
-            glwe_size,
-            polynomial_size,
-            ciphertext_modulus,
-        );
-
-        lwe_msb_bit_to_glev_by_trace_with_preprocessing(
-            input_bit.as_view(),
-            glev_mut_view,
-            fourier_bsk,
-            auto_keys,
-            ggsw_base_log,
-            ggsw_level,
-            log_lut_count,
-        );
-    }
-
-    let mut ggsw_bit_list = GgswCiphertextList::new(
-        Scalar::ZERO,
-        glwe_size,
-        polynomial_size,
-        ggsw_base_log,
-        ggsw_level,
-        GgswCiphertextCount(vec_glev.len()),
-        ciphertext_modulus,
-    );
-    for (mut ggsw, glev) in ggsw_bit_list.iter_mut().zip(vec_glev.iter()) {
-        switch_scheme(&glev, &mut ggsw, ss_key);
-    }
-
-    let mut fourier_ggsw_bit_list = FourierGgswCiphertextList::new(
-        vec![c64::default();
-        BYTESIZE * polynomial_size.to_fourier_polynomial_size().0
-            * glwe_size.0
-            * glwe_size.0
-            * ggsw_level.0
-        ],
-        BYTESIZE,
-        glwe_size,
-        polynomial_size,
-        ggsw_base_log,
-        ggsw_level,
-    );
-    for (mut fourier_ggsw, ggsw) in fourier_ggsw_bit_list.as_mut_view().into_ggsw_iter().zip(ggsw_bit_list.iter()) {
-        convert_standard_ggsw_ciphertext_to_fourier(&ggsw, &mut fourier_ggsw);
-    }
-
-    let num_par_lut = polynomial_size.0 / (1 << BYTESIZE);
-    let num_accumulator = if BYTESIZE % num_par_lut == 0 {
-        BYTESIZE / num_par_lut
-    } else {
-        BYTESIZE / num_par_lut + 1
-    };
-
-    for acc_idx in 0..num_accumulator {
-        let accumulator = (0..polynomial_size.0).map(|i| {
-            let lut_idx = acc_idx * num_par_lut + i / (1 << BYTESIZE);
-            (((AES128_SBOX[i % (1 << BYTESIZE)] & (1 << lut_idx)) as usize) << ((Scalar::BITS - 1) - lut_idx)).cast_into()
-        }).collect::<Vec<Scalar>>();
-        let accumulator_plaintext = PlaintextList::from_container(accumulator);
-        let mut accumulator = allocate_and_trivially_encrypt_new_glwe_ciphertext(glwe_size, &accumulator_plaintext, ciphertext_modulus);
-
-        for (i, fourier_ggsw_bit) in fourier_ggsw_bit_list.as_view().into_ggsw_iter().into_iter().enumerate() {
-            let mut buf = accumulator.clone();
-            glwe_ciphertext_monic_monomial_div_assign(&mut buf, MonomialDegree(1 << i));
-            glwe_ciphertext_sub_assign(&mut buf, &accumulator);
-            add_external_product_assign(&mut accumulator, &fourier_ggsw_bit, &buf);
-        }
-
-        for i in 0..num_par_lut {
-            let bit_idx = acc_idx * num_par_lut + i;
-            let mut lwe_out = output.get_mut(bit_idx);
-            extract_lwe_sample_from_glwe_ciphertext(&accumulator, &mut lwe_out, MonomialDegree(i * (1 << BYTESIZE)));
-        }
-    }
-}
-
 
 pub fn get_he_state_error<Scalar, StateCont, SkCont>(
     he_state: &LweCiphertextList<StateCont>,
@@ -313,3 +445,485 @@ where
 
     (vec_err, max_err)
 }
+
+
+/// Step of the homomorphic AES evaluation after which the hook of [`HeAes::encrypt_block_with_hook`] is called.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HeAesStep {
+    AddRoundKey,
+    /// The state is under the LWE key after keyswitching.
+    LweKeyswitch,
+    SubBytes,
+    ShiftRows,
+    MixColumns,
+    InvSubBytes,
+    InvShiftRows,
+    InvMixColumns,
+}
+
+/// Homomorphic AES encryption and decryption by patched WWL+ circuit bootstrapping, where each
+/// S-box layer is preceded by the LWE keyswitching by GLWE dimension switching.
+/// The key size is given by the number of round keys.
+pub struct HeAes<'a, Scalar: UnsignedTorus> {
+    server_key: &'a AesServerKey<Scalar>,
+    he_round_keys: Vec<LweCiphertextListOwned<Scalar>>,
+    key_size: AesKeySize,
+    /// CBS outputs of the first round key bits, computed once for all blocks with public bits.
+    fourier_ggsw_first_round_key: OnceLock<FourierGgswCiphertextList<Vec<c64>>>,
+}
+
+impl<'a, Scalar> HeAes<'a, Scalar>
+where
+    Scalar: UnsignedTorus + CastInto<usize> + CastFrom<usize>,
+{
+    /// The round keys are given by [`encrypt_aes_round_keys`].
+    pub fn new(server_key: &'a AesServerKey<Scalar>, he_round_keys: Vec<LweCiphertextListOwned<Scalar>>) -> Self {
+        assert!(!he_round_keys.is_empty(), "no AES round keys");
+        let lwe_size = server_key.fourier_bsk().output_lwe_dimension().to_lwe_size();
+        let key_size = AesKeySize::from_num_rounds(he_round_keys.len() - 1)
+            .unwrap_or_else(|| panic!("invalid number of AES round keys {}", he_round_keys.len()));
+        for he_round_key in he_round_keys.iter() {
+            assert_eq!(he_round_key.entity_count(), BLOCKSIZE_IN_BIT);
+            assert_eq!(he_round_key.lwe_size(), lwe_size);
+        }
+
+        HeAes {
+            server_key,
+            he_round_keys,
+            key_size,
+            fourier_ggsw_first_round_key: OnceLock::new(),
+        }
+    }
+
+    /// The round keys are expanded from the master key given by [`encrypt_aes_master_key`] by [`he_expand_key`].
+    pub fn from_he_master_key<Cont>(server_key: &'a AesServerKey<Scalar>, he_master_key: &LweCiphertextList<Cont>) -> Self
+    where
+        Cont: Container<Element=Scalar>,
+    {
+        Self::new(server_key, he_expand_key(he_master_key, server_key))
+    }
+
+    pub fn server_key(&self) -> &'a AesServerKey<Scalar> {
+        self.server_key
+    }
+
+    pub fn he_round_keys(&self) -> &[LweCiphertextListOwned<Scalar>] {
+        &self.he_round_keys
+    }
+
+    pub fn key_size(&self) -> AesKeySize {
+        self.key_size
+    }
+
+    pub fn num_rounds(&self) -> usize {
+        self.key_size.num_rounds()
+    }
+
+    /// Encrypts the state in place.
+    pub fn encrypt_block<Cont>(&self, he_state: &mut LweCiphertextList<Cont>)
+    where
+        Cont: ContainerMut<Element=Scalar>,
+    {
+        self.encrypt_block_with_hook(he_state, |_, _, _| {});
+    }
+
+    /// Encrypts the state in place, calling `hook` with the round and the state after each step.
+    /// The final round has no MixColumns.
+    ///
+    /// The public bits of the state, i.e. trivial encryptions such as a CTR counter block, are folded into the
+    /// S-box tables of the first round: the SubBytes input bit is then the first round key bit, whose CBS output
+    /// is computed once and reused for every block, and the LweKeyswitch step of the first round is skipped.
+    pub fn encrypt_block_with_hook<Cont, F>(&self, he_state: &mut LweCiphertextList<Cont>, mut hook: F)
+    where
+        Cont: ContainerMut<Element=Scalar>,
+        F: FnMut(usize, HeAesStep, LweCiphertextListView<Scalar>),
+    {
+        assert_eq!(he_state.entity_count(), BLOCKSIZE_IN_BIT);
+        let mut he_state_ks = new_lwe_ciphertext_list_after_ks(self.server_key, BLOCKSIZE_IN_BIT, he_state.ciphertext_modulus());
+
+        let public_bits = he_state.iter().map(|lwe| decode_public_bit(&lwe)).collect::<Vec<_>>();
+        let has_public_bits = public_bits.iter().any(Option::is_some);
+
+        he_add_round_key(he_state, &self.he_round_keys[0]);
+        hook(0, HeAesStep::AddRoundKey, he_state.as_view());
+
+        let num_rounds = self.num_rounds();
+        for r in 1..=num_rounds {
+            if r == 1 && has_public_bits {
+                self.first_sub_bytes_with_public_bits(he_state, &public_bits);
+            } else {
+                keyswitch_lwe_ciphertext_list(he_state, &mut he_state_ks, self.server_key);
+                hook(r, HeAesStep::LweKeyswitch, he_state_ks.as_view());
+
+                he_sub_bytes_by_patched_wwlp_cbs(
+                    &he_state_ks,
+                    he_state,
+                    self.server_key.fourier_bsk(),
+                    self.server_key.auto_keys(),
+                    self.server_key.ss_key(),
+                    self.server_key.cbs_acc(),
+                );
+            }
+            hook(r, HeAesStep::SubBytes, he_state.as_view());
+
+            he_shift_rows(he_state);
+            hook(r, HeAesStep::ShiftRows, he_state.as_view());
+
+            if r < num_rounds {
+                he_mix_columns(he_state);
+                hook(r, HeAesStep::MixColumns, he_state.as_view());
+            }
+
+            he_add_round_key(he_state, &self.he_round_keys[r]);
+            hook(r, HeAesStep::AddRoundKey, he_state.as_view());
+        }
+    }
+
+    /// SubBytes of the first round on the state after the first AddRoundKey, where the bits that were public
+    /// before it are evaluated from the CBS outputs of the first round key bits with the public bits as offsets.
+    fn first_sub_bytes_with_public_bits<Cont>(&self, he_state: &mut LweCiphertextList<Cont>, public_bits: &[Option<u8>])
+    where
+        Cont: ContainerMut<Element=Scalar>,
+    {
+        let encrypted_bits = public_bits.iter().enumerate()
+            .filter_map(|(bit_idx, public_bit)| public_bit.is_none().then_some(bit_idx))
+            .collect::<Vec<usize>>();
+
+        let mut he_encrypted_bits = LweCiphertextList::new(
+            Scalar::ZERO,
+            he_state.lwe_size(),
+            LweCiphertextCount(encrypted_bits.len()),
+            he_state.ciphertext_modulus(),
+        );
+        for (mut dst, &bit_idx) in he_encrypted_bits.iter_mut().zip(encrypted_bits.iter()) {
+            dst.as_mut().copy_from_slice(he_state.get(bit_idx).as_ref());
+        }
+        let mut he_encrypted_bits_ks = new_lwe_ciphertext_list_after_ks(self.server_key, encrypted_bits.len(), he_state.ciphertext_modulus());
+        keyswitch_lwe_ciphertext_list(&he_encrypted_bits, &mut he_encrypted_bits_ks, self.server_key);
+        let fourier_ggsw_encrypted_bits = circuit_bootstrap_bits(
+            &he_encrypted_bits_ks,
+            self.server_key.fourier_bsk(),
+            self.server_key.auto_keys(),
+            self.server_key.ss_key(),
+            self.server_key.cbs_acc(),
+        );
+
+        let mut fourier_ggsw_bits = self.fourier_ggsw_first_round_key().as_view().into_ggsw_iter()
+            .map(Some)
+            .collect::<Vec<_>>();
+        for (&bit_idx, fourier_ggsw) in encrypted_bits.iter().zip(fourier_ggsw_encrypted_bits.as_view().into_ggsw_iter()) {
+            fourier_ggsw_bits[bit_idx] = Some(fourier_ggsw);
+        }
+        let offset_bits = public_bits.iter().map(|public_bit| public_bit.unwrap_or(0)).collect::<Vec<u8>>();
+
+        he_eval_sbox_by_cmux_tree(&fourier_ggsw_bits, &offset_bits, &AES128_SBOX, he_state);
+    }
+
+    fn fourier_ggsw_first_round_key(&self) -> &FourierGgswCiphertextList<Vec<c64>> {
+        self.fourier_ggsw_first_round_key.get_or_init(|| {
+            let he_round_key = &self.he_round_keys[0];
+            let mut he_round_key_ks = new_lwe_ciphertext_list_after_ks(self.server_key, BLOCKSIZE_IN_BIT, he_round_key.ciphertext_modulus());
+            keyswitch_lwe_ciphertext_list(he_round_key, &mut he_round_key_ks, self.server_key);
+
+            circuit_bootstrap_bits(
+                &he_round_key_ks,
+                self.server_key.fourier_bsk(),
+                self.server_key.auto_keys(),
+                self.server_key.ss_key(),
+                self.server_key.cbs_acc(),
+            )
+        })
+    }
+
+    /// Decrypts the state in place.
+    pub fn decrypt_block<Cont>(&self, he_state: &mut LweCiphertextList<Cont>)
+    where
+        Cont: ContainerMut<Element=Scalar>,
+    {
+        self.decrypt_block_with_hook(he_state, |_, _, _| {});
+    }
+
+    /// Decrypts the state in place by the inverse cipher, calling `hook` with the index of the round key
+    /// and the state after each step. The rounds go from `num_rounds() - 1` down to 0, and the last one has no
+    /// InvMixColumns.
+    pub fn decrypt_block_with_hook<Cont, F>(&self, he_state: &mut LweCiphertextList<Cont>, mut hook: F)
+    where
+        Cont: ContainerMut<Element=Scalar>,
+        F: FnMut(usize, HeAesStep, LweCiphertextListView<Scalar>),
+    {
+        assert_eq!(he_state.entity_count(), BLOCKSIZE_IN_BIT);
+        let mut he_state_ks = new_lwe_ciphertext_list_after_ks(self.server_key, BLOCKSIZE_IN_BIT, he_state.ciphertext_modulus());
+
+        let num_rounds = self.num_rounds();
+        he_add_round_key(he_state, &self.he_round_keys[num_rounds]);
+        hook(num_rounds, HeAesStep::AddRoundKey, he_state.as_view());
+
+        for r in (0..num_rounds).rev() {
+            he_inv_shift_rows(he_state);
+            hook(r, HeAesStep::InvShiftRows, he_state.as_view());
+
+            keyswitch_lwe_ciphertext_list(he_state, &mut he_state_ks, self.server_key);
+            hook(r, HeAesStep::LweKeyswitch, he_state_ks.as_view());
+
+            he_inv_sub_bytes_by_patched_wwlp_cbs(
+                &he_state_ks,
+                he_state,
+                self.server_key.fourier_bsk(),
+                self.server_key.auto_keys(),
+                self.server_key.ss_key(),
+                self.server_key.cbs_acc(),
+            );
+            hook(r, HeAesStep::InvSubBytes, he_state.as_view());
+
+            he_add_round_key(he_state, &self.he_round_keys[r]);
+            hook(r, HeAesStep::AddRoundKey, he_state.as_view());
+
+            if r > 0 {
+                he_inv_mix_columns(he_state);
+                hook(r, HeAesStep::InvMixColumns, he_state.as_view());
+            }
+        }
+    }
+
+}
+
+/// Expands an encrypted AES-128, AES-192 or AES-256 key into the encrypted round keys.
+///
+/// Each SubWord is the SubBytes of a word after the LWE keyswitching, and each word of the round keys is
+/// the sum of the ciphertexts of its terms in [`AesKeySize::key_expansion_terms`], so that the noise of a round key
+/// is that of at most one SubWord output per round.
+pub fn he_expand_key<Scalar, Cont>(
+    he_master_key: &LweCiphertextList<Cont>,
+    server_key: &AesServerKey<Scalar>,
+) -> Vec<LweCiphertextListOwned<Scalar>>
+where
+    Scalar: UnsignedTorus + CastInto<usize> + CastFrom<usize>,
+    Cont: Container<Element=Scalar>,
+{
+    let num_bits = he_master_key.entity_count();
+    let key_size = AesKeySize::from_key_len(num_bits / BYTESIZE)
+        .filter(|_| num_bits.is_multiple_of(BYTESIZE))
+        .unwrap_or_else(|| panic!("invalid number of AES key bits {num_bits}"));
+    let lwe_size = he_master_key.lwe_size();
+    let ciphertext_modulus = he_master_key.ciphertext_modulus();
+    let terms = key_size.key_expansion_terms();
+
+    // The master key words followed by the SubWord outputs
+    let mut he_terms = he_master_key.chunks_exact(WORDSIZE_IN_BIT).map(|he_word| {
+        LweCiphertextList::from_container(he_word.as_ref().to_vec(), lwe_size, ciphertext_modulus)
+    }).collect::<Vec<_>>();
+
+    let mut he_word_ks = new_lwe_ciphertext_list_after_ks(server_key, WORDSIZE_IN_BIT, ciphertext_modulus);
+    for sub_word in terms.sub_words.iter() {
+        let mut he_word = LweCiphertextList::new(Scalar::ZERO, lwe_size, LweCiphertextCount(WORDSIZE_IN_BIT), ciphertext_modulus);
+        for &term in terms.word_terms[sub_word.word].iter() {
+            lwe_ciphertext_list_add_assign(&mut he_word, he_terms[term].as_view());
+        }
+        if sub_word.rcon.is_some() {
+            // RotWord
+            he_word.as_mut().rotate_left(BYTESIZE * lwe_size.0);
+        }
+
+        keyswitch_lwe_ciphertext_list(&he_word, &mut he_word_ks, server_key);
+        he_sub_bytes_by_patched_wwlp_cbs(
+            &he_word_ks,
+            &mut he_word,
+            server_key.fourier_bsk(),
+            server_key.auto_keys(),
+            server_key.ss_key(),
+            server_key.cbs_acc(),
+        );
+
+        if let Some(rcon) = sub_word.rcon {
+            for (bit_idx, mut he_bit) in he_word.iter_mut().take(BYTESIZE).enumerate() {
+                if rcon & (1 << bit_idx) != 0 {
+                    let body = he_bit.get_mut_body().data;
+                    *body = body.wrapping_add(Scalar::ONE << (Scalar::BITS - 1));
+                }
+            }
+        }
+
+        he_terms.push(he_word);
+    }
+
+    terms.word_terms.chunks_exact(NUM_COLUMNS).map(|round_key_terms| {
+        let mut he_round_key = LweCiphertextList::new(Scalar::ZERO, lwe_size, LweCiphertextCount(BLOCKSIZE_IN_BIT), ciphertext_modulus);
+        for (mut he_word, word_terms) in he_round_key.chunks_exact_mut(WORDSIZE_IN_BIT).zip(round_key_terms.iter()) {
+            for &term in word_terms.iter() {
+                lwe_ciphertext_list_add_assign(&mut he_word, he_terms[term].as_view());
+            }
+        }
+
+        he_round_key
+    }).collect()
+}
+
+const WORDSIZE_IN_BIT: usize = NUM_ROWS * BYTESIZE;
+
+pub(crate) fn new_lwe_ciphertext_list_after_ks<Scalar: UnsignedTorus>(
+    server_key: &AesServerKey<Scalar>,
+    count: usize,
+    ciphertext_modulus: CiphertextModulus<Scalar>,
+) -> LweCiphertextListOwned<Scalar> {
+    let glwe_ksk = server_key.glwe_ksk();
+    LweCiphertextList::new(
+        Scalar::ZERO,
+        glwe_ksk.output_glwe_size().to_glwe_dimension().to_equivalent_lwe_dimension(glwe_ksk.polynomial_size()).to_lwe_size(),
+        LweCiphertextCount(count),
+        ciphertext_modulus,
+    )
+}
+
+pub(crate) fn keyswitch_lwe_ciphertext_list<Scalar, InputCont, OutputCont>(
+    input: &LweCiphertextList<InputCont>,
+    output: &mut LweCiphertextList<OutputCont>,
+    server_key: &AesServerKey<Scalar>,
+) where
+    Scalar: UnsignedTorus,
+    InputCont: Container<Element=Scalar>,
+    OutputCont: ContainerMut<Element=Scalar>,
+{
+    for (lwe, mut lwe_ks) in input.iter().zip(output.iter_mut()) {
+        keyswitch_lwe_ciphertext_by_glwe_keyswitch(&lwe, &mut lwe_ks, server_key.glwe_ksk());
+    }
+}
+
+/// Encrypts the bits of the expanded key of AES-128, AES-192 or AES-256 under the LWE key of the bootstrapping output.
+pub fn encrypt_aes_round_keys<Scalar, KeyCont, Gen>(
+    lwe_sk: &LweSecretKey<KeyCont>,
+    key: &[u8],
+    noise_parameters: impl DispersionParameter,
+    ciphertext_modulus: CiphertextModulus<Scalar>,
+    encryption_generator: &mut EncryptionRandomGenerator<Gen>,
+) -> Vec<LweCiphertextListOwned<Scalar>>
+where
+    Scalar: UnsignedTorus,
+    KeyCont: Container<Element=Scalar>,
+    Gen: ByteRandomGenerator,
+{
+    AesRef::new(key).get_round_keys().iter().map(|round_key| {
+        let mut he_round_key = LweCiphertextList::new(
+            Scalar::ZERO,
+            lwe_sk.lwe_dimension().to_lwe_size(),
+            LweCiphertextCount(BLOCKSIZE_IN_BIT),
+            ciphertext_modulus,
+        );
+        encrypt_lwe_ciphertext_list(
+            lwe_sk,
+            &mut he_round_key,
+            &encode_bytes::<Scalar>(round_key),
+            noise_parameters,
+            encryption_generator,
+        );
+
+        he_round_key
+    }).collect()
+}
+
+/// Encrypts the bits of an AES-128, AES-192 or AES-256 key under the LWE key of the bootstrapping output,
+/// for the round keys to be expanded by [`he_expand_key`].
+pub fn encrypt_aes_master_key<Scalar, KeyCont, Gen>(
+    lwe_sk: &LweSecretKey<KeyCont>,
+    key: &[u8],
+    noise_parameters: impl DispersionParameter,
+    ciphertext_modulus: CiphertextModulus<Scalar>,
+    encryption_generator: &mut EncryptionRandomGenerator<Gen>,
+) -> LweCiphertextListOwned<Scalar>
+where
+    Scalar: UnsignedTorus,
+    KeyCont: Container<Element=Scalar>,
+    Gen: ByteRandomGenerator,
+{
+    assert!(AesKeySize::from_key_len(key.len()).is_some(), "invalid AES key length {}", key.len());
+
+    encrypt_bytes(lwe_sk, key, noise_parameters, ciphertext_modulus, encryption_generator)
+}
+
+/// Encrypts the bits of a byte string under the LWE key of the bootstrapping output,
+/// where bit i of byte b is at index 8b + i, e.g. a message to be authenticated.
+pub fn encrypt_bytes<Scalar, KeyCont, Gen>(
+    lwe_sk: &LweSecretKey<KeyCont>,
+    bytes: &[u8],
+    noise_parameters: impl DispersionParameter,
+    ciphertext_modulus: CiphertextModulus<Scalar>,
+    encryption_generator: &mut EncryptionRandomGenerator<Gen>,
+) -> LweCiphertextListOwned<Scalar>
+where
+    Scalar: UnsignedTorus,
+    KeyCont: Container<Element=Scalar>,
+    Gen: ByteRandomGenerator,
+{
+    let mut he_bytes = LweCiphertextList::new(
+        Scalar::ZERO,
+        lwe_sk.lwe_dimension().to_lwe_size(),
+        LweCiphertextCount(BYTESIZE * bytes.len()),
+        ciphertext_modulus,
+    );
+    encrypt_lwe_ciphertext_list(
+        lwe_sk,
+        &mut he_bytes,
+        &encode_bytes::<Scalar>(bytes),
+        noise_parameters,
+        encryption_generator,
+    );
+
+    he_bytes
+}
+
+/// Trivially encrypts a plaintext block, e.g. the counter of a transciphering.
+pub fn trivially_encrypt_aes_state<Scalar: UnsignedTorus>(
+    block: StateByteArray,
+    lwe_size: LweSize,
+    ciphertext_modulus: CiphertextModulus<Scalar>,
+) -> LweCiphertextListOwned<Scalar> {
+    let mut he_state = LweCiphertextList::new(Scalar::ZERO, lwe_size, LweCiphertextCount(BLOCKSIZE_IN_BIT), ciphertext_modulus);
+    for (mut he_bit, pt) in he_state.iter_mut().zip(encode_bytes::<Scalar>(&block).iter()) {
+        *he_bit.get_mut_body().data = *pt.0;
+    }
+
+    he_state
+}
+
+pub fn decrypt_aes_state<Scalar, StateCont, KeyCont>(
+    lwe_sk: &LweSecretKey<KeyCont>,
+    he_state: &LweCiphertextList<StateCont>,
+) -> StateByteArray
+where
+    Scalar: UnsignedTorus,
+    StateCont: Container<Element=Scalar>,
+    KeyCont: Container<Element=Scalar>,
+{
+    let mut block = [0u8; BLOCKSIZE_IN_BYTE];
+    block.copy_from_slice(&decrypt_bytes(lwe_sk, he_state));
+
+    block
+}
+
+/// Decrypts a list of bit encryptions where bit i of byte b is at index 8b + i, e.g. a transciphered plaintext.
+pub fn decrypt_bytes<Scalar, ListCont, KeyCont>(
+    lwe_sk: &LweSecretKey<KeyCont>,
+    he_bits: &LweCiphertextList<ListCont>,
+) -> Vec<u8>
+where
+    Scalar: UnsignedTorus,
+    ListCont: Container<Element=Scalar>,
+    KeyCont: Container<Element=Scalar>,
+{
+    let mut bytes = vec![0u8; he_bits.entity_count().div_ceil(BYTESIZE)];
+    for (bit_idx, he_bit) in he_bits.iter().enumerate() {
+        let decoded = decrypt_lwe_ciphertext(lwe_sk, &he_bit).0;
+        let bit = (decoded.wrapping_add(Scalar::ONE << (Scalar::BITS - 2)) >> (Scalar::BITS - 1)) == Scalar::ONE;
+        bytes[bit_idx / BYTESIZE] |= (bit as u8) << (bit_idx % BYTESIZE);
+    }
+
+    bytes
+}
+
+/// Bit i of byte b is encoded in the plaintext 8b + i.
+fn encode_bytes<Scalar: UnsignedTorus>(bytes: &[u8]) -> PlaintextListOwned<Scalar> {
+    PlaintextList::from_container(bytes.iter().flat_map(|&byte| {
+        (0..BYTESIZE).map(move |bit_idx| {
+            if byte & (1 << bit_idx) != 0 {Scalar::ONE << (Scalar::BITS - 1)} else {Scalar::ZERO}
+        })
+    }).collect::<Vec<Scalar>>())
+}
@@ -72,4 +72,94 @@ lazy_static! {
         LutCountLog(2), // log_lut_count
         CiphertextModulus::<u64>::new_native(), // ciphertext_modulus
     );
-}
\ No newline at end of file
+
+    // AES_SET_1 with glwe_ds_level = 4, the smallest level for which the failure probability
+    // over the 12 S-box layers of AES-192 is below that of AES-128 with AES_SET_1 (2^-481.77):
+    // 2^-481.5 with 3 levels and 2^-523.54 with 4 levels.
+    pub static ref AES192_SET_1: AesParam<u64> = AesParam::new(
+        LweDimension(768), // lwe_dimension
+        StandardDev(0.00000702047462940120), // lwe_modular_std_dev
+        PolynomialSize(2048), // polynomial_size
+        GlweDimension(1), // glwe_dimension
+        StandardDev(0.00000000000000029403601535432533), // glwe_modular_std_dev
+        DecompositionBaseLog(15), // pbs_base_log
+        DecompositionLevelCount(2), // pbs_level
+        DecompositionBaseLog(4), // glwe_ds_base_log
+        DecompositionLevelCount(4), // glwe_ds_level
+        PolynomialSize(256), // common_polynomial_size
+        FftType::Vanilla, // fft_type_ds
+        DecompositionBaseLog(7), // auto_base_log
+        DecompositionLevelCount(7), // auto_level
+        FftType::Split(37), // fft_type_auto
+        DecompositionBaseLog(8), // ss_base_log
+        DecompositionLevelCount(6), // ss_level
+        DecompositionBaseLog(5), // cbs_base_log
+        DecompositionLevelCount(3), // cbs_level
+        LutCountLog(2), // log_lut_count
+        CiphertextModulus::<u64>::new_native(), // ciphertext_modulus
+    );
+
+    // AES_SET_2 with glwe_ds_level = 4, the smallest level for which the failure probability
+    // over the 12 S-box layers of AES-192 is below that of AES-128 with AES_SET_2 (2^-239.01):
+    // 2^-238.75 with 3 levels and 2^-248.95 with 4 levels.
+    pub static ref AES192_SET_2: AesParam<u64> = AesParam::new(
+        LweDimension(768), // lwe_dimension
+        StandardDev(0.00000702047462940120), // lwe_modular_std_dev
+        PolynomialSize(1024), // polynomial_size
+        GlweDimension(2), // glwe_dimension
+        StandardDev(0.00000000000000029403601535432533), // glwe_modular_std_dev
+        DecompositionBaseLog(15), // pbs_base_log
+        DecompositionLevelCount(2), // pbs_level
+        DecompositionBaseLog(4), // glwe_ds_base_log
+        DecompositionLevelCount(4), // glwe_ds_level
+        PolynomialSize(256), // common_polynomial_size
+        FftType::Vanilla, // fft_type_ds
+        DecompositionBaseLog(7), // auto_base_log
+        DecompositionLevelCount(7), // auto_level
+        FftType::Split(37), // fft_type_auto
+        DecompositionBaseLog(8), // ss_base_log
+        DecompositionLevelCount(6), // ss_level
+        DecompositionBaseLog(5), // cbs_base_log
+        DecompositionLevelCount(3), // cbs_level
+        LutCountLog(2), // log_lut_count
+        CiphertextModulus::<u64>::new_native(), // ciphertext_modulus
+    );
+
+    // AES_SET_3 with glwe_ds_level = 4, the smallest level for which the failure probability
+    // over the 12 S-box layers of AES-192 is below that of AES-128 with AES_SET_3 (2^-75.21):
+    // 2^-74.94 with 3 levels and 2^-76.06 with 4 levels.
+    pub static ref AES192_SET_3: AesParam<u64> = AesParam::new(
+        LweDimension(768), // lwe_dimension
+        StandardDev(0.00000702047462940120), // lwe_modular_std_dev
+        PolynomialSize(512), // polynomial_size
+        GlweDimension(4), // glwe_dimension
+        StandardDev(0.00000000000000029403601535432533), // glwe_modular_std_dev
+        DecompositionBaseLog(15), // pbs_base_log
+        DecompositionLevelCount(2), // pbs_level
+        DecompositionBaseLog(4), // glwe_ds_base_log
+        DecompositionLevelCount(4), // glwe_ds_level
+        PolynomialSize(256), // common_polynomial_size
+        FftType::Vanilla, // fft_type_ds
+        DecompositionBaseLog(7), // auto_base_log
+        DecompositionLevelCount(7), // auto_level
+        FftType::Split(37), // fft_type_auto
+        DecompositionBaseLog(8), // ss_base_log
+        DecompositionLevelCount(6), // ss_level
+        DecompositionBaseLog(5), // cbs_base_log
+        DecompositionLevelCount(3), // cbs_level
+        LutCountLog(2), // log_lut_count
+        CiphertextModulus::<u64>::new_native(), // ciphertext_modulus
+    );
+
+    // Same as AES192_SET_1: over the 14 S-box layers of AES-256, the failure probability
+    // is 2^-481.28 with 3 levels, above 2^-481.77 of AES-128, and 2^-523.32 with 4 levels.
+    pub static ref AES256_SET_1: AesParam<u64> = *AES192_SET_1;
+
+    // Same as AES192_SET_2: over the 14 S-box layers of AES-256, the failure probability
+    // is 2^-238.53 with 3 levels, above 2^-239.01 of AES-128, and 2^-248.72 with 4 levels.
+    pub static ref AES256_SET_2: AesParam<u64> = *AES192_SET_2;
+
+    // Same as AES192_SET_3: over the 14 S-box layers of AES-256, the failure probability
+    // is 2^-74.72 with 3 levels, above 2^-75.21 of AES-128, and 2^-75.83 with 4 levels.
+    pub static ref AES256_SET_3: AesParam<u64> = *AES192_SET_3;
+}
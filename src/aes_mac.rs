@@ -0,0 +1,140 @@
+//! Homomorphic CBC-MAC and CMAC over encrypted data with an encrypted AES key.
+use std::sync::OnceLock;
+use tfhe::core_crypto::prelude::*;
+use crate::{aes_he::*, aes_ref::*, cbs_keys::AesServerKey, utils::lwe_ciphertext_list_add_assign};
+
+/// Homomorphic AES-based MACs, where the messages are encrypted bitwise with bit i of byte b at index 8b + i.
+pub struct HeAesMac<'a, Scalar: UnsignedTorus> {
+    he_aes: HeAes<'a, Scalar>,
+    /// Encryptions of the CMAC subkeys K1 and K2, computed once from L = AES_K(0^128).
+    he_cmac_subkeys: OnceLock<(LweCiphertextListOwned<Scalar>, LweCiphertextListOwned<Scalar>)>,
+}
+
+impl<'a, Scalar> HeAesMac<'a, Scalar>
+where
+    Scalar: UnsignedTorus + CastInto<usize> + CastFrom<usize>,
+{
+    pub fn new(he_aes: HeAes<'a, Scalar>) -> Self {
+        HeAesMac {
+            he_aes,
+            he_cmac_subkeys: OnceLock::new(),
+        }
+    }
+
+    /// The round keys are expanded from the master key given by [`encrypt_aes_master_key`].
+    pub fn from_he_master_key<Cont>(server_key: &'a AesServerKey<Scalar>, he_master_key: &LweCiphertextList<Cont>) -> Self
+    where
+        Cont: Container<Element=Scalar>,
+    {
+        Self::new(HeAes::from_he_master_key(server_key, he_master_key))
+    }
+
+    pub fn he_aes(&self) -> &HeAes<'a, Scalar> {
+        &self.he_aes
+    }
+
+    /// CBC-MAC with the zero IV of a message of full blocks.
+    ///
+    /// The first block is the input of the first AES encryption as is, so its public bits skip the CBS of the first round.
+    pub fn cbc_mac<Cont>(&self, he_message: &LweCiphertextList<Cont>) -> LweCiphertextListOwned<Scalar>
+    where
+        Cont: Container<Element=Scalar>,
+    {
+        assert_eq!(he_message.entity_count() % BLOCKSIZE_IN_BIT, 0, "CBC-MAC message is not a multiple of the block size");
+
+        let mut he_state = LweCiphertextList::new(
+            Scalar::ZERO,
+            he_message.lwe_size(),
+            LweCiphertextCount(BLOCKSIZE_IN_BIT),
+            he_message.ciphertext_modulus(),
+        );
+        for he_block in he_message.chunks_exact(BLOCKSIZE_IN_BIT) {
+            lwe_ciphertext_list_add_assign(&mut he_state, he_block);
+            self.he_aes.encrypt_block(&mut he_state);
+        }
+
+        he_state
+    }
+
+    /// Encryptions of the CMAC subkeys K1 = dbl(L) and K2 = dbl(K1), where L = AES_K(0^128).
+    pub fn cmac_subkeys(&self) -> &(LweCiphertextListOwned<Scalar>, LweCiphertextListOwned<Scalar>) {
+        self.he_cmac_subkeys.get_or_init(|| {
+            let mut he_l = trivially_encrypt_aes_state(
+                [0u8; BLOCKSIZE_IN_BYTE],
+                self.he_aes.server_key().fourier_bsk().output_lwe_dimension().to_lwe_size(),
+                self.he_aes.he_round_keys()[0].ciphertext_modulus(),
+            );
+            self.he_aes.encrypt_block(&mut he_l);
+
+            let he_k1 = he_cmac_dbl(&he_l);
+            let he_k2 = he_cmac_dbl(&he_k1);
+            (he_k1, he_k2)
+        })
+    }
+
+    /// CMAC of an encrypted byte string, whose length is public.
+    ///
+    /// The last block is XORed with K1 if it is complete, or padded by public 10^* bits and XORed with K2 otherwise.
+    pub fn cmac<Cont>(&self, he_message: &LweCiphertextList<Cont>) -> LweCiphertextListOwned<Scalar>
+    where
+        Cont: Container<Element=Scalar>,
+    {
+        let num_bits = he_message.entity_count();
+        assert_eq!(num_bits % BYTESIZE, 0, "CMAC message is not a byte string");
+
+        let num_blocks = num_bits.div_ceil(BLOCKSIZE_IN_BIT).max(1);
+        let is_complete = num_bits != 0 && num_bits.is_multiple_of(BLOCKSIZE_IN_BIT);
+
+        let mut he_formatted = LweCiphertextList::new(
+            Scalar::ZERO,
+            he_message.lwe_size(),
+            LweCiphertextCount(BLOCKSIZE_IN_BIT * num_blocks),
+            he_message.ciphertext_modulus(),
+        );
+        he_formatted.get_sub_mut(0..num_bits).as_mut().copy_from_slice(he_message.as_ref());
+        if !is_complete {
+            // Padding byte 0x80
+            let mut he_pad_bit = he_formatted.get_mut(num_bits + BYTESIZE - 1);
+            *he_pad_bit.get_mut_body().data = Scalar::ONE << (Scalar::BITS - 1);
+        }
+
+        let (he_k1, he_k2) = self.cmac_subkeys();
+        let he_subkey = if is_complete {he_k1} else {he_k2};
+        let mut he_last_block = he_formatted.get_sub_mut(BLOCKSIZE_IN_BIT * (num_blocks - 1)..BLOCKSIZE_IN_BIT * num_blocks);
+        lwe_ciphertext_list_add_assign(&mut he_last_block, he_subkey.as_view());
+
+        self.cbc_mac(&he_formatted)
+    }
+}
+
+/// Doubling in GF(2^128) of CMAC on an encrypted block, by LWE additions only.
+pub fn he_cmac_dbl<Scalar, Cont>(he_block: &LweCiphertextList<Cont>) -> LweCiphertextListOwned<Scalar>
+where
+    Scalar: UnsignedInteger,
+    Cont: Container<Element=Scalar>,
+{
+    assert_eq!(he_block.entity_count(), BLOCKSIZE_IN_BIT);
+
+    // The block is the 128-bit big-endian integer (a127, …, a0), where a_p is at index 8(15 - p/8) + p%8
+    // 2 * (a127, …, a0)
+    // = (a126, …, a0, 0) + a127 * 0x87
+    // = (a126, …, a7, a6 + a127, a5, a4, a3, a2, a1 + a127, a0 + a127, a127)
+    let bit_idx = |p: usize| BYTESIZE * (BLOCKSIZE_IN_BYTE - 1 - p / BYTESIZE) + p % BYTESIZE;
+    let mut output = LweCiphertextList::new(
+        Scalar::ZERO,
+        he_block.lwe_size(),
+        LweCiphertextCount(BLOCKSIZE_IN_BIT),
+        he_block.ciphertext_modulus(),
+    );
+
+    for p in 1..BLOCKSIZE_IN_BIT {
+        output.get_mut(bit_idx(p)).as_mut().clone_from_slice(he_block.get(bit_idx(p-1)).as_ref());
+    }
+
+    let he_msb = he_block.get(bit_idx(BLOCKSIZE_IN_BIT-1));
+    for p in [0, 1, 2, 7] {
+        lwe_ciphertext_add_assign(&mut output.get_mut(bit_idx(p)), &he_msb);
+    }
+
+    output
+}
@@ -1,7 +1,7 @@
 use tfhe::core_crypto::prelude::*;
-use crate::FftType;
+use crate::{param_error::*, security::*, param_traits::*, FftType};
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub struct AesParam<Scalar: UnsignedInteger> {
     lwe_dimension: LweDimension,
     lwe_modular_std_dev: StandardDev,
@@ -25,7 +25,33 @@ pub struct AesParam<Scalar: UnsignedInteger> {
     ciphertext_modulus: CiphertextModulus::<Scalar>,
 }
 
+/// Arguments of [`AesParam::try_new`] and [`AesParam::new_unchecked`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AesParamFields<Scalar: UnsignedInteger> {
+    pub lwe_dimension: LweDimension,
+    pub lwe_modular_std_dev: StandardDev,
+    pub polynomial_size: PolynomialSize,
+    pub glwe_dimension: GlweDimension,
+    pub glwe_modular_std_dev: StandardDev,
+    pub pbs_base_log: DecompositionBaseLog,
+    pub pbs_level: DecompositionLevelCount,
+    pub glwe_ds_base_log: DecompositionBaseLog,
+    pub glwe_ds_level: DecompositionLevelCount,
+    pub common_polynomial_size: PolynomialSize,
+    pub fft_type_ds: FftType,
+    pub auto_base_log: DecompositionBaseLog,
+    pub auto_level: DecompositionLevelCount,
+    pub fft_type_auto: FftType,
+    pub ss_base_log: DecompositionBaseLog,
+    pub ss_level: DecompositionLevelCount,
+    pub cbs_base_log: DecompositionBaseLog,
+    pub cbs_level: DecompositionLevelCount,
+    pub log_lut_count: LutCountLog,
+    pub ciphertext_modulus: CiphertextModulus::<Scalar>,
+}
+
 impl<Scalar: UnsignedInteger> AesParam<Scalar> {
+    /// Panics with the list of violated constraints if the parameters are invalid.
     pub fn new(
         lwe_dimension: LweDimension,
         lwe_modular_std_dev: StandardDev,
@@ -48,8 +74,61 @@ impl<Scalar: UnsignedInteger> AesParam<Scalar> {
         log_lut_count: LutCountLog,
         ciphertext_modulus: CiphertextModulus::<Scalar>,
     ) -> Self {
-        assert!(lwe_dimension.0 % common_polynomial_size.0 == 0);
-        assert!(polynomial_size.0 % common_polynomial_size.0 == 0);
+        Self::try_new(AesParamFields {
+            lwe_dimension,
+            lwe_modular_std_dev,
+            polynomial_size,
+            glwe_dimension,
+            glwe_modular_std_dev,
+            pbs_base_log,
+            pbs_level,
+            glwe_ds_base_log,
+            glwe_ds_level,
+            common_polynomial_size,
+            fft_type_ds,
+            auto_base_log,
+            auto_level,
+            fft_type_auto,
+            ss_base_log,
+            ss_level,
+            cbs_base_log,
+            cbs_level,
+            log_lut_count,
+            ciphertext_modulus,
+        }).unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    pub fn try_new(fields: AesParamFields<Scalar>) -> Result<Self, ParamError> {
+        let param = Self::new_unchecked(fields);
+        param.validate()?;
+
+        Ok(param)
+    }
+
+    /// Skips the validation, e.g. for toy parameters to be checked by `validate_with_min_security_bits`.
+    pub fn new_unchecked(fields: AesParamFields<Scalar>) -> Self {
+        let AesParamFields {
+            lwe_dimension,
+            lwe_modular_std_dev,
+            polynomial_size,
+            glwe_dimension,
+            glwe_modular_std_dev,
+            pbs_base_log,
+            pbs_level,
+            glwe_ds_base_log,
+            glwe_ds_level,
+            common_polynomial_size,
+            fft_type_ds,
+            auto_base_log,
+            auto_level,
+            fft_type_auto,
+            ss_base_log,
+            ss_level,
+            cbs_base_log,
+            cbs_level,
+            log_lut_count,
+            ciphertext_modulus,
+        } = fields;
 
         AesParam {
             lwe_dimension,
@@ -75,6 +154,40 @@ impl<Scalar: UnsignedInteger> AesParam<Scalar> {
         }
     }
 
+    /// Validates with the default security threshold of `DEFAULT_MIN_SECURITY_BITS`.
+    pub fn validate(&self) -> Result<(), ParamError> {
+        self.validate_with_min_security_bits(DEFAULT_MIN_SECURITY_BITS)
+    }
+
+    /// A threshold of zero explicitly allows insecure parameters, e.g. toy parameters for experiments.
+    pub fn validate_with_min_security_bits(&self, min_security_bits: usize) -> Result<(), ParamError> {
+        let mut checker = ParamChecker::new(Scalar::BITS);
+
+        checker.power_of_two("polynomial_size", self.polynomial_size.0);
+        checker.power_of_two("common_polynomial_size", self.common_polynomial_size.0);
+        checker.divisible("lwe_dimension", self.lwe_dimension.0, self.common_polynomial_size.0);
+        checker.divisible("polynomial_size", self.polynomial_size.0, self.common_polynomial_size.0);
+        checker.decomposition("pbs", self.pbs_base_log.0, self.pbs_level.0);
+        checker.decomposition("glwe_ds", self.glwe_ds_base_log.0, self.glwe_ds_level.0);
+        checker.fft_split("glwe_ds", self.fft_type_ds, self.glwe_ds_base_log.0, self.common_polynomial_size.0);
+        checker.decomposition("auto", self.auto_base_log.0, self.auto_level.0);
+        checker.fft_split("auto", self.fft_type_auto, self.auto_base_log.0, self.polynomial_size.0);
+        checker.decomposition("ss", self.ss_base_log.0, self.ss_level.0);
+        checker.cbs_decomposition(self.cbs_base_log.0, self.cbs_level.0, self.log_lut_count.0);
+
+        checker.security(|| self.security(), min_security_bits);
+
+        checker.finish()
+    }
+
+    /// Estimated security of the secret keys.
+    pub fn security(&self) -> SecurityReport {
+        SecurityReport::new(vec![
+            ("lwe", estimate_lwe_security(self.lwe_dimension.0, self.lwe_modular_std_dev, Scalar::BITS)),
+            ("glwe", estimate_glwe_security(self.glwe_dimension, self.polynomial_size, self.glwe_modular_std_dev, Scalar::BITS)),
+        ])
+    }
+
     pub fn lwe_dimension(&self) -> LweDimension {
         self.lwe_dimension
     }
@@ -155,3 +268,9 @@ impl<Scalar: UnsignedInteger> AesParam<Scalar> {
         self.ciphertext_modulus
     }
 }
+
+impl_pbs_parameters!(AesParam);
+impl_glwe_ds_parameters!(AesParam);
+impl_automorphism_parameters!(AesParam);
+impl_scheme_switching_parameters!(AesParam);
+impl_cbs_parameters!(AesParam; log_lut_count);
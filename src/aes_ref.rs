@@ -17,6 +17,25 @@ pub const AES128_SBOX: [u8; 256] = [
     0x8C, 0xA1, 0x89, 0x0D, 0xBF, 0xE6, 0x42, 0x68, 0x41, 0x99, 0x2D, 0x0F, 0xB0, 0x54, 0xBB, 0x16,
 ];
 
+pub const AES128_INV_SBOX: [u8; 256] = [
+    0x52, 0x09, 0x6A, 0xD5, 0x30, 0x36, 0xA5, 0x38, 0xBF, 0x40, 0xA3, 0x9E, 0x81, 0xF3, 0xD7, 0xFB,
+    0x7C, 0xE3, 0x39, 0x82, 0x9B, 0x2F, 0xFF, 0x87, 0x34, 0x8E, 0x43, 0x44, 0xC4, 0xDE, 0xE9, 0xCB,
+    0x54, 0x7B, 0x94, 0x32, 0xA6, 0xC2, 0x23, 0x3D, 0xEE, 0x4C, 0x95, 0x0B, 0x42, 0xFA, 0xC3, 0x4E,
+    0x08, 0x2E, 0xA1, 0x66, 0x28, 0xD9, 0x24, 0xB2, 0x76, 0x5B, 0xA2, 0x49, 0x6D, 0x8B, 0xD1, 0x25,
+    0x72, 0xF8, 0xF6, 0x64, 0x86, 0x68, 0x98, 0x16, 0xD4, 0xA4, 0x5C, 0xCC, 0x5D, 0x65, 0xB6, 0x92,
+    0x6C, 0x70, 0x48, 0x50, 0xFD, 0xED, 0xB9, 0xDA, 0x5E, 0x15, 0x46, 0x57, 0xA7, 0x8D, 0x9D, 0x84,
+    0x90, 0xD8, 0xAB, 0x00, 0x8C, 0xBC, 0xD3, 0x0A, 0xF7, 0xE4, 0x58, 0x05, 0xB8, 0xB3, 0x45, 0x06,
+    0xD0, 0x2C, 0x1E, 0x8F, 0xCA, 0x3F, 0x0F, 0x02, 0xC1, 0xAF, 0xBD, 0x03, 0x01, 0x13, 0x8A, 0x6B,
+    0x3A, 0x91, 0x11, 0x41, 0x4F, 0x67, 0xDC, 0xEA, 0x97, 0xF2, 0xCF, 0xCE, 0xF0, 0xB4, 0xE6, 0x73,
+    0x96, 0xAC, 0x74, 0x22, 0xE7, 0xAD, 0x35, 0x85, 0xE2, 0xF9, 0x37, 0xE8, 0x1C, 0x75, 0xDF, 0x6E,
+    0x47, 0xF1, 0x1A, 0x71, 0x1D, 0x29, 0xC5, 0x89, 0x6F, 0xB7, 0x62, 0x0E, 0xAA, 0x18, 0xBE, 0x1B,
+    0xFC, 0x56, 0x3E, 0x4B, 0xC6, 0xD2, 0x79, 0x20, 0x9A, 0xDB, 0xC0, 0xFE, 0x78, 0xCD, 0x5A, 0xF4,
+    0x1F, 0xDD, 0xA8, 0x33, 0x88, 0x07, 0xC7, 0x31, 0xB1, 0x12, 0x10, 0x59, 0x27, 0x80, 0xEC, 0x5F,
+    0x60, 0x51, 0x7F, 0xA9, 0x19, 0xB5, 0x4A, 0x0D, 0x2D, 0xE5, 0x7A, 0x9F, 0x93, 0xC9, 0x9C, 0xEF,
+    0xA0, 0xE0, 0x3B, 0x4D, 0xAE, 0x2A, 0xF5, 0xB0, 0xC8, 0xEB, 0xBB, 0x3C, 0x83, 0x53, 0x99, 0x61,
+    0x17, 0x2B, 0x04, 0x7E, 0xBA, 0x77, 0xD6, 0x26, 0xE1, 0x69, 0x14, 0x63, 0x55, 0x21, 0x0C, 0x7D,
+];
+
 pub const RCON: [u8; 11] = [
     0x00, 0x01, 0x02, 0x04, 0x08, 0x10, 0x20, 0x40, 0x80, 0x1B, 0x36,
 ];
@@ -26,27 +45,141 @@ pub const BLOCKSIZE_IN_BYTE: usize = 16;
 pub const BLOCKSIZE_IN_BIT: usize = 128;
 pub const NUM_COLUMNS: usize = 4;
 pub const NUM_ROWS: usize = 4;
+/// Number of rounds of AES-128.
 pub const NUM_ROUNDS: usize = 10;
 
 pub type StateByteArray = [u8; BLOCKSIZE_IN_BYTE];
 pub type StateByteMat = [[u8; NUM_COLUMNS]; NUM_ROWS];
 pub type StateBitArray = [u8; BLOCKSIZE_IN_BIT];
 
-pub struct Aes128Ref {
-    key: StateByteArray,
-    rk_mat: [StateByteMat; NUM_ROUNDS+1],
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AesKeySize {
+    Aes128,
+    Aes192,
+    Aes256,
 }
 
-impl Aes128Ref {
-    pub fn new(master_key: &StateByteArray) -> Aes128Ref {
-        let mut key = [0u8; BLOCKSIZE_IN_BYTE];
-        for i in 0..BLOCKSIZE_IN_BYTE {
-            key[i] = master_key[i];
+impl AesKeySize {
+    pub fn from_key_len(key_len_in_byte: usize) -> Option<Self> {
+        match key_len_in_byte {
+            16 => Some(AesKeySize::Aes128),
+            24 => Some(AesKeySize::Aes192),
+            32 => Some(AesKeySize::Aes256),
+            _ => None,
+        }
+    }
+
+    pub fn from_num_rounds(num_rounds: usize) -> Option<Self> {
+        [AesKeySize::Aes128, AesKeySize::Aes192, AesKeySize::Aes256].into_iter()
+            .find(|key_size| key_size.num_rounds() == num_rounds)
+    }
+
+    pub fn key_len_in_byte(&self) -> usize {
+        4 * self.num_key_words()
+    }
+
+    /// Number of 32-bit words of the key, Nk in FIPS 197.
+    pub fn num_key_words(&self) -> usize {
+        match self {
+            AesKeySize::Aes128 => 4,
+            AesKeySize::Aes192 => 6,
+            AesKeySize::Aes256 => 8,
+        }
+    }
+
+    /// Number of rounds, i.e., of S-box layers.
+    pub fn num_rounds(&self) -> usize {
+        self.num_key_words() + 6
+    }
+
+    /// Number of 32-bit words of the expanded key, i.e., of the round keys.
+    pub fn num_expanded_key_words(&self) -> usize {
+        NUM_COLUMNS * (self.num_rounds() + 1)
+    }
+
+    /// Key expansion of FIPS 197 as XORs of the master key words and of the SubWord outputs.
+    pub fn key_expansion_terms(&self) -> KeyExpansionTerms {
+        let nk = self.num_key_words();
+
+        let mut word_terms: Vec<Vec<usize>> = (0..nk).map(|j| vec![j]).collect();
+        let mut sub_words = Vec::<SubWordInput>::new();
+        for i in nk..self.num_expanded_key_words() {
+            let temp = if i % nk == 0 || (nk > 6 && i % nk == 4) {
+                sub_words.push(SubWordInput {
+                    word: i - 1,
+                    rcon: if i % nk == 0 {Some(RCON[i / nk])} else {None},
+                });
+                vec![nk + sub_words.len() - 1]
+            } else {
+                word_terms[i-1].clone()
+            };
+
+            word_terms.push(xor_terms(&word_terms[i-nk], &temp));
         }
-        let rk_mat = [[[0u8; NUM_COLUMNS]; NUM_ROWS]; NUM_ROUNDS+1];
 
-        let mut aes = Aes128Ref {
-            key: key,
+        KeyExpansionTerms {
+            num_key_words: nk,
+            word_terms,
+            sub_words,
+        }
+    }
+}
+
+/// Words w[i] of the key expansion as XORs of terms, where the term j < Nk is the master key word j
+/// and the term Nk + t is the output of the t-th SubWord.
+///
+/// Terms occurring twice in the XOR chain of the key expansion cancel out, so every term occurs at most once
+/// in a word. The homomorphic key expansion adds the ciphertexts of the terms instead of following the chain,
+/// whose noise would grow with every repeated term.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyExpansionTerms {
+    /// Nk, the number of master key terms.
+    pub num_key_words: usize,
+    /// Sorted terms of each word w[i].
+    pub word_terms: Vec<Vec<usize>>,
+    pub sub_words: Vec<SubWordInput>,
+}
+
+/// Input of a SubWord in the key expansion.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SubWordInput {
+    /// Index i of the input word w[i].
+    pub word: usize,
+    /// RotWord is applied before and Rcon after SubWord if set.
+    pub rcon: Option<u8>,
+}
+
+fn xor_terms(lhs: &[usize], rhs: &[usize]) -> Vec<usize> {
+    let mut output = lhs.iter().filter(|term| !rhs.contains(term))
+        .chain(rhs.iter().filter(|term| !lhs.contains(term)))
+        .copied()
+        .collect::<Vec<usize>>();
+    output.sort_unstable();
+
+    output
+}
+
+pub struct AesRef {
+    key_size: AesKeySize,
+    key: Vec<u8>,
+    rk_mat: Vec<StateByteMat>,
+}
+
+/// Same as [`AesRef`], which also accepts 24- and 32-byte keys: the key size is given by the
+/// key length, not by this alias.
+#[deprecated(note = "use `AesRef`, which takes the key size from the key length")]
+pub type Aes128Ref = AesRef;
+
+impl AesRef {
+    /// Panics if the key is not of 16, 24 or 32 bytes.
+    pub fn new(master_key: &[u8]) -> AesRef {
+        let key_size = AesKeySize::from_key_len(master_key.len())
+            .unwrap_or_else(|| panic!("invalid AES key length {}", master_key.len()));
+        let rk_mat = vec![[[0u8; NUM_COLUMNS]; NUM_ROWS]; key_size.num_rounds()+1];
+
+        let mut aes = AesRef {
+            key_size,
+            key: master_key.to_vec(),
             rk_mat: rk_mat,
         };
         aes.expand_key();
@@ -54,55 +187,58 @@ impl Aes128Ref {
         aes
     }
 
-    fn expand_key(&mut self) {
-        for col in 0..NUM_COLUMNS {
-            for row in 0..NUM_ROWS {
-                self.rk_mat[0][col][row] = self.key[4*col + row];
-            }
-        }
+    pub fn key_size(&self) -> AesKeySize {
+        self.key_size
+    }
 
-        for r in 1..=NUM_ROUNDS {
-            let cur_key = self.rk_mat[r-1];
-            self.rk_mat[r] = cur_key;
+    pub fn num_rounds(&self) -> usize {
+        self.key_size.num_rounds()
+    }
 
-            let sub_rot_key = [
-                AES128_SBOX[cur_key[3][1] as usize],
-                AES128_SBOX[cur_key[3][2] as usize],
-                AES128_SBOX[cur_key[3][3] as usize],
-                AES128_SBOX[cur_key[3][0] as usize],
-            ];
+    /// Key expansion of FIPS 197 on the words w[i], where w[4r + col] is the column col of the round key r.
+    fn expand_key(&mut self) {
+        let nk = self.key_size.num_key_words();
+        let num_words = NUM_COLUMNS * (self.num_rounds() + 1);
 
-            for row in 0..NUM_ROWS {
-                self.rk_mat[r][0][row] ^= sub_rot_key[row];
+        let mut words = Vec::<[u8; NUM_ROWS]>::with_capacity(num_words);
+        for i in 0..nk {
+            words.push([self.key[4*i], self.key[4*i + 1], self.key[4*i + 2], self.key[4*i + 3]]);
+        }
+
+        for i in nk..num_words {
+            let mut temp = words[i-1];
+            if i % nk == 0 {
+                temp = [
+                    AES128_SBOX[temp[1] as usize] ^ RCON[i / nk],
+                    AES128_SBOX[temp[2] as usize],
+                    AES128_SBOX[temp[3] as usize],
+                    AES128_SBOX[temp[0] as usize],
+                ];
+            } else if nk > 6 && i % nk == 4 {
+                temp = temp.map(|byte| AES128_SBOX[byte as usize]);
             }
-            self.rk_mat[r][0][0] ^= RCON[r];
 
-            for col in 1..NUM_COLUMNS {
-                for row in 0..NUM_ROWS {
-                    self.rk_mat[r][col][row] ^= self.rk_mat[r][col-1][row];
-                }
+            let mut word = words[i-nk];
+            for row in 0..NUM_ROWS {
+                word[row] ^= temp[row];
             }
+            words.push(word);
         }
-    }
 
-    pub fn get_round_keys(&self) -> [StateByteArray; NUM_ROUNDS+1] {
-        let mut round_keys = [[0u8; BLOCKSIZE_IN_BYTE]; NUM_ROUNDS+1];
-        for r in 0..=NUM_ROUNDS {
-            for col in 0..NUM_COLUMNS {
-                for row in 0..NUM_ROWS {
-                    round_keys[r][4*col + row] = self.rk_mat[r][col][row]
-                }
-            }
+        for (i, word) in words.into_iter().enumerate() {
+            self.rk_mat[i / NUM_COLUMNS][i % NUM_COLUMNS] = word;
         }
+    }
 
-        round_keys
+    pub fn get_round_keys(&self) -> Vec<StateByteArray> {
+        self.rk_mat.iter().map(|&rk| byte_mat_to_array(rk)).collect()
     }
 
     pub fn encrypt_block(&self, message: StateByteArray) -> StateByteArray {
         let mut state = byte_array_to_mat(message);
         self.add_round_key(&mut state, 0);
 
-        for r in 1..NUM_ROUNDS {
+        for r in 1..self.num_rounds() {
             self.sub_bytes(&mut state);
             self.shift_rows(&mut state);
             self.mix_columns(&mut state);
@@ -111,7 +247,25 @@ impl Aes128Ref {
 
         self.sub_bytes(&mut state);
         self.shift_rows(&mut state);
-        self.add_round_key(&mut state, NUM_ROUNDS);
+        self.add_round_key(&mut state, self.num_rounds());
+
+        byte_mat_to_array(state)
+    }
+
+    pub fn decrypt_block(&self, ciphertext: StateByteArray) -> StateByteArray {
+        let mut state = byte_array_to_mat(ciphertext);
+        self.add_round_key(&mut state, self.num_rounds());
+
+        for r in (1..self.num_rounds()).rev() {
+            self.inv_shift_rows(&mut state);
+            self.inv_sub_bytes(&mut state);
+            self.add_round_key(&mut state, r);
+            self.inv_mix_columns(&mut state);
+        }
+
+        self.inv_shift_rows(&mut state);
+        self.inv_sub_bytes(&mut state);
+        self.add_round_key(&mut state, 0);
 
         byte_mat_to_array(state)
     }
@@ -152,6 +306,85 @@ impl Aes128Ref {
             }
         }
     }
+
+    pub fn inv_sub_bytes(&self, state: &mut StateByteMat) {
+        for col in 0..NUM_COLUMNS {
+            for row in 0..NUM_ROWS {
+                state[col][row] = AES128_INV_SBOX[state[col][row] as usize];
+            }
+        }
+    }
+
+    pub fn inv_shift_rows(&self, state: &mut StateByteMat) {
+        let buf = *state;
+        for row in 0..NUM_ROWS {
+            for col in 0..NUM_COLUMNS {
+                state[(row + col) % NUM_COLUMNS][row] = buf[col][row];
+            }
+        }
+    }
+
+    pub fn inv_mix_columns(&self, state: &mut StateByteMat) {
+        let buf = *state;
+        for col in 0..NUM_COLUMNS {
+            for row in 0..NUM_ROWS {
+                state[col][row] = mult(buf[col][row], 14)
+                    ^ mult(buf[col][(row + 1) % NUM_ROWS], 11)
+                    ^ mult(buf[col][(row + 2) % NUM_ROWS], 13)
+                    ^ mult(buf[col][(row + 3) % NUM_ROWS], 9);
+            }
+        }
+    }
+
+    /// Hash subkey H = AES_K(0^128) of GHASH.
+    pub fn gcm_hash_subkey(&self) -> StateByteArray {
+        self.encrypt_block([0u8; BLOCKSIZE_IN_BYTE])
+    }
+
+    /// AES-GCM encryption of SP 800-38D with a 96-bit IV, which returns the ciphertext and the 128-bit tag.
+    pub fn gcm_encrypt(&self, iv: &GcmIv, aad: &[u8], plaintext: &[u8]) -> (Vec<u8>, StateByteArray) {
+        let j0 = gcm_initial_counter_block(iv);
+
+        let mut counter = j0;
+        let ciphertext = plaintext.chunks(BLOCKSIZE_IN_BYTE).flat_map(|plaintext_block| {
+            counter = gcm_inc32(counter);
+            let keystream = self.encrypt_block(counter);
+            plaintext_block.iter().zip(keystream.iter()).map(|(p, k)| p ^ k).collect::<Vec<u8>>()
+        }).collect::<Vec<u8>>();
+
+        let s = ghash(self.gcm_hash_subkey(), &gcm_ghash_blocks(aad, &ciphertext));
+        let mut tag = self.encrypt_block(j0);
+        for (t, s) in tag.iter_mut().zip(s.iter()) {
+            *t ^= s;
+        }
+
+        (ciphertext, tag)
+    }
+
+    /// CBC-MAC with the zero IV, i.e. the last block of the CBC encryption, of a message of full blocks.
+    pub fn cbc_mac(&self, message: &[u8]) -> StateByteArray {
+        assert_eq!(message.len() % BLOCKSIZE_IN_BYTE, 0, "CBC-MAC message is not a multiple of the block size");
+
+        message.chunks_exact(BLOCKSIZE_IN_BYTE).fold([0u8; BLOCKSIZE_IN_BYTE], |state, block| {
+            let mut state = state;
+            for (s, m) in state.iter_mut().zip(block.iter()) {
+                *s ^= m;
+            }
+            self.encrypt_block(state)
+        })
+    }
+
+    /// Subkeys K1 = dbl(L) and K2 = dbl(K1) of CMAC, where L = AES_K(0^128).
+    pub fn cmac_subkeys(&self) -> (StateByteArray, StateByteArray) {
+        let k1 = cmac_dbl(self.encrypt_block([0u8; BLOCKSIZE_IN_BYTE]));
+        (k1, cmac_dbl(k1))
+    }
+
+    /// CMAC of SP 800-38B: the CBC-MAC of the message whose last block is XORed with K1 if it is complete,
+    /// or padded by 10^* and XORed with K2 otherwise (including the empty message).
+    pub fn cmac(&self, message: &[u8]) -> StateByteArray {
+        self.cbc_mac(&cmac_last_block_formatted(message, self.cmac_subkeys()))
+    }
 }
 
 pub fn byte_array_to_mat(input: StateByteArray) -> StateByteMat {
@@ -199,3 +432,136 @@ fn mult_by_two(a: u8) -> u8 {
         a << 1
     }
 }
+
+/// Multiplication in GF(2^8) by shift-and-add.
+pub(crate) fn mult(a: u8, b: u8) -> u8 {
+    let mut output = 0u8;
+    let mut a = a;
+    for i in 0..BYTESIZE {
+        if b & (1 << i) != 0 {
+            output ^= a;
+        }
+        a = mult_by_two(a);
+    }
+
+    output
+}
+
+
+/* ======== GCM ======== */
+
+/// Length of the GCM IVs in bytes. Only 96-bit IVs are supported, whose initial counter block is public.
+pub const GCM_IV_LEN_IN_BYTE: usize = 12;
+
+pub type GcmIv = [u8; GCM_IV_LEN_IN_BYTE];
+
+/// Multiplication in GF(2^128) = GF(2)[x] / (x^128 + x^7 + x^2 + x + 1) as in SP 800-38D,
+/// where the MSB of the first byte of a block is the coefficient of x^0 and the LSB of the last byte that of x^127.
+pub fn gf128_mul(x: StateByteArray, y: StateByteArray) -> StateByteArray {
+    const R: u128 = 0xE1 << 120;
+
+    let x = u128::from_be_bytes(x);
+    let mut v = u128::from_be_bytes(y);
+    let mut z = 0u128;
+    for i in 0..BLOCKSIZE_IN_BIT {
+        if x & (1 << (BLOCKSIZE_IN_BIT - 1 - i)) != 0 {
+            z ^= v;
+        }
+        v = if v & 1 != 0 {(v >> 1) ^ R} else {v >> 1};
+    }
+
+    z.to_be_bytes()
+}
+
+/// GHASH of SP 800-38D: Y_i = (Y_{i-1} + X_i) * H from Y_0 = 0.
+pub fn ghash(h: StateByteArray, blocks: &[StateByteArray]) -> StateByteArray {
+    blocks.iter().fold([0u8; BLOCKSIZE_IN_BYTE], |y, x| {
+        let mut y = y;
+        for (y, x) in y.iter_mut().zip(x.iter()) {
+            *y ^= x;
+        }
+        gf128_mul(y, h)
+    })
+}
+
+/// GHASH input of GCM: the AAD and the ciphertext, each zero-padded to full blocks,
+/// followed by their lengths in bits as 64-bit big-endian integers.
+pub fn gcm_ghash_blocks(aad: &[u8], ciphertext: &[u8]) -> Vec<StateByteArray> {
+    let mut blocks = Vec::with_capacity(aad.len().div_ceil(BLOCKSIZE_IN_BYTE) + ciphertext.len().div_ceil(BLOCKSIZE_IN_BYTE) + 1);
+    for data in [aad, ciphertext] {
+        for chunk in data.chunks(BLOCKSIZE_IN_BYTE) {
+            let mut block = [0u8; BLOCKSIZE_IN_BYTE];
+            block[..chunk.len()].copy_from_slice(chunk);
+            blocks.push(block);
+        }
+    }
+
+    let mut len_block = [0u8; BLOCKSIZE_IN_BYTE];
+    len_block[..8].copy_from_slice(&((BYTESIZE * aad.len()) as u64).to_be_bytes());
+    len_block[8..].copy_from_slice(&((BYTESIZE * ciphertext.len()) as u64).to_be_bytes());
+    blocks.push(len_block);
+
+    blocks
+}
+
+/// Initial counter block J0 = IV || 0^31 || 1 of a 96-bit IV.
+pub fn gcm_initial_counter_block(iv: &GcmIv) -> StateByteArray {
+    let mut j0 = [0u8; BLOCKSIZE_IN_BYTE];
+    j0[..GCM_IV_LEN_IN_BYTE].copy_from_slice(iv);
+    j0[BLOCKSIZE_IN_BYTE - 1] = 1;
+
+    j0
+}
+
+/// Increments the last 32 bits of a counter block modulo 2^32.
+pub fn gcm_inc32(counter: StateByteArray) -> StateByteArray {
+    let mut output = counter;
+    let ctr = u32::from_be_bytes([counter[12], counter[13], counter[14], counter[15]]).wrapping_add(1);
+    output[12..].copy_from_slice(&ctr.to_be_bytes());
+
+    output
+}
+
+/// x^m modulo x^128 + x^7 + x^2 + x + 1, where the coefficient of x^c is the bit c.
+///
+/// This is the polynomial order, i.e. the bit-reversal of the block order of [`gf128_mul`].
+pub fn gf128_reduced_monomial(m: usize) -> u128 {
+    (0..m).fold(1u128, |v, _| {
+        if v >> (BLOCKSIZE_IN_BIT - 1) != 0 {(v << 1) ^ 0x87} else {v << 1}
+    })
+}
+
+/// Index of the coefficient of x^c in the bits of a block, where bit i of byte b is at index 8b + i.
+pub fn gf128_coeff_to_bit_idx(c: usize) -> usize {
+    BYTESIZE * (c / BYTESIZE) + (BYTESIZE - 1 - c % BYTESIZE)
+}
+
+/* ======== CMAC ======== */
+
+/// Doubling in GF(2^128) of SP 800-38B: the block as a 128-bit big-endian integer shifted to the left,
+/// with the reduction by x^128 + x^7 + x^2 + x + 1 if the MSB was set.
+pub fn cmac_dbl(block: StateByteArray) -> StateByteArray {
+    let v = u128::from_be_bytes(block);
+    let reduction = if v >> (BLOCKSIZE_IN_BIT - 1) != 0 {0x87} else {0};
+
+    ((v << 1) ^ reduction).to_be_bytes()
+}
+
+/// Message of full blocks whose CBC-MAC is the CMAC of `message` with the given subkeys.
+pub fn cmac_last_block_formatted(message: &[u8], (k1, k2): (StateByteArray, StateByteArray)) -> Vec<u8> {
+    let num_blocks = message.len().div_ceil(BLOCKSIZE_IN_BYTE).max(1);
+    let is_complete = !message.is_empty() && message.len().is_multiple_of(BLOCKSIZE_IN_BYTE);
+
+    let mut output = message.to_vec();
+    output.resize(BLOCKSIZE_IN_BYTE * num_blocks, 0);
+    if !is_complete {
+        output[message.len()] = 0x80;
+    }
+
+    let subkey = if is_complete {k1} else {k2};
+    for (byte, k) in output[BLOCKSIZE_IN_BYTE * (num_blocks - 1)..].iter_mut().zip(subkey.iter()) {
+        *byte ^= k;
+    }
+
+    output
+}
@@ -1,7 +1,7 @@
 use tfhe::core_crypto::prelude::*;
-use crate::FftType;
+use crate::{param_error::*, security::*, FftType};
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub struct PkskConvParam<Scalar: UnsignedInteger> {
     lwe_dimension: LweDimension,
     lwe_modular_std_dev: StandardDev,
@@ -15,7 +15,22 @@ pub struct PkskConvParam<Scalar: UnsignedInteger> {
     ciphertext_modulus: CiphertextModulus<Scalar>,
 }
 
-#[derive(Clone, Copy)]
+/// Arguments of [`PkskConvParam::try_new`] and [`PkskConvParam::new_unchecked`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PkskConvParamFields<Scalar: UnsignedInteger> {
+    pub lwe_dimension: LweDimension,
+    pub lwe_modular_std_dev: StandardDev,
+    pub polynomial_size: PolynomialSize,
+    pub glwe_dimension: GlweDimension,
+    pub glwe_modular_std_dev: StandardDev,
+    pub pksk_base_log: DecompositionBaseLog,
+    pub pksk_level: DecompositionLevelCount,
+    pub ks_base_log: DecompositionBaseLog,
+    pub ks_level: DecompositionLevelCount,
+    pub ciphertext_modulus: CiphertextModulus<Scalar>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub struct AutoConvParam<Scalar: UnsignedInteger> {
     polynomial_size: PolynomialSize,
     glwe_dimension: GlweDimension,
@@ -27,6 +42,7 @@ pub struct AutoConvParam<Scalar: UnsignedInteger> {
 }
 
 impl<Scalar: UnsignedInteger> PkskConvParam<Scalar> {
+    /// Panics with the list of violated constraints if the parameters are invalid.
     pub fn new(
         lwe_dimension: LweDimension,
         lwe_modular_std_dev: StandardDev,
@@ -39,6 +55,42 @@ impl<Scalar: UnsignedInteger> PkskConvParam<Scalar> {
         ks_level: DecompositionLevelCount,
         ciphertext_modulus: CiphertextModulus<Scalar>,
     ) -> Self {
+        Self::try_new(PkskConvParamFields {
+            lwe_dimension,
+            lwe_modular_std_dev,
+            polynomial_size,
+            glwe_dimension,
+            glwe_modular_std_dev,
+            pksk_base_log,
+            pksk_level,
+            ks_base_log,
+            ks_level,
+            ciphertext_modulus,
+        }).unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    pub fn try_new(fields: PkskConvParamFields<Scalar>) -> Result<Self, ParamError> {
+        let param = Self::new_unchecked(fields);
+        param.validate()?;
+
+        Ok(param)
+    }
+
+    /// Skips the validation, e.g. for toy parameters to be checked by `validate_with_min_security_bits`.
+    pub fn new_unchecked(fields: PkskConvParamFields<Scalar>) -> Self {
+        let PkskConvParamFields {
+            lwe_dimension,
+            lwe_modular_std_dev,
+            polynomial_size,
+            glwe_dimension,
+            glwe_modular_std_dev,
+            pksk_base_log,
+            pksk_level,
+            ks_base_log,
+            ks_level,
+            ciphertext_modulus,
+        } = fields;
+
         PkskConvParam {
             lwe_dimension,
             lwe_modular_std_dev,
@@ -53,6 +105,32 @@ impl<Scalar: UnsignedInteger> PkskConvParam<Scalar> {
         }
     }
 
+    /// Validates with the default security threshold of `DEFAULT_MIN_SECURITY_BITS`.
+    pub fn validate(&self) -> Result<(), ParamError> {
+        self.validate_with_min_security_bits(DEFAULT_MIN_SECURITY_BITS)
+    }
+
+    /// A threshold of zero explicitly allows insecure parameters, e.g. toy parameters for experiments.
+    pub fn validate_with_min_security_bits(&self, min_security_bits: usize) -> Result<(), ParamError> {
+        let mut checker = ParamChecker::new(Scalar::BITS);
+
+        checker.power_of_two("polynomial_size", self.polynomial_size.0);
+        checker.decomposition("pksk", self.pksk_base_log.0, self.pksk_level.0);
+        checker.decomposition("ks", self.ks_base_log.0, self.ks_level.0);
+
+        checker.security(|| self.security(), min_security_bits);
+
+        checker.finish()
+    }
+
+    /// Estimated security of the secret keys.
+    pub fn security(&self) -> SecurityReport {
+        SecurityReport::new(vec![
+            ("lwe", estimate_lwe_security(self.lwe_dimension.0, self.lwe_modular_std_dev, Scalar::BITS)),
+            ("glwe", estimate_glwe_security(self.glwe_dimension, self.polynomial_size, self.glwe_modular_std_dev, Scalar::BITS)),
+        ])
+    }
+
     pub fn lwe_dimension(&self) -> LweDimension {
         self.lwe_dimension
     }
@@ -95,6 +173,7 @@ impl<Scalar: UnsignedInteger> PkskConvParam<Scalar> {
 }
 
 impl<Scalar: UnsignedInteger> AutoConvParam<Scalar> {
+    /// Panics with the list of violated constraints if the parameters are invalid.
     pub fn new(
         polynomial_size: PolynomialSize,
         glwe_dimension: GlweDimension,
@@ -103,6 +182,50 @@ impl<Scalar: UnsignedInteger> AutoConvParam<Scalar> {
         auto_level: DecompositionLevelCount,
         fft_type: FftType,
         ciphertext_modulus: CiphertextModulus<Scalar>,
+    ) -> Self {
+        Self::try_new(
+            polynomial_size,
+            glwe_dimension,
+            glwe_modular_std_dev,
+            auto_base_log,
+            auto_level,
+            fft_type,
+            ciphertext_modulus,
+        ).unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    pub fn try_new(
+        polynomial_size: PolynomialSize,
+        glwe_dimension: GlweDimension,
+        glwe_modular_std_dev: StandardDev,
+        auto_base_log: DecompositionBaseLog,
+        auto_level: DecompositionLevelCount,
+        fft_type: FftType,
+        ciphertext_modulus: CiphertextModulus<Scalar>,
+    ) -> Result<Self, ParamError> {
+        let param = Self::new_unchecked(
+            polynomial_size,
+            glwe_dimension,
+            glwe_modular_std_dev,
+            auto_base_log,
+            auto_level,
+            fft_type,
+            ciphertext_modulus,
+        );
+        param.validate()?;
+
+        Ok(param)
+    }
+
+    /// Skips the validation, e.g. for toy parameters to be checked by `validate_with_min_security_bits`.
+    pub fn new_unchecked(
+        polynomial_size: PolynomialSize,
+        glwe_dimension: GlweDimension,
+        glwe_modular_std_dev: StandardDev,
+        auto_base_log: DecompositionBaseLog,
+        auto_level: DecompositionLevelCount,
+        fft_type: FftType,
+        ciphertext_modulus: CiphertextModulus<Scalar>,
     ) -> Self {
         AutoConvParam {
             polynomial_size,
@@ -115,6 +238,31 @@ impl<Scalar: UnsignedInteger> AutoConvParam<Scalar> {
         }
     }
 
+    /// Validates with the default security threshold of `DEFAULT_MIN_SECURITY_BITS`.
+    pub fn validate(&self) -> Result<(), ParamError> {
+        self.validate_with_min_security_bits(DEFAULT_MIN_SECURITY_BITS)
+    }
+
+    /// A threshold of zero explicitly allows insecure parameters, e.g. toy parameters for experiments.
+    pub fn validate_with_min_security_bits(&self, min_security_bits: usize) -> Result<(), ParamError> {
+        let mut checker = ParamChecker::new(Scalar::BITS);
+
+        checker.power_of_two("polynomial_size", self.polynomial_size.0);
+        checker.decomposition("auto", self.auto_base_log.0, self.auto_level.0);
+        checker.fft_split("auto", self.fft_type, self.auto_base_log.0, self.polynomial_size.0);
+
+        checker.security(|| self.security(), min_security_bits);
+
+        checker.finish()
+    }
+
+    /// Estimated security of the secret keys.
+    pub fn security(&self) -> SecurityReport {
+        SecurityReport::new(vec![
+            ("glwe", estimate_glwe_security(self.glwe_dimension, self.polynomial_size, self.glwe_modular_std_dev, Scalar::BITS)),
+        ])
+    }
+
     pub fn polynomial_size(&self) -> PolynomialSize {
         self.polynomial_size
     }
@@ -1,5 +1,6 @@
 use std::collections::HashMap;
-use aligned_vec::ABox;
+use aligned_vec::{ABox, CACHELINE_ALIGN};
+use dyn_stack::{PodStack, ReborrowMut, SizeOverflow, StackReq};
 use tfhe::core_crypto::{
     prelude::*,
     fft_impl::fft64::c64,
@@ -55,6 +56,10 @@ impl AutomorphKey<ABox<[c64]>> {
         self.polynomial_size
     }
 
+    pub fn fft_type(&self) -> FftType {
+        self.ksk.fft_type()
+    }
+
     /// Fill this object with the appropriate key switching key
     /// that is used for the automorphism operation
     /// where after_key is {S_i(X)} and before_key is computed as {S_i(X^k)}.
@@ -118,7 +123,7 @@ impl AutomorphKey<ABox<[c64]>> {
         convert_standard_glwe_keyswitch_key_to_fourier(&standard_ksk, &mut self.ksk);
     }
 
-    fn keyswitch_ciphertext<Scalar, InputCont, OutputCont>(
+    pub fn auto<Scalar, InputCont, OutputCont>(
         &self,
         after: &mut GlweCiphertext<OutputCont>,
         before: &GlweCiphertext<InputCont>,
@@ -127,24 +132,41 @@ impl AutomorphKey<ABox<[c64]>> {
         InputCont: Container<Element=Scalar>,
         OutputCont: ContainerMut<Element=Scalar>,
     {
-        keyswitch_glwe_ciphertext(&self.ksk, before, after);
+        let fft = Fft::new(self.polynomial_size);
+        let mut buffers = ComputationBuffers::new();
+        buffers.resize(
+            auto_mem_optimized_requirement::<Scalar>(
+                self.glwe_dimension.to_glwe_size(),
+                self.polynomial_size,
+                self.decomp_level_count,
+                self.fft_type(),
+                &fft,
+            )
+            .unwrap()
+            .unaligned_bytes_required(),
+        );
+
+        self.auto_mem_optimized(after, before, &fft, buffers.stack());
     }
 
-    pub fn auto<Scalar, InputCont, OutputCont>(
+    pub fn auto_mem_optimized<Scalar, InputCont, OutputCont>(
         &self,
         after: &mut GlweCiphertext<OutputCont>,
         before: &GlweCiphertext<InputCont>,
+        fft: &Fft,
+        stack: PodStack<'_>,
     ) where
-        Scalar: UnsignedTorus + Sync + Send,
+        Scalar: UnsignedTorus,
         InputCont: Container<Element=Scalar>,
         OutputCont: ContainerMut<Element=Scalar>,
     {
-        let mut before_power = GlweCiphertextOwned::new(Scalar::ZERO, before.glwe_size(), before.polynomial_size(), before.ciphertext_modulus());
+        let (mut before_power_data, stack) = stack.make_aligned_raw::<Scalar>(before.as_ref().len(), CACHELINE_ALIGN);
+        let mut before_power = GlweCiphertext::from_container(&mut *before_power_data, before.polynomial_size(), before.ciphertext_modulus());
         for (mut poly_power, poly) in before_power.as_mut_polynomial_list().iter_mut().zip(before.as_polynomial_list().iter()) {
-            poly_power.as_mut().clone_from_slice(eval_x_k(poly, self.auto_k).as_ref());
+            eval_x_k_in_memory(&mut poly_power, poly, self.auto_k);
         }
 
-        self.keyswitch_ciphertext(after, &before_power);
+        keyswitch_glwe_ciphertext_mem_optimized(&self.ksk, &before_power, after, fft, stack);
     }
 }
 
@@ -210,21 +232,93 @@ pub fn trace_partial_assign<Scalar, Cont>(
     Cont: ContainerMut<Element=Scalar>,
 {
     let glwe_size = input.glwe_size();
+    let polynomial_size = input.polynomial_size();
+    let auto_key = auto_keys.values().next().unwrap();
+
+    let fft = Fft::new(polynomial_size);
+    let mut buffers = ComputationBuffers::new();
+    buffers.resize(
+        trace_assign_mem_optimized_requirement::<Scalar>(
+            glwe_size,
+            polynomial_size,
+            auto_key.decomposition_level_count(),
+            auto_key.fft_type(),
+            &fft,
+        )
+        .unwrap()
+        .unaligned_bytes_required(),
+    );
+
+    trace_partial_assign_mem_optimized(input, auto_keys, n, &fft, buffers.stack());
+}
+
+pub fn auto_mem_optimized_requirement<Scalar: UnsignedTorus>(
+    glwe_size: GlweSize,
+    polynomial_size: PolynomialSize,
+    auto_level: DecompositionLevelCount,
+    fft_type: FftType,
+    fft: &Fft,
+) -> Result<StackReq, SizeOverflow> {
+    StackReq::try_all_of([
+        StackReq::try_new_aligned::<Scalar>(glwe_size.0 * polynomial_size.0, CACHELINE_ALIGN)?,
+        keyswitch_glwe_ciphertext_mem_optimized_requirement::<Scalar, c64>(glwe_size, polynomial_size, auto_level, fft_type, fft)?,
+    ])
+}
+
+/// The same requirement for [`trace_assign_mem_optimized`] and [`trace_partial_assign_mem_optimized`].
+pub fn trace_assign_mem_optimized_requirement<Scalar: UnsignedTorus>(
+    glwe_size: GlweSize,
+    polynomial_size: PolynomialSize,
+    auto_level: DecompositionLevelCount,
+    fft_type: FftType,
+    fft: &Fft,
+) -> Result<StackReq, SizeOverflow> {
+    let glwe_req = StackReq::try_new_aligned::<Scalar>(glwe_size.0 * polynomial_size.0, CACHELINE_ALIGN)?;
+    StackReq::try_all_of([
+        glwe_req,
+        glwe_req,
+        auto_mem_optimized_requirement::<Scalar>(glwe_size, polynomial_size, auto_level, fft_type, fft)?,
+    ])
+}
+
+pub fn trace_assign_mem_optimized<Scalar, ContMut>(
+    glwe_in: &mut GlweCiphertext<ContMut>,
+    auto_keys: &HashMap<usize, AutomorphKey<ABox<[c64]>>>,
+    fft: &Fft,
+    stack: PodStack<'_>,
+) where
+    Scalar: UnsignedTorus,
+    ContMut: ContainerMut<Element=Scalar>,
+{
+    trace_partial_assign_mem_optimized(glwe_in, auto_keys, 1, fft, stack);
+}
+
+pub fn trace_partial_assign_mem_optimized<Scalar, Cont>(
+    input: &mut GlweCiphertext<Cont>,
+    auto_keys: &HashMap<usize, AutomorphKey<ABox<[c64]>>>,
+    n: usize,
+    fft: &Fft,
+    stack: PodStack<'_>,
+) where
+    Scalar: UnsignedTorus,
+    Cont: ContainerMut<Element=Scalar>,
+{
     let polynomial_size = input.polynomial_size();
     let ciphertext_modulus = input.ciphertext_modulus();
 
     assert!(polynomial_size.0 % n == 0);
 
-    let mut buf = GlweCiphertextOwned::new(Scalar::ZERO, glwe_size, polynomial_size, ciphertext_modulus);
-    let mut out: GlweCiphertext<Vec<Scalar>> = GlweCiphertext::new(Scalar::ZERO, glwe_size, polynomial_size, ciphertext_modulus);
-    glwe_ciphertext_clone_from(&mut out, input);
+    let (mut buf_data, stack) = stack.make_aligned_raw::<Scalar>(input.as_ref().len(), CACHELINE_ALIGN);
+    let (mut out_data, mut stack) = stack.collect_aligned(CACHELINE_ALIGN, input.as_ref().iter().copied());
+    let mut buf = GlweCiphertext::from_container(&mut *buf_data, polynomial_size, ciphertext_modulus);
+    let mut out = GlweCiphertext::from_container(&mut *out_data, polynomial_size, ciphertext_modulus);
 
     let log_polynomial_size = polynomial_size.0.ilog2() as usize;
     let log_n = n.ilog2() as usize;
     for i in 1..=(log_polynomial_size - log_n) {
         let k = polynomial_size.0 / (1 << (i - 1)) + 1;
         let auto_key = auto_keys.get(&k).unwrap();
-        auto_key.auto(&mut buf, &out);
+        auto_key.auto_mem_optimized(&mut buf, &out, fft, stack.rb_mut());
         glwe_ciphertext_add_assign(&mut out, &buf);
     }
 
@@ -0,0 +1,437 @@
+use std::collections::HashMap;
+use aligned_vec::ABox;
+use tfhe::core_crypto::{
+    prelude::*,
+    commons::{generators::DeterministicSeeder, math::random::Seed},
+    fft_impl::fft64::{
+        c64,
+        crypto::bootstrap::FourierLweBootstrapKeyView,
+    },
+};
+use crate::{gen_all_auto_keys, generate_scheme_switching_key, keygen_high_prec_wwlp_cbs, keygen_pbs_from_param, keygen_pbs_with_glwe_ds, AesParam, AutomorphKey, CBSParam, CbsAccumulator, FftBackend, FourierGlweKeyswitchKeyOwned, FourierSchemeSwitchingKeyOwned, FourierSchemeSwitchingKeyView, HighPrecWWLpCBSParam, HighPrecWWLpCbsKeys, WWLpCBSParam, F128};
+
+/// Random generators derived from a single seed so that a key set can be regenerated.
+fn new_generators_from_seed(
+    seed: Seed,
+) -> (
+    SecretRandomGenerator<ActivatedRandomGenerator>,
+    EncryptionRandomGenerator<ActivatedRandomGenerator>,
+) {
+    let mut seeder = DeterministicSeeder::<ActivatedRandomGenerator>::new(seed);
+    let secret_generator = SecretRandomGenerator::<ActivatedRandomGenerator>::new(seeder.seed());
+    let encryption_generator = EncryptionRandomGenerator::<ActivatedRandomGenerator>::new(seeder.seed(), &mut seeder);
+
+    (secret_generator, encryption_generator)
+}
+
+
+/// Secret keys of the CBS by private functional packing keyswitching.
+pub struct CbsClientKey<Scalar: UnsignedTorus> {
+    lwe_secret_key: LweSecretKeyOwned<Scalar>,
+    glwe_secret_key: GlweSecretKeyOwned<Scalar>,
+    lwe_secret_key_after_ks: LweSecretKeyOwned<Scalar>,
+}
+
+/// Evaluation keys of the CBS by private functional packing keyswitching.
+pub struct CbsServerKey<Scalar: UnsignedTorus> {
+    fourier_bsk: FourierLweBootstrapKeyOwned,
+    ksk: LweKeyswitchKeyOwned<Scalar>,
+    pfpksk_list: LwePrivateFunctionalPackingKeyswitchKeyListOwned<Scalar>,
+    cbs_acc: CbsAccumulator<Scalar>,
+}
+
+impl<Scalar: UnsignedTorus + Sync + Send> CbsClientKey<Scalar> {
+    pub fn generate(param: &CBSParam<Scalar>, seed: Seed) -> (Self, CbsServerKey<Scalar>) {
+        let (mut secret_generator, mut encryption_generator) = new_generators_from_seed(seed);
+
+        let (
+            lwe_secret_key,
+            glwe_secret_key,
+            lwe_secret_key_after_ks,
+            fourier_bsk,
+            ksk,
+        ) = keygen_pbs_from_param(param, &mut secret_generator, &mut encryption_generator);
+
+        let pfpksk_list = allocate_and_generate_new_circuit_bootstrap_lwe_pfpksk_list(
+            &lwe_secret_key,
+            &glwe_secret_key,
+            param.pfks_base_log(),
+            param.pfks_level(),
+            param.glwe_modular_std_dev(),
+            param.ciphertext_modulus(),
+            &mut encryption_generator,
+        );
+
+        let cbs_acc = CbsAccumulator::from_param(param);
+
+        let client_key = CbsClientKey {
+            lwe_secret_key,
+            glwe_secret_key,
+            lwe_secret_key_after_ks,
+        };
+        let server_key = CbsServerKey {
+            fourier_bsk,
+            ksk,
+            pfpksk_list,
+            cbs_acc,
+        };
+
+        (client_key, server_key)
+    }
+
+    pub fn lwe_secret_key(&self) -> &LweSecretKeyOwned<Scalar> {
+        &self.lwe_secret_key
+    }
+
+    pub fn glwe_secret_key(&self) -> &GlweSecretKeyOwned<Scalar> {
+        &self.glwe_secret_key
+    }
+
+    pub fn lwe_secret_key_after_ks(&self) -> &LweSecretKeyOwned<Scalar> {
+        &self.lwe_secret_key_after_ks
+    }
+}
+
+impl<Scalar: UnsignedTorus> CbsServerKey<Scalar> {
+    pub fn fourier_bsk(&self) -> FourierLweBootstrapKeyView<'_> {
+        self.fourier_bsk.as_view()
+    }
+
+    pub fn ksk(&self) -> &LweKeyswitchKeyOwned<Scalar> {
+        &self.ksk
+    }
+
+    pub fn pfpksk_list(&self) -> &LwePrivateFunctionalPackingKeyswitchKeyListOwned<Scalar> {
+        &self.pfpksk_list
+    }
+
+    pub fn cbs_acc(&self) -> &CbsAccumulator<Scalar> {
+        &self.cbs_acc
+    }
+}
+
+
+/// Secret keys of the patched WWL+ CBS.
+pub struct WWLpCbsClientKey<Scalar: UnsignedTorus> {
+    lwe_secret_key: LweSecretKeyOwned<Scalar>,
+    glwe_secret_key: GlweSecretKeyOwned<Scalar>,
+    lwe_secret_key_after_ks: LweSecretKeyOwned<Scalar>,
+}
+
+/// Evaluation keys of the patched WWL+ CBS.
+pub struct WWLpCbsServerKey<Scalar: UnsignedTorus> {
+    fourier_bsk: FourierLweBootstrapKeyOwned,
+    ksk: LweKeyswitchKeyOwned<Scalar>,
+    auto_keys: HashMap<usize, AutomorphKey<ABox<[c64]>>>,
+    ss_key: FourierSchemeSwitchingKeyOwned,
+    cbs_acc: CbsAccumulator<Scalar>,
+}
+
+impl<Scalar: UnsignedTorus + Sync + Send> WWLpCbsClientKey<Scalar> {
+    pub fn generate(param: &WWLpCBSParam<Scalar>, seed: Seed) -> (Self, WWLpCbsServerKey<Scalar>) {
+        let (mut secret_generator, mut encryption_generator) = new_generators_from_seed(seed);
+
+        let (
+            lwe_secret_key,
+            glwe_secret_key,
+            lwe_secret_key_after_ks,
+            fourier_bsk,
+            ksk,
+        ) = keygen_pbs_from_param(param, &mut secret_generator, &mut encryption_generator);
+
+        let auto_keys = gen_all_auto_keys(
+            param.auto_base_log(),
+            param.auto_level(),
+            param.fft_type_auto(),
+            &glwe_secret_key,
+            param.glwe_modular_std_dev(),
+            &mut encryption_generator,
+        );
+
+        let ss_key = generate_scheme_switching_key(
+            &glwe_secret_key,
+            param.ss_base_log(),
+            param.ss_level(),
+            param.glwe_modular_std_dev(),
+            param.ciphertext_modulus(),
+            &mut encryption_generator,
+        );
+
+        let cbs_acc = CbsAccumulator::from_param(param);
+
+        let client_key = WWLpCbsClientKey {
+            lwe_secret_key,
+            glwe_secret_key,
+            lwe_secret_key_after_ks,
+        };
+        let server_key = WWLpCbsServerKey {
+            fourier_bsk,
+            ksk,
+            auto_keys,
+            ss_key,
+            cbs_acc,
+        };
+
+        (client_key, server_key)
+    }
+
+    pub fn lwe_secret_key(&self) -> &LweSecretKeyOwned<Scalar> {
+        &self.lwe_secret_key
+    }
+
+    pub fn glwe_secret_key(&self) -> &GlweSecretKeyOwned<Scalar> {
+        &self.glwe_secret_key
+    }
+
+    pub fn lwe_secret_key_after_ks(&self) -> &LweSecretKeyOwned<Scalar> {
+        &self.lwe_secret_key_after_ks
+    }
+}
+
+impl<Scalar: UnsignedTorus> WWLpCbsServerKey<Scalar> {
+    pub fn fourier_bsk(&self) -> FourierLweBootstrapKeyView<'_> {
+        self.fourier_bsk.as_view()
+    }
+
+    pub fn ksk(&self) -> &LweKeyswitchKeyOwned<Scalar> {
+        &self.ksk
+    }
+
+    pub fn auto_keys(&self) -> &HashMap<usize, AutomorphKey<ABox<[c64]>>> {
+        &self.auto_keys
+    }
+
+    pub fn ss_key(&self) -> FourierSchemeSwitchingKeyView<'_> {
+        self.ss_key.as_view()
+    }
+
+    pub fn cbs_acc(&self) -> &CbsAccumulator<Scalar> {
+        &self.cbs_acc
+    }
+}
+
+
+/// Secret keys of the high-precision WWL+ CBS, including the large GLWE secret key
+/// under which the trace is evaluated.
+pub struct HighPrecWWLpCbsClientKey<Scalar: UnsignedTorus> {
+    lwe_secret_key: LweSecretKeyOwned<Scalar>,
+    glwe_secret_key: GlweSecretKeyOwned<Scalar>,
+    lwe_secret_key_after_ks: LweSecretKeyOwned<Scalar>,
+    large_glwe_secret_key: GlweSecretKeyOwned<Scalar>,
+}
+
+/// Evaluation keys of the high-precision WWL+ CBS, whose GLWE keyswitching keys to and from the large GLWE
+/// dimension are on the FFT backend `F`, which must support their FFT types in the parameter.
+pub struct HighPrecWWLpCbsServerKey<Scalar: UnsignedTorus, F: FftBackend = F128> {
+    fourier_bsk: FourierLweBootstrapKeyOwned,
+    ksk: LweKeyswitchKeyOwned<Scalar>,
+    glwe_ksk_to_large: FourierGlweKeyswitchKeyOwned<F>,
+    glwe_ksk_from_large: FourierGlweKeyswitchKeyOwned<F>,
+    auto_keys: HashMap<usize, AutomorphKey<ABox<[c64]>>>,
+    ss_key: FourierSchemeSwitchingKeyOwned,
+    cbs_acc: CbsAccumulator<Scalar>,
+}
+
+impl<Scalar: UnsignedTorus + Sync + Send> HighPrecWWLpCbsClientKey<Scalar> {
+    pub fn generate<F: FftBackend>(param: &HighPrecWWLpCBSParam<Scalar>, seed: Seed) -> (Self, HighPrecWWLpCbsServerKey<Scalar, F>) {
+        let (mut secret_generator, mut encryption_generator) = new_generators_from_seed(seed);
+
+        let HighPrecWWLpCbsKeys {
+            lwe_secret_key,
+            glwe_secret_key,
+            lwe_secret_key_after_ks,
+            large_glwe_secret_key,
+            fourier_bsk,
+            ksk,
+            glwe_ksk_to_large,
+            glwe_ksk_from_large,
+            auto_keys,
+            ss_key,
+        } = keygen_high_prec_wwlp_cbs(param, &mut secret_generator, &mut encryption_generator);
+
+        let cbs_acc = CbsAccumulator::from_param(param);
+
+        let client_key = HighPrecWWLpCbsClientKey {
+            lwe_secret_key,
+            glwe_secret_key,
+            lwe_secret_key_after_ks,
+            large_glwe_secret_key,
+        };
+        let server_key = HighPrecWWLpCbsServerKey {
+            fourier_bsk,
+            ksk,
+            glwe_ksk_to_large,
+            glwe_ksk_from_large,
+            auto_keys,
+            ss_key,
+            cbs_acc,
+        };
+
+        (client_key, server_key)
+    }
+
+    pub fn lwe_secret_key(&self) -> &LweSecretKeyOwned<Scalar> {
+        &self.lwe_secret_key
+    }
+
+    pub fn glwe_secret_key(&self) -> &GlweSecretKeyOwned<Scalar> {
+        &self.glwe_secret_key
+    }
+
+    pub fn lwe_secret_key_after_ks(&self) -> &LweSecretKeyOwned<Scalar> {
+        &self.lwe_secret_key_after_ks
+    }
+
+    pub fn large_glwe_secret_key(&self) -> &GlweSecretKeyOwned<Scalar> {
+        &self.large_glwe_secret_key
+    }
+}
+
+impl<Scalar: UnsignedTorus, F: FftBackend> HighPrecWWLpCbsServerKey<Scalar, F> {
+    pub fn fourier_bsk(&self) -> FourierLweBootstrapKeyView<'_> {
+        self.fourier_bsk.as_view()
+    }
+
+    pub fn ksk(&self) -> &LweKeyswitchKeyOwned<Scalar> {
+        &self.ksk
+    }
+
+    pub fn glwe_ksk_to_large(&self) -> &FourierGlweKeyswitchKeyOwned<F> {
+        &self.glwe_ksk_to_large
+    }
+
+    pub fn glwe_ksk_from_large(&self) -> &FourierGlweKeyswitchKeyOwned<F> {
+        &self.glwe_ksk_from_large
+    }
+
+    pub fn auto_keys(&self) -> &HashMap<usize, AutomorphKey<ABox<[c64]>>> {
+        &self.auto_keys
+    }
+
+    pub fn ss_key(&self) -> FourierSchemeSwitchingKeyView<'_> {
+        self.ss_key.as_view()
+    }
+
+    pub fn cbs_acc(&self) -> &CbsAccumulator<Scalar> {
+        &self.cbs_acc
+    }
+}
+
+
+/// Secret keys of the AES evaluation by patched WWL+ CBS.
+pub struct AesClientKey<Scalar: UnsignedTorus> {
+    lwe_secret_key: LweSecretKeyOwned<Scalar>,
+    glwe_secret_key: GlweSecretKeyOwned<Scalar>,
+    lwe_secret_key_after_ks: LweSecretKeyOwned<Scalar>,
+}
+
+/// Evaluation keys of the bitwise circuits by patched WWL+ CBS, e.g. AES, the SPN ciphers and Trivium,
+/// where the LWE keyswitching is done by GLWE dimension switching.
+pub struct BitCbsServerKey<Scalar: UnsignedTorus> {
+    fourier_bsk: FourierLweBootstrapKeyOwned,
+    glwe_ksk: FourierGlweKeyswitchKeyOwned,
+    auto_keys: HashMap<usize, AutomorphKey<ABox<[c64]>>>,
+    ss_key: FourierSchemeSwitchingKeyOwned,
+    cbs_acc: CbsAccumulator<Scalar>,
+}
+
+/// Evaluation keys of the AES evaluation, generated by [`AesClientKey::generate`].
+pub type AesServerKey<Scalar> = BitCbsServerKey<Scalar>;
+
+impl<Scalar: UnsignedTorus + Sync + Send> AesClientKey<Scalar> {
+    pub fn generate(param: &AesParam<Scalar>, seed: Seed) -> (Self, AesServerKey<Scalar>) {
+        let (mut secret_generator, mut encryption_generator) = new_generators_from_seed(seed);
+
+        let (
+            lwe_secret_key,
+            glwe_secret_key,
+            lwe_secret_key_after_ks,
+            fourier_bsk,
+            glwe_ksk,
+        ) = keygen_pbs_with_glwe_ds(
+            param.lwe_dimension(),
+            param.glwe_dimension(),
+            param.polynomial_size(),
+            param.lwe_modular_std_dev(),
+            param.glwe_modular_std_dev(),
+            param.pbs_base_log(),
+            param.pbs_level(),
+            param.glwe_ds_base_log(),
+            param.glwe_ds_level(),
+            param.common_polynomial_size(),
+            param.fft_type_ds(),
+            param.ciphertext_modulus(),
+            &mut secret_generator,
+            &mut encryption_generator,
+        );
+
+        let ss_key = generate_scheme_switching_key(
+            &glwe_secret_key,
+            param.ss_base_log(),
+            param.ss_level(),
+            param.glwe_modular_std_dev(),
+            param.ciphertext_modulus(),
+            &mut encryption_generator,
+        );
+
+        let auto_keys = gen_all_auto_keys(
+            param.auto_base_log(),
+            param.auto_level(),
+            param.fft_type_auto(),
+            &glwe_secret_key,
+            param.glwe_modular_std_dev(),
+            &mut encryption_generator,
+        );
+
+        let cbs_acc = CbsAccumulator::from_param(param);
+
+        let client_key = AesClientKey {
+            lwe_secret_key,
+            glwe_secret_key,
+            lwe_secret_key_after_ks,
+        };
+        let server_key = BitCbsServerKey {
+            fourier_bsk,
+            glwe_ksk,
+            auto_keys,
+            ss_key,
+            cbs_acc,
+        };
+
+        (client_key, server_key)
+    }
+
+    pub fn lwe_secret_key(&self) -> &LweSecretKeyOwned<Scalar> {
+        &self.lwe_secret_key
+    }
+
+    pub fn glwe_secret_key(&self) -> &GlweSecretKeyOwned<Scalar> {
+        &self.glwe_secret_key
+    }
+
+    pub fn lwe_secret_key_after_ks(&self) -> &LweSecretKeyOwned<Scalar> {
+        &self.lwe_secret_key_after_ks
+    }
+}
+
+impl<Scalar: UnsignedTorus> BitCbsServerKey<Scalar> {
+    pub fn fourier_bsk(&self) -> FourierLweBootstrapKeyView<'_> {
+        self.fourier_bsk.as_view()
+    }
+
+    pub fn glwe_ksk(&self) -> &FourierGlweKeyswitchKeyOwned {
+        &self.glwe_ksk
+    }
+
+    pub fn auto_keys(&self) -> &HashMap<usize, AutomorphKey<ABox<[c64]>>> {
+        &self.auto_keys
+    }
+
+    pub fn ss_key(&self) -> FourierSchemeSwitchingKeyView<'_> {
+        self.ss_key.as_view()
+    }
+
+    pub fn cbs_acc(&self) -> &CbsAccumulator<Scalar> {
+        &self.cbs_acc
+    }
+}
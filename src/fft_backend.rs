@@ -0,0 +1,372 @@
+use bytemuck::{Pod, Zeroable};
+use concrete_fft::fft128::f128;
+use dyn_stack::{PodStack, StackReq};
+use tfhe::core_crypto::{
+    prelude::*,
+    fft_impl::{
+        fft64::{
+            c64,
+            crypto::ggsw::{
+                add_external_product_assign as fft64_add_external_product_assign,
+                add_external_product_assign_scratch as fft64_add_external_product_assign_scratch,
+                FourierGgswCiphertext,
+            },
+            math::polynomial::FourierPolynomial,
+        },
+        fft128::{
+            crypto::ggsw::{
+                add_external_product_assign as fft128_add_external_product_assign,
+                add_external_product_assign_scratch as fft128_add_external_product_assign_scratch,
+                Fourier128GgswCiphertext,
+            },
+            math::fft::Fft128,
+        },
+    },
+};
+
+use crate::{fourier_poly_mult::update_with_fmadd, FftType};
+
+/// Element type of the Fourier domain data, which selects the FFT backend.
+///
+/// - `c64`: 64-bit float FFT of tfhe, N/2 complex values per polynomial.
+/// - [`F128`]: f128 FFT of tfhe, whose f128 values are stored as pairs of f64. Each polynomial is stored as
+///   [re0 | re1 | im0 | im1] with N/2 values each, where (re0, re1) and (im0, im1) are the real and
+///   imaginary parts as double-f64 numbers.
+///
+/// A Fourier GGSW ciphertext is stored in the layout of the GGSW ciphertexts of tfhe for the backend, where the
+/// four f64 parts of an f128 GGSW ciphertext are concatenated. It also takes `fourier_polynomial_size`
+/// elements per polynomial.
+pub trait FftBackend: Pod + Default + Send + Sync + 'static {
+    type Fft: Send + Sync;
+
+    fn new_fft(polynomial_size: PolynomialSize) -> Self::Fft;
+
+    /// Whether a Fourier GLWE keyswitching key of `fft_type` can be stored on this backend.
+    fn supports_fft_type(fft_type: FftType) -> bool;
+
+    /// Number of elements required to store a polynomial of size `polynomial_size`.
+    fn fourier_polynomial_size(polynomial_size: PolynomialSize) -> usize;
+
+    fn fft_scratch(fft: &Self::Fft) -> StackReq;
+
+    fn forward_as_torus<Scalar: UnsignedTorus>(
+        fft: &Self::Fft,
+        fourier: &mut [Self],
+        standard: &[Scalar],
+        stack: PodStack<'_>,
+    );
+
+    fn forward_as_integer<Scalar: UnsignedTorus>(
+        fft: &Self::Fft,
+        fourier: &mut [Self],
+        standard: &[Scalar],
+        stack: PodStack<'_>,
+    );
+
+    fn backward_as_torus<Scalar: UnsignedTorus>(
+        fft: &Self::Fft,
+        standard: &mut [Scalar],
+        fourier: &[Self],
+        stack: PodStack<'_>,
+    );
+
+    /// output += lhs * rhs
+    fn mult_and_add(
+        output: &mut [Self],
+        lhs: &[Self],
+        rhs: &[Self],
+    );
+
+    /// Writes the Fourier transform of a standard GGSW ciphertext to `fourier`.
+    fn forward_ggsw<Scalar: UnsignedTorus>(
+        fft: &Self::Fft,
+        fourier: &mut [Self],
+        ggsw: GgswCiphertextView<'_, Scalar>,
+        stack: PodStack<'_>,
+    );
+
+    fn add_external_product_assign_scratch<Scalar: UnsignedTorus>(
+        fft: &Self::Fft,
+        glwe_size: GlweSize,
+        polynomial_size: PolynomialSize,
+    ) -> StackReq;
+
+    /// out += fourier_ggsw ⊡ glwe
+    fn add_external_product_assign<Scalar: UnsignedTorus>(
+        fft: &Self::Fft,
+        out: GlweCiphertextMutView<'_, Scalar>,
+        fourier_ggsw: &[Self],
+        decomposition_base_log: DecompositionBaseLog,
+        decomposition_level_count: DecompositionLevelCount,
+        glwe: GlweCiphertextView<'_, Scalar>,
+        stack: PodStack<'_>,
+    );
+}
+
+impl FftBackend for c64 {
+    type Fft = Fft;
+
+    fn new_fft(polynomial_size: PolynomialSize) -> Fft {
+        Fft::new(polynomial_size)
+    }
+
+    fn supports_fft_type(fft_type: FftType) -> bool {
+        fft_type != FftType::F128
+    }
+
+    fn fourier_polynomial_size(polynomial_size: PolynomialSize) -> usize {
+        polynomial_size.to_fourier_polynomial_size().0
+    }
+
+    fn fft_scratch(fft: &Fft) -> StackReq {
+        let fft = fft.as_view();
+        StackReq::any_of([
+            fft.forward_scratch().unwrap(),
+            fft.backward_scratch().unwrap(),
+        ])
+    }
+
+    fn forward_as_torus<Scalar: UnsignedTorus>(
+        fft: &Fft,
+        fourier: &mut [c64],
+        standard: &[Scalar],
+        stack: PodStack<'_>,
+    ) {
+        fft.as_view().forward_as_torus(
+            FourierPolynomial { data: fourier },
+            Polynomial::from_container(standard),
+            stack,
+        );
+    }
+
+    fn forward_as_integer<Scalar: UnsignedTorus>(
+        fft: &Fft,
+        fourier: &mut [c64],
+        standard: &[Scalar],
+        stack: PodStack<'_>,
+    ) {
+        fft.as_view().forward_as_integer(
+            FourierPolynomial { data: fourier },
+            Polynomial::from_container(standard),
+            stack,
+        );
+    }
+
+    fn backward_as_torus<Scalar: UnsignedTorus>(
+        fft: &Fft,
+        standard: &mut [Scalar],
+        fourier: &[c64],
+        stack: PodStack<'_>,
+    ) {
+        fft.as_view().backward_as_torus(
+            Polynomial::from_container(standard),
+            FourierPolynomial { data: fourier },
+            stack,
+        );
+    }
+
+    fn mult_and_add(
+        output: &mut [c64],
+        lhs: &[c64],
+        rhs: &[c64],
+    ) {
+        let fourier_poly_size = output.len();
+        update_with_fmadd(output, lhs, rhs, false, fourier_poly_size);
+    }
+
+    fn forward_ggsw<Scalar: UnsignedTorus>(
+        fft: &Fft,
+        fourier: &mut [c64],
+        ggsw: GgswCiphertextView<'_, Scalar>,
+        stack: PodStack<'_>,
+    ) {
+        FourierGgswCiphertext::from_container(
+            fourier,
+            ggsw.glwe_size(),
+            ggsw.polynomial_size(),
+            ggsw.decomposition_base_log(),
+            ggsw.decomposition_level_count(),
+        ).fill_with_forward_fourier(ggsw, fft.as_view(), stack);
+    }
+
+    fn add_external_product_assign_scratch<Scalar: UnsignedTorus>(
+        fft: &Fft,
+        glwe_size: GlweSize,
+        polynomial_size: PolynomialSize,
+    ) -> StackReq {
+        fft64_add_external_product_assign_scratch::<Scalar>(glwe_size, polynomial_size, fft.as_view()).unwrap()
+    }
+
+    fn add_external_product_assign<Scalar: UnsignedTorus>(
+        fft: &Fft,
+        out: GlweCiphertextMutView<'_, Scalar>,
+        fourier_ggsw: &[c64],
+        decomposition_base_log: DecompositionBaseLog,
+        decomposition_level_count: DecompositionLevelCount,
+        glwe: GlweCiphertextView<'_, Scalar>,
+        stack: PodStack<'_>,
+    ) {
+        let fourier_ggsw = FourierGgswCiphertext::from_container(
+            fourier_ggsw,
+            glwe.glwe_size(),
+            glwe.polynomial_size(),
+            decomposition_base_log,
+            decomposition_level_count,
+        );
+        fft64_add_external_product_assign(out, fourier_ggsw, glwe, fft.as_view(), stack);
+    }
+}
+
+/// Fourier domain element of the f128 FFT backend, i.e. one of the f64 parts of an f128 value.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+#[repr(transparent)]
+pub struct F128(pub f64);
+
+// SAFETY: F128 is a transparent wrapper of f64.
+unsafe impl Zeroable for F128 {}
+unsafe impl Pod for F128 {}
+
+impl FftBackend for F128 {
+    type Fft = Fft128;
+
+    fn new_fft(polynomial_size: PolynomialSize) -> Fft128 {
+        Fft128::new(polynomial_size)
+    }
+
+    fn supports_fft_type(fft_type: FftType) -> bool {
+        fft_type == FftType::F128
+    }
+
+    fn fourier_polynomial_size(polynomial_size: PolynomialSize) -> usize {
+        4 * polynomial_size.to_fourier_polynomial_size().0
+    }
+
+    fn fft_scratch(fft: &Fft128) -> StackReq {
+        fft.as_view().backward_scratch().unwrap()
+    }
+
+    fn forward_as_torus<Scalar: UnsignedTorus>(
+        fft: &Fft128,
+        fourier: &mut [F128],
+        standard: &[Scalar],
+        _stack: PodStack<'_>,
+    ) {
+        let (re0, re1, im0, im1) = split_f128_mut(fourier);
+        fft.as_view().forward_as_torus(re0, re1, im0, im1, standard);
+    }
+
+    fn forward_as_integer<Scalar: UnsignedTorus>(
+        fft: &Fft128,
+        fourier: &mut [F128],
+        standard: &[Scalar],
+        _stack: PodStack<'_>,
+    ) {
+        let (re0, re1, im0, im1) = split_f128_mut(fourier);
+        fft.as_view().forward_as_integer(re0, re1, im0, im1, standard);
+    }
+
+    fn backward_as_torus<Scalar: UnsignedTorus>(
+        fft: &Fft128,
+        standard: &mut [Scalar],
+        fourier: &[F128],
+        stack: PodStack<'_>,
+    ) {
+        let (re0, re1, im0, im1) = split_f128(fourier);
+        fft.as_view().backward_as_torus(standard, re0, re1, im0, im1, stack);
+    }
+
+    fn mult_and_add(
+        output: &mut [F128],
+        lhs: &[F128],
+        rhs: &[F128],
+    ) {
+        let (out_re0, out_re1, out_im0, out_im1) = split_f128_mut(output);
+        let (lhs_re0, lhs_re1, lhs_im0, lhs_im1) = split_f128(lhs);
+        let (rhs_re0, rhs_re1, rhs_im0, rhs_im1) = split_f128(rhs);
+
+        for i in 0..out_re0.len() {
+            let lhs_re = f128(lhs_re0[i], lhs_re1[i]);
+            let lhs_im = f128(lhs_im0[i], lhs_im1[i]);
+            let rhs_re = f128(rhs_re0[i], rhs_re1[i]);
+            let rhs_im = f128(rhs_im0[i], rhs_im1[i]);
+
+            let out_re = f128(out_re0[i], out_re1[i]) + lhs_re * rhs_re - lhs_im * rhs_im;
+            let out_im = f128(out_im0[i], out_im1[i]) + lhs_re * rhs_im + lhs_im * rhs_re;
+
+            out_re0[i] = out_re.0;
+            out_re1[i] = out_re.1;
+            out_im0[i] = out_im.0;
+            out_im1[i] = out_im.1;
+        }
+    }
+
+    fn forward_ggsw<Scalar: UnsignedTorus>(
+        fft: &Fft128,
+        fourier: &mut [F128],
+        ggsw: GgswCiphertextView<'_, Scalar>,
+        _stack: PodStack<'_>,
+    ) {
+        let (re0, re1, im0, im1) = split_f128_mut(fourier);
+        Fourier128GgswCiphertext::from_container(
+            re0,
+            re1,
+            im0,
+            im1,
+            ggsw.polynomial_size(),
+            ggsw.glwe_size(),
+            ggsw.decomposition_base_log(),
+            ggsw.decomposition_level_count(),
+        ).fill_with_forward_fourier(&ggsw, fft.as_view());
+    }
+
+    fn add_external_product_assign_scratch<Scalar: UnsignedTorus>(
+        fft: &Fft128,
+        glwe_size: GlweSize,
+        polynomial_size: PolynomialSize,
+    ) -> StackReq {
+        fft128_add_external_product_assign_scratch::<Scalar>(glwe_size, polynomial_size, fft.as_view()).unwrap()
+    }
+
+    fn add_external_product_assign<Scalar: UnsignedTorus>(
+        fft: &Fft128,
+        mut out: GlweCiphertextMutView<'_, Scalar>,
+        fourier_ggsw: &[F128],
+        decomposition_base_log: DecompositionBaseLog,
+        decomposition_level_count: DecompositionLevelCount,
+        glwe: GlweCiphertextView<'_, Scalar>,
+        stack: PodStack<'_>,
+    ) {
+        let (re0, re1, im0, im1) = split_f128(fourier_ggsw);
+        let fourier_ggsw = Fourier128GgswCiphertext::from_container(
+            re0,
+            re1,
+            im0,
+            im1,
+            glwe.polynomial_size(),
+            glwe.glwe_size(),
+            decomposition_base_log,
+            decomposition_level_count,
+        );
+        fft128_add_external_product_assign(&mut out, &fourier_ggsw, &glwe, fft.as_view(), stack);
+    }
+}
+
+fn split_f128(fourier: &[F128]) -> (&[f64], &[f64], &[f64], &[f64]) {
+    let fourier: &[f64] = bytemuck::cast_slice(fourier);
+    let half = fourier.len() / 2;
+    let quarter = fourier.len() / 4;
+    let (re, im) = fourier.split_at(half);
+    let (re0, re1) = re.split_at(quarter);
+    let (im0, im1) = im.split_at(quarter);
+    (re0, re1, im0, im1)
+}
+
+fn split_f128_mut(fourier: &mut [F128]) -> (&mut [f64], &mut [f64], &mut [f64], &mut [f64]) {
+    let fourier: &mut [f64] = bytemuck::cast_slice_mut(fourier);
+    let half = fourier.len() / 2;
+    let quarter = fourier.len() / 4;
+    let (re, im) = fourier.split_at_mut(half);
+    let (re0, re1) = re.split_at_mut(quarter);
+    let (im0, im1) = im.split_at_mut(quarter);
+    (re0, re1, im0, im1)
+}
@@ -5,9 +5,9 @@ use tfhe::core_crypto::{
     fft_impl::fft64::c64,
 };
 
-use crate::{FourierGlweCiphertextList, FourierGlweCiphertextListMutView, FourierGlweCiphertextListView, GlevCiphertext};
+use crate::{FftBackend, FourierGlweCiphertextList, FourierGlweCiphertextListMutView, FourierGlweCiphertextListView, GlevCiphertext};
 
-pub struct FourierGlevCiphertext<C: Container<Element = c64>> {
+pub struct FourierGlevCiphertext<C: Container<Element: FftBackend>> {
     data: C,
     glwe_size: GlweSize,
     polynomial_size: PolynomialSize,
@@ -15,32 +15,32 @@ pub struct FourierGlevCiphertext<C: Container<Element = c64>> {
     decomposition_level_count: DecompositionLevelCount,
 }
 
-impl<C: Container<Element = c64>> AsRef<[c64]> for FourierGlevCiphertext<C> {
-    fn as_ref(&self) -> &[c64] {
+impl<F: FftBackend, C: Container<Element = F>> AsRef<[F]> for FourierGlevCiphertext<C> {
+    fn as_ref(&self) -> &[F] {
         self.data.as_ref()
     }
 }
 
-impl<C: ContainerMut<Element = c64>> AsMut<[c64]> for FourierGlevCiphertext<C> {
-    fn as_mut(&mut self) -> &mut [c64] {
+impl<F: FftBackend, C: ContainerMut<Element = F>> AsMut<[F]> for FourierGlevCiphertext<C> {
+    fn as_mut(&mut self) -> &mut [F] {
         self.data.as_mut()
     }
 }
 
-pub fn fourier_glev_ciphertext_size(glwe_size: GlweSize, polynomial_size: PolynomialSize, decomposition_level_count: DecompositionLevelCount) -> usize {
+pub fn fourier_glev_ciphertext_size<F: FftBackend>(glwe_size: GlweSize, polynomial_size: PolynomialSize, decomposition_level_count: DecompositionLevelCount) -> usize {
     glwe_size.0
-        * polynomial_size.to_fourier_polynomial_size().0
+        * F::fourier_polynomial_size(polynomial_size)
         * decomposition_level_count.0
 }
 
 /// A [`FourierGlevCiphertext`] owning the memory for its own storage.
-pub type FourierGlevCiphertextOwned = FourierGlevCiphertext<AVec<c64>>;
+pub type FourierGlevCiphertextOwned<F = c64> = FourierGlevCiphertext<AVec<F>>;
 /// A [`FourierGlevCiphertext`] immutably borrowing memory for its own storage.
-pub type FourierGlevCiphertextView<'data> = FourierGlevCiphertext<&'data [c64]>;
+pub type FourierGlevCiphertextView<'data, F = c64> = FourierGlevCiphertext<&'data [F]>;
 /// A [`FourierGlevCiphertext`] mutably borrowing memory for its own storage.
-pub type FourierGlevCiphertextMutView<'data> = FourierGlevCiphertext<&'data mut [c64]>;
+pub type FourierGlevCiphertextMutView<'data, F = c64> = FourierGlevCiphertext<&'data mut [F]>;
 
-impl<C: Container<Element = c64>> FourierGlevCiphertext<C>
+impl<F: FftBackend, C: Container<Element = F>> FourierGlevCiphertext<C>
 {
     pub fn from_container(
         container: C,
@@ -49,7 +49,7 @@ impl<C: Container<Element = c64>> FourierGlevCiphertext<C>
         decomposition_base_log: DecompositionBaseLog,
         decomposition_level_count: DecompositionLevelCount,
     ) -> FourierGlevCiphertext<C> {
-        let fourier_poly_size = polynomial_size.to_fourier_polynomial_size().0;
+        let fourier_poly_size = F::fourier_polynomial_size(polynomial_size);
         assert_eq!(
             container.container_len(),
             glwe_size.0 * fourier_poly_size * decomposition_level_count.0
@@ -80,7 +80,7 @@ impl<C: Container<Element = c64>> FourierGlevCiphertext<C>
         self.decomposition_level_count
     }
 
-    pub fn as_fourier_glwe_ciphertext_list(&self) -> FourierGlweCiphertextListView {
+    pub fn as_fourier_glwe_ciphertext_list(&self) -> FourierGlweCiphertextListView<'_, F> {
         FourierGlweCiphertextList::from_container(
             self.data.as_ref(),
             self.glwe_size,
@@ -89,8 +89,8 @@ impl<C: Container<Element = c64>> FourierGlevCiphertext<C>
     }
 }
 
-impl<C: ContainerMut<Element = c64>> FourierGlevCiphertext<C> {
-    pub fn as_mut_fourier_glwe_ciphertext_list(&mut self) -> FourierGlweCiphertextListMutView {
+impl<F: FftBackend, C: ContainerMut<Element = F>> FourierGlevCiphertext<C> {
+    pub fn as_mut_fourier_glwe_ciphertext_list(&mut self) -> FourierGlweCiphertextListMutView<'_, F> {
         FourierGlweCiphertextList::from_container(
             self.data.as_mut(),
             self.glwe_size,
@@ -99,18 +99,18 @@ impl<C: ContainerMut<Element = c64>> FourierGlevCiphertext<C> {
     }
 }
 
-impl FourierGlevCiphertextOwned {
+impl<F: FftBackend> FourierGlevCiphertextOwned<F> {
     pub fn new(
         glwe_size: GlweSize,
         polynomial_size: PolynomialSize,
         decomposition_base_log: DecompositionBaseLog,
         decomposition_level_count: DecompositionLevelCount,
-    ) -> FourierGlevCiphertextOwned {
+    ) -> FourierGlevCiphertextOwned<F> {
         Self::from_container(
             avec![
-                c64::default();
+                F::default();
                 glwe_size.0
-                    * polynomial_size.to_fourier_polynomial_size().0
+                    * F::fourier_polynomial_size(polynomial_size)
                     * decomposition_level_count.0
             ],
             glwe_size,
@@ -121,13 +121,14 @@ impl FourierGlevCiphertextOwned {
     }
 }
 
-pub fn convert_standard_glev_ciphertext_to_fourier<Scalar, InputCont, OutputCont>(
+pub fn convert_standard_glev_ciphertext_to_fourier<Scalar, F, InputCont, OutputCont>(
     standard: &GlevCiphertext<InputCont>,
     fourier: &mut FourierGlevCiphertext<OutputCont>,
 ) where
     Scalar: UnsignedTorus,
+    F: FftBackend,
     InputCont: Container<Element = Scalar>,
-    OutputCont: ContainerMut<Element = c64>,
+    OutputCont: ContainerMut<Element = F>,
 {
     assert_eq!(standard.glwe_size(), fourier.glwe_size());
     assert_eq!(standard.polynomial_size(), fourier.polynomial_size());
@@ -135,13 +136,11 @@ pub fn convert_standard_glev_ciphertext_to_fourier<Scalar, InputCont, OutputCont
     assert_eq!(standard.decomposition_level_count(), fourier.decomposition_level_count());
 
     let polynomial_size = standard.polynomial_size();
-    let fft = Fft::new(polynomial_size);
-    let fft = fft.as_view();
+    let fft = F::new_fft(polynomial_size);
 
     let mut buffers = ComputationBuffers::new();
     buffers.resize(
-        fft.forward_scratch()
-        .unwrap()
+        F::fft_scratch(&fft)
         .unaligned_bytes_required(),
     );
     let mut stack = buffers.stack();
@@ -149,10 +148,10 @@ pub fn convert_standard_glev_ciphertext_to_fourier<Scalar, InputCont, OutputCont
     for (glwe, mut fourier_glwe) in standard.as_glwe_ciphertext_list().iter()
         .zip(fourier.as_mut_fourier_glwe_ciphertext_list().iter_mut())
     {
-        for (poly, mut fourier_poly) in glwe.as_polynomial_list().iter()
+        for (poly, fourier_poly) in glwe.as_polynomial_list().iter()
             .zip(fourier_glwe.as_mut_fourier_polynomial_list().iter_mut())
         {
-            fft.forward_as_torus(fourier_poly.as_mut_view(), poly.as_view(), stack.rb_mut());
+            F::forward_as_torus(&fft, fourier_poly.data, poly.as_ref(), stack.rb_mut());
         }
     }
 }
@@ -166,7 +165,7 @@ pub struct FourierGlevCiphertextCreationMetadata(
     pub DecompositionLevelCount,
 );
 
-impl<C: Container<Element = c64>> CreateFrom<C> for FourierGlevCiphertext<C> {
+impl<F: FftBackend, C: Container<Element = F>> CreateFrom<C> for FourierGlevCiphertext<C> {
     type Metadata = FourierGlevCiphertextCreationMetadata;
 
     #[inline]
@@ -181,7 +180,7 @@ pub struct FourierGlevCiphertextCount(pub usize);
 
 
 
-pub struct FourierGlevCiphertextList<C: Container<Element = c64>> {
+pub struct FourierGlevCiphertextList<C: Container<Element: FftBackend>> {
     data: C,
     glwe_size: GlweSize,
     polynomial_size: PolynomialSize,
@@ -189,27 +188,27 @@ pub struct FourierGlevCiphertextList<C: Container<Element = c64>> {
     decomposition_level_count: DecompositionLevelCount,
 }
 
-impl<C: Container<Element = c64>> AsRef<[c64]> for FourierGlevCiphertextList<C> {
-    fn as_ref(&self) -> &[c64] {
+impl<F: FftBackend, C: Container<Element = F>> AsRef<[F]> for FourierGlevCiphertextList<C> {
+    fn as_ref(&self) -> &[F] {
         self.data.as_ref()
     }
 }
 
-impl<C: ContainerMut<Element = c64>> AsMut<[c64]> for FourierGlevCiphertextList<C> {
-    fn as_mut(&mut self) -> &mut [c64] {
+impl<F: FftBackend, C: ContainerMut<Element = F>> AsMut<[F]> for FourierGlevCiphertextList<C> {
+    fn as_mut(&mut self) -> &mut [F] {
         self.data.as_mut()
     }
 }
 
 
 /// A [`FourierGlevCiphertextList`] owning the memory for its own storage.
-pub type FourierGlevCiphertextListOwned = FourierGlevCiphertextList<AVec<c64>>;
+pub type FourierGlevCiphertextListOwned<F = c64> = FourierGlevCiphertextList<AVec<F>>;
 /// A [`FourierGlevCiphertext`] immutably borrowing memory for its own storage.
-pub type FourierGlevCiphertextListView<'data> = FourierGlevCiphertextList<&'data [c64]>;
+pub type FourierGlevCiphertextListView<'data, F = c64> = FourierGlevCiphertextList<&'data [F]>;
 /// A [`FourierGlevCiphertext`] mutably borrowing memory for its own storage.
-pub type FourierGlevCiphertextListMutView<'data> = FourierGlevCiphertextList<&'data mut [c64]>;
+pub type FourierGlevCiphertextListMutView<'data, F = c64> = FourierGlevCiphertextList<&'data mut [F]>;
 
-impl<C: Container<Element = c64>> FourierGlevCiphertextList<C> {
+impl<F: FftBackend, C: Container<Element = F>> FourierGlevCiphertextList<C> {
     pub fn from_container(
         container: C,
         glwe_size: GlweSize,
@@ -217,7 +216,7 @@ impl<C: Container<Element = c64>> FourierGlevCiphertextList<C> {
         decomposition_base_log: DecompositionBaseLog,
         decomposition_level_count: DecompositionLevelCount,
     ) -> FourierGlevCiphertextList<C> {
-        let fourier_poly_size = polynomial_size.to_fourier_polynomial_size().0;
+        let fourier_poly_size = F::fourier_polynomial_size(polynomial_size);
         assert_eq!(
             container.container_len() % (glwe_size.0 * fourier_poly_size),
             0,
@@ -249,27 +248,27 @@ impl<C: Container<Element = c64>> FourierGlevCiphertextList<C> {
     }
 
     pub fn fourier_glev_ciphertext_count(&self) -> FourierGlevCiphertextCount {
-        let fourier_poly_size = self.polynomial_size.to_fourier_polynomial_size().0;
+        let fourier_poly_size = F::fourier_polynomial_size(self.polynomial_size);
         let count = self.data.container_len() / (self.glwe_size.0 * fourier_poly_size * self.decomposition_level_count.0);
 
         FourierGlevCiphertextCount(count)
     }
 }
 
-impl FourierGlevCiphertextListOwned {
+impl<F: FftBackend> FourierGlevCiphertextListOwned<F> {
     pub fn new(
         glwe_size: GlweSize,
         polynomial_size: PolynomialSize,
         decomposition_base_log: DecompositionBaseLog,
         decomposition_level_count: DecompositionLevelCount,
         fourier_glev_ciphertext_count: FourierGlevCiphertextCount,
-    ) -> FourierGlevCiphertextListOwned {
+    ) -> FourierGlevCiphertextListOwned<F> {
         Self::from_container(
             avec![
-                c64::default();
+                F::default();
                 fourier_glev_ciphertext_count.0
                     * glwe_size.0
-                    * polynomial_size.to_fourier_polynomial_size().0
+                    * F::fourier_polynomial_size(polynomial_size)
                     * decomposition_level_count.0
             ],
             glwe_size,
@@ -289,7 +288,7 @@ pub struct FourierGlevCiphertextListCreationMetadata(
     pub DecompositionLevelCount,
 );
 
-impl<C: Container<Element = c64>> CreateFrom<C>
+impl<F: FftBackend, C: Container<Element = F>> CreateFrom<C>
     for FourierGlevCiphertextList<C>
 {
     type Metadata = FourierGlevCiphertextListCreationMetadata;
@@ -302,20 +301,20 @@ impl<C: Container<Element = c64>> CreateFrom<C>
     }
 }
 
-impl<C: Container<Element = c64>> ContiguousEntityContainer
+impl<F: FftBackend, C: Container<Element = F>> ContiguousEntityContainer
     for FourierGlevCiphertextList<C>
 {
     type Element = C::Element;
 
     type EntityViewMetadata = FourierGlevCiphertextCreationMetadata;
 
-    type EntityView<'this> = FourierGlevCiphertextView<'this>
+    type EntityView<'this> = FourierGlevCiphertextView<'this, F>
     where
         Self: 'this;
 
     type SelfViewMetadata = FourierGlevCiphertextListCreationMetadata;
 
-    type SelfView<'this> = FourierGlevCiphertextListView<'this>
+    type SelfView<'this> = FourierGlevCiphertextListView<'this, F>
     where
         Self: 'this;
 
@@ -324,7 +323,7 @@ impl<C: Container<Element = c64>> ContiguousEntityContainer
     }
 
     fn get_entity_view_pod_size(&self) -> usize {
-        fourier_glev_ciphertext_size(self.glwe_size(), self.polynomial_size(), self.decomposition_level_count())
+        fourier_glev_ciphertext_size::<F>(self.glwe_size(), self.polynomial_size(), self.decomposition_level_count())
     }
 
     fn  get_self_view_creation_metadata(&self) -> Self::SelfViewMetadata {
@@ -337,14 +336,14 @@ impl<C: Container<Element = c64>> ContiguousEntityContainer
     }
 }
 
-impl<C: ContainerMut<Element = c64>> ContiguousEntityContainerMut
+impl<F: FftBackend, C: ContainerMut<Element = F>> ContiguousEntityContainerMut
     for FourierGlevCiphertextList<C>
 {
-    type EntityMutView<'this> = FourierGlevCiphertextMutView<'this>
+    type EntityMutView<'this> = FourierGlevCiphertextMutView<'this, F>
     where
         Self: 'this;
 
-    type SelfMutView<'this> = FourierGlevCiphertextListMutView<'this>
+    type SelfMutView<'this> = FourierGlevCiphertextListMutView<'this, F>
     where
         Self: 'this;
 }
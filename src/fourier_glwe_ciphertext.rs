@@ -4,88 +4,90 @@ use tfhe::core_crypto::{
     fft_impl::fft64::c64,
 };
 
+use crate::FftBackend;
+
 // Extension of tfhe::core_crypto::fft_impl::fft64::math::FourierPolynomialList
-pub struct FourierPolynomialList<C: Container<Element = c64>> {
+pub struct FourierPolynomialList<C: Container<Element: FftBackend>> {
     pub data: C,
     pub polynomial_size: PolynomialSize,
 }
 
-pub type FourierPolynomialListView<'data> = FourierPolynomialList<&'data [c64]>;
-pub type FourierPolynomialListMutView<'data> = FourierPolynomialList<&'data mut [c64]>;
+pub type FourierPolynomialListView<'data, F = c64> = FourierPolynomialList<&'data [F]>;
+pub type FourierPolynomialListMutView<'data, F = c64> = FourierPolynomialList<&'data mut [F]>;
 
-impl<C: Container<Element = c64>> FourierPolynomialList<C> {
+impl<F: FftBackend, C: Container<Element = F>> FourierPolynomialList<C> {
     pub fn polynomial_count(&self) -> PolynomialCount {
         PolynomialCount(
-            self.data.container_len() / self.polynomial_size.to_fourier_polynomial_size().0
+            self.data.container_len() / F::fourier_polynomial_size(self.polynomial_size)
         )
     }
 
     pub fn iter(
         &self
-    ) -> impl DoubleEndedIterator<Item = FourierPolynomial<&'_ [c64]>> {
+    ) -> impl DoubleEndedIterator<Item = FourierPolynomial<&'_ [F]>> {
         assert_eq!(
-            self.data.container_len() % self.polynomial_size.to_fourier_polynomial_size().0,
+            self.data.container_len() % F::fourier_polynomial_size(self.polynomial_size),
             0,
         );
         self.data
             .as_ref()
-            .chunks_exact(self.polynomial_size.to_fourier_polynomial_size().0)
+            .chunks_exact(F::fourier_polynomial_size(self.polynomial_size))
             .map(move |slice| FourierPolynomial { data: slice })
     }
 }
 
-impl<C: ContainerMut<Element = c64>> FourierPolynomialList<C> {
+impl<F: FftBackend, C: ContainerMut<Element = F>> FourierPolynomialList<C> {
     pub fn iter_mut(
         &mut self,
-    ) -> impl DoubleEndedIterator<Item = FourierPolynomial<&'_ mut [c64]>> {
+    ) -> impl DoubleEndedIterator<Item = FourierPolynomial<&'_ mut [F]>> {
         assert_eq!(
-            self.data.container_len() % self.polynomial_size.to_fourier_polynomial_size().0,
+            self.data.container_len() % F::fourier_polynomial_size(self.polynomial_size),
             0,
         );
         self.data
             .as_mut()
-            .chunks_exact_mut(self.polynomial_size.to_fourier_polynomial_size().0)
+            .chunks_exact_mut(F::fourier_polynomial_size(self.polynomial_size))
             .map(move |slice| FourierPolynomial { data: slice })
     }
 }
 
-pub struct FourierGlweCiphertext<C: Container<Element = c64>> {
+pub struct FourierGlweCiphertext<C: Container<Element: FftBackend>> {
     data: C,
     glwe_size: GlweSize,
     polynomial_size: PolynomialSize,
 }
 
 /// A [`FourierGlweCiphertext`] owning the memory for its own storage.
-pub type FourierGlweCiphertextOwned = FourierGlweCiphertext<AVec<c64>>;
+pub type FourierGlweCiphertextOwned<F = c64> = FourierGlweCiphertext<AVec<F>>;
 /// A [`FourierGlweCiphertext`] immutably borrowing memory for its own storage.
-pub type FourierGlweCiphertextView<'data> = FourierGlweCiphertext<&'data [c64]>;
+pub type FourierGlweCiphertextView<'data, F = c64> = FourierGlweCiphertext<&'data [F]>;
 /// A [`FourierGlevCiphertext`] mutably borrowing memory for its own storage.
-pub type FourierGlweCiphertextMutView<'data> = FourierGlweCiphertext<&'data mut [c64]>;
+pub type FourierGlweCiphertextMutView<'data, F = c64> = FourierGlweCiphertext<&'data mut [F]>;
 
-impl<C: Container<Element = c64>> AsRef<[c64]> for FourierGlweCiphertext<C> {
-    fn as_ref(&self) -> &[c64] {
+impl<F: FftBackend, C: Container<Element = F>> AsRef<[F]> for FourierGlweCiphertext<C> {
+    fn as_ref(&self) -> &[F] {
         self.data.as_ref()
     }
 }
 
-impl<C: ContainerMut<Element = c64>> AsMut<[c64]> for FourierGlweCiphertext<C> {
-    fn as_mut(&mut self) -> &mut [c64] {
+impl<F: FftBackend, C: ContainerMut<Element = F>> AsMut<[F]> for FourierGlweCiphertext<C> {
+    fn as_mut(&mut self) -> &mut [F] {
         self.data.as_mut()
     }
 }
 
-pub fn fourier_glwe_ciphertext_size(glwe_size: GlweSize, polynomial_size: PolynomialSize) -> usize {
-    glwe_size.0 * polynomial_size.to_fourier_polynomial_size().0
+pub fn fourier_glwe_ciphertext_size<F: FftBackend>(glwe_size: GlweSize, polynomial_size: PolynomialSize) -> usize {
+    glwe_size.0 * F::fourier_polynomial_size(polynomial_size)
 }
 
-impl<C: Container<Element = c64>> FourierGlweCiphertext<C>
+impl<F: FftBackend, C: Container<Element = F>> FourierGlweCiphertext<C>
 {
     pub fn from_container(
         container: C,
         glwe_size: GlweSize,
         polynomial_size: PolynomialSize,
     ) -> FourierGlweCiphertext<C> {
-        let fourier_poly_size = polynomial_size.to_fourier_polynomial_size().0;
+        let fourier_poly_size = F::fourier_polynomial_size(polynomial_size);
         assert_eq!(
             container.container_len(),
             glwe_size.0 * fourier_poly_size
@@ -106,7 +108,7 @@ impl<C: Container<Element = c64>> FourierGlweCiphertext<C>
         self.polynomial_size
     }
 
-    pub fn as_fourier_polynomial_list(&self) -> FourierPolynomialListView {
+    pub fn as_fourier_polynomial_list(&self) -> FourierPolynomialListView<'_, F> {
         FourierPolynomialList {
             data: self.data.as_ref(),
             polynomial_size: self.polynomial_size,
@@ -114,9 +116,9 @@ impl<C: Container<Element = c64>> FourierGlweCiphertext<C>
     }
 }
 
-impl<C: ContainerMut<Element = c64>> FourierGlweCiphertext<C>
+impl<F: FftBackend, C: ContainerMut<Element = F>> FourierGlweCiphertext<C>
 {
-    pub fn as_mut_fourier_polynomial_list(&mut self) -> FourierPolynomialListMutView {
+    pub fn as_mut_fourier_polynomial_list(&mut self) -> FourierPolynomialListMutView<'_, F> {
         FourierPolynomialList {
             data: self.data.as_mut(),
             polynomial_size: self.polynomial_size,
@@ -124,15 +126,15 @@ impl<C: ContainerMut<Element = c64>> FourierGlweCiphertext<C>
     }
 }
 
-impl FourierGlweCiphertextOwned {
+impl<F: FftBackend> FourierGlweCiphertextOwned<F> {
     pub fn new(
         glwe_size: GlweSize,
         polynomial_size: PolynomialSize,
-    ) -> FourierGlweCiphertextOwned {
-        let fourier_poly_size = polynomial_size.to_fourier_polynomial_size().0;
+    ) -> FourierGlweCiphertextOwned<F> {
+        let fourier_poly_size = F::fourier_polynomial_size(polynomial_size);
         Self::from_container(
             avec![
-                c64::default();
+                F::default();
                 glwe_size.0  * fourier_poly_size
             ],
             glwe_size,
@@ -148,7 +150,7 @@ pub struct FourierGlweCiphertextCreationMetadata(
     pub PolynomialSize,
 );
 
-impl<C: Container<Element = c64>> CreateFrom<C> for FourierGlweCiphertext<C> {
+impl<F: FftBackend, C: Container<Element = F>> CreateFrom<C> for FourierGlweCiphertext<C> {
     type Metadata = FourierGlweCiphertextCreationMetadata;
 
     #[inline]
@@ -162,40 +164,40 @@ impl<C: Container<Element = c64>> CreateFrom<C> for FourierGlweCiphertext<C> {
 pub struct FourierGlweCiphertextCount(pub usize);
 
 
-pub struct FourierGlweCiphertextList<C: Container<Element = c64>>
+pub struct FourierGlweCiphertextList<C: Container<Element: FftBackend>>
 {
     data: C,
     glwe_size: GlweSize,
     polynomial_size: PolynomialSize,
 }
 
-impl<C: Container<Element = c64>> AsRef<[c64]> for FourierGlweCiphertextList<C> {
-    fn as_ref(&self) -> &[c64] {
+impl<F: FftBackend, C: Container<Element = F>> AsRef<[F]> for FourierGlweCiphertextList<C> {
+    fn as_ref(&self) -> &[F] {
         self.data.as_ref()
     }
 }
 
-impl<C: ContainerMut<Element = c64>> AsMut<[c64]> for FourierGlweCiphertextList<C> {
-    fn as_mut(&mut self) -> &mut [c64] {
+impl<F: FftBackend, C: ContainerMut<Element = F>> AsMut<[F]> for FourierGlweCiphertextList<C> {
+    fn as_mut(&mut self) -> &mut [F] {
         self.data.as_mut()
     }
 }
 
 
 /// A [`FourierGlweCiphertextList`] owning the memory for its own storage.
-pub type FourierGlweCiphertextListOwned = FourierGlweCiphertextList<AVec<c64>>;
+pub type FourierGlweCiphertextListOwned<F = c64> = FourierGlweCiphertextList<AVec<F>>;
 /// A [`FourierGlweCiphertext`] immutably borrowing memory for its own storage.
-pub type FourierGlweCiphertextListView<'data> = FourierGlweCiphertextList<&'data [c64]>;
+pub type FourierGlweCiphertextListView<'data, F = c64> = FourierGlweCiphertextList<&'data [F]>;
 /// A [`FourierGlweCiphertext`] mutably borrowing memory for its own storage.
-pub type FourierGlweCiphertextListMutView<'data> = FourierGlweCiphertextList<&'data mut [c64]>;
+pub type FourierGlweCiphertextListMutView<'data, F = c64> = FourierGlweCiphertextList<&'data mut [F]>;
 
-impl<C: Container<Element = c64>> FourierGlweCiphertextList<C> {
+impl<F: FftBackend, C: Container<Element = F>> FourierGlweCiphertextList<C> {
     pub fn from_container(
         container: C,
         glwe_size: GlweSize,
         polynomial_size: PolynomialSize,
     ) -> FourierGlweCiphertextList<C> {
-        let fourier_poly_size = polynomial_size.to_fourier_polynomial_size().0;
+        let fourier_poly_size = F::fourier_polynomial_size(polynomial_size);
         assert_eq!(
             container.container_len() % (glwe_size.0 * fourier_poly_size),
             0,
@@ -217,25 +219,25 @@ impl<C: Container<Element = c64>> FourierGlweCiphertextList<C> {
     }
 
     pub fn fourier_glwe_ciphertext_count(&self) -> FourierGlweCiphertextCount {
-        let fourier_poly_size = self.polynomial_size.to_fourier_polynomial_size().0;
+        let fourier_poly_size = F::fourier_polynomial_size(self.polynomial_size);
         let count = self.data.container_len() / (self.glwe_size.0 * fourier_poly_size);
 
         FourierGlweCiphertextCount(count)
     }
 }
 
-impl FourierGlweCiphertextListOwned {
+impl<F: FftBackend> FourierGlweCiphertextListOwned<F> {
     pub fn new(
         glwe_size: GlweSize,
         polynomial_size: PolynomialSize,
         fourier_glwe_ciphertext_count: FourierGlweCiphertextCount,
-    ) -> FourierGlweCiphertextListOwned {
+    ) -> FourierGlweCiphertextListOwned<F> {
         Self::from_container(
             avec![
-                c64::default();
+                F::default();
                 fourier_glwe_ciphertext_count.0
                     * glwe_size.0
-                    * polynomial_size.to_fourier_polynomial_size().0
+                    * F::fourier_polynomial_size(polynomial_size)
             ],
             glwe_size,
             polynomial_size,
@@ -251,7 +253,7 @@ pub struct FourierGlweCiphertextListCreationMetadata(
     pub FourierGlweCiphertextCount,
 );
 
-impl<C: Container<Element = c64>> CreateFrom<C>
+impl<F: FftBackend, C: Container<Element = F>> CreateFrom<C>
     for FourierGlweCiphertextList<C>
 {
     type Metadata = FourierGlweCiphertextListCreationMetadata;
@@ -263,20 +265,20 @@ impl<C: Container<Element = c64>> CreateFrom<C>
     }
 }
 
-impl<C: Container<Element = c64>> ContiguousEntityContainer
+impl<F: FftBackend, C: Container<Element = F>> ContiguousEntityContainer
     for FourierGlweCiphertextList<C>
 {
     type Element = C::Element;
 
     type EntityViewMetadata = FourierGlweCiphertextCreationMetadata;
 
-    type EntityView<'this> = FourierGlweCiphertextView<'this>
+    type EntityView<'this> = FourierGlweCiphertextView<'this, F>
     where
         C: 'this;
 
     type SelfViewMetadata = FourierGlweCiphertextListCreationMetadata;
 
-    type SelfView<'this> = FourierGlweCiphertextListView<'this>
+    type SelfView<'this> = FourierGlweCiphertextListView<'this, F>
     where
         C: 'this;
 
@@ -285,7 +287,7 @@ impl<C: Container<Element = c64>> ContiguousEntityContainer
     }
 
     fn get_entity_view_pod_size(&self) -> usize {
-        fourier_glwe_ciphertext_size(self.glwe_size(), self.polynomial_size())
+        fourier_glwe_ciphertext_size::<F>(self.glwe_size(), self.polynomial_size())
     }
 
     fn get_self_view_creation_metadata(&self) -> Self::SelfViewMetadata {
@@ -297,14 +299,14 @@ impl<C: Container<Element = c64>> ContiguousEntityContainer
     }
 }
 
-impl<C: ContainerMut<Element = c64>> ContiguousEntityContainerMut
+impl<F: FftBackend, C: ContainerMut<Element = F>> ContiguousEntityContainerMut
     for FourierGlweCiphertextList<C>
 {
-    type EntityMutView<'this> = FourierGlweCiphertextMutView<'this>
+    type EntityMutView<'this> = FourierGlweCiphertextMutView<'this, F>
     where
         C: 'this;
 
-    type SelfMutView<'this> = FourierGlweCiphertextListMutView<'this>
+    type SelfMutView<'this> = FourierGlweCiphertextListMutView<'this, F>
     where
         C: 'this;
 }
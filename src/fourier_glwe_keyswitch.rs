@@ -1,25 +1,28 @@
-use aligned_vec::{avec, ABox};
-use dyn_stack::ReborrowMut;
+use aligned_vec::{avec, ABox, CACHELINE_ALIGN};
+use dyn_stack::{PodStack, ReborrowMut, SizeOverflow, StackReq};
 use tfhe::core_crypto::{
     prelude::*,
     fft_impl::fft64::c64,
 };
 
 use crate::{
-    convert_lwe_to_glwe_const, fourier_glev_ciphertext::*, fourier_glwe_ciphertext::*, fourier_poly_mult_and_add, glev_ciphertext::*, GlweKeyswitchKey
+    convert_lwe_to_glwe_const, fourier_glev_ciphertext::*, fourier_glwe_ciphertext::*, glev_ciphertext::*, FftBackend, GlweKeyswitchKey
 };
 
-#[derive(Debug, Clone, Copy)]
+/// Split of the keyswitching key, which also selects the FFT backend: [`FftType::F128`] is on the f128
+/// backend [`F128`](crate::F128) and the others are on the f64 backend `c64`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum FftType {
     Vanilla,
     Split(usize),
     Split16,
+    F128,
 }
 
 impl FftType {
     pub fn num_split(&self) -> usize {
         match self {
-            FftType::Vanilla => 1,
+            FftType::Vanilla | FftType::F128 => 1,
             FftType::Split(_) => 2,
             FftType::Split16 => 4,
         }
@@ -27,14 +30,14 @@ impl FftType {
 
     pub fn split_base_log(&self) -> usize {
         match self {
-            FftType::Vanilla => 64,
+            FftType::Vanilla | FftType::F128 => 64,
             FftType::Split(b) => *b,
             FftType::Split16 => 16,
         }
     }
 }
 
-pub struct FourierGlweKeyswitchKey<C: Container<Element = c64>>
+pub struct FourierGlweKeyswitchKey<C: Container<Element: FftBackend>>
 {
     data: C,
     input_glwe_size: GlweSize,
@@ -45,7 +48,7 @@ pub struct FourierGlweKeyswitchKey<C: Container<Element = c64>>
     fft_type: FftType,
 }
 
-impl<C: Container<Element = c64>> FourierGlweKeyswitchKey<C> {
+impl<F: FftBackend, C: Container<Element = F>> FourierGlweKeyswitchKey<C> {
     pub fn from_container(
         container: C,
         input_glwe_size: GlweSize,
@@ -57,12 +60,13 @@ impl<C: Container<Element = c64>> FourierGlweKeyswitchKey<C> {
     ) -> Self {
         let fourier_glev_elem_count = input_glwe_size.to_glwe_dimension().0
             * output_glwe_size.0
-            * polynomial_size.to_fourier_polynomial_size().0
+            * F::fourier_polynomial_size(polynomial_size)
             * decomp_level_count.0;
         assert_eq!(
             container.container_len(),
             fourier_glev_elem_count * fft_type.num_split(),
         );
+        assert!(F::supports_fft_type(fft_type), "{fft_type:?} is not supported by the FFT backend");
 
         Self {
             data: container,
@@ -99,7 +103,7 @@ impl<C: Container<Element = c64>> FourierGlweKeyswitchKey<C> {
         self.fft_type
     }
 
-    pub fn as_fourier_glev_ciphertext_list(&self) -> FourierGlevCiphertextListView {
+    pub fn as_fourier_glev_ciphertext_list(&self) -> FourierGlevCiphertextListView<'_, F> {
         FourierGlevCiphertextList::from_container(
             self.data.as_ref(),
             self.output_glwe_size,
@@ -110,8 +114,8 @@ impl<C: Container<Element = c64>> FourierGlweKeyswitchKey<C> {
     }
 }
 
-impl<C: ContainerMut<Element = c64>> FourierGlweKeyswitchKey<C> {
-    pub fn as_mut_fourier_glev_ciphertext_list(&mut self) -> FourierGlevCiphertextListMutView {
+impl<F: FftBackend, C: ContainerMut<Element = F>> FourierGlweKeyswitchKey<C> {
+    pub fn as_mut_fourier_glev_ciphertext_list(&mut self) -> FourierGlevCiphertextListMutView<'_, F> {
         FourierGlevCiphertextList::from_container(
             self.data.as_mut(),
             self.output_glwe_size,
@@ -122,9 +126,9 @@ impl<C: ContainerMut<Element = c64>> FourierGlweKeyswitchKey<C> {
     }
 }
 
-pub type FourierGlweKeyswitchKeyOwned = FourierGlweKeyswitchKey<ABox<[c64]>>;
+pub type FourierGlweKeyswitchKeyOwned<F = c64> = FourierGlweKeyswitchKey<ABox<[F]>>;
 
-impl FourierGlweKeyswitchKeyOwned {
+impl<F: FftBackend> FourierGlweKeyswitchKeyOwned<F> {
     pub fn new(
         input_glwe_size: GlweSize,
         output_glwe_size: GlweSize,
@@ -135,12 +139,13 @@ impl FourierGlweKeyswitchKeyOwned {
     ) -> Self {
         let count = input_glwe_size.to_glwe_dimension().0
             * output_glwe_size.0
-            * polynomial_size.to_fourier_polynomial_size().0
+            * F::fourier_polynomial_size(polynomial_size)
             * decomp_level_count.0
             * fft_type.num_split();
+        assert!(F::supports_fft_type(fft_type), "{fft_type:?} is not supported by the FFT backend");
 
         Self {
-            data: avec![c64::default(); count].into_boxed_slice(),
+            data: avec![F::default(); count].into_boxed_slice(),
             input_glwe_size: input_glwe_size,
             output_glwe_size: output_glwe_size,
             polynomial_size: polynomial_size,
@@ -151,13 +156,14 @@ impl FourierGlweKeyswitchKeyOwned {
     }
 }
 
-pub fn convert_standard_glwe_keyswitch_key_to_fourier<Scalar, InputCont, OutputCont>(
+pub fn convert_standard_glwe_keyswitch_key_to_fourier<Scalar, F, InputCont, OutputCont>(
     input_ksk: &GlweKeyswitchKey<InputCont>,
     output_ksk: &mut FourierGlweKeyswitchKey<OutputCont>,
 ) where
     Scalar: UnsignedTorus,
+    F: FftBackend,
     InputCont: Container<Element=Scalar>,
-    OutputCont: ContainerMut<Element=c64>,
+    OutputCont: ContainerMut<Element=F>,
 {
     assert_eq!(Scalar::BITS, 64, "current fourier GLWE ksk works on q = 2^64");
     assert_eq!(input_ksk.polynomial_size(), output_ksk.polynomial_size());
@@ -186,7 +192,7 @@ pub fn convert_standard_glwe_keyswitch_key_to_fourier<Scalar, InputCont, OutputC
                 .zip(input_split_glev.as_mut().iter_mut())
             {
                 match fft_type {
-                    FftType::Vanilla => {
+                    FftType::Vanilla | FftType::F128 => {
                         *dst = *src;
                     }
                     FftType::Split(_) => {
@@ -210,13 +216,68 @@ pub fn convert_standard_glwe_keyswitch_key_to_fourier<Scalar, InputCont, OutputC
     }
 }
 
-pub fn keyswitch_glwe_ciphertext<Scalar, KSKeyCont, InputCont, OutputCont>(
+pub fn keyswitch_glwe_ciphertext<Scalar, F, KSKeyCont, InputCont, OutputCont>(
     glwe_keyswitch_key: &FourierGlweKeyswitchKey<KSKeyCont>,
     input: &GlweCiphertext<InputCont>,
     output: &mut GlweCiphertext<OutputCont>,
 ) where
     Scalar: UnsignedTorus,
-    KSKeyCont: Container<Element=c64>,
+    F: FftBackend,
+    KSKeyCont: Container<Element=F>,
+    InputCont: Container<Element=Scalar>,
+    OutputCont: ContainerMut<Element=Scalar>,
+{
+    let fft = F::new_fft(glwe_keyswitch_key.polynomial_size());
+
+    let mut buffers = ComputationBuffers::new();
+    buffers.resize(
+        keyswitch_glwe_ciphertext_mem_optimized_requirement::<Scalar, F>(
+            glwe_keyswitch_key.output_glwe_size(),
+            glwe_keyswitch_key.polynomial_size(),
+            glwe_keyswitch_key.decomp_level_count(),
+            glwe_keyswitch_key.fft_type(),
+            &fft,
+        )
+        .unwrap()
+        .unaligned_bytes_required(),
+    );
+
+    keyswitch_glwe_ciphertext_mem_optimized(glwe_keyswitch_key, input, output, &fft, buffers.stack());
+}
+
+pub fn keyswitch_glwe_ciphertext_mem_optimized_requirement<Scalar, F>(
+    output_glwe_size: GlweSize,
+    polynomial_size: PolynomialSize,
+    decomp_level_count: DecompositionLevelCount,
+    fft_type: FftType,
+    fft: &F::Fft,
+) -> Result<StackReq, SizeOverflow>
+where
+    Scalar: UnsignedTorus,
+    F: FftBackend,
+{
+    let fourier_polynomial_size = F::fourier_polynomial_size(polynomial_size);
+    StackReq::try_all_of([
+        // decomposed input mask polynomial
+        StackReq::try_new_aligned::<Scalar>(decomp_level_count.0 * polynomial_size.0, CACHELINE_ALIGN)?,
+        StackReq::try_new_aligned::<F>(decomp_level_count.0 * fourier_polynomial_size, CACHELINE_ALIGN)?,
+        // output of each split in the Fourier and the standard domain
+        StackReq::try_new_aligned::<F>(fft_type.num_split() * output_glwe_size.0 * fourier_polynomial_size, CACHELINE_ALIGN)?,
+        StackReq::try_new_aligned::<Scalar>(output_glwe_size.0 * polynomial_size.0, CACHELINE_ALIGN)?,
+        F::fft_scratch(fft),
+    ])
+}
+
+pub fn keyswitch_glwe_ciphertext_mem_optimized<Scalar, F, KSKeyCont, InputCont, OutputCont>(
+    glwe_keyswitch_key: &FourierGlweKeyswitchKey<KSKeyCont>,
+    input: &GlweCiphertext<InputCont>,
+    output: &mut GlweCiphertext<OutputCont>,
+    fft: &F::Fft,
+    stack: PodStack<'_>,
+) where
+    Scalar: UnsignedTorus,
+    F: FftBackend,
+    KSKeyCont: Container<Element=F>,
     InputCont: Container<Element=Scalar>,
     OutputCont: ContainerMut<Element=Scalar>,
 {
@@ -246,17 +307,7 @@ pub fn keyswitch_glwe_ciphertext<Scalar, KSKeyCont, InputCont, OutputCont>(
     let decomp_base_log = glwe_keyswitch_key.decomp_base_log();
     let decomp_level = glwe_keyswitch_key.decomp_level_count();
     let ciphertext_modulus = input.ciphertext_modulus();
-
-    let fft = Fft::new(polynomial_size);
-    let fft = fft.as_view();
-
-    let mut buffers = ComputationBuffers::new();
-    buffers.resize(
-        fft.backward_scratch()
-        .unwrap()
-        .unaligned_bytes_required(),
-    );
-    let mut stack = buffers.stack();
+    let fourier_polynomial_size = F::fourier_polynomial_size(polynomial_size);
 
     output.as_mut().fill(Scalar::ZERO);
     output.get_mut_body().as_mut().clone_from_slice(input.get_body().as_ref());
@@ -266,17 +317,21 @@ pub fn keyswitch_glwe_ciphertext<Scalar, KSKeyCont, InputCont, OutputCont>(
     let num_split = fft_type.num_split();
     let split_base_log = fft_type.split_base_log();
 
-    let mut buffer_fourier_glwe_list = FourierGlweCiphertextList::new(output_glwe_size, polynomial_size, FourierGlweCiphertextCount(num_split));
+    let (mut input_decomp_data, stack) = stack.make_aligned_raw::<Scalar>(decomp_level.0 * polynomial_size.0, CACHELINE_ALIGN);
+    let (mut fourier_input_decomp_data, stack) = stack.make_aligned_raw::<F>(decomp_level.0 * fourier_polynomial_size, CACHELINE_ALIGN);
+    let (mut buffer_fourier_glwe_data, stack) = stack.collect_aligned(
+        CACHELINE_ALIGN,
+        std::iter::repeat_n(F::default(), num_split * output_glwe_size.0 * fourier_polynomial_size),
+    );
+    let (mut buffer_glwe_data, mut stack) = stack.make_aligned_raw::<Scalar>(output_glwe_size.0 * polynomial_size.0, CACHELINE_ALIGN);
+
+    let mut buffer_fourier_glwe_list = FourierGlweCiphertextList::from_container(&mut *buffer_fourier_glwe_data, output_glwe_size, polynomial_size);
 
     let input_mask = input.get_mask();
     for (input_mask_poly, fourier_glev_split_list) in input_mask.as_polynomial_list().iter()
         .zip(glwe_keyswitch_key.as_fourier_glev_ciphertext_list().chunks_exact(num_split))
     {
-        let mut input_decomp_poly_list = PolynomialList::new(
-            Scalar::ZERO,
-            polynomial_size,
-            PolynomialCount(decomp_level.0),
-        );
+        let mut input_decomp_poly_list = PolynomialList::from_container(&mut *input_decomp_data, polynomial_size);
 
         for (i, val) in input_mask_poly.iter().enumerate() {
             let decomposition_iter = decomposer.decompose(*val);
@@ -287,20 +342,17 @@ pub fn keyswitch_glwe_ciphertext<Scalar, KSKeyCont, InputCont, OutputCont>(
         }
 
         let mut fourier_input_decomp_poly_list = FourierPolynomialList {
-            data: avec![
-                c64::default();
-                polynomial_size.to_fourier_polynomial_size().0
-                    * decomp_level.0
-            ].into_boxed_slice(),
-            polynomial_size: polynomial_size,
+            data: &mut *fourier_input_decomp_data,
+            polynomial_size,
         };
 
-        for (decomp_poly, mut fourier_decomp_poly) in input_decomp_poly_list.iter()
+        for (decomp_poly, fourier_decomp_poly) in input_decomp_poly_list.iter()
             .zip(fourier_input_decomp_poly_list.iter_mut())
         {
-            fft.forward_as_integer(
-                fourier_decomp_poly.as_mut_view(),
-                decomp_poly.as_view(),
+            F::forward_as_integer(
+                fft,
+                fourier_decomp_poly.data,
+                decomp_poly.as_ref(),
                 stack.rb_mut(),
             );
         }
@@ -311,28 +363,25 @@ pub fn keyswitch_glwe_ciphertext<Scalar, KSKeyCont, InputCont, OutputCont>(
             for (fourier_decomp_poly, fourier_glwe) in fourier_input_decomp_poly_list.iter_mut()
                 .zip(fourier_glev_split.as_fourier_glwe_ciphertext_list().iter().rev())
             {
-                for (mut buffer_poly, fourier_poly) in buffer_fourier_glwe.as_mut_fourier_polynomial_list().iter_mut()
+                for (buffer_poly, fourier_poly) in buffer_fourier_glwe.as_mut_fourier_polynomial_list().iter_mut()
                     .zip(fourier_glwe.as_fourier_polynomial_list().iter())
                 {
-                    fourier_poly_mult_and_add(&mut buffer_poly, &fourier_decomp_poly, &fourier_poly);
+                    F::mult_and_add(buffer_poly.data, fourier_decomp_poly.data, fourier_poly.data);
                 }
             }
         }
     }
 
-    let mut buffer_glwe_list = GlweCiphertextList::new(Scalar::ZERO, output_glwe_size, polynomial_size, GlweCiphertextCount(num_split), ciphertext_modulus);
-    for (k, (mut buffer_glwe, buffer_fourier_glwe)) in buffer_glwe_list.iter_mut()
-        .zip(buffer_fourier_glwe_list.iter())
-        .enumerate()
-    {
+    let mut buffer_glwe = GlweCiphertext::from_container(&mut *buffer_glwe_data, polynomial_size, ciphertext_modulus);
+    for (k, buffer_fourier_glwe) in buffer_fourier_glwe_list.iter().enumerate() {
         for (mut buffer_poly, buffer_fourier_poly) in buffer_glwe.as_mut_polynomial_list().iter_mut()
             .zip(buffer_fourier_glwe.as_fourier_polynomial_list().iter())
         {
-            fft.backward_as_torus(buffer_poly.as_mut_view(), buffer_fourier_poly.as_view(), stack.rb_mut());
+            F::backward_as_torus(fft, buffer_poly.as_mut(), buffer_fourier_poly.data, stack.rb_mut());
         }
 
         let log_scaling = match fft_type {
-            FftType::Vanilla => 0,
+            FftType::Vanilla | FftType::F128 => 0,
             FftType::Split(_) => if k == 0 {0} else {split_base_log},
             FftType::Split16 => k * split_base_log,
         };
@@ -341,7 +390,7 @@ pub fn keyswitch_glwe_ciphertext<Scalar, KSKeyCont, InputCont, OutputCont>(
     }
 }
 
-pub fn keyswitch_lwe_ciphertext_by_glwe_keyswitch<Scalar, InputCont, OutputCont, KSKeyCont>(
+pub fn keyswitch_lwe_ciphertext_by_glwe_keyswitch<Scalar, F, InputCont, OutputCont, KSKeyCont>(
     input: &LweCiphertext<InputCont>,
     output: &mut LweCiphertext<OutputCont>,
     glwe_keyswitch_key: &FourierGlweKeyswitchKey<KSKeyCont>
@@ -349,7 +398,8 @@ pub fn keyswitch_lwe_ciphertext_by_glwe_keyswitch<Scalar, InputCont, OutputCont,
     Scalar: UnsignedTorus,
     InputCont: Container<Element = Scalar>,
     OutputCont: ContainerMut<Element = Scalar>,
-    KSKeyCont: Container<Element = c64>,
+    F: FftBackend,
+    KSKeyCont: Container<Element = F>,
 {
     assert_eq!(input.ciphertext_modulus(), output.ciphertext_modulus());
     let ciphertext_modulus = input.ciphertext_modulus();
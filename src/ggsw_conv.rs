@@ -1,14 +1,118 @@
 use std::collections::HashMap;
-use aligned_vec::{ABox, CACHELINE_ALIGN};
+use aligned_vec::{avec, ABox};
+use dyn_stack::{PodStack, ReborrowMut, SizeOverflow, StackReq};
+#[cfg(feature = "multithread")]
+use rayon::prelude::*;
 use tfhe::core_crypto::{
-    fft_impl::fft64::{
-        c64,
-        crypto::{
-            bootstrap::FourierLweBootstrapKeyView, ggsw::FourierGgswCiphertextListView
+    fft_impl::{
+        fft64::{
+            c64,
+            crypto::{
+                bootstrap::FourierLweBootstrapKeyView, ggsw::{FourierGgswCiphertext, FourierGgswCiphertextList}
+            },
         },
     }, prelude::{polynomial_algorithms::*, *}
 };
-use crate::{automorphism::*, glwe_conv::*, lwe_preprocessing_assign, pbs::*, utils::*};
+use crate::{automorphism::*, fft_backend::{FftBackend, F128}, fourier_glwe_keyswitch::FourierGlweKeyswitchKey, glwe_conv::*, lwe_preprocessing_assign, pbs::*, utils::*};
+
+/// Scheme switching key in the Fourier domain of the FFT backend given by the element type, i.e. the GGSW
+/// encryptions of the GLWE secret key polynomials.
+#[derive(Clone, Copy)]
+pub struct FourierSchemeSwitchingKey<C: Container<Element: FftBackend>> {
+    data: C,
+    glwe_size: GlweSize,
+    polynomial_size: PolynomialSize,
+    decomposition_base_log: DecompositionBaseLog,
+    decomposition_level_count: DecompositionLevelCount,
+}
+
+/// A [`FourierSchemeSwitchingKey`] owning the memory for its own storage.
+pub type FourierSchemeSwitchingKeyOwned<F = c64> = FourierSchemeSwitchingKey<ABox<[F]>>;
+/// A [`FourierSchemeSwitchingKey`] immutably borrowing memory for its own storage.
+pub type FourierSchemeSwitchingKeyView<'data, F = c64> = FourierSchemeSwitchingKey<&'data [F]>;
+
+pub fn fourier_ggsw_ciphertext_size<F: FftBackend>(
+    glwe_size: GlweSize,
+    polynomial_size: PolynomialSize,
+    decomposition_level_count: DecompositionLevelCount,
+) -> usize {
+    glwe_size.0 * glwe_size.0 * decomposition_level_count.0 * F::fourier_polynomial_size(polynomial_size)
+}
+
+impl<F: FftBackend, C: Container<Element = F>> FourierSchemeSwitchingKey<C> {
+    pub fn from_container(
+        container: C,
+        glwe_size: GlweSize,
+        polynomial_size: PolynomialSize,
+        decomposition_base_log: DecompositionBaseLog,
+        decomposition_level_count: DecompositionLevelCount,
+    ) -> Self {
+        assert_eq!(
+            container.container_len(),
+            glwe_size.to_glwe_dimension().0
+                * fourier_ggsw_ciphertext_size::<F>(glwe_size, polynomial_size, decomposition_level_count),
+        );
+
+        Self {
+            data: container,
+            glwe_size,
+            polynomial_size,
+            decomposition_base_log,
+            decomposition_level_count,
+        }
+    }
+
+    pub fn glwe_size(&self) -> GlweSize {
+        self.glwe_size
+    }
+
+    pub fn polynomial_size(&self) -> PolynomialSize {
+        self.polynomial_size
+    }
+
+    pub fn decomposition_base_log(&self) -> DecompositionBaseLog {
+        self.decomposition_base_log
+    }
+
+    pub fn decomposition_level_count(&self) -> DecompositionLevelCount {
+        self.decomposition_level_count
+    }
+
+    pub fn as_view(&self) -> FourierSchemeSwitchingKeyView<'_, F> {
+        FourierSchemeSwitchingKey {
+            data: self.data.as_ref(),
+            glwe_size: self.glwe_size,
+            polynomial_size: self.polynomial_size,
+            decomposition_base_log: self.decomposition_base_log,
+            decomposition_level_count: self.decomposition_level_count,
+        }
+    }
+
+    /// Fourier GGSW ciphertexts of the GLWE secret key polynomials in the layout of [`FftBackend`].
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &'_ [F]> {
+        self.data
+            .as_ref()
+            .chunks_exact(fourier_ggsw_ciphertext_size::<F>(self.glwe_size, self.polynomial_size, self.decomposition_level_count))
+    }
+}
+
+impl<F: FftBackend> FourierSchemeSwitchingKeyOwned<F> {
+    pub fn new(
+        glwe_size: GlweSize,
+        polynomial_size: PolynomialSize,
+        decomposition_base_log: DecompositionBaseLog,
+        decomposition_level_count: DecompositionLevelCount,
+    ) -> Self {
+        let ggsw_size = fourier_ggsw_ciphertext_size::<F>(glwe_size, polynomial_size, decomposition_level_count);
+        Self::from_container(
+            avec![F::default(); glwe_size.to_glwe_dimension().0 * ggsw_size].into_boxed_slice(),
+            glwe_size,
+            polynomial_size,
+            decomposition_base_log,
+            decomposition_level_count,
+        )
+    }
+}
 
 pub fn generate_scheme_switching_key<Scalar, G>(
     glwe_secret_key: &GlweSecretKeyOwned<Scalar>,
@@ -17,7 +121,76 @@ pub fn generate_scheme_switching_key<Scalar, G>(
     noise_parameters: impl DispersionParameter,
     ciphertext_modulus: CiphertextModulus<Scalar>,
     generator: &mut EncryptionRandomGenerator<G>,
-) -> FourierGgswCiphertextList<Vec<c64>>
+) -> FourierSchemeSwitchingKeyOwned
+where
+    Scalar: UnsignedTorus,
+    G: ByteRandomGenerator,
+{
+    generate_fourier_scheme_switching_key(glwe_secret_key, ss_base_log, ss_level, noise_parameters, ciphertext_modulus, generator)
+}
+
+/// Generates the scheme switching key on the f128 FFT backend.
+pub fn generate_scheme_switching_key_f128<Scalar, G>(
+    glwe_secret_key: &GlweSecretKeyOwned<Scalar>,
+    ss_base_log: DecompositionBaseLog,
+    ss_level: DecompositionLevelCount,
+    noise_parameters: impl DispersionParameter,
+    ciphertext_modulus: CiphertextModulus<Scalar>,
+    generator: &mut EncryptionRandomGenerator<G>,
+) -> FourierSchemeSwitchingKeyOwned<F128>
+where
+    Scalar: UnsignedTorus,
+    G: ByteRandomGenerator,
+{
+    generate_fourier_scheme_switching_key(glwe_secret_key, ss_base_log, ss_level, noise_parameters, ciphertext_modulus, generator)
+}
+
+fn generate_fourier_scheme_switching_key<Scalar, F, G>(
+    glwe_secret_key: &GlweSecretKeyOwned<Scalar>,
+    ss_base_log: DecompositionBaseLog,
+    ss_level: DecompositionLevelCount,
+    noise_parameters: impl DispersionParameter,
+    ciphertext_modulus: CiphertextModulus<Scalar>,
+    generator: &mut EncryptionRandomGenerator<G>,
+) -> FourierSchemeSwitchingKeyOwned<F>
+where
+    Scalar: UnsignedTorus,
+    F: FftBackend,
+    G: ByteRandomGenerator,
+{
+    let glwe_size = glwe_secret_key.glwe_dimension().to_glwe_size();
+    let polynomial_size = glwe_secret_key.polynomial_size();
+
+    let ggsw_key = generate_standard_scheme_switching_key(
+        glwe_secret_key,
+        ss_base_log,
+        ss_level,
+        noise_parameters,
+        ciphertext_modulus,
+        generator,
+    );
+
+    let fft = F::new_fft(polynomial_size);
+    let mut buffers = ComputationBuffers::new();
+    buffers.resize(F::fft_scratch(&fft).unaligned_bytes_required());
+
+    let mut fourier_ggsw_key = FourierSchemeSwitchingKey::new(glwe_size, polynomial_size, ss_base_log, ss_level);
+    let ggsw_size = fourier_ggsw_ciphertext_size::<F>(glwe_size, polynomial_size, ss_level);
+    for (fourier_ggsw, ggsw) in fourier_ggsw_key.data.chunks_exact_mut(ggsw_size).zip(ggsw_key.iter()) {
+        F::forward_ggsw(&fft, fourier_ggsw, ggsw, buffers.stack());
+    }
+
+    fourier_ggsw_key
+}
+
+fn generate_standard_scheme_switching_key<Scalar, G>(
+    glwe_secret_key: &GlweSecretKeyOwned<Scalar>,
+    ss_base_log: DecompositionBaseLog,
+    ss_level: DecompositionLevelCount,
+    noise_parameters: impl DispersionParameter,
+    ciphertext_modulus: CiphertextModulus<Scalar>,
+    generator: &mut EncryptionRandomGenerator<G>,
+) -> GgswCiphertextListOwned<Scalar>
 where
     Scalar: UnsignedTorus,
     G: ByteRandomGenerator,
@@ -71,35 +244,62 @@ where
         }
     }
 
-    let mut fourier_ggsw_key = FourierGgswCiphertextList::new(
-        vec![
-            c64::default();
-            glwe_dimension.0 * polynomial_size.to_fourier_polynomial_size().0
-                * glwe_size.0
-                * glwe_size.0
-                * ss_level.0
-        ],
-        glwe_dimension.0,
-        glwe_size,
-        polynomial_size,
-        ss_base_log,
-        ss_level,
-    );
+    ggsw_key
+}
 
-    for (mut fourier_ggsw, ggsw) in fourier_ggsw_key.as_mut_view().into_ggsw_iter().zip(ggsw_key.iter()) {
-        convert_standard_ggsw_ciphertext_to_fourier(&ggsw, &mut fourier_ggsw);
-    }
 
-    fourier_ggsw_key
+pub fn switch_scheme<Scalar, F, InputCont, OutputCont>(
+    glev: &GlweCiphertextList<InputCont>,
+    ggsw: &mut GgswCiphertext<OutputCont>,
+    ss_key: FourierSchemeSwitchingKeyView<F>,
+) where
+    Scalar: UnsignedTorus,
+    F: FftBackend,
+    InputCont: Container<Element=Scalar>,
+    OutputCont: ContainerMut<Element=Scalar>,
+{
+    assert_eq!(glev.ciphertext_modulus(), ggsw.ciphertext_modulus());
+    assert_eq!(glev.polynomial_size(), ggsw.polynomial_size());
+    assert_eq!(glev.polynomial_size(), ss_key.polynomial_size());
+    assert_eq!(glev.glwe_size(), ggsw.glwe_size());
+    assert_eq!(glev.glwe_size(), ss_key.glwe_size());
+    assert_eq!(glev.glwe_ciphertext_count().0, ggsw.decomposition_level_count().0);
+
+    let glwe_size = glev.glwe_size();
+    let polynomial_size = glev.polynomial_size();
+
+    let fft = F::new_fft(polynomial_size);
+    let mut buffers = ComputationBuffers::new();
+    buffers.resize(
+        switch_scheme_mem_optimized_requirement::<Scalar, F>(glwe_size, polynomial_size, &fft)
+        .unwrap()
+        .unaligned_bytes_required(),
+    );
+
+    switch_scheme_mem_optimized(glev, ggsw, ss_key, &fft, buffers.stack());
 }
 
+pub fn switch_scheme_mem_optimized_requirement<Scalar, F>(
+    glwe_size: GlweSize,
+    polynomial_size: PolynomialSize,
+    fft: &F::Fft,
+) -> Result<StackReq, SizeOverflow>
+where
+    Scalar: UnsignedTorus,
+    F: FftBackend,
+{
+    Ok(F::add_external_product_assign_scratch::<Scalar>(fft, glwe_size, polynomial_size))
+}
 
-pub fn switch_scheme<Scalar, InputCont, OutputCont>(
+pub fn switch_scheme_mem_optimized<Scalar, F, InputCont, OutputCont>(
     glev: &GlweCiphertextList<InputCont>,
     ggsw: &mut GgswCiphertext<OutputCont>,
-    ss_key: FourierGgswCiphertextListView,
+    ss_key: FourierSchemeSwitchingKeyView<F>,
+    fft: &F::Fft,
+    mut stack: PodStack<'_>,
 ) where
     Scalar: UnsignedTorus,
+    F: FftBackend,
     InputCont: Container<Element=Scalar>,
     OutputCont: ContainerMut<Element=Scalar>,
 {
@@ -119,8 +319,16 @@ pub fn switch_scheme<Scalar, InputCont, OutputCont>(
         let glwe_bit = glev.get(col);
         let (mut glwe_mask_list, mut glwe_body_list) = glwe_list.split_at_mut(glwe_dimension.0);
 
-        for (mut glwe_mask, ss_key_ggsw) in glwe_mask_list.iter_mut().zip(ss_key.into_ggsw_iter()) {
-            add_external_product_assign(&mut glwe_mask, &ss_key_ggsw, &glwe_bit);
+        for (mut glwe_mask, ss_key_ggsw) in glwe_mask_list.iter_mut().zip(ss_key.iter()) {
+            F::add_external_product_assign(
+                fft,
+                glwe_mask.as_mut_view(),
+                ss_key_ggsw,
+                ss_key.decomposition_base_log(),
+                ss_key.decomposition_level_count(),
+                glwe_bit.as_view(),
+                stack.rb_mut(),
+            );
         }
         glwe_ciphertext_clone_from(&mut glwe_body_list.get_mut(0), &glwe_bit);
     }
@@ -131,7 +339,7 @@ pub fn lwe_msb_bit_to_ggsw_by_pfpks<Scalar, InputCont, OutputCont, KeyCont>(
     output: &mut GgswCiphertext<OutputCont>,
     fourier_bsk: FourierLweBootstrapKeyView,
     pfpksk_list: &LwePrivateFunctionalPackingKeyswitchKeyList<KeyCont>,
-    log_lut_count: LutCountLog,
+    cbs_acc: &CbsAccumulator<Scalar>,
 ) where
     Scalar: UnsignedTorus + CastInto<usize>,
     InputCont: Container<Element=Scalar>,
@@ -141,13 +349,15 @@ pub fn lwe_msb_bit_to_ggsw_by_pfpks<Scalar, InputCont, OutputCont, KeyCont>(
     assert_eq!(input.lwe_size(), fourier_bsk.input_lwe_dimension().to_lwe_size());
     assert_eq!(input.ciphertext_modulus(), output.ciphertext_modulus());
 
-    let ggsw_base_log = output.decomposition_base_log();
+    assert_eq!(output.decomposition_base_log(), cbs_acc.decomposition_base_log());
+    assert_eq!(output.decomposition_level_count(), cbs_acc.decomposition_level_count());
+
     let ggsw_level = output.decomposition_level_count();
     let ciphertext_modulus = output.ciphertext_modulus();
 
     let lwe_size = fourier_bsk.output_lwe_dimension().to_lwe_size();
     let mut lev = LweCiphertextList::new(Scalar::ZERO, lwe_size, LweCiphertextCount(ggsw_level.0), ciphertext_modulus);
-    lwe_msb_bit_to_lev(input, &mut lev, fourier_bsk, ggsw_base_log, ggsw_level, log_lut_count);
+    lwe_msb_bit_to_lev(input, &mut lev, fourier_bsk, cbs_acc);
 
     for (lwe, mut ggsw_level_matrix) in lev.iter().zip(output.iter_mut()) {
         for (pfpksk, mut glwe) in pfpksk_list.iter()
@@ -162,200 +372,184 @@ pub fn lwe_msb_bit_to_ggsw_by_pfpks<Scalar, InputCont, OutputCont, KeyCont>(
     }
 }
 
-pub fn lwe_msb_bit_to_glev_by_trace_with_preprocessing<Scalar>(
+/// Variant of the LWE-to-GLWE conversion by trace in the WWL+ circuit bootstrapping.
+///
+/// - `Patched`: the LEV is mod-switched to q/N before the trace, which is the fix of this crate.
+/// - `Original`: the LEV is scaled by 1/N in the PBS and directly traced as in the original WWL+,
+///   so the PBS error is also multiplied by N. It requires `CbsAccumulator::new_original_wwlp`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WWLpCbsMode {
+    Patched,
+    Original,
+}
+
+pub fn lwe_msb_bit_to_glev_by_trace<Scalar>(
     lwe_in: LweCiphertextView<Scalar>,
     mut glev: GlweCiphertextListMutView<Scalar>,
     fourier_bsk: FourierLweBootstrapKeyView,
     auto_keys: &HashMap<usize, AutomorphKey<ABox<[c64]>>>,
-    glev_base_log: DecompositionBaseLog,
-    glev_level: DecompositionLevelCount,
-    log_lut_count: LutCountLog,
+    cbs_acc: &CbsAccumulator<Scalar>,
+    mode: WWLpCbsMode,
 ) where
     Scalar: UnsignedTorus + CastInto<usize> + CastFrom<u128>,
 {
     assert_eq!(lwe_in.lwe_size(), fourier_bsk.input_lwe_dimension().to_lwe_size());
-    assert_eq!(glev.entity_count(), glev_level.0);
+    assert_eq!(glev.entity_count(), cbs_acc.decomposition_level_count().0);
 
-    let glwe_size = fourier_bsk.glwe_size();
     let polynomial_size = fourier_bsk.polynomial_size();
-    let half_box_size = polynomial_size.0 / 2;
     let ciphertext_modulus = lwe_in.ciphertext_modulus();
 
-    let lut_count = 1 << log_lut_count.0;
-    for (acc_idx, mut glev_chunk) in glev.chunks_mut(lut_count).enumerate() {
-        let mut accumulator = (0..polynomial_size.0).map(|i| {
-            let k = i % lut_count;
-            let log_scale = Scalar::BITS - (acc_idx * lut_count + k + 1) * glev_base_log.0;
-            (Scalar::ONE).wrapping_neg() << (log_scale - 1)
-        }).collect::<Vec<Scalar>>();
-
-        for a_i in accumulator[0..half_box_size].iter_mut() {
-            *a_i = (*a_i).wrapping_neg();
-        }
-        accumulator.rotate_left(half_box_size);
-
-        let accumulator_plaintext = PlaintextList::from_container(accumulator);
-        let accumulator = allocate_and_trivially_encrypt_new_glwe_ciphertext(
-            glwe_size,
-            &accumulator_plaintext,
-            ciphertext_modulus,
-        );
-
-        let mut buffers = ComputationBuffers::new();
-        let fft = Fft::new(polynomial_size);
-        let fft = fft.as_view();
-
-        buffers.resize(
-            programmable_bootstrap_lwe_ciphertext_mem_optimized_requirement::<Scalar>(
-                glwe_size,
-                polynomial_size,
-                fft,
-            )
-            .unwrap()
-            .unaligned_bytes_required(),
-        );
-        let stack = buffers.stack();
-
-        let (mut local_accumulator_data, stack) = stack.collect_aligned(CACHELINE_ALIGN, accumulator.as_ref().iter().copied());
-        let mut local_accumulator = GlweCiphertextMutView::from_container(
-            &mut *local_accumulator_data,
-            polynomial_size,
-            ciphertext_modulus,
-        );
-
-        gen_blind_rotate_local_assign(
-            fourier_bsk,
-            local_accumulator.as_mut_view(),
-            ModulusSwitchOffset(0),
-            log_lut_count,
-            lwe_in.as_ref(),
-            fft,
-            stack,
-        );
-
-        let mut buf_glwe = GlweCiphertext::new(Scalar::ZERO, glwe_size, polynomial_size, ciphertext_modulus);
-        let mut buf_lwe = LweCiphertext::new(Scalar::ZERO, fourier_bsk.output_lwe_dimension().to_lwe_size(), ciphertext_modulus);
-        for (k, mut glwe) in glev_chunk.iter_mut().enumerate() {
-            let cur_level = acc_idx * lut_count + k + 1;
-            let log_scale = Scalar::BITS - cur_level * glev_base_log.0;
+    match mode {
+        WWLpCbsMode::Patched => assert_eq!(cbs_acc.log_scale_offset(), 0),
+        WWLpCbsMode::Original => assert_eq!(cbs_acc.log_scale_offset(), polynomial_size.log2().0),
+    }
 
-            glwe_ciphertext_clone_from(&mut buf_glwe, &local_accumulator);
-            glwe_ciphertext_monic_monomial_div_assign(&mut buf_glwe, MonomialDegree(k));
-            glwe_ciphertext_plaintext_add_assign(&mut buf_glwe, Plaintext(Scalar::ONE << (log_scale - 1)));
+    let lwe_size = fourier_bsk.output_lwe_dimension().to_lwe_size();
+    let mut lev = LweCiphertextList::new(Scalar::ZERO, lwe_size, LweCiphertextCount(glev.entity_count()), ciphertext_modulus);
+    lwe_msb_bit_to_lev(&lwe_in, &mut lev, fourier_bsk, cbs_acc);
 
-            extract_lwe_sample_from_glwe_ciphertext(&buf_glwe, &mut buf_lwe, MonomialDegree(0));
-            lwe_preprocessing_assign(&mut buf_lwe, polynomial_size);
-            convert_lwe_to_glwe_const(&buf_lwe, &mut glwe);
-            trace_assign(&mut glwe, &auto_keys);
+    for (mut lwe, mut glwe) in lev.iter_mut().zip(glev.iter_mut()) {
+        if mode == WWLpCbsMode::Patched {
+            lwe_preprocessing_assign(&mut lwe, polynomial_size);
         }
+        convert_lwe_to_glwe_const(&lwe, &mut glwe);
+        trace_assign(&mut glwe, auto_keys);
     }
 }
 
+pub fn lwe_msb_bit_to_glev_by_trace_with_preprocessing<Scalar>(
+    lwe_in: LweCiphertextView<Scalar>,
+    glev: GlweCiphertextListMutView<Scalar>,
+    fourier_bsk: FourierLweBootstrapKeyView,
+    auto_keys: &HashMap<usize, AutomorphKey<ABox<[c64]>>>,
+    cbs_acc: &CbsAccumulator<Scalar>,
+) where
+    Scalar: UnsignedTorus + CastInto<usize> + CastFrom<u128>,
+{
+    lwe_msb_bit_to_glev_by_trace(lwe_in, glev, fourier_bsk, auto_keys, cbs_acc, WWLpCbsMode::Patched);
+}
+
 
 pub fn lwe_msb_bit_to_glev_by_pksk<Scalar>(
     lwe_in: LweCiphertextView<Scalar>,
     mut glev: GlweCiphertextListMutView<Scalar>,
     fourier_bsk: FourierLweBootstrapKeyView,
     pksk: &LwePackingKeyswitchKeyView<Scalar>,
-    glev_base_log: DecompositionBaseLog,
-    glev_level: DecompositionLevelCount,
-    log_lut_count: LutCountLog,
+    cbs_acc: &CbsAccumulator<Scalar>,
 ) where
     Scalar: UnsignedTorus + CastInto<usize> + CastFrom<u128>,
 {
     assert_eq!(lwe_in.lwe_size(), fourier_bsk.input_lwe_dimension().to_lwe_size());
-    assert_eq!(glev.entity_count(), glev_level.0);
+    assert_eq!(glev.entity_count(), cbs_acc.decomposition_level_count().0);
 
-    let glwe_size = fourier_bsk.glwe_size();
+    let ciphertext_modulus = lwe_in.ciphertext_modulus();
+
+    let lwe_size = fourier_bsk.output_lwe_dimension().to_lwe_size();
+    let mut lev = LweCiphertextList::new(Scalar::ZERO, lwe_size, LweCiphertextCount(glev.entity_count()), ciphertext_modulus);
+    lwe_msb_bit_to_lev(&lwe_in, &mut lev, fourier_bsk, cbs_acc);
+
+    for (lwe, mut glwe) in lev.iter().zip(glev.iter_mut()) {
+        keyswitch_lwe_ciphertext_into_glwe_ciphertext(pksk, &lwe, &mut glwe);
+    }
+}
+
+
+pub fn circuit_bootstrap_lwe_ciphertext_by_trace<Scalar>(
+    lwe_in: LweCiphertextView<Scalar>,
+    fourier_bsk: FourierLweBootstrapKeyView,
+    auto_keys: &HashMap<usize, AutomorphKey<ABox<[c64]>>>,
+    ss_key: FourierSchemeSwitchingKeyView,
+    cbs_acc: &CbsAccumulator<Scalar>,
+    mode: WWLpCbsMode,
+) -> FourierGgswCiphertext<ABox<[c64]>>
+where
+    Scalar: UnsignedTorus + CastInto<usize> + CastFrom<u128>
+{
+    assert!(fourier_bsk.polynomial_size() == ss_key.polynomial_size());
+    assert!(fourier_bsk.glwe_size() == ss_key.glwe_size());
+    assert!(lwe_in.ciphertext_modulus().is_native_modulus());
+
+    let ggsw_base_log = cbs_acc.decomposition_base_log();
+    let ggsw_level = cbs_acc.decomposition_level_count();
     let polynomial_size = fourier_bsk.polynomial_size();
-    let half_box_size = polynomial_size.0 / 2;
+    let glwe_size = fourier_bsk.glwe_size();
     let ciphertext_modulus = lwe_in.ciphertext_modulus();
 
-    let lut_count = 1 << log_lut_count.0;
-    for (acc_idx, mut glev_chunk) in glev.chunks_mut(lut_count).enumerate() {
-        let mut accumulator = (0..polynomial_size.0).map(|i| {
-            let k = i % lut_count;
-            let log_scale = Scalar::BITS - (acc_idx * lut_count + k + 1) * glev_base_log.0;
-            (Scalar::ONE).wrapping_neg() << (log_scale - 1)
-        }).collect::<Vec<Scalar>>();
+    let mut glev = GlweCiphertextList::new(Scalar::ZERO, glwe_size, polynomial_size, GlweCiphertextCount(ggsw_level.0), ciphertext_modulus);
+    let glev_mut_view = GlweCiphertextListMutView::from_container(glev.as_mut(), glwe_size, polynomial_size, ciphertext_modulus);
 
-        for a_i in accumulator[0..half_box_size].iter_mut() {
-            *a_i = (*a_i).wrapping_neg();
-        }
-        accumulator.rotate_left(half_box_size);
+    lwe_msb_bit_to_glev_by_trace(lwe_in.as_view(), glev_mut_view, fourier_bsk, auto_keys, cbs_acc, mode);
 
-        let accumulator_plaintext = PlaintextList::from_container(accumulator);
-        let accumulator = allocate_and_trivially_encrypt_new_glwe_ciphertext(
-            glwe_size,
-            &accumulator_plaintext,
-            ciphertext_modulus,
-        );
+    glev_to_fourier_ggsw(&glev, ss_key, ggsw_base_log)
+}
 
-        let mut buffers = ComputationBuffers::new();
-        let fft = Fft::new(polynomial_size);
-        let fft = fft.as_view();
 
-        buffers.resize(
-            programmable_bootstrap_lwe_ciphertext_mem_optimized_requirement::<Scalar>(
-                glwe_size,
-                polynomial_size,
-                fft,
-            )
-            .unwrap()
-            .unaligned_bytes_required(),
-        );
-        let stack = buffers.stack();
+pub fn circuit_bootstrap_lwe_ciphertext_by_trace_with_preprocessing<Scalar>(
+    lwe_in: LweCiphertextView<Scalar>,
+    fourier_bsk: FourierLweBootstrapKeyView,
+    auto_keys: &HashMap<usize, AutomorphKey<ABox<[c64]>>>,
+    ss_key: FourierSchemeSwitchingKeyView,
+    cbs_acc: &CbsAccumulator<Scalar>,
+) -> FourierGgswCiphertext<ABox<[c64]>>
+where
+    Scalar: UnsignedTorus + CastInto<usize> + CastFrom<u128>
+{
+    circuit_bootstrap_lwe_ciphertext_by_trace(lwe_in, fourier_bsk, auto_keys, ss_key, cbs_acc, WWLpCbsMode::Patched)
+}
 
-        let (mut local_accumulator_data, stack) = stack.collect_aligned(CACHELINE_ALIGN, accumulator.as_ref().iter().copied());
-        let mut local_accumulator = GlweCiphertextMutView::from_container(
-            &mut *local_accumulator_data,
-            polynomial_size,
-            ciphertext_modulus,
-        );
 
-        gen_blind_rotate_local_assign(
-            fourier_bsk,
-            local_accumulator.as_mut_view(),
-            ModulusSwitchOffset(0),
-            log_lut_count,
-            lwe_in.as_ref(),
-            fft,
-            stack,
-        );
+pub fn lwe_msb_bit_to_glev_by_trace_with_preprocessing_high_prec<Scalar, F, KSKeyCont>(
+    lwe_in: LweCiphertextView<Scalar>,
+    mut glev: GlweCiphertextListMutView<Scalar>,
+    fourier_bsk: FourierLweBootstrapKeyView,
+    glwe_ksk_to_large: &FourierGlweKeyswitchKey<KSKeyCont>,
+    glwe_ksk_from_large: &FourierGlweKeyswitchKey<KSKeyCont>,
+    auto_keys: &HashMap<usize, AutomorphKey<ABox<[c64]>>>,
+    cbs_acc: &CbsAccumulator<Scalar>,
+) where
+    Scalar: UnsignedTorus + CastInto<usize> + CastFrom<u128>,
+    F: FftBackend,
+    KSKeyCont: Container<Element=F>,
+{
+    assert_eq!(lwe_in.lwe_size(), fourier_bsk.input_lwe_dimension().to_lwe_size());
+    assert_eq!(glev.entity_count(), cbs_acc.decomposition_level_count().0);
+    assert_eq!(cbs_acc.log_scale_offset(), 0);
 
-        for (k, mut glwe) in glev_chunk.iter_mut().enumerate() {
-            let cur_level = acc_idx * lut_count + k + 1;
-            let log_scale = Scalar::BITS - cur_level * glev_base_log.0;
+    let ciphertext_modulus = lwe_in.ciphertext_modulus();
 
-            let mut buf = GlweCiphertext::new(Scalar::ZERO, glwe_size, polynomial_size, ciphertext_modulus);
-            glwe_ciphertext_clone_from(&mut buf, &local_accumulator);
-            glwe_ciphertext_monic_monomial_div_assign(&mut buf, MonomialDegree(k));
-            glwe_ciphertext_plaintext_add_assign(&mut buf, Plaintext(Scalar::ONE << (log_scale - 1)));
+    let lwe_size = fourier_bsk.output_lwe_dimension().to_lwe_size();
+    let mut lev = LweCiphertextList::new(Scalar::ZERO, lwe_size, LweCiphertextCount(glev.entity_count()), ciphertext_modulus);
+    lwe_msb_bit_to_lev(&lwe_in, &mut lev, fourier_bsk, cbs_acc);
 
-            let mut lwe_out = LweCiphertext::new(Scalar::ZERO, fourier_bsk.output_lwe_dimension().to_lwe_size(), ciphertext_modulus);
-            extract_lwe_sample_from_glwe_ciphertext(&buf, &mut lwe_out, MonomialDegree(0));
-            keyswitch_lwe_ciphertext_into_glwe_ciphertext(&pksk, &lwe_out, &mut glwe);
-        }
+    for (lwe, mut glwe) in lev.iter().zip(glev.iter_mut()) {
+        convert_lwe_to_glwe_by_trace_with_preprocessing_high_prec(&lwe, &mut glwe, glwe_ksk_to_large, glwe_ksk_from_large, auto_keys);
     }
 }
 
 
-pub fn circuit_bootstrap_lwe_ciphertext_by_trace_with_preprocessing<Scalar>(
+/// High-precision variant of the patched WWL+ circuit bootstrapping, where the preprocessing and the
+/// trace are performed under a larger GLWE dimension. `auto_keys` are the automorphism keys of the large GLWE secret key.
+pub fn circuit_bootstrap_lwe_ciphertext_by_trace_with_preprocessing_high_prec<Scalar, F, KSKeyCont>(
     lwe_in: LweCiphertextView<Scalar>,
     fourier_bsk: FourierLweBootstrapKeyView,
+    glwe_ksk_to_large: &FourierGlweKeyswitchKey<KSKeyCont>,
+    glwe_ksk_from_large: &FourierGlweKeyswitchKey<KSKeyCont>,
     auto_keys: &HashMap<usize, AutomorphKey<ABox<[c64]>>>,
-    ss_key: FourierGgswCiphertextListView,
-    ggsw_base_log: DecompositionBaseLog,
-    ggsw_level: DecompositionLevelCount,
-    log_lut_count: LutCountLog,
+    ss_key: FourierSchemeSwitchingKeyView,
+    cbs_acc: &CbsAccumulator<Scalar>,
 ) -> FourierGgswCiphertext<ABox<[c64]>>
 where
-    Scalar: UnsignedTorus + CastInto<usize> + CastFrom<u128>
+    Scalar: UnsignedTorus + CastInto<usize> + CastFrom<u128>,
+    F: FftBackend,
+    KSKeyCont: Container<Element=F>,
 {
     assert!(fourier_bsk.polynomial_size() == ss_key.polynomial_size());
     assert!(fourier_bsk.glwe_size() == ss_key.glwe_size());
+    assert!(fourier_bsk.glwe_size() == glwe_ksk_to_large.input_glwe_size());
     assert!(lwe_in.ciphertext_modulus().is_native_modulus());
 
+    let ggsw_base_log = cbs_acc.decomposition_base_log();
+    let ggsw_level = cbs_acc.decomposition_level_count();
     let polynomial_size = fourier_bsk.polynomial_size();
     let glwe_size = fourier_bsk.glwe_size();
     let ciphertext_modulus = lwe_in.ciphertext_modulus();
@@ -363,26 +557,129 @@ where
     let mut glev = GlweCiphertextList::new(Scalar::ZERO, glwe_size, polynomial_size, GlweCiphertextCount(ggsw_level.0), ciphertext_modulus);
     let glev_mut_view = GlweCiphertextListMutView::from_container(glev.as_mut(), glwe_size, polynomial_size, ciphertext_modulus);
 
-    lwe_msb_bit_to_glev_by_trace_with_preprocessing(lwe_in.as_view(), glev_mut_view, fourier_bsk, auto_keys, ggsw_base_log, ggsw_level, log_lut_count);
+    lwe_msb_bit_to_glev_by_trace_with_preprocessing_high_prec(lwe_in.as_view(), glev_mut_view, fourier_bsk, glwe_ksk_to_large, glwe_ksk_from_large, auto_keys, cbs_acc);
 
-    let mut ggsw = GgswCiphertext::new(Scalar::ZERO, glwe_size, polynomial_size, ggsw_base_log, ggsw_level, ciphertext_modulus);
-    switch_scheme(&glev, &mut ggsw, ss_key);
+    glev_to_fourier_ggsw(&glev, ss_key, ggsw_base_log)
+}
 
-    let mut fourier_ggsw = FourierGgswCiphertext::new(glwe_size, polynomial_size, ggsw_base_log, ggsw_level);
-    convert_standard_ggsw_ciphertext_to_fourier(&ggsw, &mut fourier_ggsw);
 
-    fourier_ggsw
+/// Circuit bootstrap all LWE ciphertexts in `lwe_list_in` into `fourier_ggsw_list_out`.
+///
+/// The FFT plan and the buffers, sized for the PBS, the trace, the scheme switching and the
+/// Fourier conversion, are allocated once (per thread under the `multithread` feature)
+/// and reused for all ciphertexts.
+pub fn circuit_bootstrap_lwe_ciphertext_list_by_trace_with_preprocessing<Scalar, InputCont, OutputCont>(
+    lwe_list_in: &LweCiphertextList<InputCont>,
+    fourier_ggsw_list_out: &mut FourierGgswCiphertextList<OutputCont>,
+    fourier_bsk: FourierLweBootstrapKeyView,
+    auto_keys: &HashMap<usize, AutomorphKey<ABox<[c64]>>>,
+    ss_key: FourierSchemeSwitchingKeyView,
+    cbs_acc: &CbsAccumulator<Scalar>,
+) where
+    Scalar: UnsignedTorus + CastInto<usize> + CastFrom<u128>,
+    InputCont: Container<Element=Scalar>,
+    OutputCont: ContainerMut<Element=c64>,
+{
+    assert!(fourier_bsk.polynomial_size() == ss_key.polynomial_size());
+    assert!(fourier_bsk.glwe_size() == ss_key.glwe_size());
+    assert!(lwe_list_in.ciphertext_modulus().is_native_modulus());
+    assert_eq!(lwe_list_in.lwe_size(), fourier_bsk.input_lwe_dimension().to_lwe_size());
+    assert_eq!(lwe_list_in.entity_count(), fourier_ggsw_list_out.count());
+    assert_eq!(fourier_ggsw_list_out.polynomial_size(), fourier_bsk.polynomial_size());
+    assert_eq!(fourier_ggsw_list_out.glwe_size(), fourier_bsk.glwe_size());
+    assert_eq!(fourier_ggsw_list_out.decomposition_base_log(), cbs_acc.decomposition_base_log());
+    assert_eq!(fourier_ggsw_list_out.decomposition_level_count(), cbs_acc.decomposition_level_count());
+    assert_eq!(cbs_acc.log_scale_offset(), 0);
+
+    let ggsw_base_log = cbs_acc.decomposition_base_log();
+    let ggsw_level = cbs_acc.decomposition_level_count();
+    let polynomial_size = fourier_bsk.polynomial_size();
+    let glwe_size = fourier_bsk.glwe_size();
+    let ciphertext_modulus = lwe_list_in.ciphertext_modulus();
+
+    let lwe_size = lwe_list_in.lwe_size().0;
+    let fourier_ggsw_size = polynomial_size.to_fourier_polynomial_size().0
+        * glwe_size.0
+        * glwe_size.0
+        * ggsw_level.0;
+
+    let auto_key = auto_keys.values().next().unwrap();
+    let auto_level = auto_key.decomposition_level_count();
+    let auto_fft_type = auto_key.fft_type();
+
+    let fft = Fft::new(polynomial_size);
+    let fft_view = fft.as_view();
+    let stack_size = StackReq::try_any_of([
+        lwe_msb_bit_to_lev_mem_optimized_requirement::<Scalar>(glwe_size, polynomial_size, fft_view).unwrap(),
+        trace_assign_mem_optimized_requirement::<Scalar>(glwe_size, polynomial_size, auto_level, auto_fft_type, &fft).unwrap(),
+        switch_scheme_mem_optimized_requirement::<Scalar, c64>(glwe_size, polynomial_size, &fft).unwrap(),
+        convert_standard_ggsw_ciphertext_to_fourier_mem_optimized_requirement(fft_view).unwrap(),
+    ]).unwrap().unaligned_bytes_required();
+
+    let new_cbs_buffers = || {
+        let mut buffers = ComputationBuffers::new();
+        buffers.resize(stack_size);
+        (
+            LweCiphertextList::new(Scalar::ZERO, fourier_bsk.output_lwe_dimension().to_lwe_size(), LweCiphertextCount(ggsw_level.0), ciphertext_modulus),
+            GlweCiphertextList::new(Scalar::ZERO, glwe_size, polynomial_size, GlweCiphertextCount(ggsw_level.0), ciphertext_modulus),
+            GgswCiphertext::new(Scalar::ZERO, glwe_size, polynomial_size, ggsw_base_log, ggsw_level, ciphertext_modulus),
+            buffers,
+        )
+    };
+
+    let cbs_with_buffers = |
+        (lev, glev, ggsw, buffers): &mut CbsBuffers<Scalar>,
+        lwe_in: &[Scalar],
+        fourier_ggsw_out: &mut [c64],
+    | {
+        let lwe_in = LweCiphertextView::from_container(lwe_in, ciphertext_modulus);
+        lwe_msb_bit_to_lev_mem_optimized(&lwe_in, lev, fourier_bsk, cbs_acc, fft_view, buffers.stack());
+
+        for (mut lwe, mut glwe) in lev.iter_mut().zip(glev.iter_mut()) {
+            lwe_preprocessing_assign(&mut lwe, polynomial_size);
+            convert_lwe_to_glwe_const(&lwe, &mut glwe);
+            trace_assign_mem_optimized(&mut glwe, auto_keys, &fft, buffers.stack());
+        }
+        switch_scheme_mem_optimized(glev, ggsw, ss_key, &fft, buffers.stack());
+
+        let mut fourier_ggsw_out = FourierGgswCiphertext::from_container(fourier_ggsw_out, glwe_size, polynomial_size, ggsw_base_log, ggsw_level);
+        convert_standard_ggsw_ciphertext_to_fourier_mem_optimized(ggsw, &mut fourier_ggsw_out, fft_view, buffers.stack());
+    };
+
+    let fourier_ggsw_list_data = fourier_ggsw_list_out.as_mut_view().data();
+
+    #[cfg(feature = "multithread")]
+    lwe_list_in.as_ref().par_chunks_exact(lwe_size)
+        .zip(fourier_ggsw_list_data.par_chunks_exact_mut(fourier_ggsw_size))
+        .for_each_init(new_cbs_buffers, |cbs_buffers, (lwe_in, fourier_ggsw_out)| {
+            cbs_with_buffers(cbs_buffers, lwe_in, fourier_ggsw_out);
+        });
+
+    #[cfg(not(feature = "multithread"))]
+    {
+        let mut cbs_buffers = new_cbs_buffers();
+        for (lwe_in, fourier_ggsw_out) in lwe_list_in.as_ref().chunks_exact(lwe_size)
+            .zip(fourier_ggsw_list_data.chunks_exact_mut(fourier_ggsw_size))
+        {
+            cbs_with_buffers(&mut cbs_buffers, lwe_in, fourier_ggsw_out);
+        }
+    }
 }
 
+type CbsBuffers<Scalar> = (
+    LweCiphertextListOwned<Scalar>,
+    GlweCiphertextListOwned<Scalar>,
+    GgswCiphertextOwned<Scalar>,
+    ComputationBuffers,
+);
+
 
 pub fn circuit_bootstrap_lwe_ciphertext_by_pksk<Scalar>(
     lwe_in: LweCiphertextView<Scalar>,
     fourier_bsk: FourierLweBootstrapKeyView,
     pksk: &LwePackingKeyswitchKeyView<Scalar>,
-    ss_key: FourierGgswCiphertextListView,
-    ggsw_base_log: DecompositionBaseLog,
-    ggsw_level: DecompositionLevelCount,
-    log_lut_count: LutCountLog,
+    ss_key: FourierSchemeSwitchingKeyView,
+    cbs_acc: &CbsAccumulator<Scalar>,
 ) -> FourierGgswCiphertext<ABox<[c64]>>
 where
     Scalar: UnsignedTorus + CastInto<usize> + CastFrom<u128>
@@ -391,6 +688,8 @@ where
     assert!(fourier_bsk.glwe_size() == ss_key.glwe_size());
     assert!(lwe_in.ciphertext_modulus().is_native_modulus());
 
+    let ggsw_base_log = cbs_acc.decomposition_base_log();
+    let ggsw_level = cbs_acc.decomposition_level_count();
     let polynomial_size = fourier_bsk.polynomial_size();
     let glwe_size = fourier_bsk.glwe_size();
     let ciphertext_modulus = lwe_in.ciphertext_modulus();
@@ -398,14 +697,92 @@ where
     let mut glev = GlweCiphertextList::new(Scalar::ZERO, glwe_size, polynomial_size, GlweCiphertextCount(ggsw_level.0), ciphertext_modulus);
     let glev_mut_view = GlweCiphertextListMutView::from_container(glev.as_mut(), glwe_size, polynomial_size, ciphertext_modulus);
 
-    lwe_msb_bit_to_glev_by_pksk(lwe_in.as_view(), glev_mut_view, fourier_bsk, pksk, ggsw_base_log, ggsw_level, log_lut_count);
+    lwe_msb_bit_to_glev_by_pksk(lwe_in.as_view(), glev_mut_view, fourier_bsk, pksk, cbs_acc);
+
+    glev_to_fourier_ggsw(&glev, ss_key, ggsw_base_log)
+}
+
+
 
-    let mut ggsw = GgswCiphertext::new(Scalar::ZERO, glwe_size, polynomial_size, ggsw_base_log, ggsw_level, ciphertext_modulus);
-    switch_scheme(&glev, &mut ggsw, ss_key);
+/// GLEV of f(m) in {0, 1} for an LWE ciphertext of a multi-bit message m encoded as
+/// m * 2^(BITS - 1) / message_modulus, where `cbs_acc` is built by [`CbsAccumulator::new_boolean_function`].
+pub fn lwe_boolean_function_to_glev_by_trace_with_preprocessing<Scalar>(
+    lwe_in: LweCiphertextView<Scalar>,
+    glev: GlweCiphertextListMutView<Scalar>,
+    fourier_bsk: FourierLweBootstrapKeyView,
+    auto_keys: &HashMap<usize, AutomorphKey<ABox<[c64]>>>,
+    cbs_acc: &CbsAccumulator<Scalar>,
+) where
+    Scalar: UnsignedTorus + CastInto<usize> + CastFrom<u128>,
+{
+    lwe_msb_bit_to_glev_by_trace(lwe_in, glev, fourier_bsk, auto_keys, cbs_acc, WWLpCbsMode::Patched);
+}
+
+
+pub fn lwe_boolean_function_to_glev_by_pksk<Scalar>(
+    lwe_in: LweCiphertextView<Scalar>,
+    glev: GlweCiphertextListMutView<Scalar>,
+    fourier_bsk: FourierLweBootstrapKeyView,
+    pksk: &LwePackingKeyswitchKeyView<Scalar>,
+    cbs_acc: &CbsAccumulator<Scalar>,
+) where
+    Scalar: UnsignedTorus + CastInto<usize> + CastFrom<u128>,
+{
+    lwe_msb_bit_to_glev_by_pksk(lwe_in, glev, fourier_bsk, pksk, cbs_acc);
+}
+
+
+/// Circuit bootstrapping of f(m) in {0, 1} for an LWE ciphertext of a multi-bit message m
+/// encoded as m * 2^(BITS - 1) / message_modulus.
+///
+/// `cbs_acc` is built once by [`CbsAccumulator::new_boolean_function`] and reused for all inputs.
+pub fn programmable_circuit_bootstrap_lwe_ciphertext_by_trace_with_preprocessing<Scalar>(
+    lwe_in: LweCiphertextView<Scalar>,
+    fourier_bsk: FourierLweBootstrapKeyView,
+    auto_keys: &HashMap<usize, AutomorphKey<ABox<[c64]>>>,
+    ss_key: FourierSchemeSwitchingKeyView,
+    cbs_acc: &CbsAccumulator<Scalar>,
+) -> FourierGgswCiphertext<ABox<[c64]>>
+where
+    Scalar: UnsignedTorus + CastInto<usize> + CastFrom<u128>,
+{
+    circuit_bootstrap_lwe_ciphertext_by_trace(lwe_in, fourier_bsk, auto_keys, ss_key, cbs_acc, WWLpCbsMode::Patched)
+}
+
+
+pub fn programmable_circuit_bootstrap_lwe_ciphertext_by_pksk<Scalar>(
+    lwe_in: LweCiphertextView<Scalar>,
+    fourier_bsk: FourierLweBootstrapKeyView,
+    pksk: &LwePackingKeyswitchKeyView<Scalar>,
+    ss_key: FourierSchemeSwitchingKeyView,
+    cbs_acc: &CbsAccumulator<Scalar>,
+) -> FourierGgswCiphertext<ABox<[c64]>>
+where
+    Scalar: UnsignedTorus + CastInto<usize> + CastFrom<u128>,
+{
+    circuit_bootstrap_lwe_ciphertext_by_pksk(lwe_in, fourier_bsk, pksk, ss_key, cbs_acc)
+}
+
+
+/// Scheme switching of the GLEV output of a circuit bootstrapping to a Fourier GGSW ciphertext.
+fn glev_to_fourier_ggsw<Scalar, InputCont>(
+    glev: &GlweCiphertextList<InputCont>,
+    ss_key: FourierSchemeSwitchingKeyView,
+    ggsw_base_log: DecompositionBaseLog,
+) -> FourierGgswCiphertext<ABox<[c64]>>
+where
+    Scalar: UnsignedTorus,
+    InputCont: Container<Element=Scalar>,
+{
+    let glwe_size = glev.glwe_size();
+    let polynomial_size = glev.polynomial_size();
+    let ggsw_level = DecompositionLevelCount(glev.glwe_ciphertext_count().0);
+
+    let mut ggsw = GgswCiphertext::new(Scalar::ZERO, glwe_size, polynomial_size, ggsw_base_log, ggsw_level, glev.ciphertext_modulus());
+    switch_scheme(glev, &mut ggsw, ss_key);
 
     let mut fourier_ggsw = FourierGgswCiphertext::new(glwe_size, polynomial_size, ggsw_base_log, ggsw_level);
     convert_standard_ggsw_ciphertext_to_fourier(&ggsw, &mut fourier_ggsw);
 
     fourier_ggsw
 }
-
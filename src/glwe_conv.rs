@@ -6,7 +6,7 @@ use tfhe::core_crypto::{
     algorithms::slice_algorithms::slice_wrapping_opposite_assign,
 };
 use crate::{
-    automorphism::*, keyswitch_glwe_ciphertext, mod_switch::*, utils::*, FourierGlweKeyswitchKey
+    automorphism::*, keyswitch_glwe_ciphertext, mod_switch::*, utils::*, FftBackend, FourierGlweKeyswitchKey
 };
 
 pub fn convert_lwe_to_glwe_const<Scalar, InputCont, OutputCont>(
@@ -80,16 +80,18 @@ pub fn convert_lwe_to_glwe_by_trace_with_preprocessing<Scalar, InputCont, Output
 }
 
 
-pub fn convert_lwe_to_glwe_by_trace_with_preprocessing_high_prec<Scalar, InputCont, OutputCont>(
+pub fn convert_lwe_to_glwe_by_trace_with_preprocessing_high_prec<Scalar, F, InputCont, OutputCont, KSKeyCont>(
     input: &LweCiphertext<InputCont>,
     output: &mut GlweCiphertext<OutputCont>,
-    glwe_ksk_to_large: &FourierGlweKeyswitchKey<ABox<[c64]>>,
-    glwe_ksk_from_large: &FourierGlweKeyswitchKey<ABox<[c64]>>,
+    glwe_ksk_to_large: &FourierGlweKeyswitchKey<KSKeyCont>,
+    glwe_ksk_from_large: &FourierGlweKeyswitchKey<KSKeyCont>,
     auto_keys: &HashMap<usize, AutomorphKey<ABox<[c64]>>>
 ) where
     Scalar: UnsignedTorus,
+    F: FftBackend,
     InputCont: Container<Element=Scalar>,
     OutputCont: ContainerMut<Element=Scalar>,
+    KSKeyCont: Container<Element=F>,
 {
     assert_eq!(input.ciphertext_modulus(), output.ciphertext_modulus());
     assert!(
@@ -1,6 +1,11 @@
-use tfhe::core_crypto::prelude::*;
+use std::collections::HashMap;
+use aligned_vec::ABox;
+use tfhe::core_crypto::{
+    prelude::*,
+    fft_impl::fft64::c64,
+};
 
-use crate::{allocate_and_generate_new_glwe_keyswitch_key, convert_standard_glwe_keyswitch_key_to_fourier, FftType, FourierGlweKeyswitchKey, FourierGlweKeyswitchKeyOwned};
+use crate::{allocate_and_generate_new_glwe_keyswitch_key, convert_standard_glwe_keyswitch_key_to_fourier, gen_all_auto_keys, generate_scheme_switching_key, AutomorphKey, FftBackend, FftType, FourierGlweKeyswitchKey, FourierGlweKeyswitchKeyOwned, FourierSchemeSwitchingKeyOwned, HighPrecWWLpCBSParam, LweKeyswitchParameters, PbsParameters};
 
 pub fn keygen_pbs<Scalar: UnsignedTorus, G: ByteRandomGenerator>(
     lwe_dimension: LweDimension,
@@ -61,6 +66,38 @@ pub fn keygen_pbs<Scalar: UnsignedTorus, G: ByteRandomGenerator>(
     (lwe_secret_key, glwe_secret_key, lwe_secret_key_after_ks, fourier_bsk, ksk)
 }
 
+/// `keygen_pbs` with the parameters taken from any parameter set with a standard LWE keyswitching.
+pub fn keygen_pbs_from_param<Scalar, P, G>(
+    param: &P,
+    secret_generator: &mut SecretRandomGenerator<G>,
+    encryption_generator: &mut EncryptionRandomGenerator<G>,
+) -> (
+    LweSecretKey<Vec<Scalar>>,
+    GlweSecretKey<Vec<Scalar>>,
+    LweSecretKey<Vec<Scalar>>,
+    FourierLweBootstrapKeyOwned,
+    LweKeyswitchKey<Vec<Scalar>>,
+)
+where
+    Scalar: UnsignedTorus,
+    P: PbsParameters<Scalar> + LweKeyswitchParameters,
+    G: ByteRandomGenerator,
+{
+    keygen_pbs(
+        param.lwe_dimension(),
+        param.glwe_dimension(),
+        param.polynomial_size(),
+        param.lwe_modular_std_dev(),
+        param.glwe_modular_std_dev(),
+        param.pbs_base_log(),
+        param.pbs_level(),
+        param.ks_base_log(),
+        param.ks_level(),
+        secret_generator,
+        encryption_generator,
+    )
+}
+
 pub fn keygen_pbs_without_ksk<Scalar: UnsignedTorus, G: ByteRandomGenerator>(
     lwe_dimension: LweDimension,
     glwe_dimension: GlweDimension,
@@ -181,4 +218,115 @@ pub fn keygen_pbs_with_glwe_ds<Scalar: UnsignedTorus, G: ByteRandomGenerator>(
     convert_standard_glwe_keyswitch_key_to_fourier(&glwe_ksk, &mut fourier_glwe_ksk);
 
     (lwe_secret_key, glwe_secret_key, lwe_secret_key_after_ks, fourier_bsk, fourier_glwe_ksk)
-}
\ No newline at end of file
+}
+
+/// Keys of the high-precision WWL+ circuit bootstrapping generated by [`keygen_high_prec_wwlp_cbs`].
+pub struct HighPrecWWLpCbsKeys<Scalar: UnsignedTorus, F: FftBackend> {
+    pub lwe_secret_key: LweSecretKey<Vec<Scalar>>,
+    pub glwe_secret_key: GlweSecretKey<Vec<Scalar>>,
+    pub lwe_secret_key_after_ks: LweSecretKey<Vec<Scalar>>,
+    pub large_glwe_secret_key: GlweSecretKey<Vec<Scalar>>,
+    pub fourier_bsk: FourierLweBootstrapKeyOwned,
+    pub ksk: LweKeyswitchKey<Vec<Scalar>>,
+    pub glwe_ksk_to_large: FourierGlweKeyswitchKeyOwned<F>,
+    pub glwe_ksk_from_large: FourierGlweKeyswitchKeyOwned<F>,
+    pub auto_keys: HashMap<usize, AutomorphKey<ABox<[c64]>>>,
+    pub ss_key: FourierSchemeSwitchingKeyOwned,
+}
+
+/// The GLWE keyswitching keys to and from the large GLWE dimension are on the FFT backend `F`, e.g.
+/// [`F128`](crate::F128) for `FftType::F128`.
+pub fn keygen_high_prec_wwlp_cbs<Scalar, F, G>(
+    param: &HighPrecWWLpCBSParam<Scalar>,
+    secret_generator: &mut SecretRandomGenerator<G>,
+    encryption_generator: &mut EncryptionRandomGenerator<G>,
+) -> HighPrecWWLpCbsKeys<Scalar, F>
+where
+    Scalar: UnsignedTorus + Sync + Send,
+    F: FftBackend,
+    G: ByteRandomGenerator,
+{
+    let polynomial_size = param.polynomial_size();
+    let glwe_size = param.glwe_dimension().to_glwe_size();
+    let large_glwe_size = param.large_glwe_dimension().to_glwe_size();
+    let ciphertext_modulus = param.ciphertext_modulus();
+
+    let (
+        lwe_secret_key,
+        glwe_secret_key,
+        lwe_secret_key_after_ks,
+        fourier_bsk,
+        ksk,
+    ) = keygen_pbs_from_param(param, secret_generator, encryption_generator);
+
+    let large_glwe_secret_key: GlweSecretKey<Vec<Scalar>> = GlweSecretKey::generate_new_binary(param.large_glwe_dimension(), polynomial_size, secret_generator);
+
+    let glwe_ksk_to_large = allocate_and_generate_new_glwe_keyswitch_key(
+        &glwe_secret_key,
+        &large_glwe_secret_key,
+        param.glwe_ds_to_large_base_log(),
+        param.glwe_ds_to_large_level(),
+        param.large_glwe_modular_std_dev(),
+        ciphertext_modulus,
+        encryption_generator,
+    );
+    let mut fourier_glwe_ksk_to_large = FourierGlweKeyswitchKey::new(
+        glwe_size,
+        large_glwe_size,
+        polynomial_size,
+        param.glwe_ds_to_large_base_log(),
+        param.glwe_ds_to_large_level(),
+        param.fft_type_to_large(),
+    );
+    convert_standard_glwe_keyswitch_key_to_fourier(&glwe_ksk_to_large, &mut fourier_glwe_ksk_to_large);
+
+    let glwe_ksk_from_large = allocate_and_generate_new_glwe_keyswitch_key(
+        &large_glwe_secret_key,
+        &glwe_secret_key,
+        param.glwe_ds_from_large_base_log(),
+        param.glwe_ds_from_large_level(),
+        param.glwe_modular_std_dev(),
+        ciphertext_modulus,
+        encryption_generator,
+    );
+    let mut fourier_glwe_ksk_from_large = FourierGlweKeyswitchKey::new(
+        large_glwe_size,
+        glwe_size,
+        polynomial_size,
+        param.glwe_ds_from_large_base_log(),
+        param.glwe_ds_from_large_level(),
+        param.fft_type_from_large(),
+    );
+    convert_standard_glwe_keyswitch_key_to_fourier(&glwe_ksk_from_large, &mut fourier_glwe_ksk_from_large);
+
+    let auto_keys = gen_all_auto_keys(
+        param.auto_base_log(),
+        param.auto_level(),
+        param.fft_type_auto(),
+        &large_glwe_secret_key,
+        param.large_glwe_modular_std_dev(),
+        encryption_generator,
+    );
+
+    let ss_key = generate_scheme_switching_key(
+        &glwe_secret_key,
+        param.ss_base_log(),
+        param.ss_level(),
+        param.glwe_modular_std_dev(),
+        ciphertext_modulus,
+        encryption_generator,
+    );
+
+    HighPrecWWLpCbsKeys {
+        lwe_secret_key,
+        glwe_secret_key,
+        lwe_secret_key_after_ks,
+        large_glwe_secret_key,
+        fourier_bsk,
+        ksk,
+        glwe_ksk_to_large: fourier_glwe_ksk_to_large,
+        glwe_ksk_from_large: fourier_glwe_ksk_from_large,
+        auto_keys,
+        ss_key,
+    }
+}
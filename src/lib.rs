@@ -1,7 +1,9 @@
 pub mod utils;
 pub mod fourier_poly_mult;
+pub mod fft_backend;
 pub mod mod_switch;
 pub mod keygen;
+pub mod cbs_keys;
 pub mod glev_ciphertext;
 pub mod fourier_glwe_ciphertext;
 pub mod fourier_glev_ciphertext;
@@ -11,19 +13,36 @@ pub mod automorphism;
 pub mod glwe_conv;
 pub mod pbs;
 pub mod ggsw_conv;
+pub mod lut_eval;
 pub mod aes_ref;
 pub mod aes_he;
+pub mod aes_ctr;
+pub mod aes_gcm;
+pub mod aes_mac;
+pub mod spn_ref;
+pub mod spn_ciphers;
+pub mod spn_he;
+pub mod trivium_ref;
+pub mod trivium_he;
 pub mod auto_conv_params;
 pub mod auto_conv_instance;
+pub mod param_error;
+pub mod security;
+pub mod param_traits;
 pub mod wwlp_cbs_params;
 pub mod wwlp_cbs_instance;
 pub mod aes_params;
 pub mod aes_instances;
+pub mod param_file;
+pub mod noise;
+pub mod optimizer;
 
 pub use utils::*;
 pub use fourier_poly_mult::*;
+pub use fft_backend::*;
 pub use mod_switch::*;
 pub use keygen::*;
+pub use cbs_keys::*;
 pub use glev_ciphertext::*;
 pub use fourier_glwe_ciphertext::*;
 pub use fourier_glev_ciphertext::*;
@@ -33,11 +52,26 @@ pub use automorphism::*;
 pub use glwe_conv::*;
 pub use pbs::*;
 pub use ggsw_conv::*;
+pub use lut_eval::*;
 pub use aes_ref::*;
 pub use aes_he::*;
+pub use aes_ctr::*;
+pub use aes_gcm::*;
+pub use aes_mac::*;
+pub use spn_ref::*;
+pub use spn_ciphers::*;
+pub use spn_he::*;
+pub use trivium_ref::*;
+pub use trivium_he::*;
 pub use auto_conv_params::*;
 pub use auto_conv_instance::*;
+pub use param_error::*;
+pub use security::*;
+pub use param_traits::*;
 pub use wwlp_cbs_params::*;
 pub use wwlp_cbs_instance::*;
 pub use aes_params::*;
 pub use aes_instances::*;
+pub use param_file::*;
+pub use noise::*;
+pub use optimizer::*;
@@ -0,0 +1,117 @@
+use tfhe::core_crypto::{
+    prelude::*,
+    fft_impl::fft64::{
+        c64,
+        crypto::ggsw::FourierGgswCiphertext,
+    },
+};
+use crate::utils::*;
+
+/// Evaluate a LUT on the index encrypted by the GGSW ciphertexts of its bits by CMux tree.
+///
+/// - `ggsw_bit_list[i]` encrypts the i-th bit of the index x, from the LSB.
+/// - `table[x]` is the output value of x, and the j-th output LWE encrypts its j-th bit
+///   as b_j * 2^(BITS - 1) under the GLWE secret key, which is the input format of the
+///   circuit bootstrapping. Every `table[x]` should be less than 2^(output bit count).
+///
+/// If the table fits in a polynomial, several output bits are evaluated by a single accumulator
+/// (horizontal packing). Otherwise, the table is split into polynomials which are selected by
+/// CMux tree with the upper bits and then blind rotated with the lower bits (vertical packing).
+pub fn lut_eval_by_cmux_tree<Scalar, GgswCont, OutputCont>(
+    ggsw_bit_list: &[FourierGgswCiphertext<GgswCont>],
+    table: &[usize],
+    lwe_out_list: &mut LweCiphertextList<OutputCont>,
+) where
+    Scalar: UnsignedTorus + CastFrom<usize>,
+    GgswCont: Container<Element=c64>,
+    OutputCont: ContainerMut<Element=Scalar>,
+{
+    assert!(!ggsw_bit_list.is_empty());
+    let num_bits = ggsw_bit_list.len();
+    let table_size = 1 << num_bits;
+    assert_eq!(table.len(), table_size);
+
+    let glwe_size = ggsw_bit_list[0].glwe_size();
+    let polynomial_size = ggsw_bit_list[0].polynomial_size();
+    let ciphertext_modulus = lwe_out_list.ciphertext_modulus();
+    let log_polynomial_size = polynomial_size.log2().0;
+    assert_eq!(
+        lwe_out_list.lwe_size(),
+        LweSize(glwe_size.to_glwe_dimension().0 * polynomial_size.0 + 1),
+    );
+
+    let output_bit_count = lwe_out_list.entity_count();
+    assert!(table.iter().all(|&val| val.checked_shr(output_bit_count as u32).unwrap_or(0) == 0));
+    let encode_bit = |val: usize, bit_idx: usize| -> Scalar {
+        Scalar::cast_from((val >> bit_idx) & 1) << (Scalar::BITS - 1)
+    };
+
+    if num_bits <= log_polynomial_size {
+        // Horizontal packing
+        let num_par_lut = polynomial_size.0 / table_size;
+
+        for (acc_idx, mut lwe_out_chunk) in lwe_out_list.chunks_mut(num_par_lut).enumerate() {
+            let accumulator = (0..polynomial_size.0).map(|i| {
+                let bit_idx = acc_idx * num_par_lut + i / table_size;
+                if bit_idx < output_bit_count {
+                    encode_bit(table[i % table_size], bit_idx)
+                } else {
+                    Scalar::ZERO
+                }
+            }).collect::<Vec<Scalar>>();
+            let accumulator_plaintext = PlaintextList::from_container(accumulator);
+            let mut accumulator = allocate_and_trivially_encrypt_new_glwe_ciphertext(glwe_size, &accumulator_plaintext, ciphertext_modulus);
+
+            blind_rotate_by_cmux(&mut accumulator, ggsw_bit_list);
+
+            for (i, mut lwe_out) in lwe_out_chunk.iter_mut().enumerate() {
+                extract_lwe_sample_from_glwe_ciphertext(&accumulator, &mut lwe_out, MonomialDegree(i * table_size));
+            }
+        }
+    } else {
+        // Vertical packing
+        let (ggsw_low_bits, ggsw_high_bits) = ggsw_bit_list.split_at(log_polynomial_size);
+
+        for (bit_idx, mut lwe_out) in lwe_out_list.iter_mut().enumerate() {
+            let mut glwe_list = table.chunks_exact(polynomial_size.0).map(|table_chunk| {
+                let accumulator = table_chunk.iter().map(|&val| encode_bit(val, bit_idx)).collect::<Vec<Scalar>>();
+                let accumulator_plaintext = PlaintextList::from_container(accumulator);
+                allocate_and_trivially_encrypt_new_glwe_ciphertext(glwe_size, &accumulator_plaintext, ciphertext_modulus)
+            }).collect::<Vec<GlweCiphertextOwned<Scalar>>>();
+
+            // CMux tree: ct0 + GGSW(b) * (ct1 - ct0)
+            let mut buf = GlweCiphertext::new(Scalar::ZERO, glwe_size, polynomial_size, ciphertext_modulus);
+            for ggsw in ggsw_high_bits.iter() {
+                glwe_list = glwe_list.chunks_exact(2).map(|glwe_pair| {
+                    let mut ct0 = glwe_pair[0].clone();
+                    glwe_ciphertext_clone_from(&mut buf, &glwe_pair[1]);
+                    glwe_ciphertext_sub_assign(&mut buf, &ct0);
+                    add_external_product_assign(&mut ct0, ggsw, &buf);
+                    ct0
+                }).collect();
+            }
+
+            let mut accumulator = glwe_list.pop().unwrap();
+            blind_rotate_by_cmux(&mut accumulator, ggsw_low_bits);
+
+            extract_lwe_sample_from_glwe_ciphertext(&accumulator, &mut lwe_out, MonomialDegree(0));
+        }
+    }
+}
+
+/// Rotate the accumulator by X^(-x) where x is the index encrypted by the GGSW ciphertexts of its bits.
+fn blind_rotate_by_cmux<Scalar, GgswCont>(
+    accumulator: &mut GlweCiphertextOwned<Scalar>,
+    ggsw_bit_list: &[FourierGgswCiphertext<GgswCont>],
+) where
+    Scalar: UnsignedTorus,
+    GgswCont: Container<Element=c64>,
+{
+    let mut buf = GlweCiphertext::new(Scalar::ZERO, accumulator.glwe_size(), accumulator.polynomial_size(), accumulator.ciphertext_modulus());
+    for (i, ggsw) in ggsw_bit_list.iter().enumerate() {
+        glwe_ciphertext_clone_from(&mut buf, accumulator);
+        glwe_ciphertext_monic_monomial_div_assign(&mut buf, MonomialDegree(1 << i));
+        glwe_ciphertext_sub_assign(&mut buf, accumulator);
+        add_external_product_assign(accumulator, ggsw, &buf);
+    }
+}
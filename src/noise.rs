@@ -0,0 +1,559 @@
+//! Noise model of the circuit bootstrapping and the AES evaluation, ported from `err_analysis.sage`.
+//!
+//! Unless stated otherwise, variances of ciphertexts are given over Z_q (i.e., scaled by q^2),
+//! while the variances of the secret key encryptions are given over the torus as in `StandardDev`.
+use tfhe::core_crypto::prelude::*;
+use crate::{param_traits::*, gf128_reduced_monomial, AesKeySize, AesParam, CBSParam, HighPrecWWLpCBSParam, WWLpCbsMode, BLOCKSIZE_IN_BIT, BYTESIZE, NUM_ROWS};
+
+/// Number of CBS in the AES evaluation: one per state bit in each of the 10, 12 or 14 S-box layers.
+pub fn aes_num_cbs(key_size: AesKeySize) -> usize {
+    key_size.num_rounds() * BLOCKSIZE_IN_BIT
+}
+
+/// Number of CBS in the homomorphic key expansion: one per bit of each SubWord.
+pub fn aes_key_expansion_num_cbs(key_size: AesKeySize) -> usize {
+    key_size.key_expansion_terms().sub_words.len() * NUM_ROWS * BYTESIZE
+}
+
+/// Number of LWE ciphertexts added to a state bit by MixColumns and AddRoundKey after SubBytes.
+pub const AES_LINEAR_LAYER_NUM_TERMS: usize = 7;
+
+/// Largest number of input bits of an output bit of InvMixColumns, i.e. the largest row weight
+/// of its matrix over GF(2). In the inverse cipher, each of them is an InvSubBytes output plus a round key bit.
+pub const AES_INV_LINEAR_LAYER_NUM_TERMS: usize = 19;
+
+/// Ciphertext modulus q = 2^BITS as f64.
+pub fn modulus<Scalar: UnsignedInteger>() -> f64 {
+    2f64.powi(Scalar::BITS as i32)
+}
+
+
+/* ======== Ported from err_analysis.sage ======== */
+
+/// Added variance of an external product by a GGSW ciphertext whose noise variance is `var_ggsw`.
+pub fn get_var_external_product(
+    polynomial_size: usize,
+    glwe_dimension: usize,
+    var_ggsw: f64,
+    q: f64,
+    base_log: usize,
+    level: usize,
+) -> f64 {
+    let big_n = polynomial_size as f64;
+    let k = glwe_dimension as f64;
+    let b = 2f64.powi(base_log as i32);
+    let b_pow_2l = 2f64.powi((2 * base_log * level) as i32);
+
+    let mut var = 0.0;
+    var += level as f64 * (k + 1.0) * big_n * (b * b + 2.0) / 12.0 * var_ggsw;
+    var += (q * q - b_pow_2l) / (24.0 * b_pow_2l) * (1.0 + k * big_n / 2.0);
+    var += k * big_n / 32.0;
+    var += (1.0 - k * big_n / 2.0).powi(2) / 16.0;
+
+    var
+}
+
+/// `get_var_pbs`: variance of the PBS output, which is n external products by the BSK.
+pub fn get_var_pbs(
+    polynomial_size: usize,
+    glwe_dimension: usize,
+    lwe_dimension: usize,
+    var_glwe: f64,
+    q: f64,
+    base_log: usize,
+    level: usize,
+) -> f64 {
+    let var_bsk = var_glwe * q * q;
+    lwe_dimension as f64 * get_var_external_product(polynomial_size, glwe_dimension, var_bsk, q, base_log, level)
+}
+
+/// `get_var_glwe_ks`: added variance of the GLWE keyswitching from `k_src` polynomials.
+pub fn get_var_glwe_ks(
+    polynomial_size: usize,
+    k_src: usize,
+    var_dst: f64,
+    q: f64,
+    base_log: usize,
+    level: usize,
+) -> f64 {
+    let var_ksk = var_dst * q * q;
+    let k_src_n = (k_src * polynomial_size) as f64;
+    let b = 2f64.powi(base_log as i32);
+    let b_pow_2l = 2f64.powi((2 * base_log * level) as i32);
+
+    let mut var = 0.0;
+    var += k_src_n * level as f64 * var_ksk * (b / 2.0).powi(2);
+    var += k_src_n / 12.0 * (q * q / b_pow_2l - 1.0);
+
+    var
+}
+
+/// `get_var_lwe_ks`: added variance of the LWE keyswitching from the LWE dimension kN.
+pub fn get_var_lwe_ks(
+    glwe_dimension: usize,
+    polynomial_size: usize,
+    var_lwe: f64,
+    q: f64,
+    base_log: usize,
+    level: usize,
+) -> f64 {
+    let var_ksk = var_lwe * q * q;
+    let k_n = (glwe_dimension * polynomial_size) as f64;
+    let b = 2f64.powi(base_log as i32);
+    let b_pow_2l = 2f64.powi((2 * base_log * level) as i32);
+
+    let mut var = 0.0;
+    var += k_n * level as f64 * var_ksk * (b * b / 12.0 + 1.0 / 6.0);
+    var += k_n / 24.0 * (q * q / b_pow_2l + 0.5);
+
+    var
+}
+
+/// `get_gamma`: ratio of the PBSmanyLUT decision margin to the standard deviation of the
+/// mod-switched phase, where the input encodes its bit by `delta_in`.
+pub fn get_gamma(
+    lwe_dimension: usize,
+    polynomial_size: usize,
+    q: f64,
+    theta: usize,
+    delta_in: f64,
+    var_in: f64,
+) -> f64 {
+    let n = lwe_dimension as f64;
+    let w = 2.0 * polynomial_size as f64 * 2f64.powi(-(theta as i32));
+
+    let mut var = 0.0;
+    var += (w / q).powi(2) * (var_in - 1.0 / 12.0);
+    var += n / 48.0 * ((w / q).powi(2) + 2.0);
+    var += 1.0 / 12.0;
+
+    w * delta_in / (2.0 * q * var.sqrt())
+}
+
+/// `get_fail_prob` in log2 scale, which does not underflow for large gamma.
+pub fn get_log2_fail_prob(gamma: f64) -> f64 {
+    ln_erfc(gamma / 2f64.sqrt()) / 2f64.ln()
+}
+
+/// ln(erfc(x)) by the Chebyshev approximation of erfc with relative error below 1.2e-7.
+fn ln_erfc(x: f64) -> f64 {
+    let z = x.abs();
+    let t = 1.0 / (1.0 + 0.5 * z);
+    let poly = -z * z - 1.26551223 + t * (1.00002368 + t * (0.37409196 + t * (0.09678418
+        + t * (-0.18628806 + t * (0.27886807 + t * (-1.13520398 + t * (1.48851587
+        + t * (-0.82215223 + t * 0.17087277))))))));
+    let ln_erfc_z = t.ln() + poly;
+
+    if x >= 0.0 {
+        ln_erfc_z
+    } else {
+        (2.0 - ln_erfc_z.exp()).ln()
+    }
+}
+
+
+/* ======== Stages of the circuit bootstrapping ======== */
+
+/// Variance of the LEV (and of a plain PBS output) produced by the bootstrapping key.
+pub fn var_lev<Scalar, P>(param: &P) -> f64
+where
+    Scalar: UnsignedInteger,
+    P: PbsParameters<Scalar>,
+{
+    get_var_pbs(
+        param.polynomial_size().0,
+        param.glwe_dimension().0,
+        param.lwe_dimension().0,
+        param.glwe_modular_std_dev().get_variance(),
+        modulus::<Scalar>(),
+        param.pbs_base_log().0,
+        param.pbs_level().0,
+    )
+}
+
+/// Variance of the rounding error of the mod-switch preprocessing to q/N,
+/// which rounds every coefficient of an LWE ciphertext of dimension kN to a multiple of N.
+pub fn var_preprocessing(polynomial_size: usize, glwe_dimension: usize) -> f64 {
+    let big_n = polynomial_size as f64;
+    let k_n = (glwe_dimension * polynomial_size) as f64;
+
+    (big_n * big_n - 1.0) / 12.0 * (1.0 + k_n / 2.0)
+}
+
+/// Added variance of a single automorphism, i.e. a GLWE keyswitching to the same key.
+pub fn var_automorphism<Scalar, P>(param: &P) -> f64
+where
+    Scalar: UnsignedInteger,
+    P: PbsParameters<Scalar> + AutomorphismParameters,
+{
+    get_var_glwe_ks(
+        param.polynomial_size().0,
+        param.glwe_dimension().0,
+        param.glwe_modular_std_dev().get_variance(),
+        modulus::<Scalar>(),
+        param.auto_base_log().0,
+        param.auto_level().0,
+    )
+}
+
+/// Variance of every coefficient added by the automorphisms of the trace.
+///
+/// The automorphism error of the i-th step is multiplied by N / 2^i by the remaining steps,
+/// which adds (N^2 - 1) / 3 times the variance of a single automorphism.
+pub fn var_trace_auto(polynomial_size: usize, var_auto: f64) -> f64 {
+    let big_n = polynomial_size as f64;
+    (big_n * big_n - 1.0) / 3.0 * var_auto
+}
+
+/// Variance of the constant term after the LWE-to-GLWE conversion by trace.
+///
+/// The input error is kept as is by the patched method up to the preprocessing error,
+/// but multiplied by N in the original method.
+pub fn var_trace(
+    polynomial_size: usize,
+    glwe_dimension: usize,
+    var_in: f64,
+    var_auto: f64,
+    mode: WWLpCbsMode,
+) -> f64 {
+    let big_n = polynomial_size as f64;
+    let var_auto_total = var_trace_auto(polynomial_size, var_auto);
+
+    match mode {
+        WWLpCbsMode::Patched => var_in + var_preprocessing(polynomial_size, glwe_dimension) + var_auto_total,
+        WWLpCbsMode::Original => big_n * big_n * var_in + var_auto_total,
+    }
+}
+
+/// Average variance of the GGSW coefficients obtained by scheme switching from a GLEV whose
+/// constant terms have variance `var_glev_const` and other coefficients have variance `var_glev_coeff`.
+///
+/// The mask rows multiply the GLEV error by a binary key polynomial, so every coefficient gets half of
+/// the error norm of the GLEV on top of the external product error `var_ss`, and the body rows are the GLEV itself.
+/// This average is the GGSW variance which determines the external product error.
+pub fn get_var_scheme_switching(
+    polynomial_size: usize,
+    glwe_dimension: usize,
+    var_glev_const: f64,
+    var_glev_coeff: f64,
+    var_ss: f64,
+) -> f64 {
+    let glev_err_norm = var_glev_const + (polynomial_size - 1) as f64 * var_glev_coeff;
+    let var_mask_row = glev_err_norm / 2.0 + var_ss;
+    let var_body_row = glev_err_norm / polynomial_size as f64;
+
+    (glwe_dimension as f64 * var_mask_row + var_body_row) / (glwe_dimension + 1) as f64
+}
+
+/// Added variance of an external product by the scheme switching key.
+pub fn var_ss_external_product<Scalar, P>(param: &P) -> f64
+where
+    Scalar: UnsignedInteger,
+    P: PbsParameters<Scalar> + SchemeSwitchingParameters,
+{
+    let q = modulus::<Scalar>();
+    get_var_external_product(
+        param.polynomial_size().0,
+        param.glwe_dimension().0,
+        param.glwe_modular_std_dev().get_variance() * q * q,
+        q,
+        param.ss_base_log().0,
+        param.ss_level().0,
+    )
+}
+
+/// [`get_var_scheme_switching`] with the scheme switching key of `param`.
+pub fn var_scheme_switching<Scalar, P>(param: &P, var_glev_const: f64, var_glev_coeff: f64) -> f64
+where
+    Scalar: UnsignedInteger,
+    P: PbsParameters<Scalar> + SchemeSwitchingParameters,
+{
+    get_var_scheme_switching(
+        param.polynomial_size().0,
+        param.glwe_dimension().0,
+        var_glev_const,
+        var_glev_coeff,
+        var_ss_external_product(param),
+    )
+}
+
+/// Average variance of the GGSW coefficients produced by the WWL+ circuit bootstrapping.
+pub fn var_wwlp_cbs<Scalar, P>(param: &P, mode: WWLpCbsMode) -> f64
+where
+    Scalar: UnsignedInteger,
+    P: CbsParameters<Scalar> + AutomorphismParameters + SchemeSwitchingParameters,
+{
+    let polynomial_size = param.polynomial_size().0;
+    let var_auto = var_automorphism(param);
+    let var_glev_const = var_trace(
+        polynomial_size,
+        param.glwe_dimension().0,
+        var_lev(param),
+        var_auto,
+        mode,
+    );
+
+    var_scheme_switching(param, var_glev_const, var_trace_auto(polynomial_size, var_auto))
+}
+
+/// Average variance of the GGSW coefficients produced by the high-precision WWL+ circuit bootstrapping,
+/// where the trace is evaluated under the large GLWE key.
+pub fn var_high_prec_wwlp_cbs<Scalar: UnsignedInteger>(param: &HighPrecWWLpCBSParam<Scalar>) -> f64 {
+    let q = modulus::<Scalar>();
+    let polynomial_size = param.polynomial_size().0;
+    let glwe_dimension = param.glwe_dimension().0;
+    let large_glwe_dimension = param.large_glwe_dimension().0;
+
+    let var_to_large = get_var_glwe_ks(
+        polynomial_size,
+        glwe_dimension,
+        param.large_glwe_modular_std_dev().get_variance(),
+        q,
+        param.glwe_ds_to_large_base_log().0,
+        param.glwe_ds_to_large_level().0,
+    );
+    let var_auto = get_var_glwe_ks(
+        polynomial_size,
+        large_glwe_dimension,
+        param.large_glwe_modular_std_dev().get_variance(),
+        q,
+        param.auto_base_log().0,
+        param.auto_level().0,
+    );
+    let var_from_large = get_var_glwe_ks(
+        polynomial_size,
+        large_glwe_dimension,
+        param.glwe_modular_std_dev().get_variance(),
+        q,
+        param.glwe_ds_from_large_base_log().0,
+        param.glwe_ds_from_large_level().0,
+    );
+
+    let var_glev_const = var_trace(
+        polynomial_size,
+        large_glwe_dimension,
+        var_lev(param) + var_to_large,
+        var_auto,
+        WWLpCbsMode::Patched,
+    ) + var_from_large;
+    let var_glev_coeff = var_trace_auto(polynomial_size, var_auto) + var_from_large;
+
+    var_scheme_switching(param, var_glev_const, var_glev_coeff)
+}
+
+/// Average variance of the GGSW coefficients produced by the CBS by private functional packing keyswitching.
+///
+/// The LEV error lies in the constant term and is multiplied by the key polynomials in the mask rows.
+pub fn var_pfks_cbs<Scalar: UnsignedInteger>(param: &CBSParam<Scalar>) -> f64 {
+    let polynomial_size = param.polynomial_size().0 as f64;
+    let glwe_dimension = param.glwe_dimension().0 as f64;
+    let var_lev = var_lev(param);
+    let var_pfks = get_var_lwe_ks(
+        param.glwe_dimension().0,
+        param.polynomial_size().0,
+        param.glwe_modular_std_dev().get_variance(),
+        modulus::<Scalar>(),
+        param.pfks_base_log().0,
+        param.pfks_level().0,
+    );
+
+    (glwe_dimension * var_lev / 2.0 + var_lev / polynomial_size) / (glwe_dimension + 1.0) + var_pfks
+}
+
+/// Added variance of a CMux tree (or blind rotation by CMux) of the given depth.
+pub fn var_cmux_tree<Scalar, P>(param: &P, var_ggsw: f64, depth: usize) -> f64
+where
+    Scalar: UnsignedInteger,
+    P: CbsParameters<Scalar>,
+{
+    depth as f64 * get_var_external_product(
+        param.polynomial_size().0,
+        param.glwe_dimension().0,
+        var_ggsw,
+        modulus::<Scalar>(),
+        param.cbs_base_log().0,
+        param.cbs_level().0,
+    )
+}
+
+/// Variance of the CBS input: a refreshing PBS followed by the LWE keyswitching.
+pub fn var_cbs_input<Scalar, P>(param: &P, refresh_base_log: DecompositionBaseLog, refresh_level: DecompositionLevelCount) -> f64
+where
+    Scalar: UnsignedInteger,
+    P: PbsParameters<Scalar> + LweKeyswitchParameters,
+{
+    let q = modulus::<Scalar>();
+    let var_refresh = get_var_pbs(
+        param.polynomial_size().0,
+        param.glwe_dimension().0,
+        param.lwe_dimension().0,
+        param.glwe_modular_std_dev().get_variance(),
+        q,
+        refresh_base_log.0,
+        refresh_level.0,
+    );
+    let var_ks = get_var_lwe_ks(
+        param.glwe_dimension().0,
+        param.polynomial_size().0,
+        param.lwe_modular_std_dev().get_variance(),
+        q,
+        param.ks_base_log().0,
+        param.ks_level().0,
+    );
+
+    var_refresh + var_ks
+}
+
+/// Failure probability (log2) of the LEV generation by PBSmanyLUT on an input of variance `var_in`.
+pub fn log2_cbs_fail_prob<Scalar, P>(param: &P, var_in: f64) -> f64
+where
+    Scalar: UnsignedInteger,
+    P: CbsParameters<Scalar>,
+{
+    let gamma = get_gamma(
+        param.lwe_dimension().0,
+        param.polynomial_size().0,
+        modulus::<Scalar>(),
+        param.log_lut_count().0,
+        2f64.powi(Scalar::BITS as i32 - 1),
+        var_in,
+    );
+
+    get_log2_fail_prob(gamma)
+}
+
+
+/* ======== AES evaluation ======== */
+
+/// Added variance of the LWE keyswitching by GLWE dimension switching over the common polynomial size.
+pub fn var_aes_glwe_ds<Scalar: UnsignedInteger>(param: &AesParam<Scalar>) -> f64 {
+    let common_polynomial_size = param.common_polynomial_size().0;
+    let k_src = param.glwe_dimension().0 * param.polynomial_size().0 / common_polynomial_size;
+
+    get_var_glwe_ks(
+        common_polynomial_size,
+        k_src,
+        param.lwe_modular_std_dev().get_variance(),
+        modulus::<Scalar>(),
+        param.glwe_ds_base_log().0,
+        param.glwe_ds_level().0,
+    )
+}
+
+/// Variance of an SubBytes output bit: 8 external products by the CBS outputs.
+pub fn var_aes_sbox_output<Scalar: UnsignedInteger>(param: &AesParam<Scalar>) -> f64 {
+    var_cmux_tree(param, var_wwlp_cbs(param, WWLpCbsMode::Patched), 8)
+}
+
+/// Variance of a state bit at the input of the CBS of the next round.
+///
+/// This differs from `err_analysis.sage`, which takes `Var_in = 2 * Var_KS` and so assumes that
+/// the errors of the SubBytes outputs and of the round key are covered by doubling the keyswitching error.
+/// Here the doubled keyswitching error is kept as a margin, and the `AES_LINEAR_LAYER_NUM_TERMS` SubBytes
+/// outputs and the round key added by the linear layer are counted explicitly on top of it. It is therefore
+/// never below the sage value, and it stays an upper bound for parameter sets (e.g. from the optimizer)
+/// whose CBS output error is not negligible compared to the keyswitching error.
+pub fn var_aes_round_input<Scalar: UnsignedInteger>(param: &AesParam<Scalar>) -> f64 {
+    var_aes_round_input_with_round_key(param, var_aes_fresh_round_key(param))
+}
+
+/// Variance of a freshly encrypted round key (or master key) bit.
+pub fn var_aes_fresh_round_key<Scalar: UnsignedInteger>(param: &AesParam<Scalar>) -> f64 {
+    let q = modulus::<Scalar>();
+    param.glwe_modular_std_dev().get_variance() * q * q
+}
+
+/// Largest variance of a round key bit expanded homomorphically from a fresh master key encryption,
+/// i.e. of the sum of the master key words and SubWord outputs in a word of `AesKeySize::key_expansion_terms`.
+pub fn var_aes_he_round_key<Scalar: UnsignedInteger>(param: &AesParam<Scalar>, key_size: AesKeySize) -> f64 {
+    let var_master_key = var_aes_fresh_round_key(param);
+    let var_sub_word = var_aes_sbox_output(param);
+    let terms = key_size.key_expansion_terms();
+
+    terms.word_terms.iter().map(|word_terms| {
+        word_terms.iter().map(|&term| {
+            if term < terms.num_key_words {var_master_key} else {var_sub_word}
+        }).sum::<f64>()
+    }).fold(0.0, f64::max)
+}
+
+fn var_aes_round_input_with_round_key<Scalar: UnsignedInteger>(param: &AesParam<Scalar>, var_round_key: f64) -> f64 {
+    2.0 * var_aes_glwe_ds(param)
+        + AES_LINEAR_LAYER_NUM_TERMS as f64 * var_aes_sbox_output(param)
+        + var_round_key
+}
+
+/// Variance of a state bit at the input of the CBS of the next round of the inverse cipher,
+/// where AddRoundKey comes before InvMixColumns. The keyswitching error is doubled as in `var_aes_round_input`.
+pub fn var_aes_decryption_round_input<Scalar: UnsignedInteger>(param: &AesParam<Scalar>) -> f64 {
+    2.0 * var_aes_glwe_ds(param)
+        + AES_INV_LINEAR_LAYER_NUM_TERMS as f64 * (var_aes_sbox_output(param) + var_aes_fresh_round_key(param))
+}
+
+/// Failure probability (log2) of a single CBS in the AES evaluation.
+pub fn log2_aes_round_fail_prob<Scalar: UnsignedInteger>(param: &AesParam<Scalar>) -> f64 {
+    log2_cbs_fail_prob(param, var_aes_round_input(param))
+}
+
+/// Failure probability (log2) of the whole AES evaluation, by the union bound over all CBS.
+pub fn log2_aes_fail_prob<Scalar: UnsignedInteger>(param: &AesParam<Scalar>, key_size: AesKeySize) -> f64 {
+    log2_aes_round_fail_prob(param) + (aes_num_cbs(key_size) as f64).log2()
+}
+
+/// Failure probability (log2) of the AES decryption by the inverse cipher, by the union bound over all CBS.
+pub fn log2_aes_decryption_fail_prob<Scalar: UnsignedInteger>(param: &AesParam<Scalar>, key_size: AesKeySize) -> f64 {
+    log2_cbs_fail_prob(param, var_aes_decryption_round_input(param)) + (aes_num_cbs(key_size) as f64).log2()
+}
+
+/// Failure probability (log2) of the AES evaluation with the round keys expanded homomorphically,
+/// by the union bound over the CBS of the S-box layers and of the key expansion.
+///
+/// The input of a SubWord is a word of the round keys, whose variance is below that of the state input,
+/// so every CBS is bounded by the failure probability of a state bit with the noisiest round key.
+pub fn log2_aes_fail_prob_with_he_key_expansion<Scalar: UnsignedInteger>(param: &AesParam<Scalar>, key_size: AesKeySize) -> f64 {
+    let var_in = var_aes_round_input_with_round_key(param, var_aes_he_round_key(param, key_size));
+    let num_cbs = aes_num_cbs(key_size) + aes_key_expansion_num_cbs(key_size);
+
+    log2_cbs_fail_prob(param, var_in) + (num_cbs as f64).log2()
+}
+
+
+/* ======== GHASH ======== */
+
+/// Largest number of coefficients of H summed into a coefficient of H x^k for k < 128.
+pub fn ghash_max_h_terms() -> usize {
+    let reduced_monomials = (0..(2 * BLOCKSIZE_IN_BIT - 1)).map(gf128_reduced_monomial).collect::<Vec<u128>>();
+    (0..BLOCKSIZE_IN_BIT).flat_map(|k| {
+        let reduced_monomials = &reduced_monomials;
+        (0..BLOCKSIZE_IN_BIT).map(move |j| {
+            (0..BLOCKSIZE_IN_BIT).filter(|&l| (reduced_monomials[l + k] >> j) & 1 != 0).count()
+        })
+    }).max().unwrap()
+}
+
+/// Variance of a coefficient of the packed H x^k: a sum of refreshed bits of H, packed by the patched trace.
+pub fn var_ghash_h_power<Scalar: UnsignedInteger>(param: &AesParam<Scalar>) -> f64 {
+    var_trace(
+        param.polynomial_size().0,
+        param.glwe_dimension().0,
+        ghash_max_h_terms() as f64 * var_lev(param),
+        var_automorphism(param),
+        WWLpCbsMode::Patched,
+    )
+}
+
+/// Variance of a bit of X * H when all 128 coefficients of X are encrypted ones:
+/// each adds an external product by its CBS output and the error of the packed H x^k.
+pub fn var_ghash_output<Scalar: UnsignedInteger>(param: &AesParam<Scalar>) -> f64 {
+    BLOCKSIZE_IN_BIT as f64 * (
+        var_cmux_tree(param, var_wwlp_cbs(param, WWLpCbsMode::Patched), 1)
+        + var_ghash_h_power(param)
+    )
+}
+
+/// Failure probability (log2) of a GHASH block with public data, by the union bound over the CBS of Y_{i-1}.
+pub fn log2_ghash_block_fail_prob<Scalar: UnsignedInteger>(param: &AesParam<Scalar>) -> f64 {
+    let var_in = 2.0 * var_aes_glwe_ds(param) + var_ghash_output(param);
+
+    log2_cbs_fail_prob(param, var_in) + (BLOCKSIZE_IN_BIT as f64).log2()
+}
@@ -0,0 +1,768 @@
+//! Search for the cheapest decomposition parameters and FFT splits meeting a target failure probability.
+//!
+//! The variance of each stage is given by the [`noise`](crate::noise) model together with the FFT error of
+//! the split keyswitching, and the cost is estimated by the number of floating point operations.
+use std::fmt;
+use tfhe::core_crypto::prelude::*;
+use crate::{noise::*, param_error::{fft_precision_bits, ParamChecker}, param_traits::*, AesKeySize, AesParam, AesParamFields, FftType, WWLpCBSParam, WWLpCBSParamFields, WWLpCbsMode};
+
+const MAX_BASE_LOG: usize = 32;
+const MAX_LEVEL: usize = 12;
+const MAX_LOG_LUT_COUNT: usize = 3;
+const MIN_FFT_SPLIT: usize = 16;
+const MAX_FFT_SPLIT: usize = 48;
+
+/// Number of CMux per output bit of the AES S-box evaluated by a CMux tree over 8 input bits.
+const AES_SBOX_CMUX_COUNT: usize = 255;
+
+
+/// Dimensions and noise of the secret keys, which are fixed by the security level.
+#[derive(Clone, Copy, Debug)]
+pub struct KeyParam<Scalar: UnsignedInteger> {
+    lwe_dimension: LweDimension,
+    lwe_modular_std_dev: StandardDev,
+    polynomial_size: PolynomialSize,
+    glwe_dimension: GlweDimension,
+    glwe_modular_std_dev: StandardDev,
+    ciphertext_modulus: CiphertextModulus::<Scalar>,
+}
+
+impl<Scalar: UnsignedInteger> KeyParam<Scalar> {
+    pub fn new(
+        lwe_dimension: LweDimension,
+        lwe_modular_std_dev: StandardDev,
+        polynomial_size: PolynomialSize,
+        glwe_dimension: GlweDimension,
+        glwe_modular_std_dev: StandardDev,
+        ciphertext_modulus: CiphertextModulus::<Scalar>,
+    ) -> Self {
+        KeyParam {
+            lwe_dimension,
+            lwe_modular_std_dev,
+            polynomial_size,
+            glwe_dimension,
+            glwe_modular_std_dev,
+            ciphertext_modulus,
+        }
+    }
+
+    pub fn lwe_dimension(&self) -> LweDimension {
+        self.lwe_dimension
+    }
+
+    pub fn lwe_modular_std_dev(&self) -> StandardDev {
+        self.lwe_modular_std_dev
+    }
+
+    pub fn polynomial_size(&self) -> PolynomialSize {
+        self.polynomial_size
+    }
+
+    pub fn glwe_dimension(&self) -> GlweDimension {
+        self.glwe_dimension
+    }
+
+    pub fn glwe_modular_std_dev(&self) -> StandardDev {
+        self.glwe_modular_std_dev
+    }
+
+    pub fn ciphertext_modulus(&self) -> CiphertextModulus::<Scalar> {
+        self.ciphertext_modulus
+    }
+}
+
+
+/// Predicted variance of a single stage, over Z_q.
+#[derive(Clone, Copy, Debug)]
+pub struct StageNoise {
+    pub stage: &'static str,
+    pub variance: f64,
+}
+
+impl StageNoise {
+    pub fn log2_std(&self) -> f64 {
+        self.variance.log2() / 2.0
+    }
+}
+
+/// Parameters together with their estimated cost, failure probability and variance per stage.
+#[derive(Clone, Debug)]
+pub struct OptimizedParam<P> {
+    param: P,
+    cost: f64,
+    log2_fail_prob: f64,
+    stages: Vec<StageNoise>,
+}
+
+impl<P> OptimizedParam<P> {
+    pub fn param(&self) -> &P {
+        &self.param
+    }
+
+    /// Number of floating point operations.
+    pub fn cost(&self) -> f64 {
+        self.cost
+    }
+
+    pub fn log2_fail_prob(&self) -> f64 {
+        self.log2_fail_prob
+    }
+
+    pub fn stages(&self) -> &[StageNoise] {
+        &self.stages
+    }
+}
+
+impl<P> fmt::Display for OptimizedParam<P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "cost: 2^{:.2} flops, failure probability: 2^{:.2}", self.cost.log2(), self.log2_fail_prob)?;
+        for stage in self.stages.iter() {
+            write!(f, "\n- {}: std 2^{:.2}", stage.stage, stage.log2_std())?;
+        }
+        Ok(())
+    }
+}
+
+
+/* ======== Cost model ======== */
+
+pub fn cost_fft(polynomial_size: usize) -> f64 {
+    let big_n = polynomial_size as f64;
+    big_n * big_n.log2()
+}
+
+/// Forward FFTs of the decomposed input, pointwise products and inverse FFTs of an external product.
+pub fn cost_external_product(polynomial_size: usize, glwe_dimension: usize, level: usize) -> f64 {
+    let glwe_size = (glwe_dimension + 1) as f64;
+    let level = level as f64;
+
+    (glwe_size * level + glwe_size) * cost_fft(polynomial_size)
+        + glwe_size * glwe_size * level * polynomial_size as f64
+}
+
+pub fn cost_pbs(lwe_dimension: usize, polynomial_size: usize, glwe_dimension: usize, level: usize) -> f64 {
+    lwe_dimension as f64 * cost_external_product(polynomial_size, glwe_dimension, level)
+}
+
+pub fn cost_lwe_ks(input_lwe_dimension: usize, output_lwe_dimension: usize, level: usize) -> f64 {
+    (input_lwe_dimension * level * (output_lwe_dimension + 1)) as f64
+}
+
+/// GLWE keyswitching where the keyswitching key is split into `fft_type.num_split()` parts.
+pub fn cost_glwe_ks(polynomial_size: usize, k_src: usize, k_dst: usize, level: usize, fft_type: FftType) -> f64 {
+    let num_split = fft_type.num_split() as f64;
+    let num_input = (k_src * level) as f64;
+    let glwe_size = (k_dst + 1) as f64;
+
+    num_input * cost_fft(polynomial_size)
+        + num_split * (num_input * glwe_size * polynomial_size as f64 + glwe_size * cost_fft(polynomial_size))
+}
+
+pub fn cost_trace(polynomial_size: usize, glwe_dimension: usize, level: usize, fft_type: FftType) -> f64 {
+    polynomial_size.ilog2() as f64 * cost_glwe_ks(polynomial_size, glwe_dimension, glwe_dimension, level, fft_type)
+}
+
+/// Variance of the f64 rounding error of a GLWE keyswitching by the given FFT type.
+///
+/// Each part of the key is multiplied in f64, and its error vanishes by the rounding after the inverse FFT
+/// when it is small enough; otherwise it is scaled back together with the part.
+pub fn var_fft_error(
+    fft_type: FftType,
+    scalar_bits: usize,
+    polynomial_size: usize,
+    k_src: usize,
+    base_log: usize,
+    level: usize,
+) -> f64 {
+    let parts: Vec<(usize, usize)> = match fft_type {
+        FftType::Vanilla => vec![(scalar_bits, 0)],
+        FftType::Split(b) => vec![(b, 0), (scalar_bits - b, b)],
+        FftType::Split16 => (0..scalar_bits / 16).map(|i| (16, 16 * i)).collect(),
+        FftType::F128 => vec![(scalar_bits, 0)],
+    };
+
+    let precision_bits = fft_precision_bits(fft_type);
+    let log_num_terms = ((k_src * level * polynomial_size) as f64).log2() / 2.0;
+    parts.iter().map(|&(part_bits, scale_bits)| {
+        let log_err = (part_bits + base_log) as f64 + log_num_terms - precision_bits as f64;
+        if log_err < -1.0 {
+            0.0
+        } else {
+            2f64.powf(2.0 * (log_err + scale_bits as f64))
+        }
+    }).sum()
+}
+
+/// Variance of the f64 rounding error of an external product, whose GGSW is not split.
+pub fn var_fft_error_external_product(
+    scalar_bits: usize,
+    polynomial_size: usize,
+    glwe_dimension: usize,
+    base_log: usize,
+    level: usize,
+) -> f64 {
+    var_fft_error(FftType::Vanilla, scalar_bits, polynomial_size, glwe_dimension + 1, base_log, level)
+}
+
+
+/* ======== Search ======== */
+
+#[derive(Clone, Copy, Debug)]
+struct Candidate {
+    base_log: usize,
+    level: usize,
+    fft_type: FftType,
+    cost: f64,
+    var: f64,
+}
+
+/// Candidates which are not beaten by a cheaper one with a smaller variance, sorted by increasing cost.
+fn pareto_front(mut candidates: Vec<Candidate>) -> Vec<Candidate> {
+    candidates.sort_by(|a, b| a.cost.total_cmp(&b.cost).then(a.var.total_cmp(&b.var)));
+
+    let mut front: Vec<Candidate> = vec![];
+    for candidate in candidates {
+        if front.last().is_none_or(|last| candidate.var < last.var) {
+            front.push(candidate);
+        }
+    }
+    front
+}
+
+fn decompositions(scalar_bits: usize) -> impl Iterator<Item=(usize, usize)> {
+    (1..=MAX_BASE_LOG).flat_map(move |base_log| {
+        (1..=MAX_LEVEL).filter(move |level| base_log * level <= scalar_bits).map(move |level| (base_log, level))
+    })
+}
+
+fn fft_types() -> impl Iterator<Item=FftType> {
+    [FftType::Vanilla, FftType::Split16].into_iter()
+        .chain((MIN_FFT_SPLIT..=MAX_FFT_SPLIT).map(FftType::Split))
+}
+
+/// Decompositions and FFT types of a GLWE keyswitching accepted by the parameter validation.
+fn glwe_ks_front(
+    scalar_bits: usize,
+    polynomial_size: usize,
+    k_src: usize,
+    k_dst: usize,
+    var_dst: f64,
+    cost_factor: f64,
+) -> Vec<Candidate> {
+    let q = 2f64.powi(scalar_bits as i32);
+    let mut candidates = vec![];
+    for (base_log, level) in decompositions(scalar_bits) {
+        let var_ks = get_var_glwe_ks(polynomial_size, k_src, var_dst, q, base_log, level);
+        for fft_type in fft_types() {
+            let mut checker = ParamChecker::new(scalar_bits);
+            checker.fft_split("glwe_ks", fft_type, base_log, polynomial_size);
+            if checker.finish().is_err() {
+                continue;
+            }
+
+            candidates.push(Candidate {
+                base_log,
+                level,
+                fft_type,
+                cost: cost_factor * cost_glwe_ks(polynomial_size, k_src, k_dst, level, fft_type),
+                var: var_ks + var_fft_error(fft_type, scalar_bits, polynomial_size, k_src, base_log, level),
+            });
+        }
+    }
+    pareto_front(candidates)
+}
+
+fn external_product_front(
+    scalar_bits: usize,
+    polynomial_size: usize,
+    glwe_dimension: usize,
+    var_ggsw: f64,
+    cost_factor: f64,
+) -> Vec<Candidate> {
+    let q = 2f64.powi(scalar_bits as i32);
+    pareto_front(decompositions(scalar_bits).map(|(base_log, level)| Candidate {
+        base_log,
+        level,
+        fft_type: FftType::Vanilla,
+        cost: cost_factor * cost_external_product(polynomial_size, glwe_dimension, level),
+        var: get_var_external_product(polynomial_size, glwe_dimension, var_ggsw, q, base_log, level)
+            + var_fft_error_external_product(scalar_bits, polynomial_size, glwe_dimension, base_log, level),
+    }).collect())
+}
+
+/// Largest variance whose (decreasing) log2 failure probability is at most `log2_target`, by bisection in log scale.
+fn max_variance(log2_fail_prob: impl Fn(f64) -> f64, scalar_bits: usize, log2_target: f64) -> f64 {
+    let mut lo = 0.0;
+    let mut hi = 2.0 * scalar_bits as f64;
+    if log2_fail_prob(2f64.powf(lo)) > log2_target {
+        return 0.0;
+    }
+    for _ in 0..64 {
+        let mid = (lo + hi) / 2.0;
+        if log2_fail_prob(2f64.powf(mid)) <= log2_target {
+            lo = mid;
+        } else {
+            hi = mid;
+        }
+    }
+    2f64.powf(lo)
+}
+
+/// Coefficients (c, a) of the added variance c + a * var_ggsw of an external product.
+/// The slope is measured at the scale of c, since it vanishes in f64 next to c otherwise.
+fn external_product_affine(scalar_bits: usize, polynomial_size: usize, glwe_dimension: usize, base_log: usize, level: usize) -> (f64, f64) {
+    let q = 2f64.powi(scalar_bits as i32);
+    let ext_zero = get_var_external_product(polynomial_size, glwe_dimension, 0.0, q, base_log, level);
+    let scale = ext_zero.max(1.0);
+    let ext_slope = (get_var_external_product(polynomial_size, glwe_dimension, scale, q, base_log, level) - ext_zero) / scale;
+    let var_fft = var_fft_error_external_product(scalar_bits, polynomial_size, glwe_dimension, base_log, level);
+    (ext_zero + var_fft, ext_slope)
+}
+
+fn log2_bit_fail_prob(q: f64, var: f64) -> f64 {
+    get_log2_fail_prob(q / 4.0 / var.sqrt())
+}
+
+fn log2_sum(log2_a: f64, log2_b: f64) -> f64 {
+    let max = log2_a.max(log2_b);
+    max + (2f64.powf(log2_a - max) + 2f64.powf(log2_b - max)).log2()
+}
+
+fn lut_count_pbs(cbs_level: usize, log_lut_count: usize) -> f64 {
+    cbs_level.div_ceil(1 << log_lut_count) as f64
+}
+
+/// GLEV and GGSW variances of the patched WWL+ CBS with `var_auto` including the FFT error.
+fn var_glev_ggsw(polynomial_size: usize, glwe_dimension: usize, var_lev: f64, var_auto: f64, var_ss: f64) -> (f64, f64) {
+    let var_glev = var_trace(polynomial_size, glwe_dimension, var_lev, var_auto, WWLpCbsMode::Patched);
+    let var_ggsw = get_var_scheme_switching(
+        polynomial_size,
+        glwe_dimension,
+        var_glev,
+        var_trace_auto(polynomial_size, var_auto),
+        var_ss,
+    );
+    (var_glev, var_ggsw)
+}
+
+/// Stage fronts shared by the WWL+ CBS and the AES search, with the cost per GLEV level.
+struct CbsFronts {
+    pbs: Vec<Candidate>,
+    auto: Vec<Candidate>,
+    ss: Vec<Candidate>,
+}
+
+impl CbsFronts {
+    fn new<Scalar: UnsignedInteger>(key: &KeyParam<Scalar>) -> Self {
+        let scalar_bits = Scalar::BITS;
+        let q = modulus::<Scalar>();
+        let lwe_dimension = key.lwe_dimension.0;
+        let polynomial_size = key.polynomial_size.0;
+        let glwe_dimension = key.glwe_dimension.0;
+        let var_glwe = key.glwe_modular_std_dev.get_variance() * q * q;
+
+        CbsFronts {
+            pbs: external_product_front(scalar_bits, polynomial_size, glwe_dimension, var_glwe, lwe_dimension as f64)
+                .into_iter()
+                .map(|candidate| Candidate { var: lwe_dimension as f64 * candidate.var, ..candidate })
+                .collect(),
+            auto: glwe_ks_front(
+                scalar_bits,
+                polynomial_size,
+                glwe_dimension,
+                glwe_dimension,
+                key.glwe_modular_std_dev.get_variance(),
+                polynomial_size.ilog2() as f64,
+            ),
+            ss: external_product_front(scalar_bits, polynomial_size, glwe_dimension, var_glwe, glwe_dimension as f64),
+        }
+    }
+
+    /// Cheapest (pbs, auto, ss) whose GGSW variance is at most `max_var_ggsw`, given the number of PBS and GLEV levels.
+    fn cheapest_ggsw(
+        &self,
+        polynomial_size: usize,
+        glwe_dimension: usize,
+        num_pbs: f64,
+        cbs_level: usize,
+        max_var_ggsw: f64,
+        max_cost: f64,
+    ) -> Option<(f64, Candidate, Candidate, Candidate)> {
+        let cbs_level = cbs_level as f64;
+        let mut best: Option<(f64, Candidate, Candidate, Candidate)> = None;
+
+        for pbs in self.pbs.iter() {
+            let cost_pbs = num_pbs * pbs.cost;
+            if cost_pbs >= best.map_or(max_cost, |b| b.0) {
+                break;
+            }
+            for auto in self.auto.iter() {
+                let cost_auto = cost_pbs + cbs_level * auto.cost;
+                if cost_auto >= best.map_or(max_cost, |b| b.0) {
+                    break;
+                }
+                for ss in self.ss.iter() {
+                    let cost = cost_auto + cbs_level * ss.cost;
+                    if cost >= best.map_or(max_cost, |b| b.0) {
+                        break;
+                    }
+                    let (_, var_ggsw) = var_glev_ggsw(polynomial_size, glwe_dimension, pbs.var, auto.var, ss.var);
+                    if var_ggsw <= max_var_ggsw {
+                        best = Some((cost, *pbs, *auto, *ss));
+                        break;
+                    }
+                }
+            }
+        }
+
+        best
+    }
+}
+
+/// Cheapest patched WWL+ CBS parameters, followed by a CMux tree of depth `lut_depth`, whose total failure
+/// probability is at most 2^`log2_target`: the LEV generation on a refreshed input and the decryption of the LUT output.
+pub fn optimize_wwlp_cbs_param<Scalar: UnsignedInteger>(
+    key: &KeyParam<Scalar>,
+    lut_depth: usize,
+    log2_target: f64,
+) -> Option<OptimizedParam<WWLpCBSParam<Scalar>>> {
+    let scalar_bits = Scalar::BITS;
+    let q = modulus::<Scalar>();
+    let lwe_dimension = key.lwe_dimension.0;
+    let polynomial_size = key.polynomial_size.0;
+    let glwe_dimension = key.glwe_dimension.0;
+    let var_glwe = key.glwe_modular_std_dev.get_variance() * q * q;
+    let log2_stage_target = log2_target - 1.0;
+
+    let fronts = CbsFronts::new(key);
+    let mut input_candidates = vec![];
+    for (refresh_base_log, refresh_level) in decompositions(scalar_bits) {
+        let var_refresh = get_var_pbs(polynomial_size, glwe_dimension, lwe_dimension, key.glwe_modular_std_dev.get_variance(), q, refresh_base_log, refresh_level);
+        let cost_refresh = cost_pbs(lwe_dimension, polynomial_size, glwe_dimension, refresh_level);
+        for (ks_base_log, ks_level) in decompositions(scalar_bits) {
+            input_candidates.push((
+                Candidate { base_log: refresh_base_log, level: refresh_level, fft_type: FftType::Vanilla, cost: cost_refresh, var: var_refresh },
+                Candidate {
+                    base_log: ks_base_log,
+                    level: ks_level,
+                    fft_type: FftType::Vanilla,
+                    cost: cost_lwe_ks(glwe_dimension * polynomial_size, lwe_dimension, ks_level),
+                    var: get_var_lwe_ks(glwe_dimension, polynomial_size, key.lwe_modular_std_dev.get_variance(), q, ks_base_log, ks_level),
+                },
+            ));
+        }
+    }
+    input_candidates.sort_by(|a, b| (a.0.cost + a.1.cost).total_cmp(&(b.0.cost + b.1.cost)));
+
+    let max_var_out = max_variance(|var| log2_bit_fail_prob(q, var), scalar_bits, log2_stage_target);
+    let max_var_cmux = (max_var_out - var_glwe) / lut_depth as f64;
+
+    let mut best: Option<(f64, WWLpCBSParam<Scalar>)> = None;
+    for log_lut_count in 0..=MAX_LOG_LUT_COUNT {
+        let max_var_in = max_variance(
+            |var| get_log2_fail_prob(get_gamma(lwe_dimension, polynomial_size, q, log_lut_count, q / 2.0, var)),
+            scalar_bits,
+            log2_stage_target,
+        );
+        let Some((refresh, ks)) = input_candidates.iter().find(|(refresh, ks)| refresh.var + ks.var <= max_var_in) else {
+            continue;
+        };
+        let cost_input = refresh.cost + ks.cost;
+
+        for (cbs_base_log, cbs_level) in decompositions(scalar_bits) {
+            let mut checker = ParamChecker::new(scalar_bits);
+            checker.cbs_decomposition(cbs_base_log, cbs_level, log_lut_count);
+            if checker.finish().is_err() {
+                continue;
+            }
+
+            let (ext_zero, ext_slope) = external_product_affine(scalar_bits, polynomial_size, glwe_dimension, cbs_base_log, cbs_level);
+            let max_var_ggsw = (max_var_cmux - ext_zero) / ext_slope;
+            if max_var_ggsw <= 0.0 {
+                continue;
+            }
+
+            let cost_lut = ((1 << lut_depth) - 1) as f64 * cost_external_product(polynomial_size, glwe_dimension, cbs_level);
+            let fixed_cost = cost_input + cost_lut;
+            let max_cost = best.as_ref().map_or(f64::INFINITY, |b| b.0) - fixed_cost;
+            if max_cost <= 0.0 {
+                continue;
+            }
+
+            if let Some((cost, pbs, auto, ss)) = fronts.cheapest_ggsw(
+                polynomial_size,
+                glwe_dimension,
+                lut_count_pbs(cbs_level, log_lut_count),
+                cbs_level,
+                max_var_ggsw,
+                max_cost,
+            ) {
+                let param = WWLpCBSParam::try_new(WWLpCBSParamFields {
+                    lwe_dimension: key.lwe_dimension,
+                    lwe_modular_std_dev: key.lwe_modular_std_dev,
+                    polynomial_size: key.polynomial_size,
+                    glwe_dimension: key.glwe_dimension,
+                    glwe_modular_std_dev: key.glwe_modular_std_dev,
+                    pbs_base_log: DecompositionBaseLog(pbs.base_log),
+                    pbs_level: DecompositionLevelCount(pbs.level),
+                    refresh_base_log: DecompositionBaseLog(refresh.base_log),
+                    refresh_level: DecompositionLevelCount(refresh.level),
+                    ks_base_log: DecompositionBaseLog(ks.base_log),
+                    ks_level: DecompositionLevelCount(ks.level),
+                    auto_base_log: DecompositionBaseLog(auto.base_log),
+                    auto_level: DecompositionLevelCount(auto.level),
+                    fft_type_auto: auto.fft_type,
+                    ss_base_log: DecompositionBaseLog(ss.base_log),
+                    ss_level: DecompositionLevelCount(ss.level),
+                    cbs_base_log: DecompositionBaseLog(cbs_base_log),
+                    cbs_level: DecompositionLevelCount(cbs_level),
+                    log_lut_count: LutCountLog(log_lut_count),
+                    ciphertext_modulus: key.ciphertext_modulus,
+                });
+                if let Ok(param) = param {
+                    best = Some((fixed_cost + cost, param));
+                }
+            }
+        }
+    }
+
+    best.map(|(_, param)| evaluate_wwlp_cbs_param(&param, lut_depth))
+}
+
+/// Variances of the LEV, a single automorphism, the GLEV and the GGSW of the patched WWL+ CBS, including the FFT errors.
+fn cbs_stage_variances<Scalar, P>(param: &P) -> (f64, f64, f64, f64)
+where
+    Scalar: UnsignedInteger,
+    P: CbsParameters<Scalar> + AutomorphismParameters + SchemeSwitchingParameters,
+{
+    let polynomial_size = param.polynomial_size().0;
+    let glwe_dimension = param.glwe_dimension().0;
+
+    let var_lev = var_lev(param) + param.lwe_dimension().0 as f64 * var_fft_error_external_product(
+        Scalar::BITS,
+        polynomial_size,
+        glwe_dimension,
+        param.pbs_base_log().0,
+        param.pbs_level().0,
+    );
+    let var_auto = var_automorphism(param) + var_fft_error(
+        param.fft_type_auto(),
+        Scalar::BITS,
+        polynomial_size,
+        glwe_dimension,
+        param.auto_base_log().0,
+        param.auto_level().0,
+    );
+    let var_ss = var_ss_external_product(param) + var_fft_error_external_product(
+        Scalar::BITS,
+        polynomial_size,
+        glwe_dimension,
+        param.ss_base_log().0,
+        param.ss_level().0,
+    );
+    let (var_glev, var_ggsw) = var_glev_ggsw(polynomial_size, glwe_dimension, var_lev, var_auto, var_ss);
+
+    (var_lev, var_auto, var_glev, var_ggsw)
+}
+
+fn var_cmux_tree_with_fft_error<Scalar, P>(param: &P, var_ggsw: f64, depth: usize) -> f64
+where
+    Scalar: UnsignedInteger,
+    P: CbsParameters<Scalar>,
+{
+    var_cmux_tree(param, var_ggsw, depth) + depth as f64 * var_fft_error_external_product(
+        Scalar::BITS,
+        param.polynomial_size().0,
+        param.glwe_dimension().0,
+        param.cbs_base_log().0,
+        param.cbs_level().0,
+    )
+}
+
+/// Cost, failure probability and variance per stage of the patched WWL+ CBS followed by a CMux tree of depth `lut_depth`.
+pub fn evaluate_wwlp_cbs_param<Scalar: UnsignedInteger>(
+    param: &WWLpCBSParam<Scalar>,
+    lut_depth: usize,
+) -> OptimizedParam<WWLpCBSParam<Scalar>> {
+    let q = modulus::<Scalar>();
+    let lwe_dimension = param.lwe_dimension().0;
+    let polynomial_size = param.polynomial_size().0;
+    let glwe_dimension = param.glwe_dimension().0;
+    let cbs_level = param.cbs_level().0;
+    let log_lut_count = param.log_lut_count().0;
+
+    let var_in = var_cbs_input(param, param.refresh_base_log(), param.refresh_level());
+    let (var_lev, var_auto, var_glev, var_ggsw) = cbs_stage_variances(param);
+    let var_out = var_cmux_tree_with_fft_error(param, var_ggsw, lut_depth) + param.glwe_modular_std_dev().get_variance() * q * q;
+
+    let cost = cost_pbs(lwe_dimension, polynomial_size, glwe_dimension, param.refresh_level().0)
+        + cost_lwe_ks(glwe_dimension * polynomial_size, lwe_dimension, param.ks_level().0)
+        + lut_count_pbs(cbs_level, log_lut_count) * cost_pbs(lwe_dimension, polynomial_size, glwe_dimension, param.pbs_level().0)
+        + cbs_level as f64 * (
+            cost_trace(polynomial_size, glwe_dimension, param.auto_level().0, param.fft_type_auto())
+            + glwe_dimension as f64 * cost_external_product(polynomial_size, glwe_dimension, param.ss_level().0)
+        )
+        + ((1 << lut_depth) - 1) as f64 * cost_external_product(polynomial_size, glwe_dimension, cbs_level);
+
+    OptimizedParam {
+        param: *param,
+        cost,
+        log2_fail_prob: log2_sum(log2_cbs_fail_prob(param, var_in), log2_bit_fail_prob(q, var_out)),
+        stages: vec![
+            StageNoise { stage: "cbs input", variance: var_in },
+            StageNoise { stage: "lev", variance: var_lev },
+            StageNoise { stage: "automorphism", variance: var_auto },
+            StageNoise { stage: "glev", variance: var_glev },
+            StageNoise { stage: "ggsw", variance: var_ggsw },
+            StageNoise { stage: "lut output", variance: var_out },
+        ],
+    }
+}
+
+/// Cheapest AES parameters over the common polynomial size `common_polynomial_size` whose failure probability
+/// of the whole AES evaluation with the given key size is at most 2^`log2_target`.
+pub fn optimize_aes_param<Scalar: UnsignedInteger>(
+    key: &KeyParam<Scalar>,
+    common_polynomial_size: PolynomialSize,
+    key_size: AesKeySize,
+    log2_target: f64,
+) -> Option<OptimizedParam<AesParam<Scalar>>> {
+    let scalar_bits = Scalar::BITS;
+    let q = modulus::<Scalar>();
+    let lwe_dimension = key.lwe_dimension.0;
+    let polynomial_size = key.polynomial_size.0;
+    let glwe_dimension = key.glwe_dimension.0;
+    let var_glwe = key.glwe_modular_std_dev.get_variance() * q * q;
+    let log2_round_target = log2_target - (aes_num_cbs(key_size) as f64).log2();
+
+    if common_polynomial_size.0 == 0
+        || !polynomial_size.is_multiple_of(common_polynomial_size.0)
+        || !lwe_dimension.is_multiple_of(common_polynomial_size.0)
+    {
+        return None;
+    }
+
+    let fronts = CbsFronts::new(key);
+    let ds_front = glwe_ks_front(
+        scalar_bits,
+        common_polynomial_size.0,
+        glwe_dimension * polynomial_size / common_polynomial_size.0,
+        lwe_dimension / common_polynomial_size.0,
+        key.lwe_modular_std_dev.get_variance(),
+        1.0,
+    );
+
+    let mut best: Option<(f64, AesParam<Scalar>)> = None;
+    for log_lut_count in 0..=MAX_LOG_LUT_COUNT {
+        let max_var_in = max_variance(
+            |var| get_log2_fail_prob(get_gamma(lwe_dimension, polynomial_size, q, log_lut_count, q / 2.0, var)),
+            scalar_bits,
+            log2_round_target,
+        );
+
+        for (cbs_base_log, cbs_level) in decompositions(scalar_bits) {
+            let mut checker = ParamChecker::new(scalar_bits);
+            checker.cbs_decomposition(cbs_base_log, cbs_level, log_lut_count);
+            if checker.finish().is_err() {
+                continue;
+            }
+
+            let cost_lut = AES_SBOX_CMUX_COUNT as f64 * cost_external_product(polynomial_size, glwe_dimension, cbs_level);
+            let num_pbs = lut_count_pbs(cbs_level, log_lut_count);
+            let (ext_zero, ext_slope) = external_product_affine(scalar_bits, polynomial_size, glwe_dimension, cbs_base_log, cbs_level);
+
+            for ds in ds_front.iter() {
+                let fixed_cost = cost_lut + ds.cost;
+                let max_cost = best.as_ref().map_or(f64::INFINITY, |b| b.0) - fixed_cost;
+                if max_cost <= 0.0 {
+                    break;
+                }
+
+                let max_var_sbox = (max_var_in - 2.0 * ds.var - var_glwe) / AES_LINEAR_LAYER_NUM_TERMS as f64;
+                let max_var_ggsw = (max_var_sbox / 8.0 - ext_zero) / ext_slope;
+                if max_var_ggsw <= 0.0 {
+                    continue;
+                }
+
+                if let Some((cost, pbs, auto, ss)) = fronts.cheapest_ggsw(
+                    polynomial_size,
+                    glwe_dimension,
+                    num_pbs,
+                    cbs_level,
+                    max_var_ggsw,
+                    max_cost,
+                ) {
+                    let param = AesParam::try_new(AesParamFields {
+                        lwe_dimension: key.lwe_dimension,
+                        lwe_modular_std_dev: key.lwe_modular_std_dev,
+                        polynomial_size: key.polynomial_size,
+                        glwe_dimension: key.glwe_dimension,
+                        glwe_modular_std_dev: key.glwe_modular_std_dev,
+                        pbs_base_log: DecompositionBaseLog(pbs.base_log),
+                        pbs_level: DecompositionLevelCount(pbs.level),
+                        glwe_ds_base_log: DecompositionBaseLog(ds.base_log),
+                        glwe_ds_level: DecompositionLevelCount(ds.level),
+                        common_polynomial_size,
+                        fft_type_ds: ds.fft_type,
+                        auto_base_log: DecompositionBaseLog(auto.base_log),
+                        auto_level: DecompositionLevelCount(auto.level),
+                        fft_type_auto: auto.fft_type,
+                        ss_base_log: DecompositionBaseLog(ss.base_log),
+                        ss_level: DecompositionLevelCount(ss.level),
+                        cbs_base_log: DecompositionBaseLog(cbs_base_log),
+                        cbs_level: DecompositionLevelCount(cbs_level),
+                        log_lut_count: LutCountLog(log_lut_count),
+                        ciphertext_modulus: key.ciphertext_modulus,
+                    });
+                    if let Ok(param) = param {
+                        best = Some((fixed_cost + cost, param));
+                    }
+                }
+            }
+        }
+    }
+
+    best.map(|(_, param)| evaluate_aes_param(&param, key_size))
+}
+
+/// Cost of a state bit per round, failure probability of the whole AES evaluation and variance per stage.
+pub fn evaluate_aes_param<Scalar: UnsignedInteger>(param: &AesParam<Scalar>, key_size: AesKeySize) -> OptimizedParam<AesParam<Scalar>> {
+    let q = modulus::<Scalar>();
+    let lwe_dimension = param.lwe_dimension().0;
+    let polynomial_size = param.polynomial_size().0;
+    let glwe_dimension = param.glwe_dimension().0;
+    let common_polynomial_size = param.common_polynomial_size().0;
+    let k_src = glwe_dimension * polynomial_size / common_polynomial_size;
+    let cbs_level = param.cbs_level().0;
+
+    let (var_lev, var_auto, var_glev, var_ggsw) = cbs_stage_variances(param);
+    let var_sbox = var_cmux_tree_with_fft_error(param, var_ggsw, 8);
+    let var_ds = var_aes_glwe_ds(param)
+        + var_fft_error(param.fft_type_ds(), Scalar::BITS, common_polynomial_size, k_src, param.glwe_ds_base_log().0, param.glwe_ds_level().0);
+    let var_in = 2.0 * var_ds
+        + AES_LINEAR_LAYER_NUM_TERMS as f64 * var_sbox
+        + param.glwe_modular_std_dev().get_variance() * q * q;
+
+    let cost = lut_count_pbs(cbs_level, param.log_lut_count().0) * cost_pbs(lwe_dimension, polynomial_size, glwe_dimension, param.pbs_level().0)
+        + cbs_level as f64 * (
+            cost_trace(polynomial_size, glwe_dimension, param.auto_level().0, param.fft_type_auto())
+            + glwe_dimension as f64 * cost_external_product(polynomial_size, glwe_dimension, param.ss_level().0)
+        )
+        + cost_glwe_ks(common_polynomial_size, k_src, lwe_dimension / common_polynomial_size, param.glwe_ds_level().0, param.fft_type_ds())
+        + AES_SBOX_CMUX_COUNT as f64 * cost_external_product(polynomial_size, glwe_dimension, cbs_level);
+
+    OptimizedParam {
+        param: *param,
+        cost,
+        log2_fail_prob: log2_cbs_fail_prob(param, var_in) + (aes_num_cbs(key_size) as f64).log2(),
+        stages: vec![
+            StageNoise { stage: "lev", variance: var_lev },
+            StageNoise { stage: "automorphism", variance: var_auto },
+            StageNoise { stage: "glev", variance: var_glev },
+            StageNoise { stage: "ggsw", variance: var_ggsw },
+            StageNoise { stage: "sbox output", variance: var_sbox },
+            StageNoise { stage: "glwe ds", variance: var_ds },
+            StageNoise { stage: "round input", variance: var_in },
+        ],
+    }
+}
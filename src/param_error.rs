@@ -0,0 +1,189 @@
+use std::fmt;
+use crate::{security::SecurityReport, FftType};
+
+/// Mantissa precision of f64 on which the FFT-based products rely.
+pub(crate) const FFT_PRECISION_BITS: usize = 53;
+/// Mantissa precision of the double-f64 values of the f128 FFT, with a margin of two bits.
+pub(crate) const FFT128_PRECISION_BITS: usize = 104;
+
+pub(crate) fn fft_precision_bits(fft_type: FftType) -> usize {
+    match fft_type {
+        FftType::F128 => FFT128_PRECISION_BITS,
+        _ => FFT_PRECISION_BITS,
+    }
+}
+
+/// A single constraint violated by a parameter set.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParamViolation {
+    /// The value must be a power of two.
+    NotPowerOfTwo { name: &'static str, value: usize },
+    /// The value must be a multiple of the divisor.
+    NotDivisible { name: &'static str, value: usize, divisor: usize },
+    /// The gadget decomposition must be nonempty and fit in the ciphertext modulus.
+    InvalidDecomposition { name: &'static str, base_log: usize, level: usize, max_bits: usize },
+    /// The CBS levels must fill the PBSmanyLUT accumulators when more than one is required.
+    LutCountMismatch { cbs_level: usize, log_lut_count: usize },
+    /// The scaled part of the split key times the decomposed input exceeds the FFT precision.
+    FftSplitOverflow { name: &'static str, fft_type: FftType, base_log: usize, polynomial_size: usize },
+    /// The keyswitching must be exact, which an unsplit key on the f64 FFT backend is not.
+    InexactFft { name: &'static str },
+    /// The estimated security of a secret key is below the configured threshold.
+    InsufficientSecurity { name: &'static str, bits: usize, min_bits: usize },
+}
+
+impl fmt::Display for ParamViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            ParamViolation::NotPowerOfTwo { name, value } => {
+                write!(f, "{name} = {value} is not a power of two")
+            }
+            ParamViolation::NotDivisible { name, value, divisor } => {
+                write!(f, "{name} = {value} is not divisible by {divisor}")
+            }
+            ParamViolation::InvalidDecomposition { name, base_log, level, max_bits } => {
+                write!(f, "{name}: base_log {base_log} and level {level} must be nonzero with base_log * level <= {max_bits}")
+            }
+            ParamViolation::LutCountMismatch { cbs_level, log_lut_count } => {
+                write!(f, "cbs_level {cbs_level} exceeds and is not divisible by the lut count 2^{log_lut_count}")
+            }
+            ParamViolation::FftSplitOverflow { name, fft_type, base_log, polynomial_size } => {
+                let precision_bits = fft_precision_bits(fft_type);
+                write!(f, "{name}: {fft_type:?} with base_log {base_log} and polynomial size {polynomial_size} overflows {precision_bits} bits")
+            }
+            ParamViolation::InexactFft { name } => {
+                write!(f, "{name}: Vanilla on the f64 FFT backend is not exact, use a split or F128")
+            }
+            ParamViolation::InsufficientSecurity { name, bits, min_bits } => {
+                write!(f, "{name}: estimated security {bits} bits is below {min_bits} bits")
+            }
+        }
+    }
+}
+
+/// All constraints violated by a parameter set.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParamError {
+    violations: Vec<ParamViolation>,
+}
+
+impl ParamError {
+    pub fn violations(&self) -> &[ParamViolation] {
+        &self.violations
+    }
+}
+
+impl fmt::Display for ParamError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} parameter constraint(s) violated", self.violations.len())?;
+        for violation in self.violations.iter() {
+            write!(f, "\n- {violation}")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ParamError {}
+
+
+/// Collects the violated constraints of a parameter set.
+pub(crate) struct ParamChecker {
+    scalar_bits: usize,
+    violations: Vec<ParamViolation>,
+}
+
+impl ParamChecker {
+    pub(crate) fn new(scalar_bits: usize) -> Self {
+        ParamChecker {
+            scalar_bits,
+            violations: vec![],
+        }
+    }
+
+    pub(crate) fn power_of_two(&mut self, name: &'static str, value: usize) {
+        if !value.is_power_of_two() {
+            self.violations.push(ParamViolation::NotPowerOfTwo { name, value });
+        }
+    }
+
+    pub(crate) fn divisible(&mut self, name: &'static str, value: usize, divisor: usize) {
+        if divisor == 0 || !value.is_multiple_of(divisor) {
+            self.violations.push(ParamViolation::NotDivisible { name, value, divisor });
+        }
+    }
+
+    pub(crate) fn decomposition(&mut self, name: &'static str, base_log: usize, level: usize) {
+        self.decomposition_with_max_bits(name, base_log, level, self.scalar_bits);
+    }
+
+    /// The CBS output levels are built by scaling b * 2^(BITS - 1), so one bit is kept free.
+    pub(crate) fn cbs_decomposition(&mut self, base_log: usize, level: usize, log_lut_count: usize) {
+        self.decomposition_with_max_bits("cbs", base_log, level, self.scalar_bits - 1);
+
+        let lut_count = 1 << log_lut_count;
+        if level > lut_count && !level.is_multiple_of(lut_count) {
+            self.violations.push(ParamViolation::LutCountMismatch { cbs_level: level, log_lut_count });
+        }
+    }
+
+    /// For split FFT types, every part except the lowest one is scaled back by a power of two
+    /// after the FFT, so its product with the decomposed input must be exact in f64.
+    /// An unsplit key on the f128 backend must be exact in f128 as a whole.
+    pub(crate) fn fft_split(&mut self, name: &'static str, fft_type: FftType, base_log: usize, polynomial_size: usize) {
+        let scaled_part_bits = match fft_type {
+            FftType::Vanilla => return,
+            FftType::Split(b) => {
+                if b == 0 || b >= self.scalar_bits {
+                    self.violations.push(ParamViolation::FftSplitOverflow { name, fft_type, base_log, polynomial_size });
+                    return;
+                }
+                self.scalar_bits - b
+            }
+            FftType::Split16 => 16,
+            FftType::F128 => self.scalar_bits,
+        };
+
+        let log_polynomial_size = polynomial_size.next_power_of_two().ilog2() as usize;
+        if scaled_part_bits + base_log + log_polynomial_size > fft_precision_bits(fft_type) {
+            self.violations.push(ParamViolation::FftSplitOverflow { name, fft_type, base_log, polynomial_size });
+        }
+    }
+
+    /// Same as `fft_split`, but also rejects `FftType::Vanilla`, for the keyswitchings whose FFT error
+    /// is not in the noise model.
+    pub(crate) fn exact_fft(&mut self, name: &'static str, fft_type: FftType, base_log: usize, polynomial_size: usize) {
+        if fft_type == FftType::Vanilla {
+            self.violations.push(ParamViolation::InexactFft { name });
+        } else {
+            self.fft_split(name, fft_type, base_log, polynomial_size);
+        }
+    }
+
+    /// Skips the estimation when the threshold is zero, i.e. insecure parameters are explicitly allowed.
+    pub(crate) fn security(&mut self, report: impl FnOnce() -> SecurityReport, min_bits: usize) {
+        if min_bits == 0 {
+            return;
+        }
+
+        for (name, security) in report().parts() {
+            let bits = security.bits().floor() as usize;
+            if bits < min_bits {
+                self.violations.push(ParamViolation::InsufficientSecurity { name, bits, min_bits });
+            }
+        }
+    }
+
+    pub(crate) fn finish(self) -> Result<(), ParamError> {
+        if self.violations.is_empty() {
+            Ok(())
+        } else {
+            Err(ParamError { violations: self.violations })
+        }
+    }
+
+    fn decomposition_with_max_bits(&mut self, name: &'static str, base_log: usize, level: usize, max_bits: usize) {
+        if base_log == 0 || level == 0 || base_log * level > max_bits {
+            self.violations.push(ParamViolation::InvalidDecomposition { name, base_log, level, max_bits });
+        }
+    }
+}
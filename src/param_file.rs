@@ -0,0 +1,644 @@
+//! Text format of the parameter sets, so that a new set can be tried without recompiling.
+//!
+//! A parameter file consists of sections. Each section starts with a header naming the struct kind and,
+//! optionally, the parameter set, followed by `key = value` lines with the arguments of `try_new`.
+//! Everything after `#` is a comment. The loaded sets are validated with `DEFAULT_MIN_SECURITY_BITS`,
+//! or with the threshold given to the `_with_min_security_bits` loaders, e.g. zero for toy parameters.
+//!
+//! ```text
+//! [AutoConvParam AUTO_PARAM_2_2_LEV_3]
+//! polynomial_size = 2048
+//! glwe_dimension = 1
+//! glwe_modular_std_dev = 0.00000000000000029403601535432533
+//! auto_base_log = 13
+//! auto_level = 3
+//! fft_type = split(42) # vanilla, split(b) or split16
+//! ciphertext_modulus = native # or the custom modulus
+//! ```
+use std::{fmt, fs, io, path::Path};
+use tfhe::core_crypto::prelude::*;
+use crate::{aes_params::*, auto_conv_params::*, param_error::ParamError, security::DEFAULT_MIN_SECURITY_BITS, wwlp_cbs_params::*, FftType};
+
+/// Error while parsing a parameter file.
+#[derive(Debug)]
+pub enum ParamFileError {
+    Io(io::Error),
+    /// The line is neither a section header nor a `key = value` entry.
+    Syntax { line: usize, message: &'static str },
+    /// No section of the expected kind and name.
+    SectionNotFound { kind: &'static str, name: Option<String> },
+    /// More than one section of the expected kind matches.
+    AmbiguousSection { kind: &'static str, count: usize },
+    UnexpectedKind { expected: &'static str, found: String },
+    MissingKey { kind: &'static str, key: &'static str },
+    UnknownKey { line: usize, key: String },
+    DuplicateKey { line: usize, key: String },
+    InvalidValue { line: usize, key: String, value: String },
+    /// The parsed parameters violate the parameter constraints.
+    Param(ParamError),
+}
+
+impl fmt::Display for ParamFileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParamFileError::Io(err) => write!(f, "{err}"),
+            ParamFileError::Syntax { line, message } => write!(f, "line {line}: {message}"),
+            ParamFileError::SectionNotFound { kind, name: Some(name) } => write!(f, "no section [{kind} {name}]"),
+            ParamFileError::SectionNotFound { kind, name: None } => write!(f, "no section [{kind}]"),
+            ParamFileError::AmbiguousSection { kind, count } => {
+                write!(f, "{count} sections of kind {kind}, the name of the parameter set is required")
+            }
+            ParamFileError::UnexpectedKind { expected, found } => write!(f, "expected a {expected} section, found {found}"),
+            ParamFileError::MissingKey { kind, key } => write!(f, "{kind}: missing key {key}"),
+            ParamFileError::UnknownKey { line, key } => write!(f, "line {line}: unknown key {key}"),
+            ParamFileError::DuplicateKey { line, key } => write!(f, "line {line}: duplicate key {key}"),
+            ParamFileError::InvalidValue { line, key, value } => write!(f, "line {line}: invalid value {value} for {key}"),
+            ParamFileError::Param(err) => write!(f, "{err}"),
+        }
+    }
+}
+
+impl std::error::Error for ParamFileError {}
+
+impl From<io::Error> for ParamFileError {
+    fn from(err: io::Error) -> Self {
+        ParamFileError::Io(err)
+    }
+}
+
+impl From<ParamError> for ParamFileError {
+    fn from(err: ParamError) -> Self {
+        ParamFileError::Param(err)
+    }
+}
+
+
+#[derive(Clone, Debug)]
+struct ParamEntry {
+    key: String,
+    value: String,
+    line: usize,
+}
+
+/// A section of a parameter file, before the conversion into a parameter struct.
+#[derive(Clone, Debug)]
+pub struct ParamSection {
+    kind: String,
+    name: Option<String>,
+    entries: Vec<ParamEntry>,
+}
+
+impl ParamSection {
+    pub fn kind(&self) -> &str {
+        &self.kind
+    }
+
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+}
+
+/// Splits a parameter file into its sections.
+pub fn parse_param_file(text: &str) -> Result<Vec<ParamSection>, ParamFileError> {
+    let mut sections: Vec<ParamSection> = vec![];
+    for (i, line) in text.lines().enumerate() {
+        let line_number = i + 1;
+        let line = line.split('#').next().unwrap_or_default().trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        if let Some(header) = line.strip_prefix('[') {
+            let header = header.strip_suffix(']').ok_or(ParamFileError::Syntax {
+                line: line_number,
+                message: "unterminated section header",
+            })?;
+            let mut words = header.split_whitespace();
+            let (Some(kind), name, None) = (words.next(), words.next(), words.next()) else {
+                return Err(ParamFileError::Syntax {
+                    line: line_number,
+                    message: "a section header is [kind] or [kind name]",
+                });
+            };
+            sections.push(ParamSection {
+                kind: kind.to_string(),
+                name: name.map(str::to_string),
+                entries: vec![],
+            });
+            continue;
+        }
+
+        let Some((key, value)) = line.split_once('=') else {
+            return Err(ParamFileError::Syntax { line: line_number, message: "expected key = value" });
+        };
+        let Some(section) = sections.last_mut() else {
+            return Err(ParamFileError::Syntax { line: line_number, message: "entry outside of a section" });
+        };
+        let key = key.trim();
+        if section.entries.iter().any(|entry| entry.key == key) {
+            return Err(ParamFileError::DuplicateKey { line: line_number, key: key.to_string() });
+        }
+        section.entries.push(ParamEntry {
+            key: key.to_string(),
+            value: value.trim().to_string(),
+            line: line_number,
+        });
+    }
+
+    Ok(sections)
+}
+
+
+/// A value of a parameter file entry.
+pub trait ParamValue: Sized {
+    fn to_param_value(&self) -> String;
+    fn from_param_value(value: &str) -> Option<Self>;
+}
+
+macro_rules! impl_param_value_for_usize_wrapper {
+    ($($ty:ident),*) => {
+        $(
+            impl ParamValue for $ty {
+                fn to_param_value(&self) -> String {
+                    self.0.to_string()
+                }
+
+                fn from_param_value(value: &str) -> Option<Self> {
+                    value.parse().ok().map($ty)
+                }
+            }
+        )*
+    };
+}
+
+impl_param_value_for_usize_wrapper!(
+    LweDimension,
+    GlweDimension,
+    PolynomialSize,
+    DecompositionBaseLog,
+    DecompositionLevelCount,
+    LutCountLog
+);
+
+/// Written in the shortest form that parses back to the same f64.
+impl ParamValue for StandardDev {
+    fn to_param_value(&self) -> String {
+        self.0.to_string()
+    }
+
+    fn from_param_value(value: &str) -> Option<Self> {
+        value.parse().ok().filter(|std_dev: &f64| std_dev.is_finite() && *std_dev > 0.0).map(StandardDev)
+    }
+}
+
+impl ParamValue for FftType {
+    fn to_param_value(&self) -> String {
+        match self {
+            FftType::Vanilla => "vanilla".to_string(),
+            FftType::Split(b) => format!("split({b})"),
+            FftType::Split16 => "split16".to_string(),
+            FftType::F128 => "f128".to_string(),
+        }
+    }
+
+    fn from_param_value(value: &str) -> Option<Self> {
+        match value {
+            "vanilla" => Some(FftType::Vanilla),
+            "split16" => Some(FftType::Split16),
+            "f128" => Some(FftType::F128),
+            _ => value.strip_prefix("split(")?.strip_suffix(')')?.trim().parse().ok().map(FftType::Split),
+        }
+    }
+}
+
+impl<Scalar: UnsignedInteger> ParamValue for CiphertextModulus<Scalar> {
+    fn to_param_value(&self) -> String {
+        if self.is_native_modulus() {
+            "native".to_string()
+        } else {
+            self.get_custom_modulus().to_string()
+        }
+    }
+
+    fn from_param_value(value: &str) -> Option<Self> {
+        if value == "native" {
+            Some(CiphertextModulus::new_native())
+        } else {
+            value.parse().ok().filter(|&modulus: &u128| modulus != 0)
+                .and_then(|modulus| CiphertextModulus::try_new(modulus).ok())
+        }
+    }
+}
+
+
+/// Emits the entries of a section.
+pub struct ParamWriter {
+    text: String,
+}
+
+impl ParamWriter {
+    pub fn field<V: ParamValue>(&mut self, key: &str, value: V) {
+        self.text.push_str(&format!("{key} = {}\n", value.to_param_value()));
+    }
+}
+
+/// Reads the entries of a section, keeping track of the unused ones.
+pub struct ParamReader<'a> {
+    kind: &'static str,
+    section: &'a ParamSection,
+    used: Vec<bool>,
+}
+
+impl ParamReader<'_> {
+    pub fn field<V: ParamValue>(&mut self, key: &'static str) -> Result<V, ParamFileError> {
+        let (i, entry) = self.section.entries.iter().enumerate()
+            .find(|(_, entry)| entry.key == key)
+            .ok_or(ParamFileError::MissingKey { kind: self.kind, key })?;
+        self.used[i] = true;
+
+        V::from_param_value(&entry.value).ok_or_else(|| ParamFileError::InvalidValue {
+            line: entry.line,
+            key: entry.key.clone(),
+            value: entry.value.clone(),
+        })
+    }
+
+    fn finish(self) -> Result<(), ParamFileError> {
+        match self.section.entries.iter().zip(self.used.iter()).find(|(_, &used)| !used) {
+            Some((entry, _)) => Err(ParamFileError::UnknownKey { line: entry.line, key: entry.key.clone() }),
+            None => Ok(()),
+        }
+    }
+}
+
+
+/// Conversion between a parameter struct and a section of a parameter file.
+pub trait ParamFileFormat: Sized {
+    /// Struct kind in the section header.
+    const KIND: &'static str;
+
+    fn write_fields(&self, writer: &mut ParamWriter);
+
+    /// Constructs the parameters with `new_unchecked`, so that they can be validated with any security threshold.
+    fn read_fields(reader: &mut ParamReader) -> Result<Self, ParamFileError>;
+
+    fn validate_with_min_security_bits(&self, min_security_bits: usize) -> Result<(), ParamError>;
+
+    /// Emits a section, with the name of the parameter set in the header if given.
+    fn to_param_string(&self, name: Option<&str>) -> String {
+        let header = match name {
+            Some(name) => format!("[{} {name}]\n", Self::KIND),
+            None => format!("[{}]\n", Self::KIND),
+        };
+        let mut writer = ParamWriter { text: header };
+        self.write_fields(&mut writer);
+        writer.text
+    }
+
+    /// Validates the parameters with the default security threshold of `DEFAULT_MIN_SECURITY_BITS`.
+    fn from_param_section(section: &ParamSection) -> Result<Self, ParamFileError> {
+        Self::from_param_section_with_min_security_bits(section, DEFAULT_MIN_SECURITY_BITS)
+    }
+
+    /// A threshold of zero explicitly allows insecure parameters, e.g. toy parameters for experiments.
+    fn from_param_section_with_min_security_bits(section: &ParamSection, min_security_bits: usize) -> Result<Self, ParamFileError> {
+        if section.kind != Self::KIND {
+            return Err(ParamFileError::UnexpectedKind { expected: Self::KIND, found: section.kind.clone() });
+        }
+
+        let mut reader = ParamReader {
+            kind: Self::KIND,
+            section,
+            used: vec![false; section.entries.len()],
+        };
+        let param = Self::read_fields(&mut reader)?;
+        reader.finish()?;
+        param.validate_with_min_security_bits(min_security_bits)?;
+
+        Ok(param)
+    }
+
+    /// Parses the section of this kind with the given name, or the only section of this kind if no name is given.
+    fn from_param_str(text: &str, name: Option<&str>) -> Result<Self, ParamFileError> {
+        Self::from_param_str_with_min_security_bits(text, name, DEFAULT_MIN_SECURITY_BITS)
+    }
+
+    fn from_param_str_with_min_security_bits(text: &str, name: Option<&str>, min_security_bits: usize) -> Result<Self, ParamFileError> {
+        let sections = parse_param_file(text)?;
+        let mut candidates = sections.iter()
+            .filter(|section| section.kind == Self::KIND && (name.is_none() || section.name() == name));
+
+        match (candidates.next(), candidates.count()) {
+            (Some(section), 0) => Self::from_param_section_with_min_security_bits(section, min_security_bits),
+            (Some(_), count) => Err(ParamFileError::AmbiguousSection { kind: Self::KIND, count: count + 1 }),
+            (None, _) => Err(ParamFileError::SectionNotFound { kind: Self::KIND, name: name.map(str::to_string) }),
+        }
+    }
+
+    fn load(path: impl AsRef<Path>, name: Option<&str>) -> Result<Self, ParamFileError> {
+        Self::load_with_min_security_bits(path, name, DEFAULT_MIN_SECURITY_BITS)
+    }
+
+    fn load_with_min_security_bits(path: impl AsRef<Path>, name: Option<&str>, min_security_bits: usize) -> Result<Self, ParamFileError> {
+        Self::from_param_str_with_min_security_bits(&fs::read_to_string(path)?, name, min_security_bits)
+    }
+
+    fn save(&self, path: impl AsRef<Path>, name: Option<&str>) -> Result<(), ParamFileError> {
+        Ok(fs::write(path, self.to_param_string(name))?)
+    }
+}
+
+
+impl<Scalar: UnsignedInteger> ParamFileFormat for CBSParam<Scalar> {
+    const KIND: &'static str = "CBSParam";
+
+    fn write_fields(&self, writer: &mut ParamWriter) {
+        writer.field("lwe_dimension", self.lwe_dimension());
+        writer.field("lwe_modular_std_dev", self.lwe_modular_std_dev());
+        writer.field("polynomial_size", self.polynomial_size());
+        writer.field("glwe_dimension", self.glwe_dimension());
+        writer.field("glwe_modular_std_dev", self.glwe_modular_std_dev());
+        writer.field("pbs_base_log", self.pbs_base_log());
+        writer.field("pbs_level", self.pbs_level());
+        writer.field("ks_base_log", self.ks_base_log());
+        writer.field("ks_level", self.ks_level());
+        writer.field("pfks_base_log", self.pfks_base_log());
+        writer.field("pfks_level", self.pfks_level());
+        writer.field("cbs_base_log", self.cbs_base_log());
+        writer.field("cbs_level", self.cbs_level());
+        writer.field("ciphertext_modulus", self.ciphertext_modulus());
+    }
+
+    fn read_fields(reader: &mut ParamReader) -> Result<Self, ParamFileError> {
+        Ok(CBSParam::new_unchecked(CBSParamFields {
+            lwe_dimension: reader.field("lwe_dimension")?,
+            lwe_modular_std_dev: reader.field("lwe_modular_std_dev")?,
+            polynomial_size: reader.field("polynomial_size")?,
+            glwe_dimension: reader.field("glwe_dimension")?,
+            glwe_modular_std_dev: reader.field("glwe_modular_std_dev")?,
+            pbs_base_log: reader.field("pbs_base_log")?,
+            pbs_level: reader.field("pbs_level")?,
+            ks_base_log: reader.field("ks_base_log")?,
+            ks_level: reader.field("ks_level")?,
+            pfks_base_log: reader.field("pfks_base_log")?,
+            pfks_level: reader.field("pfks_level")?,
+            cbs_base_log: reader.field("cbs_base_log")?,
+            cbs_level: reader.field("cbs_level")?,
+            ciphertext_modulus: reader.field("ciphertext_modulus")?,
+        }))
+    }
+
+    fn validate_with_min_security_bits(&self, min_security_bits: usize) -> Result<(), ParamError> {
+        CBSParam::validate_with_min_security_bits(self, min_security_bits)
+    }
+}
+
+impl<Scalar: UnsignedInteger> ParamFileFormat for WWLpCBSParam<Scalar> {
+    const KIND: &'static str = "WWLpCBSParam";
+
+    fn write_fields(&self, writer: &mut ParamWriter) {
+        writer.field("lwe_dimension", self.lwe_dimension());
+        writer.field("lwe_modular_std_dev", self.lwe_modular_std_dev());
+        writer.field("polynomial_size", self.polynomial_size());
+        writer.field("glwe_dimension", self.glwe_dimension());
+        writer.field("glwe_modular_std_dev", self.glwe_modular_std_dev());
+        writer.field("pbs_base_log", self.pbs_base_log());
+        writer.field("pbs_level", self.pbs_level());
+        writer.field("refresh_base_log", self.refresh_base_log());
+        writer.field("refresh_level", self.refresh_level());
+        writer.field("ks_base_log", self.ks_base_log());
+        writer.field("ks_level", self.ks_level());
+        writer.field("auto_base_log", self.auto_base_log());
+        writer.field("auto_level", self.auto_level());
+        writer.field("fft_type_auto", self.fft_type_auto());
+        writer.field("ss_base_log", self.ss_base_log());
+        writer.field("ss_level", self.ss_level());
+        writer.field("cbs_base_log", self.cbs_base_log());
+        writer.field("cbs_level", self.cbs_level());
+        writer.field("log_lut_count", self.log_lut_count());
+        writer.field("ciphertext_modulus", self.ciphertext_modulus());
+    }
+
+    fn read_fields(reader: &mut ParamReader) -> Result<Self, ParamFileError> {
+        Ok(WWLpCBSParam::new_unchecked(WWLpCBSParamFields {
+            lwe_dimension: reader.field("lwe_dimension")?,
+            lwe_modular_std_dev: reader.field("lwe_modular_std_dev")?,
+            polynomial_size: reader.field("polynomial_size")?,
+            glwe_dimension: reader.field("glwe_dimension")?,
+            glwe_modular_std_dev: reader.field("glwe_modular_std_dev")?,
+            pbs_base_log: reader.field("pbs_base_log")?,
+            pbs_level: reader.field("pbs_level")?,
+            refresh_base_log: reader.field("refresh_base_log")?,
+            refresh_level: reader.field("refresh_level")?,
+            ks_base_log: reader.field("ks_base_log")?,
+            ks_level: reader.field("ks_level")?,
+            auto_base_log: reader.field("auto_base_log")?,
+            auto_level: reader.field("auto_level")?,
+            fft_type_auto: reader.field("fft_type_auto")?,
+            ss_base_log: reader.field("ss_base_log")?,
+            ss_level: reader.field("ss_level")?,
+            cbs_base_log: reader.field("cbs_base_log")?,
+            cbs_level: reader.field("cbs_level")?,
+            log_lut_count: reader.field("log_lut_count")?,
+            ciphertext_modulus: reader.field("ciphertext_modulus")?,
+        }))
+    }
+
+    fn validate_with_min_security_bits(&self, min_security_bits: usize) -> Result<(), ParamError> {
+        WWLpCBSParam::validate_with_min_security_bits(self, min_security_bits)
+    }
+}
+
+impl<Scalar: UnsignedInteger> ParamFileFormat for HighPrecWWLpCBSParam<Scalar> {
+    const KIND: &'static str = "HighPrecWWLpCBSParam";
+
+    fn write_fields(&self, writer: &mut ParamWriter) {
+        writer.field("lwe_dimension", self.lwe_dimension());
+        writer.field("lwe_modular_std_dev", self.lwe_modular_std_dev());
+        writer.field("polynomial_size", self.polynomial_size());
+        writer.field("glwe_dimension", self.glwe_dimension());
+        writer.field("large_glwe_dimension", self.large_glwe_dimension());
+        writer.field("glwe_modular_std_dev", self.glwe_modular_std_dev());
+        writer.field("large_glwe_modular_std_dev", self.large_glwe_modular_std_dev());
+        writer.field("pbs_base_log", self.pbs_base_log());
+        writer.field("pbs_level", self.pbs_level());
+        writer.field("refresh_base_log", self.refresh_base_log());
+        writer.field("refresh_level", self.refresh_level());
+        writer.field("ks_base_log", self.ks_base_log());
+        writer.field("ks_level", self.ks_level());
+        writer.field("glwe_ds_to_large_base_log", self.glwe_ds_to_large_base_log());
+        writer.field("glwe_ds_to_large_level", self.glwe_ds_to_large_level());
+        writer.field("fft_type_to_large", self.fft_type_to_large());
+        writer.field("auto_base_log", self.auto_base_log());
+        writer.field("auto_level", self.auto_level());
+        writer.field("fft_type_auto", self.fft_type_auto());
+        writer.field("glwe_ds_from_large_base_log", self.glwe_ds_from_large_base_log());
+        writer.field("glwe_ds_from_large_level", self.glwe_ds_from_large_level());
+        writer.field("fft_type_from_large", self.fft_type_from_large());
+        writer.field("ss_base_log", self.ss_base_log());
+        writer.field("ss_level", self.ss_level());
+        writer.field("cbs_base_log", self.cbs_base_log());
+        writer.field("cbs_level", self.cbs_level());
+        writer.field("log_lut_count", self.log_lut_count());
+        writer.field("ciphertext_modulus", self.ciphertext_modulus());
+    }
+
+    fn read_fields(reader: &mut ParamReader) -> Result<Self, ParamFileError> {
+        Ok(HighPrecWWLpCBSParam::new_unchecked(HighPrecWWLpCBSParamFields {
+            lwe_dimension: reader.field("lwe_dimension")?,
+            lwe_modular_std_dev: reader.field("lwe_modular_std_dev")?,
+            polynomial_size: reader.field("polynomial_size")?,
+            glwe_dimension: reader.field("glwe_dimension")?,
+            large_glwe_dimension: reader.field("large_glwe_dimension")?,
+            glwe_modular_std_dev: reader.field("glwe_modular_std_dev")?,
+            large_glwe_modular_std_dev: reader.field("large_glwe_modular_std_dev")?,
+            pbs_base_log: reader.field("pbs_base_log")?,
+            pbs_level: reader.field("pbs_level")?,
+            refresh_base_log: reader.field("refresh_base_log")?,
+            refresh_level: reader.field("refresh_level")?,
+            ks_base_log: reader.field("ks_base_log")?,
+            ks_level: reader.field("ks_level")?,
+            glwe_ds_to_large_base_log: reader.field("glwe_ds_to_large_base_log")?,
+            glwe_ds_to_large_level: reader.field("glwe_ds_to_large_level")?,
+            fft_type_to_large: reader.field("fft_type_to_large")?,
+            auto_base_log: reader.field("auto_base_log")?,
+            auto_level: reader.field("auto_level")?,
+            fft_type_auto: reader.field("fft_type_auto")?,
+            glwe_ds_from_large_base_log: reader.field("glwe_ds_from_large_base_log")?,
+            glwe_ds_from_large_level: reader.field("glwe_ds_from_large_level")?,
+            fft_type_from_large: reader.field("fft_type_from_large")?,
+            ss_base_log: reader.field("ss_base_log")?,
+            ss_level: reader.field("ss_level")?,
+            cbs_base_log: reader.field("cbs_base_log")?,
+            cbs_level: reader.field("cbs_level")?,
+            log_lut_count: reader.field("log_lut_count")?,
+            ciphertext_modulus: reader.field("ciphertext_modulus")?,
+        }))
+    }
+
+    fn validate_with_min_security_bits(&self, min_security_bits: usize) -> Result<(), ParamError> {
+        HighPrecWWLpCBSParam::validate_with_min_security_bits(self, min_security_bits)
+    }
+}
+
+impl<Scalar: UnsignedInteger> ParamFileFormat for AesParam<Scalar> {
+    const KIND: &'static str = "AesParam";
+
+    fn write_fields(&self, writer: &mut ParamWriter) {
+        writer.field("lwe_dimension", self.lwe_dimension());
+        writer.field("lwe_modular_std_dev", self.lwe_modular_std_dev());
+        writer.field("polynomial_size", self.polynomial_size());
+        writer.field("glwe_dimension", self.glwe_dimension());
+        writer.field("glwe_modular_std_dev", self.glwe_modular_std_dev());
+        writer.field("pbs_base_log", self.pbs_base_log());
+        writer.field("pbs_level", self.pbs_level());
+        writer.field("glwe_ds_base_log", self.glwe_ds_base_log());
+        writer.field("glwe_ds_level", self.glwe_ds_level());
+        writer.field("common_polynomial_size", self.common_polynomial_size());
+        writer.field("fft_type_ds", self.fft_type_ds());
+        writer.field("auto_base_log", self.auto_base_log());
+        writer.field("auto_level", self.auto_level());
+        writer.field("fft_type_auto", self.fft_type_auto());
+        writer.field("ss_base_log", self.ss_base_log());
+        writer.field("ss_level", self.ss_level());
+        writer.field("cbs_base_log", self.cbs_base_log());
+        writer.field("cbs_level", self.cbs_level());
+        writer.field("log_lut_count", self.log_lut_count());
+        writer.field("ciphertext_modulus", self.ciphertext_modulus());
+    }
+
+    fn read_fields(reader: &mut ParamReader) -> Result<Self, ParamFileError> {
+        Ok(AesParam::new_unchecked(AesParamFields {
+            lwe_dimension: reader.field("lwe_dimension")?,
+            lwe_modular_std_dev: reader.field("lwe_modular_std_dev")?,
+            polynomial_size: reader.field("polynomial_size")?,
+            glwe_dimension: reader.field("glwe_dimension")?,
+            glwe_modular_std_dev: reader.field("glwe_modular_std_dev")?,
+            pbs_base_log: reader.field("pbs_base_log")?,
+            pbs_level: reader.field("pbs_level")?,
+            glwe_ds_base_log: reader.field("glwe_ds_base_log")?,
+            glwe_ds_level: reader.field("glwe_ds_level")?,
+            common_polynomial_size: reader.field("common_polynomial_size")?,
+            fft_type_ds: reader.field("fft_type_ds")?,
+            auto_base_log: reader.field("auto_base_log")?,
+            auto_level: reader.field("auto_level")?,
+            fft_type_auto: reader.field("fft_type_auto")?,
+            ss_base_log: reader.field("ss_base_log")?,
+            ss_level: reader.field("ss_level")?,
+            cbs_base_log: reader.field("cbs_base_log")?,
+            cbs_level: reader.field("cbs_level")?,
+            log_lut_count: reader.field("log_lut_count")?,
+            ciphertext_modulus: reader.field("ciphertext_modulus")?,
+        }))
+    }
+
+    fn validate_with_min_security_bits(&self, min_security_bits: usize) -> Result<(), ParamError> {
+        AesParam::validate_with_min_security_bits(self, min_security_bits)
+    }
+}
+
+impl<Scalar: UnsignedInteger> ParamFileFormat for PkskConvParam<Scalar> {
+    const KIND: &'static str = "PkskConvParam";
+
+    fn write_fields(&self, writer: &mut ParamWriter) {
+        writer.field("lwe_dimension", self.lwe_dimension());
+        writer.field("lwe_modular_std_dev", self.lwe_modular_std_dev());
+        writer.field("polynomial_size", self.polynomial_size());
+        writer.field("glwe_dimension", self.glwe_dimension());
+        writer.field("glwe_modular_std_dev", self.glwe_modular_std_dev());
+        writer.field("pksk_base_log", self.pksk_base_log());
+        writer.field("pksk_level", self.pksk_level());
+        writer.field("ks_base_log", self.ks_base_log());
+        writer.field("ks_level", self.ks_level());
+        writer.field("ciphertext_modulus", self.ciphertext_modulus());
+    }
+
+    fn read_fields(reader: &mut ParamReader) -> Result<Self, ParamFileError> {
+        Ok(PkskConvParam::new_unchecked(PkskConvParamFields {
+            lwe_dimension: reader.field("lwe_dimension")?,
+            lwe_modular_std_dev: reader.field("lwe_modular_std_dev")?,
+            polynomial_size: reader.field("polynomial_size")?,
+            glwe_dimension: reader.field("glwe_dimension")?,
+            glwe_modular_std_dev: reader.field("glwe_modular_std_dev")?,
+            pksk_base_log: reader.field("pksk_base_log")?,
+            pksk_level: reader.field("pksk_level")?,
+            ks_base_log: reader.field("ks_base_log")?,
+            ks_level: reader.field("ks_level")?,
+            ciphertext_modulus: reader.field("ciphertext_modulus")?,
+        }))
+    }
+
+    fn validate_with_min_security_bits(&self, min_security_bits: usize) -> Result<(), ParamError> {
+        PkskConvParam::validate_with_min_security_bits(self, min_security_bits)
+    }
+}
+
+impl<Scalar: UnsignedInteger> ParamFileFormat for AutoConvParam<Scalar> {
+    const KIND: &'static str = "AutoConvParam";
+
+    fn write_fields(&self, writer: &mut ParamWriter) {
+        writer.field("polynomial_size", self.polynomial_size());
+        writer.field("glwe_dimension", self.glwe_dimension());
+        writer.field("glwe_modular_std_dev", self.glwe_modular_std_dev());
+        writer.field("auto_base_log", self.auto_base_log());
+        writer.field("auto_level", self.auto_level());
+        writer.field("fft_type", self.fft_type());
+        writer.field("ciphertext_modulus", self.ciphertext_modulus());
+    }
+
+    fn read_fields(reader: &mut ParamReader) -> Result<Self, ParamFileError> {
+        Ok(AutoConvParam::new_unchecked(
+            reader.field("polynomial_size")?,
+            reader.field("glwe_dimension")?,
+            reader.field("glwe_modular_std_dev")?,
+            reader.field("auto_base_log")?,
+            reader.field("auto_level")?,
+            reader.field("fft_type")?,
+            reader.field("ciphertext_modulus")?,
+        ))
+    }
+
+    fn validate_with_min_security_bits(&self, min_security_bits: usize) -> Result<(), ParamError> {
+        AutoConvParam::validate_with_min_security_bits(self, min_security_bits)
+    }
+}
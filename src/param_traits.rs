@@ -0,0 +1,149 @@
+use tfhe::core_crypto::prelude::*;
+use crate::FftType;
+
+/// Parameters of the LWE/GLWE keys and the bootstrapping key.
+pub trait PbsParameters<Scalar: UnsignedInteger> {
+    fn lwe_dimension(&self) -> LweDimension;
+    fn lwe_modular_std_dev(&self) -> StandardDev;
+    fn polynomial_size(&self) -> PolynomialSize;
+    fn glwe_dimension(&self) -> GlweDimension;
+    fn glwe_modular_std_dev(&self) -> StandardDev;
+    fn pbs_base_log(&self) -> DecompositionBaseLog;
+    fn pbs_level(&self) -> DecompositionLevelCount;
+    fn ciphertext_modulus(&self) -> CiphertextModulus<Scalar>;
+}
+
+/// Parameters of the standard LWE keyswitching key from the large LWE key to the small LWE key.
+pub trait LweKeyswitchParameters {
+    fn ks_base_log(&self) -> DecompositionBaseLog;
+    fn ks_level(&self) -> DecompositionLevelCount;
+}
+
+/// Parameters of the LWE keyswitching by GLWE dimension switching over the common polynomial size.
+pub trait GlweDsParameters {
+    fn glwe_ds_base_log(&self) -> DecompositionBaseLog;
+    fn glwe_ds_level(&self) -> DecompositionLevelCount;
+    fn common_polynomial_size(&self) -> PolynomialSize;
+    fn fft_type_ds(&self) -> FftType;
+}
+
+/// Parameters of the automorphism keys used by the trace.
+pub trait AutomorphismParameters {
+    fn auto_base_log(&self) -> DecompositionBaseLog;
+    fn auto_level(&self) -> DecompositionLevelCount;
+    fn fft_type_auto(&self) -> FftType;
+}
+
+/// Parameters of the scheme switching key from GLEV to GGSW.
+pub trait SchemeSwitchingParameters {
+    fn ss_base_log(&self) -> DecompositionBaseLog;
+    fn ss_level(&self) -> DecompositionLevelCount;
+}
+
+/// Parameters shared by every circuit bootstrapping family.
+pub trait CbsParameters<Scalar: UnsignedInteger>: PbsParameters<Scalar> {
+    fn cbs_base_log(&self) -> DecompositionBaseLog;
+    fn cbs_level(&self) -> DecompositionLevelCount;
+
+    /// Number of LEV levels evaluated by a single PBS, which is one unless PBSmanyLUT is used.
+    fn log_lut_count(&self) -> LutCountLog {
+        LutCountLog(0)
+    }
+}
+
+// The parameter structs keep their inherent getters, so that the presets can be used without
+// importing the traits. The trait implementations below delegate to these getters.
+
+macro_rules! impl_pbs_parameters {
+    ($($param:ident),*) => {
+        $(
+            impl<Scalar: UnsignedInteger> $crate::param_traits::PbsParameters<Scalar> for $param<Scalar> {
+                fn lwe_dimension(&self) -> LweDimension { $param::lwe_dimension(self) }
+                fn lwe_modular_std_dev(&self) -> StandardDev { $param::lwe_modular_std_dev(self) }
+                fn polynomial_size(&self) -> PolynomialSize { $param::polynomial_size(self) }
+                fn glwe_dimension(&self) -> GlweDimension { $param::glwe_dimension(self) }
+                fn glwe_modular_std_dev(&self) -> StandardDev { $param::glwe_modular_std_dev(self) }
+                fn pbs_base_log(&self) -> DecompositionBaseLog { $param::pbs_base_log(self) }
+                fn pbs_level(&self) -> DecompositionLevelCount { $param::pbs_level(self) }
+                fn ciphertext_modulus(&self) -> CiphertextModulus<Scalar> { $param::ciphertext_modulus(self) }
+            }
+        )*
+    };
+}
+
+macro_rules! impl_lwe_keyswitch_parameters {
+    ($($param:ident),*) => {
+        $(
+            impl<Scalar: UnsignedInteger> $crate::param_traits::LweKeyswitchParameters for $param<Scalar> {
+                fn ks_base_log(&self) -> DecompositionBaseLog { $param::ks_base_log(self) }
+                fn ks_level(&self) -> DecompositionLevelCount { $param::ks_level(self) }
+            }
+        )*
+    };
+}
+
+macro_rules! impl_glwe_ds_parameters {
+    ($($param:ident),*) => {
+        $(
+            impl<Scalar: UnsignedInteger> $crate::param_traits::GlweDsParameters for $param<Scalar> {
+                fn glwe_ds_base_log(&self) -> DecompositionBaseLog { $param::glwe_ds_base_log(self) }
+                fn glwe_ds_level(&self) -> DecompositionLevelCount { $param::glwe_ds_level(self) }
+                fn common_polynomial_size(&self) -> PolynomialSize { $param::common_polynomial_size(self) }
+                fn fft_type_ds(&self) -> $crate::FftType { $param::fft_type_ds(self) }
+            }
+        )*
+    };
+}
+
+macro_rules! impl_automorphism_parameters {
+    ($($param:ident),*) => {
+        $(
+            impl<Scalar: UnsignedInteger> $crate::param_traits::AutomorphismParameters for $param<Scalar> {
+                fn auto_base_log(&self) -> DecompositionBaseLog { $param::auto_base_log(self) }
+                fn auto_level(&self) -> DecompositionLevelCount { $param::auto_level(self) }
+                fn fft_type_auto(&self) -> $crate::FftType { $param::fft_type_auto(self) }
+            }
+        )*
+    };
+}
+
+macro_rules! impl_scheme_switching_parameters {
+    ($($param:ident),*) => {
+        $(
+            impl<Scalar: UnsignedInteger> $crate::param_traits::SchemeSwitchingParameters for $param<Scalar> {
+                fn ss_base_log(&self) -> DecompositionBaseLog { $param::ss_base_log(self) }
+                fn ss_level(&self) -> DecompositionLevelCount { $param::ss_level(self) }
+            }
+        )*
+    };
+}
+
+/// Without `log_lut_count`, the trait default of a single LUT per PBS is used.
+macro_rules! impl_cbs_parameters {
+    ($($param:ident),*) => {
+        $(
+            impl<Scalar: UnsignedInteger> $crate::param_traits::CbsParameters<Scalar> for $param<Scalar> {
+                fn cbs_base_log(&self) -> DecompositionBaseLog { $param::cbs_base_log(self) }
+                fn cbs_level(&self) -> DecompositionLevelCount { $param::cbs_level(self) }
+            }
+        )*
+    };
+    ($($param:ident),* ; log_lut_count) => {
+        $(
+            impl<Scalar: UnsignedInteger> $crate::param_traits::CbsParameters<Scalar> for $param<Scalar> {
+                fn cbs_base_log(&self) -> DecompositionBaseLog { $param::cbs_base_log(self) }
+                fn cbs_level(&self) -> DecompositionLevelCount { $param::cbs_level(self) }
+                fn log_lut_count(&self) -> LutCountLog { $param::log_lut_count(self) }
+            }
+        )*
+    };
+}
+
+pub(crate) use {
+    impl_automorphism_parameters,
+    impl_cbs_parameters,
+    impl_glwe_ds_parameters,
+    impl_lwe_keyswitch_parameters,
+    impl_pbs_parameters,
+    impl_scheme_switching_parameters,
+};
@@ -8,8 +8,8 @@ use tfhe::core_crypto::{
     }, prelude::*
 };
 use aligned_vec::CACHELINE_ALIGN;
-use dyn_stack::{PodStack, ReborrowMut};
-use crate::utils::*;
+use dyn_stack::{PodStack, ReborrowMut, SizeOverflow, StackReq};
+use crate::{param_traits::CbsParameters, utils::*};
 
 pub fn generate_accumulator<Scalar, F>(
     polynomial_size: PolynomialSize,
@@ -167,17 +167,32 @@ pub fn lwe_msb_bit_refresh<Scalar, InputCont, OutputCont>(
     InputCont: Container<Element = Scalar>,
     OutputCont: ContainerMut<Element = Scalar>,
 {
-    assert_eq!(input.lwe_size(), refresh_bsk.input_lwe_dimension().to_lwe_size());
-    assert_eq!(output.lwe_size(), refresh_bsk.output_lwe_dimension().to_lwe_size());
+    lwe_msb_bit_to_scaled_bit(input, output, refresh_bsk, Scalar::BITS - 1);
+}
+
+/// Compute an LWE ciphertext of b * 2^log_scale from an LWE ciphertext of b * 2^(BITS - 1).
+fn lwe_msb_bit_to_scaled_bit<Scalar, InputCont, OutputCont>(
+    input: &LweCiphertext<InputCont>,
+    output: &mut LweCiphertext<OutputCont>,
+    fourier_bsk: FourierLweBootstrapKeyView,
+    log_scale: usize,
+) where
+    Scalar: UnsignedTorus + CastInto<usize>,
+    InputCont: Container<Element = Scalar>,
+    OutputCont: ContainerMut<Element = Scalar>,
+{
+    assert_eq!(input.lwe_size(), fourier_bsk.input_lwe_dimension().to_lwe_size());
+    assert_eq!(output.lwe_size(), fourier_bsk.output_lwe_dimension().to_lwe_size());
     assert_eq!(input.ciphertext_modulus(), output.ciphertext_modulus());
+    assert!(0 < log_scale && log_scale < Scalar::BITS);
 
-    let glwe_size = refresh_bsk.glwe_size();
-    let polynomial_size = refresh_bsk.polynomial_size();
+    let glwe_size = fourier_bsk.glwe_size();
+    let polynomial_size = fourier_bsk.polynomial_size();
     let half_box_size = polynomial_size.0 / 2;
     let ciphertext_modulus = input.ciphertext_modulus();
 
     let mut accumulator = (0..polynomial_size.0).map(|_| {
-        (Scalar::ONE).wrapping_neg() << (Scalar::BITS - 2)
+        (Scalar::ONE).wrapping_neg() << (log_scale - 1)
     }).collect::<Vec<Scalar>>();
 
     for a_i in accumulator[0..half_box_size].iter_mut() {
@@ -192,67 +207,234 @@ pub fn lwe_msb_bit_refresh<Scalar, InputCont, OutputCont>(
         ciphertext_modulus,
     );
 
-    programmable_bootstrap_lwe_ciphertext(input, output, &accumulator, &refresh_bsk);
-    lwe_ciphertext_plaintext_add_assign(output, Plaintext(Scalar::ONE << (Scalar::BITS - 2)));
+    programmable_bootstrap_lwe_ciphertext(input, output, &accumulator, &fourier_bsk);
+    lwe_ciphertext_plaintext_add_assign(output, Plaintext(Scalar::ONE << (log_scale - 1)));
+}
+
+/// Precomputed accumulators of the LEV/GLEV generation for the circuit bootstrapping.
+///
+/// The i-th accumulator computes the levels i * lut_count + 1, ..., (i + 1) * lut_count
+/// of the output LEV from an LWE ciphertext of b * 2^(BITS - 1).
+/// It is built once and shared among all circuit bootstrappings with the same parameters.
+pub struct CbsAccumulator<Scalar: UnsignedTorus> {
+    accumulator_list: GlweCiphertextListOwned<Scalar>,
+    base_log: DecompositionBaseLog,
+    level: DecompositionLevelCount,
+    log_lut_count: LutCountLog,
+    log_scale_offset: usize,
+}
+
+/// Gadget decomposition of the LEV computed by the accumulators, with `lut_count` levels per accumulator.
+#[derive(Clone, Copy)]
+struct LevLayout {
+    base_log: DecompositionBaseLog,
+    level: DecompositionLevelCount,
+    log_lut_count: LutCountLog,
+    log_scale_offset: usize,
+}
+
+impl<Scalar: UnsignedTorus> CbsAccumulator<Scalar> {
+    pub fn new(
+        polynomial_size: PolynomialSize,
+        glwe_size: GlweSize,
+        base_log: DecompositionBaseLog,
+        level: DecompositionLevelCount,
+        log_lut_count: LutCountLog,
+    ) -> Self {
+        Self::new_with_log_scale_offset(polynomial_size, glwe_size, base_log, level, log_lut_count, 0)
+    }
+
+    /// Accumulators of the given circuit bootstrapping parameters.
+    pub fn from_param<P: CbsParameters<Scalar>>(param: &P) -> Self {
+        Self::new(
+            param.polynomial_size(),
+            param.glwe_dimension().to_glwe_size(),
+            param.cbs_base_log(),
+            param.cbs_level(),
+            param.log_lut_count(),
+        )
+    }
+
+    /// Accumulators of the original WWL+ circuit bootstrapping, whose LEV is scaled by 1/N
+    /// so that the following trace without the mod-switch preprocessing recovers the levels.
+    pub fn new_original_wwlp(
+        polynomial_size: PolynomialSize,
+        glwe_size: GlweSize,
+        base_log: DecompositionBaseLog,
+        level: DecompositionLevelCount,
+        log_lut_count: LutCountLog,
+    ) -> Self {
+        let log_polynomial_size = polynomial_size.log2().0;
+        Self::new_with_log_scale_offset(polynomial_size, glwe_size, base_log, level, log_lut_count, log_polynomial_size)
+    }
+
+    fn new_with_log_scale_offset(
+        polynomial_size: PolynomialSize,
+        glwe_size: GlweSize,
+        base_log: DecompositionBaseLog,
+        level: DecompositionLevelCount,
+        log_lut_count: LutCountLog,
+        log_scale_offset: usize,
+    ) -> Self {
+        let layout = LevLayout { base_log, level, log_lut_count, log_scale_offset };
+        Self::new_with_lut(polynomial_size, glwe_size, layout, 1, |_| false)
+    }
+
+    /// Accumulators of the programmable circuit bootstrapping of f(m) in {0, 1} for an LWE ciphertext
+    /// of a message m encoded as m * 2^(BITS - 1) / message_modulus.
+    pub fn new_boolean_function<F: Fn(usize) -> bool>(
+        polynomial_size: PolynomialSize,
+        glwe_size: GlweSize,
+        base_log: DecompositionBaseLog,
+        level: DecompositionLevelCount,
+        log_lut_count: LutCountLog,
+        message_modulus: usize,
+        f: F,
+    ) -> Self {
+        let layout = LevLayout { base_log, level, log_lut_count, log_scale_offset: 0 };
+        Self::new_with_lut(polynomial_size, glwe_size, layout, message_modulus, f)
+    }
+
+    fn new_with_lut<F: Fn(usize) -> bool>(
+        polynomial_size: PolynomialSize,
+        glwe_size: GlweSize,
+        layout: LevLayout,
+        message_modulus: usize,
+        f: F,
+    ) -> Self {
+        let LevLayout { base_log, level, log_lut_count, log_scale_offset } = layout;
+        assert!(base_log.0 * level.0 + log_scale_offset < Scalar::BITS);
+
+        let ciphertext_modulus = CiphertextModulus::<Scalar>::new_native();
+        let lut_count = 1 << log_lut_count.0;
+        let accumulator_count = (level.0 - 1) / lut_count + 1;
+
+        let mut accumulator_list = GlweCiphertextList::new(
+            Scalar::ZERO,
+            glwe_size,
+            polynomial_size,
+            GlweCiphertextCount(accumulator_count),
+            ciphertext_modulus,
+        );
+
+        for (acc_idx, mut accumulator) in accumulator_list.iter_mut().enumerate() {
+            let accumulator_body = lev_accumulator_body(polynomial_size, layout, acc_idx, message_modulus, &f);
+            accumulator.get_mut_body().as_mut().clone_from_slice(&accumulator_body);
+        }
+
+        CbsAccumulator {
+            accumulator_list,
+            base_log,
+            level,
+            log_lut_count,
+            log_scale_offset,
+        }
+    }
+
+    pub fn polynomial_size(&self) -> PolynomialSize {
+        self.accumulator_list.polynomial_size()
+    }
+
+    pub fn glwe_size(&self) -> GlweSize {
+        self.accumulator_list.glwe_size()
+    }
+
+    pub fn decomposition_base_log(&self) -> DecompositionBaseLog {
+        self.base_log
+    }
+
+    pub fn decomposition_level_count(&self) -> DecompositionLevelCount {
+        self.level
+    }
+
+    pub fn log_lut_count(&self) -> LutCountLog {
+        self.log_lut_count
+    }
+
+    /// Log of the extra factor 1/2^offset on the LEV, which is zero except for the original WWL+.
+    pub fn log_scale_offset(&self) -> usize {
+        self.log_scale_offset
+    }
+
+    pub fn accumulator_count(&self) -> usize {
+        self.accumulator_list.glwe_ciphertext_count().0
+    }
+
+    pub fn get_accumulator(&self, acc_idx: usize) -> GlweCiphertextView<'_, Scalar> {
+        self.accumulator_list.get(acc_idx)
+    }
 }
 
 pub fn lwe_msb_bit_to_lev<Scalar, InputCont, OutputCont>(
     lwe: &LweCiphertext<InputCont>,
     lev: &mut LweCiphertextList<OutputCont>,
     fourier_bsk: FourierLweBootstrapKeyView,
-    lev_base_log: DecompositionBaseLog,
-    lev_level: DecompositionLevelCount,
-    log_lut_count: LutCountLog,
+    cbs_acc: &CbsAccumulator<Scalar>,
+) where
+    Scalar: UnsignedTorus + CastInto<usize>,
+    InputCont: Container<Element=Scalar>,
+    OutputCont: ContainerMut<Element=Scalar>,
+{
+    let fft = Fft::new(fourier_bsk.polynomial_size());
+    let fft = fft.as_view();
+
+    let mut buffers = ComputationBuffers::new();
+    buffers.resize(
+        lwe_msb_bit_to_lev_mem_optimized_requirement::<Scalar>(
+            fourier_bsk.glwe_size(),
+            fourier_bsk.polynomial_size(),
+            fft,
+        )
+        .unwrap()
+        .unaligned_bytes_required(),
+    );
+
+    lwe_msb_bit_to_lev_mem_optimized(lwe, lev, fourier_bsk, cbs_acc, fft, buffers.stack());
+}
+
+pub fn lwe_msb_bit_to_lev_mem_optimized_requirement<Scalar: UnsignedTorus>(
+    glwe_size: GlweSize,
+    polynomial_size: PolynomialSize,
+    fft: FftView<'_>,
+) -> Result<StackReq, SizeOverflow> {
+    programmable_bootstrap_lwe_ciphertext_mem_optimized_requirement::<Scalar>(
+        glwe_size,
+        polynomial_size,
+        fft,
+    )
+}
+
+pub fn lwe_msb_bit_to_lev_mem_optimized<Scalar, InputCont, OutputCont>(
+    lwe: &LweCiphertext<InputCont>,
+    lev: &mut LweCiphertextList<OutputCont>,
+    fourier_bsk: FourierLweBootstrapKeyView,
+    cbs_acc: &CbsAccumulator<Scalar>,
+    fft: FftView<'_>,
+    mut stack: PodStack<'_>,
 ) where
     Scalar: UnsignedTorus + CastInto<usize>,
     InputCont: Container<Element=Scalar>,
     OutputCont: ContainerMut<Element=Scalar>,
 {
     assert_eq!(lwe.lwe_size(), fourier_bsk.input_lwe_dimension().to_lwe_size());
-    assert_eq!(lev.entity_count(), lev_level.0);
+    assert_eq!(lev.entity_count(), cbs_acc.decomposition_level_count().0);
     assert_eq!(lwe.ciphertext_modulus(), lev.ciphertext_modulus());
+    assert_eq!(cbs_acc.polynomial_size(), fourier_bsk.polynomial_size());
+    assert_eq!(cbs_acc.glwe_size(), fourier_bsk.glwe_size());
 
+    let lev_base_log = cbs_acc.decomposition_base_log();
+    let log_lut_count = cbs_acc.log_lut_count();
+    let log_scale_offset = cbs_acc.log_scale_offset();
     let glwe_size = fourier_bsk.glwe_size();
     let polynomial_size = fourier_bsk.polynomial_size();
-    let half_box_size = polynomial_size.0 / 2;
     let ciphertext_modulus = lwe.ciphertext_modulus();
 
     let lut_count = 1 << log_lut_count.0;
+    let mut buf = GlweCiphertext::new(Scalar::ZERO, glwe_size, polynomial_size, ciphertext_modulus);
     for (acc_idx, mut lev_chunk) in lev.chunks_mut(lut_count).enumerate() {
-        let mut accumulator = (0..polynomial_size.0).map(|i| {
-            let k = i % lut_count;
-            let log_scale = Scalar::BITS - (acc_idx * lut_count + k + 1) * lev_base_log.0;
-            (Scalar::ONE).wrapping_neg() << (log_scale - 1)
-        }).collect::<Vec<Scalar>>();
+        let accumulator = cbs_acc.get_accumulator(acc_idx);
 
-        for a_i in accumulator[0..half_box_size].iter_mut() {
-            *a_i = (*a_i).wrapping_neg();
-        }
-        accumulator.rotate_left(half_box_size);
-
-        let accumulator_plaintext = PlaintextList::from_container(accumulator);
-        let accumulator = allocate_and_trivially_encrypt_new_glwe_ciphertext(
-            glwe_size,
-            &accumulator_plaintext,
-            ciphertext_modulus,
-        );
-
-        let mut buffers = ComputationBuffers::new();
-        let fft = Fft::new(polynomial_size);
-        let fft = fft.as_view();
-
-        buffers.resize(
-            programmable_bootstrap_lwe_ciphertext_mem_optimized_requirement::<Scalar>(
-                glwe_size,
-                polynomial_size,
-                fft,
-            )
-            .unwrap()
-            .unaligned_bytes_required(),
-        );
-        let stack = buffers.stack();
-
-        let (mut local_accumulator_data, stack) = stack.collect_aligned(CACHELINE_ALIGN, accumulator.as_ref().iter().copied());
+        let (mut local_accumulator_data, stack) = stack.rb_mut().collect_aligned(CACHELINE_ALIGN, accumulator.as_ref().iter().copied());
         let mut local_accumulator = GlweCiphertextMutView::from_container(
             &mut *local_accumulator_data,
             polynomial_size,
@@ -271,9 +453,8 @@ pub fn lwe_msb_bit_to_lev<Scalar, InputCont, OutputCont>(
 
         for (k, mut lwe_out) in lev_chunk.iter_mut().enumerate() {
             let cur_level = acc_idx * lut_count + k + 1;
-            let log_scale = Scalar::BITS - cur_level * lev_base_log.0;
+            let log_scale = Scalar::BITS - cur_level * lev_base_log.0 - log_scale_offset;
 
-            let mut buf = GlweCiphertext::new(Scalar::ZERO, glwe_size, polynomial_size, ciphertext_modulus);
             glwe_ciphertext_clone_from(&mut buf, &local_accumulator);
             glwe_ciphertext_monic_monomial_div_assign(&mut buf, MonomialDegree(k));
             glwe_ciphertext_plaintext_add_assign(&mut buf, Plaintext(Scalar::ONE << (log_scale - 1)));
@@ -282,3 +463,145 @@ pub fn lwe_msb_bit_to_lev<Scalar, InputCont, OutputCont>(
         }
     }
 }
+
+/// Body of the `acc_idx`-th accumulator of the LEV generation, whose level `cur_level` in the box of a message m
+/// is ±2^(BITS - cur_level * base_log - log_scale_offset - 1) with the sign of f(m). The MSB-only accumulator
+/// of the circuit bootstrapping is the case of `message_modulus = 1` and f = 0.
+fn lev_accumulator_body<Scalar, F>(
+    polynomial_size: PolynomialSize,
+    layout: LevLayout,
+    acc_idx: usize,
+    message_modulus: usize,
+    f: F,
+) -> Vec<Scalar>
+where
+    Scalar: UnsignedTorus,
+    F: Fn(usize) -> bool,
+{
+    let LevLayout { base_log: lev_base_log, level: lev_level, log_lut_count, log_scale_offset } = layout;
+    assert!(message_modulus.is_power_of_two());
+
+    let lut_count = 1 << log_lut_count.0;
+    let num_slot = polynomial_size.0 / lut_count;
+    assert!(
+        num_slot >= 2 * message_modulus,
+        "polynomial size is too small for the message modulus and the lut count"
+    );
+
+    // Box of message m: slots [m * box_size, (m+1) * box_size),
+    // and the k-th LUT is stored in the coefficients of index = k mod lut_count
+    let box_size = num_slot / message_modulus;
+    let half_box_size = box_size / 2;
+
+    let mut accumulator = (0..polynomial_size.0).map(|i| {
+        let k = i % lut_count;
+        let msg = (i / lut_count) / box_size;
+        let cur_level = acc_idx * lut_count + k + 1;
+
+        if cur_level > lev_level.0 {
+            Scalar::ZERO
+        } else {
+            let log_scale = Scalar::BITS - cur_level * lev_base_log.0 - log_scale_offset;
+            if f(msg) {
+                Scalar::ONE << (log_scale - 1)
+            } else {
+                (Scalar::ONE).wrapping_neg() << (log_scale - 1)
+            }
+        }
+    }).collect::<Vec<Scalar>>();
+
+    for a_i in accumulator[0..half_box_size * lut_count].iter_mut() {
+        *a_i = (*a_i).wrapping_neg();
+    }
+    accumulator.rotate_left(half_box_size * lut_count);
+
+    accumulator
+}
+
+/// Extract the bits of m from an LWE ciphertext of m * 2^delta_log under the large key.
+///
+/// The bits are extracted sequentially from the LSB: the current bit is shifted to the MSB and
+/// keyswitched to the small key, and it is then bootstrapped back to its original position and
+/// subtracted from the input so that the next bit becomes the lowest one.
+///
+/// Unlike an MSB-down extraction, every PBS input is b * 2^(BITS - 1) plus the shifted noise, since the
+/// lower bits have already been removed: the PBS only decides one bit whatever the number of bits, and
+/// its failure probability is that of a single bit refresh as long as the input noise is below 2^(delta_log - 1).
+/// Extracting from the MSB would leave the lower bits in the PBS input, so that the messages next to
+/// the box boundaries (e.g. 0111..1) would be flipped by the mod-switch noise unless 2N >> 2^num_bits.
+///
+/// The i-th output LWE encrypts the i-th bit of m as b_i * 2^(BITS - 1) under the small key,
+/// which is the input format of the circuit bootstrapping.
+pub fn extract_bits_from_lwe_ciphertext<Scalar, InputCont, OutputCont, KSKeyCont>(
+    lwe_in: &LweCiphertext<InputCont>,
+    lwe_bit_list_out: &mut LweCiphertextList<OutputCont>,
+    fourier_bsk: FourierLweBootstrapKeyView,
+    ksk: &LweKeyswitchKey<KSKeyCont>,
+    delta_log: DeltaLog,
+) where
+    Scalar: UnsignedTorus + CastInto<usize>,
+    InputCont: Container<Element=Scalar>,
+    OutputCont: ContainerMut<Element=Scalar>,
+    KSKeyCont: Container<Element=Scalar>,
+{
+    let num_bits = lwe_bit_list_out.entity_count();
+    let ciphertext_modulus = lwe_in.ciphertext_modulus();
+
+    assert_eq!(lwe_in.lwe_size(), ksk.input_key_lwe_dimension().to_lwe_size());
+    assert_eq!(lwe_in.lwe_size(), fourier_bsk.output_lwe_dimension().to_lwe_size());
+    assert_eq!(lwe_bit_list_out.lwe_size(), ksk.output_key_lwe_dimension().to_lwe_size());
+    assert_eq!(lwe_bit_list_out.lwe_size(), fourier_bsk.input_lwe_dimension().to_lwe_size());
+    assert_eq!(ciphertext_modulus, lwe_bit_list_out.ciphertext_modulus());
+    assert!(ciphertext_modulus.is_native_modulus());
+    assert!(delta_log.0 >= 1 && delta_log.0 + num_bits <= Scalar::BITS);
+
+    let mut lwe_in_buf = LweCiphertext::from_container(lwe_in.as_ref().to_vec(), ciphertext_modulus);
+    let mut lwe_in_shifted = LweCiphertext::new(Scalar::ZERO, lwe_in.lwe_size(), ciphertext_modulus);
+    let mut lwe_bit_buf = LweCiphertext::new(Scalar::ZERO, lwe_in.lwe_size(), ciphertext_modulus);
+
+    for (bit_idx, mut lwe_bit) in lwe_bit_list_out.iter_mut().enumerate() {
+        // Shift the current bit to the MSB
+        let shift = Scalar::BITS - delta_log.0 - bit_idx - 1;
+        for (dst, &src) in lwe_in_shifted.as_mut().iter_mut().zip(lwe_in_buf.as_ref().iter()) {
+            *dst = src.wrapping_shl(shift as u32);
+        }
+        keyswitch_lwe_ciphertext(ksk, &lwe_in_shifted, &mut lwe_bit);
+
+        if bit_idx == num_bits - 1 {
+            break;
+        }
+
+        // Remove the current bit from the input
+        lwe_msb_bit_to_scaled_bit(&lwe_bit, &mut lwe_bit_buf, fourier_bsk, delta_log.0 + bit_idx);
+        lwe_ciphertext_sub_assign(&mut lwe_in_buf, &lwe_bit_buf);
+    }
+}
+
+/// Recompose an LWE ciphertext of m * 2^delta_log under the large key from LWE ciphertexts of
+/// the bits of m, where the i-th input LWE encrypts the i-th bit of m as b_i * 2^(BITS - 1)
+/// under the small key, as output by [`extract_bits_from_lwe_ciphertext`].
+pub fn recompose_lwe_ciphertext_from_bits<Scalar, InputCont, OutputCont>(
+    lwe_bit_list_in: &LweCiphertextList<InputCont>,
+    lwe_out: &mut LweCiphertext<OutputCont>,
+    fourier_bsk: FourierLweBootstrapKeyView,
+    delta_log: DeltaLog,
+) where
+    Scalar: UnsignedTorus + CastInto<usize>,
+    InputCont: Container<Element=Scalar>,
+    OutputCont: ContainerMut<Element=Scalar>,
+{
+    let num_bits = lwe_bit_list_in.entity_count();
+    let ciphertext_modulus = lwe_out.ciphertext_modulus();
+
+    assert_eq!(lwe_bit_list_in.lwe_size(), fourier_bsk.input_lwe_dimension().to_lwe_size());
+    assert_eq!(lwe_out.lwe_size(), fourier_bsk.output_lwe_dimension().to_lwe_size());
+    assert_eq!(lwe_bit_list_in.ciphertext_modulus(), ciphertext_modulus);
+    assert!(delta_log.0 >= 1 && delta_log.0 + num_bits <= Scalar::BITS);
+
+    lwe_out.as_mut().fill(Scalar::ZERO);
+    let mut lwe_bit_buf = LweCiphertext::new(Scalar::ZERO, lwe_out.lwe_size(), ciphertext_modulus);
+    for (bit_idx, lwe_bit) in lwe_bit_list_in.iter().enumerate() {
+        lwe_msb_bit_to_scaled_bit(&lwe_bit, &mut lwe_bit_buf, fourier_bsk, delta_log.0 + bit_idx);
+        lwe_ciphertext_add_assign(lwe_out, &lwe_bit_buf);
+    }
+}
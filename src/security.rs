@@ -0,0 +1,156 @@
+//! Core-SVP estimate of the bit security of the LWE and GLWE parts of the parameter sets.
+//!
+//! The cost of BKZ with block size beta in dimension d is 2^(0.292 beta + 16.4 + log2(8d)), the core-SVP cost of
+//! classical sieving times the number of SVP calls of the BDGL16 cost model. The primal attack solves
+//! the uSVP in the Kannan embedding, and the dual attack distinguishes the samples by short dual vectors,
+//! repeating the attack when a single sieving call does not give enough of them. Both scale the secret
+//! coordinates to balance them with the error, since the secret keys are uniform binary.
+use std::{f64::consts::{E, PI}, fmt};
+use tfhe::core_crypto::prelude::*;
+
+/// Security level required by `validate` and `try_new` of the parameter sets.
+/// Another threshold is given per call by `validate_with_min_security_bits`.
+pub const DEFAULT_MIN_SECURITY_BITS: usize = 128;
+
+const CORE_SVP_EXPONENT: f64 = 0.292;
+const SIEVE_CONSTANT_BITS: f64 = 16.4;
+/// Number of short vectors produced by a single sieving call, 2^(0.2075 beta).
+const SIEVE_OUTPUT_EXPONENT: f64 = 0.2075;
+const MIN_BLOCK_SIZE: usize = 50;
+/// Standard deviation of a centered uniform binary secret coefficient.
+const BINARY_SECRET_STD_DEV: f64 = 0.5;
+const NUM_SAMPLE_STEPS: usize = 64;
+
+
+/// Estimated bit security of an LWE instance against the primal and the dual attacks.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LweSecurity {
+    pub primal_bits: f64,
+    pub dual_bits: f64,
+}
+
+impl LweSecurity {
+    pub fn bits(&self) -> f64 {
+        self.primal_bits.min(self.dual_bits)
+    }
+}
+
+/// Estimated security of every LWE/GLWE secret key of a parameter set.
+#[derive(Clone, Debug)]
+pub struct SecurityReport {
+    parts: Vec<(&'static str, LweSecurity)>,
+}
+
+impl SecurityReport {
+    pub(crate) fn new(parts: Vec<(&'static str, LweSecurity)>) -> Self {
+        SecurityReport { parts }
+    }
+
+    pub fn parts(&self) -> &[(&'static str, LweSecurity)] {
+        &self.parts
+    }
+
+    /// Security of the weakest part.
+    pub fn bits(&self) -> f64 {
+        self.parts.iter().map(|(_, security)| security.bits()).fold(f64::INFINITY, f64::min)
+    }
+}
+
+impl fmt::Display for SecurityReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:.1} bits", self.bits())?;
+        for (name, security) in self.parts.iter() {
+            write!(f, "\n- {name}: {:.1} bits (primal {:.1}, dual {:.1})", security.bits(), security.primal_bits, security.dual_bits)?;
+        }
+        Ok(())
+    }
+}
+
+
+/// Root Hermite factor of BKZ with block size `beta`.
+fn log2_delta(beta: usize) -> f64 {
+    let beta = beta as f64;
+    ((PI * beta).powf(1.0 / beta) * beta / (2.0 * PI * E)).log2() / (2.0 * (beta - 1.0))
+}
+
+fn bkz_bits(beta: usize, lattice_dimension: f64) -> f64 {
+    CORE_SVP_EXPONENT * beta as f64 + SIEVE_CONSTANT_BITS + (8.0 * lattice_dimension).log2()
+}
+
+/// Numbers of samples tried for each block size.
+fn sample_counts(dimension: usize) -> impl Iterator<Item=usize> {
+    let step = (2 * dimension / NUM_SAMPLE_STEPS).max(1);
+    (1..=NUM_SAMPLE_STEPS).map(move |i| i * step)
+}
+
+/// Scaling of the secret coordinates and the log2 of the error standard deviation over Z_q.
+fn log2_secret_scaling(std_dev: StandardDev, log_modulus: usize) -> (f64, f64) {
+    let log2_err = std_dev.0.log2() + log_modulus as f64;
+    ((log2_err - BINARY_SECRET_STD_DEV.log2()).max(0.0), log2_err)
+}
+
+/// Primal uSVP attack: the smallest block size such that sigma sqrt(beta) <= delta^(2 beta - d - 1) vol^(1/d).
+fn primal_bits(dimension: usize, std_dev: StandardDev, log_modulus: usize) -> f64 {
+    let (log2_scaling, log2_err) = log2_secret_scaling(std_dev, log_modulus);
+    let n = dimension as f64;
+    let log_q = log_modulus as f64;
+
+    for beta in MIN_BLOCK_SIZE..=(3 * dimension) {
+        let lhs = log2_err + 0.5 * (beta as f64).log2();
+        let lattice_dimension = sample_counts(dimension).map(|m| (m + dimension + 1) as f64).find(|&d| {
+            let m = d - n - 1.0;
+            beta as f64 <= d
+                && lhs <= (2.0 * beta as f64 - d - 1.0) * log2_delta(beta) + (m * log_q + n * log2_scaling) / d
+        });
+        if let Some(d) = lattice_dimension {
+            return bkz_bits(beta, d);
+        }
+    }
+    bkz_bits(3 * dimension, (3 * dimension) as f64)
+}
+
+/// Dual distinguishing attack on the lattice {(w, v) : wA = v mod q} of volume q^n with the secret part scaled,
+/// with the advantage exp(-2 pi^2 tau^2) of a dual vector of norm delta^d vol^(1/d), repeated 1/eps^2 times
+/// beyond the vectors given by a single sieving call.
+fn dual_bits(dimension: usize, std_dev: StandardDev, log_modulus: usize) -> f64 {
+    let (log2_scaling, log2_err) = log2_secret_scaling(std_dev, log_modulus);
+    let n = dimension as f64;
+    let log_q = log_modulus as f64;
+
+    let mut best = f64::INFINITY;
+    for beta in MIN_BLOCK_SIZE..=(3 * dimension) {
+        if CORE_SVP_EXPONENT * beta as f64 >= best {
+            break;
+        }
+        for m in sample_counts(dimension) {
+            let d = (m + dimension) as f64;
+            if (beta as f64) > d {
+                continue;
+            }
+            let log2_norm = d * log2_delta(beta) + n * (log_q - log2_scaling) / d;
+            let log2_tau = log2_norm + log2_err - log_q;
+            let log2_inv_adv = 2.0 * PI * PI * 2f64.powf(2.0 * log2_tau) * E.log2();
+            let num_repeat = (2.0 * log2_inv_adv - SIEVE_OUTPUT_EXPONENT * beta as f64).max(0.0);
+            best = best.min(bkz_bits(beta, d) + num_repeat);
+        }
+    }
+    best
+}
+
+/// Estimated security of LWE of the given dimension with a uniform binary secret over Z_(2^log_modulus).
+pub fn estimate_lwe_security(dimension: usize, std_dev: StandardDev, log_modulus: usize) -> LweSecurity {
+    LweSecurity {
+        primal_bits: primal_bits(dimension, std_dev, log_modulus),
+        dual_bits: dual_bits(dimension, std_dev, log_modulus),
+    }
+}
+
+/// Estimated security of GLWE, which is analyzed as LWE of dimension kN.
+pub fn estimate_glwe_security(
+    glwe_dimension: GlweDimension,
+    polynomial_size: PolynomialSize,
+    std_dev: StandardDev,
+    log_modulus: usize,
+) -> LweSecurity {
+    estimate_lwe_security(glwe_dimension.0 * polynomial_size.0, std_dev, log_modulus)
+}
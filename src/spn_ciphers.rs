@@ -0,0 +1,426 @@
+//! PRESENT, SKINNY and LowMC in the canonical form of [`crate::spn_ref`].
+use crate::spn_ref::*;
+
+/* ======== PRESENT ======== */
+
+pub const PRESENT_SBOX: [u8; 16] = [0xC, 0x5, 0x6, 0xB, 0x9, 0x0, 0xA, 0xD, 0x3, 0xE, 0xF, 0x8, 0x4, 0x7, 0x1, 0x2];
+pub const PRESENT_BLOCKSIZE_IN_BIT: usize = 64;
+pub const PRESENT_NUM_ROUNDS: usize = 31;
+pub const PRESENT80_KEY_LEN_IN_BYTE: usize = 10;
+
+/// PRESENT-80, where the state bit i is the bit i of the 64-bit big-endian block.
+///
+/// A round of PRESENT is addRoundKey, sBoxLayer and pLayer, and the final addRoundKey is the last key
+/// addition of the canonical form, so k_r is the round key K_{r+1}.
+pub struct Present80 {
+    permutation: Vec<usize>,
+}
+
+impl Present80 {
+    pub fn new() -> Self {
+        // The bit i is moved to the bit 16i mod 63, and the bit 63 is fixed
+        let mut permutation = vec![0usize; PRESENT_BLOCKSIZE_IN_BIT];
+        for i in 0..PRESENT_BLOCKSIZE_IN_BIT {
+            let dst = if i == PRESENT_BLOCKSIZE_IN_BIT - 1 {i} else {(16 * i) % (PRESENT_BLOCKSIZE_IN_BIT - 1)};
+            permutation[dst] = i;
+        }
+
+        Present80 {
+            permutation,
+        }
+    }
+}
+
+impl Default for Present80 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SpnCipher for Present80 {
+    fn block_size(&self) -> usize {
+        PRESENT_BLOCKSIZE_IN_BIT
+    }
+
+    fn num_rounds(&self) -> usize {
+        PRESENT_NUM_ROUNDS
+    }
+
+    fn sbox(&self) -> &[u8] {
+        &PRESENT_SBOX
+    }
+
+    fn num_sboxes(&self) -> usize {
+        PRESENT_BLOCKSIZE_IN_BIT / 4
+    }
+
+    fn bit_permutation(&self, _round: usize) -> Option<&[usize]> {
+        Some(&self.permutation)
+    }
+
+    fn linear_matrix(&self, _round: usize) -> Option<&Gf2Matrix> {
+        None
+    }
+
+    fn round_constant(&self, _round: usize) -> Vec<u8> {
+        vec![0u8; PRESENT_BLOCKSIZE_IN_BIT]
+    }
+
+    /// The 80-bit key register is rotated left by 61 bits, its top nibble goes through the S-box and the round
+    /// counter is XORed into its bits 19 to 15. The round key is the top 64 bits of the register.
+    fn round_keys(&self, key: &[u8]) -> Vec<Vec<u8>> {
+        assert_eq!(key.len(), PRESENT80_KEY_LEN_IN_BYTE, "invalid PRESENT-80 key length {}", key.len());
+        let mask = (1u128 << 80) - 1;
+
+        let mut reg = key.iter().fold(0u128, |acc, &byte| (acc << 8) | byte as u128);
+        let mut round_keys = Vec::with_capacity(PRESENT_NUM_ROUNDS + 1);
+        for counter in 1..=(PRESENT_NUM_ROUNDS + 1) as u128 {
+            round_keys.push(u64_to_bits((reg >> 16) as u64));
+
+            reg = ((reg << 61) | (reg >> 19)) & mask;
+            let top = PRESENT_SBOX[(reg >> 76) as usize] as u128;
+            reg = (reg & !(0xF << 76)) | (top << 76);
+            reg ^= counter << 15;
+        }
+
+        round_keys
+    }
+
+    fn block_to_bits(&self, block: &[u8]) -> Vec<u8> {
+        u64_to_bits(u64::from_be_bytes(block.try_into().expect("invalid PRESENT block length")))
+    }
+
+    fn bits_to_block(&self, bits: &[u8]) -> Vec<u8> {
+        bits_to_u64(bits).to_be_bytes().to_vec()
+    }
+}
+
+fn u64_to_bits(x: u64) -> Vec<u8> {
+    (0..u64::BITS).map(|i| ((x >> i) & 1) as u8).collect()
+}
+
+fn bits_to_u64(bits: &[u8]) -> u64 {
+    bits.iter().enumerate().fold(0u64, |acc, (i, &bit)| acc | ((bit as u64) << i))
+}
+
+/* ======== SKINNY ======== */
+
+pub const SKINNY_SBOX4: [u8; 16] = [0xc, 0x6, 0x9, 0x0, 0x1, 0xa, 0x2, 0xb, 0x3, 0x8, 0x5, 0xd, 0x4, 0xe, 0x7, 0xf];
+pub const SKINNY64_BLOCKSIZE_IN_BIT: usize = 64;
+pub const SKINNY64_64_NUM_ROUNDS: usize = 32;
+pub const SKINNY64_64_KEY_LEN_IN_BYTE: usize = 8;
+const SKINNY_NUM_CELLS: usize = 16;
+const SKINNY_CELLSIZE: usize = 4;
+/// Output cell i of ShiftRows is the input cell `SKINNY_SHIFT_ROWS[i]`.
+const SKINNY_SHIFT_ROWS: [usize; SKINNY_NUM_CELLS] = [0, 1, 2, 3, 7, 4, 5, 6, 10, 11, 8, 9, 13, 14, 15, 12];
+/// Tweakey cell permutation, where the output cell i is the input cell `SKINNY_TWEAKEY_PERM[i]`.
+const SKINNY_TWEAKEY_PERM: [usize; SKINNY_NUM_CELLS] = [9, 15, 8, 13, 10, 14, 12, 11, 0, 1, 2, 3, 4, 5, 6, 7];
+const SKINNY_MIX_COLUMNS: [[u8; 4]; 4] = [[1, 0, 1, 1], [1, 0, 0, 0], [0, 1, 1, 0], [1, 0, 1, 0]];
+
+/// SKINNY-64-64, where the cell i of the row i/4 and column i%4 is at the bits 4i to 4i + 3, and the cell 2b
+/// is the high nibble of the byte b of the block.
+///
+/// A round of SKINNY is SubCells, AddConstants, AddRoundTweakey, ShiftRows and MixColumns, so the canonical
+/// constants and round keys are the constants and round tweakeys through ShiftRows and MixColumns, and k_0 is zero.
+pub struct Skinny64_64 {
+    permutation: Vec<usize>,
+    mix_columns: Gf2Matrix,
+    round_constants: Vec<Vec<u8>>,
+}
+
+impl Skinny64_64 {
+    pub fn new() -> Self {
+        let permutation = (0..SKINNY64_BLOCKSIZE_IN_BIT)
+            .map(|i| SKINNY_CELLSIZE * SKINNY_SHIFT_ROWS[i / SKINNY_CELLSIZE] + i % SKINNY_CELLSIZE)
+            .collect();
+
+        let rows = (0..SKINNY64_BLOCKSIZE_IN_BIT).map(|i| {
+            let (cell, bit) = (i / SKINNY_CELLSIZE, i % SKINNY_CELLSIZE);
+            let (row, col) = (cell / 4, cell % 4);
+            (0..4).filter(|&src_row| SKINNY_MIX_COLUMNS[row][src_row] == 1)
+                .map(|src_row| SKINNY_CELLSIZE * (4 * src_row + col) + bit)
+                .collect()
+        }).collect();
+        let mix_columns = Gf2Matrix::new(SKINNY64_BLOCKSIZE_IN_BIT, rows);
+
+        let mut cipher = Skinny64_64 {
+            permutation,
+            mix_columns,
+            round_constants: Vec::new(),
+        };
+
+        // 6-bit affine LFSR (rc5, …, rc0) -> (rc4, …, rc0, rc5 + rc4 + 1), updated before each round
+        let mut rc = 0u8;
+        cipher.round_constants = (0..SKINNY64_64_NUM_ROUNDS).map(|r| {
+            rc = ((rc << 1) & 0x3F) | (((rc >> 5) ^ (rc >> 4) ^ 1) & 1);
+
+            let mut cells = [0u8; SKINNY_NUM_CELLS];
+            cells[0] = rc & 0xF;
+            cells[4] = rc >> 4;
+            cells[8] = 0x2;
+            spn_linear_layer(&cipher, r, &skinny_cells_to_bits(&cells))
+        }).collect();
+
+        cipher
+    }
+}
+
+impl Default for Skinny64_64 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SpnCipher for Skinny64_64 {
+    fn block_size(&self) -> usize {
+        SKINNY64_BLOCKSIZE_IN_BIT
+    }
+
+    fn num_rounds(&self) -> usize {
+        SKINNY64_64_NUM_ROUNDS
+    }
+
+    fn sbox(&self) -> &[u8] {
+        &SKINNY_SBOX4
+    }
+
+    fn num_sboxes(&self) -> usize {
+        SKINNY_NUM_CELLS
+    }
+
+    fn bit_permutation(&self, _round: usize) -> Option<&[usize]> {
+        Some(&self.permutation)
+    }
+
+    fn linear_matrix(&self, _round: usize) -> Option<&Gf2Matrix> {
+        Some(&self.mix_columns)
+    }
+
+    fn round_constant(&self, round: usize) -> Vec<u8> {
+        self.round_constants[round].clone()
+    }
+
+    /// The round tweakey is the first two rows of TK1, whose cells are permuted after each round.
+    fn round_keys(&self, key: &[u8]) -> Vec<Vec<u8>> {
+        assert_eq!(key.len(), SKINNY64_64_KEY_LEN_IN_BYTE, "invalid SKINNY-64-64 key length {}", key.len());
+
+        let mut tk = skinny_block_to_cells(key);
+        let mut round_keys = vec![vec![0u8; SKINNY64_BLOCKSIZE_IN_BIT]];
+        for r in 0..SKINNY64_64_NUM_ROUNDS {
+            let mut cells = [0u8; SKINNY_NUM_CELLS];
+            cells[..SKINNY_NUM_CELLS / 2].copy_from_slice(&tk[..SKINNY_NUM_CELLS / 2]);
+            round_keys.push(spn_linear_layer(self, r, &skinny_cells_to_bits(&cells)));
+
+            tk = SKINNY_TWEAKEY_PERM.map(|src| tk[src]);
+        }
+
+        round_keys
+    }
+
+    fn block_to_bits(&self, block: &[u8]) -> Vec<u8> {
+        assert_eq!(block.len(), SKINNY64_BLOCKSIZE_IN_BIT / 8, "invalid SKINNY-64 block length {}", block.len());
+        skinny_cells_to_bits(&skinny_block_to_cells(block))
+    }
+
+    fn bits_to_block(&self, bits: &[u8]) -> Vec<u8> {
+        let cells = bits.chunks_exact(SKINNY_CELLSIZE)
+            .map(|cell| cell.iter().enumerate().fold(0u8, |acc, (j, &bit)| acc | (bit << j)))
+            .collect::<Vec<u8>>();
+        cells.chunks_exact(2).map(|pair| (pair[0] << 4) | pair[1]).collect()
+    }
+}
+
+fn skinny_block_to_cells(block: &[u8]) -> [u8; SKINNY_NUM_CELLS] {
+    let mut cells = [0u8; SKINNY_NUM_CELLS];
+    for (b, &byte) in block.iter().enumerate() {
+        cells[2*b] = byte >> 4;
+        cells[2*b + 1] = byte & 0xF;
+    }
+
+    cells
+}
+
+fn skinny_cells_to_bits(cells: &[u8]) -> Vec<u8> {
+    cells.iter().flat_map(|&cell| (0..SKINNY_CELLSIZE).map(move |j| (cell >> j) & 1)).collect()
+}
+
+/* ======== LowMC ======== */
+
+pub const LOWMC_SBOX: [u8; 8] = [0x0, 0x1, 0x3, 0x6, 0x7, 0x4, 0x5, 0x2];
+
+/// LowMC with a partial S-box layer of 3-bit S-boxes, whose block and key bits are the bits 7 - i of the bytes b
+/// at the index 8b + i, i.e. the blocks are read MSB first as in the reference implementation.
+///
+/// The matrices and constants are sampled from the Grain LFSR as in the LowMC specification: the linear layers,
+/// then the round constants, then the key matrices, with the invertible and full-rank matrices rejection-sampled.
+/// The row i of a matrix is given by the next bits of the columns 0, 1, …, so that LowMC-128-128-10-20 is the
+/// LowMC instance of Picnic-L1.
+pub struct LowMc {
+    block_size: usize,
+    key_size: usize,
+    num_sboxes: usize,
+    num_rounds: usize,
+    linear_matrices: Vec<Gf2Matrix>,
+    round_constants: Vec<Vec<u8>>,
+    key_matrices: Vec<Gf2Matrix>,
+}
+
+impl LowMc {
+    /// The block and key sizes are multiples of 8 of at most 128 bits.
+    pub fn new(block_size: usize, key_size: usize, num_sboxes: usize, num_rounds: usize) -> Self {
+        assert!(block_size.is_multiple_of(8) && block_size <= 128, "invalid LowMC block size {block_size}");
+        assert!(key_size.is_multiple_of(8) && key_size <= 128, "invalid LowMC key size {key_size}");
+        assert!(3 * num_sboxes <= block_size, "too many LowMC S-boxes {num_sboxes}");
+
+        let mut grain = GrainLfsr::new();
+        let linear_matrices = (0..num_rounds)
+            .map(|_| grain.full_rank_matrix(block_size, block_size))
+            .collect::<Vec<_>>();
+        let round_constants = (0..num_rounds)
+            .map(|_| (0..block_size).map(|_| grain.next_random_bit()).collect())
+            .collect();
+        let key_matrices = (0..=num_rounds)
+            .map(|_| grain.full_rank_matrix(block_size, key_size))
+            .collect();
+
+        LowMc {
+            block_size,
+            key_size,
+            num_sboxes,
+            num_rounds,
+            linear_matrices,
+            round_constants,
+            key_matrices,
+        }
+    }
+
+    /// LowMC with 128-bit blocks and keys, 10 S-boxes and 20 rounds.
+    pub fn new_128_128_10_20() -> Self {
+        Self::new(128, 128, 10, 20)
+    }
+
+    pub fn key_size(&self) -> usize {
+        self.key_size
+    }
+}
+
+impl SpnCipher for LowMc {
+    fn block_size(&self) -> usize {
+        self.block_size
+    }
+
+    fn num_rounds(&self) -> usize {
+        self.num_rounds
+    }
+
+    fn sbox(&self) -> &[u8] {
+        &LOWMC_SBOX
+    }
+
+    fn num_sboxes(&self) -> usize {
+        self.num_sboxes
+    }
+
+    fn bit_permutation(&self, _round: usize) -> Option<&[usize]> {
+        None
+    }
+
+    fn linear_matrix(&self, round: usize) -> Option<&Gf2Matrix> {
+        Some(&self.linear_matrices[round])
+    }
+
+    fn round_constant(&self, round: usize) -> Vec<u8> {
+        self.round_constants[round].clone()
+    }
+
+    fn round_keys(&self, key: &[u8]) -> Vec<Vec<u8>> {
+        assert_eq!(8 * key.len(), self.key_size, "invalid LowMC key length {}", key.len());
+        let key_bits = bytes_to_bits(key);
+        self.key_matrices.iter().map(|key_matrix| key_matrix.mul_bits(&key_bits)).collect()
+    }
+
+    fn block_to_bits(&self, block: &[u8]) -> Vec<u8> {
+        assert_eq!(8 * block.len(), self.block_size, "invalid LowMC block length {}", block.len());
+        bytes_to_bits(block)
+    }
+
+    fn bits_to_block(&self, bits: &[u8]) -> Vec<u8> {
+        bits.chunks(8).map(|byte| byte.iter().fold(0u8, |acc, &bit| (acc << 1) | bit)).collect()
+    }
+}
+
+fn bytes_to_bits(bytes: &[u8]) -> Vec<u8> {
+    bytes.iter().flat_map(|&byte| (0..8).rev().map(move |i| (byte >> i) & 1)).collect()
+}
+
+/// Grain LFSR of the LowMC instance generation, whose 80-bit state is initialized to all ones.
+struct GrainLfsr {
+    state: [u8; 80],
+}
+
+impl GrainLfsr {
+    fn new() -> Self {
+        let mut grain = GrainLfsr {
+            state: [1u8; 80],
+        };
+        for _ in 0..160 {
+            grain.next_bit();
+        }
+
+        grain
+    }
+
+    fn next_bit(&mut self) -> u8 {
+        let s = &self.state;
+        let bit = s[0] ^ s[13] ^ s[23] ^ s[38] ^ s[51] ^ s[62];
+        self.state.rotate_left(1);
+        self.state[79] = bit;
+
+        bit
+    }
+
+    /// Output bit kept only if the preceding choice bit is one.
+    fn next_random_bit(&mut self) -> u8 {
+        loop {
+            let choice = self.next_bit();
+            let bit = self.next_bit();
+            if choice == 1 {
+                return bit;
+            }
+        }
+    }
+
+    fn full_rank_matrix(&mut self, num_rows: usize, num_cols: usize) -> Gf2Matrix {
+        loop {
+            let dense = (0..num_rows)
+                .map(|_| (0..num_cols).map(|_| self.next_random_bit()).collect::<Vec<u8>>())
+                .collect::<Vec<_>>();
+            if gf2_rank(&dense) == num_rows.min(num_cols) {
+                return Gf2Matrix::from_dense(&dense);
+            }
+        }
+    }
+}
+
+/// Rank of a GF(2) matrix of at most 128 columns by Gaussian elimination.
+fn gf2_rank(dense: &[Vec<u8>]) -> usize {
+    let mut rows = dense.iter()
+        .map(|row| row.iter().enumerate().fold(0u128, |acc, (col, &bit)| acc | ((bit as u128) << col)))
+        .collect::<Vec<u128>>();
+
+    let mut rank = 0;
+    for col in 0..u128::BITS {
+        let Some(pivot) = (rank..rows.len()).find(|&i| (rows[i] >> col) & 1 == 1) else {
+            continue;
+        };
+        rows.swap(rank, pivot);
+        for i in 0..rows.len() {
+            if i != rank && (rows[i] >> col) & 1 == 1 {
+                rows[i] ^= rows[rank];
+            }
+        }
+        rank += 1;
+    }
+
+    rank
+}
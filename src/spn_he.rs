@@ -0,0 +1,260 @@
+//! Homomorphic evaluation of the SPN ciphers of [`crate::spn_ref`] by patched WWL+ circuit bootstrapping.
+use tfhe::core_crypto::prelude::*;
+use crate::{aes_he::*, cbs_keys::BitCbsServerKey, pbs::lwe_msb_bit_refresh, spn_ref::*, utils::get_val_and_bit_and_abs_err};
+
+/// Step of the homomorphic SPN evaluation after which the hook of [`HeSpn::encrypt_block_with_hook`] is called.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HeSpnStep {
+    AddRoundKey,
+    /// The state is under the LWE key after keyswitching.
+    LweKeyswitch,
+    SubstitutionLayer,
+    LinearLayer,
+    AddRoundConstant,
+}
+
+/// Homomorphic encryption of an SPN cipher, where each S-box layer is preceded by the LWE keyswitching by
+/// GLWE dimension switching. The S-boxes are evaluated by CMux trees on the CBS outputs, and the bits outside
+/// the S-boxes are refreshed by a PBS so that the noise of the linear layers does not grow over the rounds.
+pub struct HeSpn<'a, Scalar: UnsignedTorus, C: SpnCipher> {
+    server_key: &'a BitCbsServerKey<Scalar>,
+    cipher: &'a C,
+    he_round_keys: Vec<LweCiphertextListOwned<Scalar>>,
+}
+
+impl<'a, Scalar, C> HeSpn<'a, Scalar, C>
+where
+    Scalar: UnsignedTorus + CastInto<usize> + CastFrom<usize>,
+    C: SpnCipher,
+{
+    /// The round keys are given by [`encrypt_spn_round_keys`].
+    pub fn new(server_key: &'a BitCbsServerKey<Scalar>, cipher: &'a C, he_round_keys: Vec<LweCiphertextListOwned<Scalar>>) -> Self {
+        let lwe_size = server_key.fourier_bsk().output_lwe_dimension().to_lwe_size();
+        assert_eq!(he_round_keys.len(), cipher.num_rounds() + 1, "invalid number of round keys {}", he_round_keys.len());
+        for he_round_key in he_round_keys.iter() {
+            assert_eq!(he_round_key.entity_count(), cipher.block_size());
+            assert_eq!(he_round_key.lwe_size(), lwe_size);
+        }
+
+        HeSpn {
+            server_key,
+            cipher,
+            he_round_keys,
+        }
+    }
+
+    pub fn server_key(&self) -> &'a BitCbsServerKey<Scalar> {
+        self.server_key
+    }
+
+    pub fn cipher(&self) -> &'a C {
+        self.cipher
+    }
+
+    pub fn he_round_keys(&self) -> &[LweCiphertextListOwned<Scalar>] {
+        &self.he_round_keys
+    }
+
+    /// Encrypts the state in place.
+    pub fn encrypt_block<Cont>(&self, he_state: &mut LweCiphertextList<Cont>)
+    where
+        Cont: ContainerMut<Element=Scalar>,
+    {
+        self.encrypt_block_with_hook(he_state, |_, _, _| {});
+    }
+
+    /// Encrypts the state in place, calling `hook` with the round and the state after each step.
+    /// The rounds go from 1 to `num_rounds()`, and the initial key addition is in the round 0.
+    pub fn encrypt_block_with_hook<Cont, F>(&self, he_state: &mut LweCiphertextList<Cont>, mut hook: F)
+    where
+        Cont: ContainerMut<Element=Scalar>,
+        F: FnMut(usize, HeSpnStep, LweCiphertextListView<Scalar>),
+    {
+        let block_size = self.cipher.block_size();
+        let sbox_bits = self.cipher.num_sboxes() * self.cipher.sbox_size();
+        assert_eq!(he_state.entity_count(), block_size);
+        let mut he_state_ks = new_lwe_ciphertext_list_after_ks(self.server_key, block_size, he_state.ciphertext_modulus());
+
+        he_add_round_key(he_state, &self.he_round_keys[0]);
+        hook(0, HeSpnStep::AddRoundKey, he_state.as_view());
+
+        for r in 1..=self.cipher.num_rounds() {
+            keyswitch_lwe_ciphertext_list(he_state, &mut he_state_ks, self.server_key);
+            hook(r, HeSpnStep::LweKeyswitch, he_state_ks.as_view());
+
+            he_sub_bytes_by_table(
+                &he_state_ks.get_sub(0..sbox_bits),
+                &mut he_state.get_sub_mut(0..sbox_bits),
+                self.server_key.fourier_bsk(),
+                self.server_key.auto_keys(),
+                self.server_key.ss_key(),
+                self.server_key.cbs_acc(),
+                self.cipher.sbox(),
+            );
+            for bit_idx in sbox_bits..block_size {
+                let lwe_ks = he_state_ks.get(bit_idx);
+                let mut lwe = he_state.get_mut(bit_idx);
+                if decode_public_bit(&lwe).is_none() {
+                    lwe_msb_bit_refresh(&lwe_ks, &mut lwe, self.server_key.fourier_bsk());
+                }
+            }
+            hook(r, HeSpnStep::SubstitutionLayer, he_state.as_view());
+
+            he_spn_linear_layer(self.cipher, r - 1, he_state);
+            hook(r, HeSpnStep::LinearLayer, he_state.as_view());
+
+            he_add_public_bits(he_state, &self.cipher.round_constant(r - 1));
+            hook(r, HeSpnStep::AddRoundConstant, he_state.as_view());
+
+            he_add_round_key(he_state, &self.he_round_keys[r]);
+            hook(r, HeSpnStep::AddRoundKey, he_state.as_view());
+        }
+    }
+}
+
+/// Applies the linear layer of the round `round` by LWE additions only.
+pub fn he_spn_linear_layer<Scalar, Cont, C>(cipher: &C, round: usize, he_state: &mut LweCiphertextList<Cont>)
+where
+    Scalar: UnsignedInteger,
+    Cont: ContainerMut<Element=Scalar>,
+    C: SpnCipher + ?Sized,
+{
+    if let Some(permutation) = cipher.bit_permutation(round) {
+        let he_input = LweCiphertextList::from_container(he_state.as_ref().to_vec(), he_state.lwe_size(), he_state.ciphertext_modulus());
+        for (mut lwe_out, &src) in he_state.iter_mut().zip(permutation.iter()) {
+            lwe_out.as_mut().copy_from_slice(he_input.get(src).as_ref());
+        }
+    }
+
+    if let Some(matrix) = cipher.linear_matrix(round) {
+        let he_input = LweCiphertextList::from_container(he_state.as_ref().to_vec(), he_state.lwe_size(), he_state.ciphertext_modulus());
+        for (mut lwe_out, row) in he_state.iter_mut().zip(matrix.rows().iter()) {
+            lwe_out.as_mut().fill(Scalar::ZERO);
+            for &col in row.iter() {
+                lwe_ciphertext_add_assign(&mut lwe_out, &he_input.get(col));
+            }
+        }
+    }
+}
+
+/// Adds public bits, e.g. round constants, to the bodies of the state.
+pub fn he_add_public_bits<Scalar, Cont>(he_state: &mut LweCiphertextList<Cont>, bits: &[u8])
+where
+    Scalar: UnsignedInteger,
+    Cont: ContainerMut<Element=Scalar>,
+{
+    for (mut lwe, &bit) in he_state.iter_mut().zip(bits.iter()) {
+        if bit & 1 == 1 {
+            let body = lwe.get_mut_body().data;
+            *body = body.wrapping_add(Scalar::ONE << (Scalar::BITS - 1));
+        }
+    }
+}
+
+/// Encrypts the canonical round keys of an SPN cipher under the LWE key of the bootstrapping output.
+pub fn encrypt_spn_round_keys<Scalar, KeyCont, Gen, C>(
+    lwe_sk: &LweSecretKey<KeyCont>,
+    cipher: &C,
+    key: &[u8],
+    noise_parameters: impl DispersionParameter,
+    ciphertext_modulus: CiphertextModulus<Scalar>,
+    encryption_generator: &mut EncryptionRandomGenerator<Gen>,
+) -> Vec<LweCiphertextListOwned<Scalar>>
+where
+    Scalar: UnsignedTorus,
+    KeyCont: Container<Element=Scalar>,
+    Gen: ByteRandomGenerator,
+    C: SpnCipher + ?Sized,
+{
+    cipher.round_keys(key).iter()
+        .map(|round_key| encrypt_spn_bits(lwe_sk, round_key, noise_parameters, ciphertext_modulus, encryption_generator))
+        .collect()
+}
+
+/// Encrypts state bits under the LWE key of the bootstrapping output.
+pub fn encrypt_spn_bits<Scalar, KeyCont, Gen>(
+    lwe_sk: &LweSecretKey<KeyCont>,
+    bits: &[u8],
+    noise_parameters: impl DispersionParameter,
+    ciphertext_modulus: CiphertextModulus<Scalar>,
+    encryption_generator: &mut EncryptionRandomGenerator<Gen>,
+) -> LweCiphertextListOwned<Scalar>
+where
+    Scalar: UnsignedTorus,
+    KeyCont: Container<Element=Scalar>,
+    Gen: ByteRandomGenerator,
+{
+    let mut he_bits = LweCiphertextList::new(
+        Scalar::ZERO,
+        lwe_sk.lwe_dimension().to_lwe_size(),
+        LweCiphertextCount(bits.len()),
+        ciphertext_modulus,
+    );
+    encrypt_lwe_ciphertext_list(
+        lwe_sk,
+        &mut he_bits,
+        &encode_spn_bits::<Scalar>(bits),
+        noise_parameters,
+        encryption_generator,
+    );
+
+    he_bits
+}
+
+/// Trivially encrypts state bits, e.g. a public plaintext block.
+pub fn trivially_encrypt_spn_bits<Scalar: UnsignedTorus>(
+    bits: &[u8],
+    lwe_size: LweSize,
+    ciphertext_modulus: CiphertextModulus<Scalar>,
+) -> LweCiphertextListOwned<Scalar> {
+    let mut he_bits = LweCiphertextList::new(Scalar::ZERO, lwe_size, LweCiphertextCount(bits.len()), ciphertext_modulus);
+    he_add_public_bits(&mut he_bits, bits);
+
+    he_bits
+}
+
+pub fn decrypt_spn_bits<Scalar, ListCont, KeyCont>(
+    lwe_sk: &LweSecretKey<KeyCont>,
+    he_bits: &LweCiphertextList<ListCont>,
+) -> Vec<u8>
+where
+    Scalar: UnsignedTorus,
+    ListCont: Container<Element=Scalar>,
+    KeyCont: Container<Element=Scalar>,
+{
+    he_bits.iter().map(|he_bit| {
+        let decoded = decrypt_lwe_ciphertext(lwe_sk, &he_bit).0;
+        (decoded.wrapping_add(Scalar::ONE << (Scalar::BITS - 2)) >> (Scalar::BITS - 1)) == Scalar::ONE
+    }).map(|bit| bit as u8).collect()
+}
+
+/// Maximum absolute error of the state bits.
+pub fn get_he_spn_state_error<Scalar, StateCont, SkCont>(
+    he_state: &LweCiphertextList<StateCont>,
+    plain_bits: &[u8],
+    lwe_sk: &LweSecretKey<SkCont>,
+) -> Scalar
+where
+    Scalar: UnsignedInteger + CastFrom<u8>,
+    StateCont: Container<Element=Scalar>,
+    SkCont: Container<Element=Scalar>,
+{
+    let mut max_err = Scalar::ZERO;
+    for (&correct_val, he_bit) in plain_bits.iter().zip(he_state.iter()) {
+        let (_decoded, _bit_err, abs_err) = get_val_and_bit_and_abs_err(
+            lwe_sk,
+            &he_bit,
+            Scalar::cast_from(correct_val),
+            Scalar::ONE << (Scalar::BITS - 1),
+        );
+        max_err = std::cmp::max(max_err, abs_err);
+    }
+
+    max_err
+}
+
+fn encode_spn_bits<Scalar: UnsignedTorus>(bits: &[u8]) -> PlaintextListOwned<Scalar> {
+    PlaintextList::from_container(bits.iter().map(|&bit| {
+        if bit & 1 == 1 {Scalar::ONE << (Scalar::BITS - 1)} else {Scalar::ZERO}
+    }).collect::<Vec<Scalar>>())
+}
@@ -0,0 +1,158 @@
+//! Generic substitution-permutation networks for the homomorphic evaluation by CBS.
+//!
+//! A cipher is given in the canonical form
+//!
+//! ```text
+//! x_0     = m + k_0
+//! x_{r+1} = L_r(S(x_r)) + c_r + k_{r+1},  r = 0, …, R - 1
+//! ```
+//!
+//! where S applies the S-box to the first `num_sboxes` chunks of `sbox_size` bits and leaves the other bits
+//! unchanged, L_r is a bit permutation followed by a GF(2)-linear map, c_r is a public round constant and
+//! k_r is a round key. The bit j of an S-box chunk is the bit j of the S-box input, from the LSB.
+//! Ciphers that add their round keys or constants before the linear layer give them through L_r.
+
+/// Sparse GF(2) matrix, where each output bit is the XOR of the input bits listed in its row.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Gf2Matrix {
+    num_cols: usize,
+    rows: Vec<Vec<usize>>,
+}
+
+impl Gf2Matrix {
+    /// Panics if a column index is out of range.
+    pub fn new(num_cols: usize, rows: Vec<Vec<usize>>) -> Self {
+        for row in rows.iter() {
+            assert!(row.iter().all(|&col| col < num_cols), "column index out of range");
+        }
+
+        Gf2Matrix {
+            num_cols,
+            rows,
+        }
+    }
+
+    /// `dense[i][j]` is the entry of the row i and column j.
+    pub fn from_dense(dense: &[Vec<u8>]) -> Self {
+        let num_cols = dense.first().map_or(0, |row| row.len());
+        let rows = dense.iter().map(|row| {
+            assert_eq!(row.len(), num_cols);
+            row.iter().enumerate().filter_map(|(col, &entry)| (entry & 1 != 0).then_some(col)).collect()
+        }).collect();
+
+        Gf2Matrix {
+            num_cols,
+            rows,
+        }
+    }
+
+    pub fn num_rows(&self) -> usize {
+        self.rows.len()
+    }
+
+    pub fn num_cols(&self) -> usize {
+        self.num_cols
+    }
+
+    pub fn rows(&self) -> &[Vec<usize>] {
+        &self.rows
+    }
+
+    /// Largest number of input bits summed into an output bit.
+    pub fn max_row_weight(&self) -> usize {
+        self.rows.iter().map(Vec::len).max().unwrap_or(0)
+    }
+
+    pub fn mul_bits(&self, bits: &[u8]) -> Vec<u8> {
+        assert_eq!(bits.len(), self.num_cols);
+        self.rows.iter().map(|row| row.iter().fold(0u8, |acc, &col| acc ^ bits[col])).collect()
+    }
+}
+
+/// Public description of an SPN cipher in the canonical form of this module, with its key schedule in the clear.
+pub trait SpnCipher {
+    /// Block size in bits.
+    fn block_size(&self) -> usize;
+
+    fn num_rounds(&self) -> usize;
+
+    /// S-box table, whose size is 2^sbox_size.
+    fn sbox(&self) -> &[u8];
+
+    /// Number of S-boxes applied on the first bits of the state.
+    fn num_sboxes(&self) -> usize;
+
+    /// Bit permutation of the round `round`, where the output bit i is the input bit `permutation[i]`.
+    fn bit_permutation(&self, round: usize) -> Option<&[usize]>;
+
+    /// GF(2)-linear map of the round `round`, applied after the bit permutation.
+    fn linear_matrix(&self, round: usize) -> Option<&Gf2Matrix>;
+
+    /// Public constant c_r added after the linear layer of the round `round`.
+    fn round_constant(&self, round: usize) -> Vec<u8>;
+
+    /// Round keys k_0, …, k_R of the canonical form as bits.
+    fn round_keys(&self, key: &[u8]) -> Vec<Vec<u8>>;
+
+    /// State bits of a block given as bytes.
+    fn block_to_bits(&self, block: &[u8]) -> Vec<u8>;
+
+    fn bits_to_block(&self, bits: &[u8]) -> Vec<u8>;
+
+    fn sbox_size(&self) -> usize {
+        self.sbox().len().ilog2() as usize
+    }
+}
+
+/// Applies the S-box layer S to the state bits.
+pub fn spn_sbox_layer<C: SpnCipher + ?Sized>(cipher: &C, bits: &[u8]) -> Vec<u8> {
+    let sbox = cipher.sbox();
+    let sbox_size = cipher.sbox_size();
+
+    let mut output = bits.to_vec();
+    for chunk in output.chunks_exact_mut(sbox_size).take(cipher.num_sboxes()) {
+        let input = chunk.iter().enumerate().fold(0usize, |acc, (j, &bit)| acc | ((bit as usize) << j));
+        for (j, bit) in chunk.iter_mut().enumerate() {
+            *bit = (sbox[input] >> j) & 1;
+        }
+    }
+
+    output
+}
+
+/// Applies the linear layer L_r to the state bits.
+pub fn spn_linear_layer<C: SpnCipher + ?Sized>(cipher: &C, round: usize, bits: &[u8]) -> Vec<u8> {
+    let mut output = match cipher.bit_permutation(round) {
+        Some(permutation) => permutation.iter().map(|&src| bits[src]).collect(),
+        None => bits.to_vec(),
+    };
+    if let Some(matrix) = cipher.linear_matrix(round) {
+        output = matrix.mul_bits(&output);
+    }
+
+    output
+}
+
+/// Encryption of the state bits in the canonical form with the round keys of [`SpnCipher::round_keys`].
+pub fn spn_encrypt_bits<C: SpnCipher + ?Sized>(cipher: &C, round_keys: &[Vec<u8>], bits: &[u8]) -> Vec<u8> {
+    assert_eq!(round_keys.len(), cipher.num_rounds() + 1);
+    assert_eq!(bits.len(), cipher.block_size());
+
+    let xor = |lhs: &mut Vec<u8>, rhs: &[u8]| lhs.iter_mut().zip(rhs.iter()).for_each(|(l, r)| *l ^= r);
+
+    let mut state = bits.to_vec();
+    xor(&mut state, &round_keys[0]);
+    for r in 0..cipher.num_rounds() {
+        state = spn_linear_layer(cipher, r, &spn_sbox_layer(cipher, &state));
+        xor(&mut state, &cipher.round_constant(r));
+        xor(&mut state, &round_keys[r + 1]);
+    }
+
+    state
+}
+
+/// Encryption of a block given as bytes in the canonical form.
+pub fn spn_encrypt_block<C: SpnCipher + ?Sized>(cipher: &C, key: &[u8], block: &[u8]) -> Vec<u8> {
+    let bits = spn_encrypt_bits(cipher, &cipher.round_keys(key), &cipher.block_to_bits(block));
+    cipher.bits_to_block(&bits)
+}
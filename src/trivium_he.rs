@@ -0,0 +1,317 @@
+//! Homomorphic Trivium and Kreyvium keystream generation by patched WWL+ circuit bootstrapping.
+use tfhe::core_crypto::{
+    prelude::*,
+    fft_impl::fft64::{c64, crypto::ggsw::FourierGgswCiphertext},
+};
+use crate::{aes_he::*, cbs_keys::BitCbsServerKey, spn_he::{he_add_public_bits, trivially_encrypt_spn_bits}, trivium_ref::*};
+
+/// Number of steps evaluated at once. A feedback bit is used at the earliest 66 steps after it is computed,
+/// so the feedback bits of 64 steps only depend on the state before them.
+pub const TRIVIUM_MAX_PARALLEL_STEPS: usize = 64;
+
+/// Table of t = a + b + c * d + e indexed by (e, d, c, b, a) from the MSB.
+const TRIVIUM_FEEDBACK_TABLE: [u8; 32] = {
+    let mut table = [0u8; 32];
+    let mut idx = 0;
+    while idx < 32 {
+        table[idx] = ((idx & 1) ^ ((idx >> 1) & 1) ^ (((idx >> 2) & 1) & ((idx >> 3) & 1)) ^ ((idx >> 4) & 1)) as u8;
+        idx += 1;
+    }
+    table
+};
+
+/// Homomorphic Trivium, whose state bits are LWE encryptions under the key of the bootstrapping output.
+///
+/// The feedback bits are evaluated as 5-bit tables by CMux trees on the CBS outputs of the state bits, so every
+/// state bit is a fresh table output and the keystream bits are the sums of six of them.
+pub struct HeTrivium<'a, Scalar: UnsignedTorus> {
+    server_key: &'a BitCbsServerKey<Scalar>,
+    he_state: LweCiphertextListOwned<Scalar>,
+}
+
+impl<'a, Scalar> HeTrivium<'a, Scalar>
+where
+    Scalar: UnsignedTorus + CastInto<usize> + CastFrom<usize>,
+{
+    /// Loads the key given by [`encrypt_bytes`] and the public IV, and runs the initialization.
+    pub fn new<Cont>(server_key: &'a BitCbsServerKey<Scalar>, he_key: &LweCiphertextList<Cont>, iv: &[u8]) -> Self
+    where
+        Cont: Container<Element=Scalar>,
+    {
+        assert_eq!(he_key.entity_count(), 8 * TRIVIUM_KEY_LEN_IN_BYTE, "invalid Trivium key length {}", he_key.entity_count());
+        assert_eq!(iv.len(), TRIVIUM_IV_LEN_IN_BYTE, "invalid Trivium IV length {}", iv.len());
+
+        // The key bits go to (s1, …, s80) as in trivium_initial_state
+        let mut he_state = trivially_encrypt_spn_bits(
+            &trivium_initial_state(&[0u8; TRIVIUM_KEY_LEN_IN_BYTE], iv),
+            he_key.lwe_size(),
+            he_key.ciphertext_modulus(),
+        );
+        for (m, he_key_bit) in he_key.iter().enumerate() {
+            he_state.get_mut(he_key.entity_count() - 1 - m).as_mut().copy_from_slice(he_key_bit.as_ref());
+        }
+
+        let mut he_trivium = HeTrivium {
+            server_key,
+            he_state,
+        };
+        for num_steps in steps_in_batches(TRIVIUM_NUM_INIT_STEPS) {
+            he_trivium_clock(server_key, &mut he_trivium.he_state, num_steps, None, &[]);
+        }
+
+        he_trivium
+    }
+
+    pub fn server_key(&self) -> &'a BitCbsServerKey<Scalar> {
+        self.server_key
+    }
+
+    /// Encryptions of the next keystream bits.
+    pub fn keystream(&mut self, num_bits: usize) -> LweCiphertextListOwned<Scalar> {
+        let mut he_keystream = LweCiphertextList::new(
+            Scalar::ZERO,
+            self.he_state.lwe_size(),
+            LweCiphertextCount(num_bits),
+            self.he_state.ciphertext_modulus(),
+        );
+
+        let mut bit_idx = 0;
+        for num_steps in steps_in_batches(num_bits) {
+            let he_batch = he_trivium_clock(self.server_key, &mut self.he_state, num_steps, None, &[]);
+            he_keystream.get_sub_mut(bit_idx..bit_idx + num_steps).as_mut().copy_from_slice(he_batch.as_ref());
+            bit_idx += num_steps;
+        }
+
+        he_keystream
+    }
+
+    /// Converts a ciphertext into the LWE encryptions of the plaintext bits, where bit i of byte b is at index 8b + i,
+    /// by adding the trivial encryptions of the ciphertext bits to the next keystream bits.
+    pub fn transcipher(&mut self, ciphertext: &[u8]) -> LweCiphertextListOwned<Scalar> {
+        let mut he_plaintext = self.keystream(8 * ciphertext.len());
+        add_public_bytes(&mut he_plaintext, ciphertext);
+
+        he_plaintext
+    }
+}
+
+/// Homomorphic Kreyvium, evaluated as [`HeTrivium`] where the encrypted key bits and the public IV bits of the
+/// rotating registers are added to the feedback and keystream bits.
+pub struct HeKreyvium<'a, Scalar: UnsignedTorus> {
+    server_key: &'a BitCbsServerKey<Scalar>,
+    he_state: LweCiphertextListOwned<Scalar>,
+    he_key: LweCiphertextListOwned<Scalar>,
+    iv_bits: Vec<u8>,
+    num_steps: usize,
+}
+
+impl<'a, Scalar> HeKreyvium<'a, Scalar>
+where
+    Scalar: UnsignedTorus + CastInto<usize> + CastFrom<usize>,
+{
+    /// Loads the key given by [`encrypt_bytes`] and the public IV, and runs the initialization.
+    pub fn new<Cont>(server_key: &'a BitCbsServerKey<Scalar>, he_key: &LweCiphertextList<Cont>, iv: &[u8]) -> Self
+    where
+        Cont: Container<Element=Scalar>,
+    {
+        let key_len = 8 * KREYVIUM_KEY_LEN_IN_BYTE;
+        assert_eq!(he_key.entity_count(), key_len, "invalid Kreyvium key length {}", he_key.entity_count());
+        assert_eq!(iv.len(), KREYVIUM_IV_LEN_IN_BYTE, "invalid Kreyvium IV length {}", iv.len());
+
+        // The key bits (127, …, 35) go to (s1, …, s93) as in kreyvium_initial_state
+        let mut he_state = trivially_encrypt_spn_bits(
+            &kreyvium_initial_state(&[0u8; KREYVIUM_KEY_LEN_IN_BYTE], iv),
+            he_key.lwe_size(),
+            he_key.ciphertext_modulus(),
+        );
+        for j in 0..93 {
+            he_state.get_mut(j).as_mut().copy_from_slice(he_key.get(key_len - 1 - j).as_ref());
+        }
+
+        let mut he_kreyvium = HeKreyvium {
+            server_key,
+            he_state,
+            he_key: LweCiphertextList::from_container(he_key.as_ref().to_vec(), he_key.lwe_size(), he_key.ciphertext_modulus()),
+            iv_bits: bytes_to_bits(iv),
+            num_steps: 0,
+        };
+        for num_steps in steps_in_batches(TRIVIUM_NUM_INIT_STEPS) {
+            he_kreyvium.clock(num_steps);
+        }
+
+        he_kreyvium
+    }
+
+    pub fn server_key(&self) -> &'a BitCbsServerKey<Scalar> {
+        self.server_key
+    }
+
+    /// Encryptions of the next keystream bits.
+    pub fn keystream(&mut self, num_bits: usize) -> LweCiphertextListOwned<Scalar> {
+        let mut he_keystream = LweCiphertextList::new(
+            Scalar::ZERO,
+            self.he_state.lwe_size(),
+            LweCiphertextCount(num_bits),
+            self.he_state.ciphertext_modulus(),
+        );
+
+        let mut bit_idx = 0;
+        for num_steps in steps_in_batches(num_bits) {
+            let he_batch = self.clock(num_steps);
+            he_keystream.get_sub_mut(bit_idx..bit_idx + num_steps).as_mut().copy_from_slice(he_batch.as_ref());
+            bit_idx += num_steps;
+        }
+
+        he_keystream
+    }
+
+    /// Converts a ciphertext into the LWE encryptions of the plaintext bits, where bit i of byte b is at index 8b + i,
+    /// by adding the trivial encryptions of the ciphertext bits to the next keystream bits.
+    pub fn transcipher(&mut self, ciphertext: &[u8]) -> LweCiphertextListOwned<Scalar> {
+        let mut he_plaintext = self.keystream(8 * ciphertext.len());
+        add_public_bytes(&mut he_plaintext, ciphertext);
+
+        he_plaintext
+    }
+
+    /// The step t uses the key bit and the IV bit 127 - (t mod 128) as in [`KreyviumRef`].
+    fn clock(&mut self, num_steps: usize) -> LweCiphertextListOwned<Scalar> {
+        let key_len = self.he_key.entity_count();
+        let mut he_key_bits = LweCiphertextList::new(
+            Scalar::ZERO,
+            self.he_key.lwe_size(),
+            LweCiphertextCount(num_steps),
+            self.he_key.ciphertext_modulus(),
+        );
+        let mut iv_bits = Vec::with_capacity(num_steps);
+        for (j, mut he_key_bit) in he_key_bits.iter_mut().enumerate() {
+            let reg_idx = key_len - 1 - (self.num_steps + j) % key_len;
+            he_key_bit.as_mut().copy_from_slice(self.he_key.get(reg_idx).as_ref());
+            iv_bits.push(self.iv_bits[reg_idx]);
+        }
+        self.num_steps += num_steps;
+
+        he_trivium_clock(self.server_key, &mut self.he_state, num_steps, Some(he_key_bits.as_view()), &iv_bits)
+    }
+}
+
+/// Runs `num_steps` steps at once on the state and returns the encryptions of the keystream bits.
+///
+/// For Kreyvium, the step j adds the key bit `he_key_bits[j]` to t3 and to the keystream bit and the IV bit
+/// `iv_bits[j]` to t1.
+fn he_trivium_clock<Scalar, Cont>(
+    server_key: &BitCbsServerKey<Scalar>,
+    he_state: &mut LweCiphertextList<Cont>,
+    num_steps: usize,
+    he_key_bits: Option<LweCiphertextListView<Scalar>>,
+    iv_bits: &[u8],
+) -> LweCiphertextListOwned<Scalar>
+where
+    Scalar: UnsignedTorus + CastInto<usize> + CastFrom<usize>,
+    Cont: ContainerMut<Element=Scalar>,
+{
+    assert!(num_steps <= TRIVIUM_MAX_PARALLEL_STEPS);
+    let lwe_size = he_state.lwe_size();
+    let ciphertext_modulus = he_state.ciphertext_modulus();
+
+    // The tap s_p of the step j is the state bit s_{p-j} before the steps, at the index p - 1 - j
+    let mut is_cbs_input = [false; TRIVIUM_STATE_SIZE];
+    for (taps, _) in TRIVIUM_FEEDBACKS.iter() {
+        for &p in taps.iter() {
+            is_cbs_input[p - num_steps..p].fill(true);
+        }
+    }
+    let public_bits = he_state.iter().map(|lwe| decode_public_bit(&lwe)).collect::<Vec<_>>();
+    let encrypted_bits = (0..TRIVIUM_STATE_SIZE)
+        .filter(|&idx| is_cbs_input[idx] && public_bits[idx].is_none())
+        .collect::<Vec<usize>>();
+
+    let mut he_encrypted_bits = LweCiphertextList::new(Scalar::ZERO, lwe_size, LweCiphertextCount(encrypted_bits.len()), ciphertext_modulus);
+    for (mut dst, &idx) in he_encrypted_bits.iter_mut().zip(encrypted_bits.iter()) {
+        dst.as_mut().copy_from_slice(he_state.get(idx).as_ref());
+    }
+    let mut he_encrypted_bits_ks = new_lwe_ciphertext_list_after_ks(server_key, encrypted_bits.len(), ciphertext_modulus);
+    keyswitch_lwe_ciphertext_list(&he_encrypted_bits, &mut he_encrypted_bits_ks, server_key);
+    let fourier_ggsw_encrypted_bits = circuit_bootstrap_bits(
+        &he_encrypted_bits_ks,
+        server_key.fourier_bsk(),
+        server_key.auto_keys(),
+        server_key.ss_key(),
+        server_key.cbs_acc(),
+    );
+
+    let mut fourier_ggsw_bits: Vec<Option<FourierGgswCiphertext<&[c64]>>> = vec![None; TRIVIUM_STATE_SIZE];
+    for (&idx, fourier_ggsw) in encrypted_bits.iter().zip(fourier_ggsw_encrypted_bits.as_view().into_ggsw_iter()) {
+        fourier_ggsw_bits[idx] = Some(fourier_ggsw);
+    }
+    let offset_bits = public_bits.iter().map(|public_bit| public_bit.unwrap_or(0)).collect::<Vec<u8>>();
+
+    // Keystream bits as sums of the output taps
+    let mut he_keystream = LweCiphertextList::new(Scalar::ZERO, lwe_size, LweCiphertextCount(num_steps), ciphertext_modulus);
+    for (j, mut he_z) in he_keystream.iter_mut().enumerate() {
+        for &p in TRIVIUM_OUTPUT_TAPS.iter() {
+            lwe_ciphertext_add_assign(&mut he_z, &he_state.get(p - 1 - j));
+        }
+        if let Some(he_key_bits) = he_key_bits.as_ref() {
+            lwe_ciphertext_add_assign(&mut he_z, &he_key_bits.get(j));
+        }
+    }
+
+    // Feedback bits, where t_k of the step j is at the index j of the k-th list
+    let he_feedbacks = TRIVIUM_FEEDBACKS.iter().enumerate().map(|(k, (taps, _))| {
+        let mut he_t = LweCiphertextList::new(Scalar::ZERO, lwe_size, LweCiphertextCount(num_steps), ciphertext_modulus);
+        for (j, mut he_t_j) in he_t.chunks_exact_mut(1).enumerate() {
+            let input_idx = taps.map(|p| p - 1 - j);
+            he_eval_lut_by_cmux_tree(
+                &input_idx.map(|idx| fourier_ggsw_bits[idx]),
+                &input_idx.map(|idx| offset_bits[idx]),
+                &TRIVIUM_FEEDBACK_TABLE,
+                &mut he_t_j,
+            );
+        }
+
+        if k == 0 {
+            he_add_public_bits(&mut he_t, iv_bits);
+        }
+        if k == 2 {
+            if let Some(he_key_bits) = he_key_bits.as_ref() {
+                for (mut he_t_j, he_key_bit) in he_t.iter_mut().zip(he_key_bits.iter()) {
+                    lwe_ciphertext_add_assign(&mut he_t_j, &he_key_bit);
+                }
+            }
+        }
+
+        he_t
+    }).collect::<Vec<_>>();
+
+    // Shift the registers by num_steps, the feedback bit of the last step being the first one
+    for ((_, (start, end)), he_t) in TRIVIUM_FEEDBACKS.iter().zip(he_feedbacks.iter()) {
+        let lwe_size = lwe_size.0;
+        let register = &mut he_state.as_mut()[(start - 1) * lwe_size..end * lwe_size];
+        register.copy_within(0..register.len() - num_steps * lwe_size, num_steps * lwe_size);
+        for (j, he_t_j) in he_t.iter().enumerate() {
+            let dst = num_steps - 1 - j;
+            register[dst * lwe_size..(dst + 1) * lwe_size].copy_from_slice(he_t_j.as_ref());
+        }
+    }
+
+    he_keystream
+}
+
+fn steps_in_batches(num_steps: usize) -> impl Iterator<Item = usize> {
+    (0..num_steps).step_by(TRIVIUM_MAX_PARALLEL_STEPS)
+        .map(move |step| TRIVIUM_MAX_PARALLEL_STEPS.min(num_steps - step))
+}
+
+/// Adds the trivial encryptions of the bits of a public byte string, where bit i of byte b is at index 8b + i.
+fn add_public_bytes<Scalar, Cont>(he_bits: &mut LweCiphertextList<Cont>, bytes: &[u8])
+where
+    Scalar: UnsignedInteger,
+    Cont: ContainerMut<Element=Scalar>,
+{
+    he_add_public_bits(he_bits, &bytes_to_bits(bytes));
+}
+
+fn bytes_to_bits(bytes: &[u8]) -> Vec<u8> {
+    bytes.iter().flat_map(|&byte| (0..8).map(move |i| (byte >> i) & 1)).collect()
+}
@@ -0,0 +1,166 @@
+//! Trivium and Kreyvium stream ciphers.
+//!
+//! The key, IV and keystream are byte strings whose bit i of byte b is the bit 8b + i. As in the eSTREAM
+//! test vectors of Trivium, the bit m of an L-bit key or IV is the bit K_{L-m} of the specification, so that
+//! the key register (s1, …, s80) of Trivium is loaded with the key bits (79, …, 0).
+
+pub const TRIVIUM_STATE_SIZE: usize = 288;
+pub const TRIVIUM_KEY_LEN_IN_BYTE: usize = 10;
+pub const TRIVIUM_IV_LEN_IN_BYTE: usize = 10;
+pub const KREYVIUM_KEY_LEN_IN_BYTE: usize = 16;
+pub const KREYVIUM_IV_LEN_IN_BYTE: usize = 16;
+/// Number of initialization steps without output, 4 times the state size.
+pub const TRIVIUM_NUM_INIT_STEPS: usize = 4 * TRIVIUM_STATE_SIZE;
+
+/// Positions from 1 to 288 of the state bits summed into the keystream bit.
+pub const TRIVIUM_OUTPUT_TAPS: [usize; 6] = [66, 93, 162, 177, 243, 288];
+/// Feedback bits t1, t2 and t3 as `([a, b, c, d, e], (start, end))`, where t = s_a + s_b + s_c * s_d + s_e
+/// is shifted into the register (s_start, …, s_end).
+pub const TRIVIUM_FEEDBACKS: [([usize; 5], (usize, usize)); 3] = [
+    ([66, 93, 91, 92, 171], (94, 177)),
+    ([162, 177, 175, 176, 264], (178, 288)),
+    ([243, 288, 286, 287, 69], (1, 93)),
+];
+
+/// Trivium with an 80-bit key and an 80-bit IV.
+#[derive(Debug, Clone)]
+pub struct TriviumRef {
+    state: [u8; TRIVIUM_STATE_SIZE],
+}
+
+impl TriviumRef {
+    /// Loads the key and the IV and runs the initialization.
+    pub fn new(key: &[u8], iv: &[u8]) -> Self {
+        assert_eq!(key.len(), TRIVIUM_KEY_LEN_IN_BYTE, "invalid Trivium key length {}", key.len());
+        assert_eq!(iv.len(), TRIVIUM_IV_LEN_IN_BYTE, "invalid Trivium IV length {}", iv.len());
+
+        let mut trivium = TriviumRef {
+            state: trivium_initial_state(key, iv),
+        };
+        for _ in 0..TRIVIUM_NUM_INIT_STEPS {
+            trivium_step(&mut trivium.state, 0, 0);
+        }
+
+        trivium
+    }
+
+    pub fn next_bit(&mut self) -> u8 {
+        trivium_step(&mut self.state, 0, 0)
+    }
+
+    pub fn keystream_bits(&mut self, num_bits: usize) -> Vec<u8> {
+        (0..num_bits).map(|_| self.next_bit()).collect()
+    }
+
+    pub fn keystream_bytes(&mut self, num_bytes: usize) -> Vec<u8> {
+        bits_to_bytes(&self.keystream_bits(8 * num_bytes))
+    }
+}
+
+/// Kreyvium with a 128-bit key and a 128-bit IV, whose key and IV registers K* and IV* are rotated at each step.
+///
+/// As in the reference implementation, (K*_127, …, K*_0) = (K_128, …, K_1), so the step t uses the key bit and
+/// the IV bit 127 - (t mod 128).
+#[derive(Debug, Clone)]
+pub struct KreyviumRef {
+    state: [u8; TRIVIUM_STATE_SIZE],
+    key_bits: Vec<u8>,
+    iv_bits: Vec<u8>,
+    num_steps: usize,
+}
+
+impl KreyviumRef {
+    /// Loads the key and the IV and runs the initialization.
+    pub fn new(key: &[u8], iv: &[u8]) -> Self {
+        assert_eq!(key.len(), KREYVIUM_KEY_LEN_IN_BYTE, "invalid Kreyvium key length {}", key.len());
+        assert_eq!(iv.len(), KREYVIUM_IV_LEN_IN_BYTE, "invalid Kreyvium IV length {}", iv.len());
+
+        let mut kreyvium = KreyviumRef {
+            state: kreyvium_initial_state(key, iv),
+            key_bits: bytes_to_bits(key),
+            iv_bits: bytes_to_bits(iv),
+            num_steps: 0,
+        };
+        for _ in 0..TRIVIUM_NUM_INIT_STEPS {
+            kreyvium.next_bit();
+        }
+
+        kreyvium
+    }
+
+    pub fn next_bit(&mut self) -> u8 {
+        let reg_idx = self.key_bits.len() - 1 - self.num_steps % self.key_bits.len();
+        self.num_steps += 1;
+        trivium_step(&mut self.state, self.key_bits[reg_idx], self.iv_bits[reg_idx])
+    }
+
+    pub fn keystream_bits(&mut self, num_bits: usize) -> Vec<u8> {
+        (0..num_bits).map(|_| self.next_bit()).collect()
+    }
+
+    pub fn keystream_bytes(&mut self, num_bytes: usize) -> Vec<u8> {
+        bits_to_bytes(&self.keystream_bits(8 * num_bytes))
+    }
+}
+
+/// (s1, …, s93) = (K_1, …, K_80, 0, …), (s94, …, s177) = (IV_1, …, IV_80, 0, …) and (s178, …, s288) = (0, …, 0, 1, 1, 1),
+/// where the state bit s_p is at the index p - 1.
+pub fn trivium_initial_state(key: &[u8], iv: &[u8]) -> [u8; TRIVIUM_STATE_SIZE] {
+    let mut state = [0u8; TRIVIUM_STATE_SIZE];
+    load_reversed_bits(&mut state[0..80], key);
+    load_reversed_bits(&mut state[93..173], iv);
+    state[285..288].fill(1);
+
+    state
+}
+
+/// (s1, …, s93) = (K_1, …, K_93), (s94, …, s177) = (IV_1, …, IV_84) and
+/// (s178, …, s288) = (IV_85, …, IV_128, 1, …, 1, 0), where the state bit s_p is at the index p - 1.
+pub fn kreyvium_initial_state(key: &[u8], iv: &[u8]) -> [u8; TRIVIUM_STATE_SIZE] {
+    let mut key_bits = vec![0u8; 8 * key.len()];
+    let mut iv_bits = vec![0u8; 8 * iv.len()];
+    load_reversed_bits(&mut key_bits, key);
+    load_reversed_bits(&mut iv_bits, iv);
+
+    let mut state = [0u8; TRIVIUM_STATE_SIZE];
+    state[0..93].copy_from_slice(&key_bits[0..93]);
+    state[93..221].copy_from_slice(&iv_bits);
+    state[221..287].fill(1);
+
+    state
+}
+
+/// Runs one step and returns the keystream bit, where `key_bit` and `iv_bit` are K*_0 and IV*_0 of Kreyvium,
+/// or zeros for Trivium.
+pub fn trivium_step(state: &mut [u8; TRIVIUM_STATE_SIZE], key_bit: u8, iv_bit: u8) -> u8 {
+    let s = |p: usize| state[p - 1];
+
+    let z = TRIVIUM_OUTPUT_TAPS.iter().fold(key_bit, |acc, &p| acc ^ s(p));
+    let mut t = TRIVIUM_FEEDBACKS.map(|([a, b, c, d, e], _)| s(a) ^ s(b) ^ (s(c) & s(d)) ^ s(e));
+    t[0] ^= iv_bit;
+    t[2] ^= key_bit;
+
+    for (&(_, (start, end)), t) in TRIVIUM_FEEDBACKS.iter().zip(t) {
+        state.copy_within(start - 1..end - 1, start);
+        state[start - 1] = t;
+    }
+
+    z
+}
+
+/// Writes the bits (L - 1, …, 0) of an L-bit string to `bits`.
+fn load_reversed_bits(bits: &mut [u8], bytes: &[u8]) {
+    let num_bits = 8 * bytes.len();
+    for (j, bit) in bits.iter_mut().enumerate().take(num_bits) {
+        let m = num_bits - 1 - j;
+        *bit = (bytes[m / 8] >> (m % 8)) & 1;
+    }
+}
+
+fn bytes_to_bits(bytes: &[u8]) -> Vec<u8> {
+    bytes.iter().flat_map(|&byte| (0..8).map(move |i| (byte >> i) & 1)).collect()
+}
+
+fn bits_to_bytes(bits: &[u8]) -> Vec<u8> {
+    bits.chunks(8).map(|byte| byte.iter().enumerate().fold(0u8, |acc, (i, &bit)| acc | (bit << i))).collect()
+}
@@ -94,13 +94,13 @@ lazy_static! {
         DecompositionLevelCount(1), // ks_level
         DecompositionBaseLog(15), // glwe_ds_to_large_base_log
         DecompositionLevelCount(3), // glwe_ds_to_large_level
-        FftType::Split(44), // fft_type_to_large
+        FftType::F128, // fft_type_to_large
         DecompositionBaseLog(6), // auto_base_log
         DecompositionLevelCount(10), // auto_level
         FftType::Split(36), // fft_type_auto
         DecompositionBaseLog(5), // glwe_ds_from_large_base_log
         DecompositionLevelCount(10), // glwe_ds_from_large_level
-        FftType::Split(35), // fft_type_from_large
+        FftType::F128, // fft_type_from_large
         DecompositionBaseLog(6), // ss_base_log
         DecompositionLevelCount(9), // ss_level
         DecompositionBaseLog(6), // cbs_base_log
@@ -125,13 +125,13 @@ lazy_static! {
         DecompositionLevelCount(1), // ks_level
         DecompositionBaseLog(15), // glwe_ds_to_large_base_log
         DecompositionLevelCount(3), // glwe_ds_to_large_level
-        FftType::Split(44), // fft_type_to_large
+        FftType::F128, // fft_type_to_large
         DecompositionBaseLog(6), // auto_base_log
         DecompositionLevelCount(10), // auto_level
         FftType::Split(36), // fft_type_auto
         DecompositionBaseLog(5), // glwe_ds_from_large_base_log
         DecompositionLevelCount(10), // glwe_ds_from_large_level
-        FftType::Split(35), // fft_type_from_large
+        FftType::F128, // fft_type_from_large
         DecompositionBaseLog(6), // ss_base_log
         DecompositionLevelCount(9), // ss_level
         DecompositionBaseLog(4), // cbs_base_log
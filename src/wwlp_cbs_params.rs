@@ -1,7 +1,7 @@
 use tfhe::core_crypto::prelude::*;
-use crate::FftType;
+use crate::{param_error::*, security::*, param_traits::*, FftType};
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub struct CBSParam<Scalar: UnsignedInteger> {
     lwe_dimension: LweDimension,
     lwe_modular_std_dev: StandardDev,
@@ -19,7 +19,26 @@ pub struct CBSParam<Scalar: UnsignedInteger> {
     ciphertext_modulus: CiphertextModulus::<Scalar>,
 }
 
-#[derive(Clone, Copy)]
+/// Arguments of [`CBSParam::try_new`] and [`CBSParam::new_unchecked`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CBSParamFields<Scalar: UnsignedInteger> {
+    pub lwe_dimension: LweDimension,
+    pub lwe_modular_std_dev: StandardDev,
+    pub polynomial_size: PolynomialSize,
+    pub glwe_dimension: GlweDimension,
+    pub glwe_modular_std_dev: StandardDev,
+    pub pbs_base_log: DecompositionBaseLog,
+    pub pbs_level: DecompositionLevelCount,
+    pub ks_base_log: DecompositionBaseLog,
+    pub ks_level: DecompositionLevelCount,
+    pub pfks_base_log: DecompositionBaseLog,
+    pub pfks_level: DecompositionLevelCount,
+    pub cbs_base_log: DecompositionBaseLog,
+    pub cbs_level: DecompositionLevelCount,
+    pub ciphertext_modulus: CiphertextModulus::<Scalar>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub struct WWLpCBSParam<Scalar: UnsignedInteger> {
     lwe_dimension: LweDimension,
     lwe_modular_std_dev: StandardDev,
@@ -43,7 +62,32 @@ pub struct WWLpCBSParam<Scalar: UnsignedInteger> {
     ciphertext_modulus: CiphertextModulus::<Scalar>,
 }
 
-#[derive(Clone, Copy)]
+/// Arguments of [`WWLpCBSParam::try_new`] and [`WWLpCBSParam::new_unchecked`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct WWLpCBSParamFields<Scalar: UnsignedInteger> {
+    pub lwe_dimension: LweDimension,
+    pub lwe_modular_std_dev: StandardDev,
+    pub polynomial_size: PolynomialSize,
+    pub glwe_dimension: GlweDimension,
+    pub glwe_modular_std_dev: StandardDev,
+    pub pbs_base_log: DecompositionBaseLog,
+    pub pbs_level: DecompositionLevelCount,
+    pub refresh_base_log: DecompositionBaseLog,
+    pub refresh_level: DecompositionLevelCount,
+    pub ks_base_log: DecompositionBaseLog,
+    pub ks_level: DecompositionLevelCount,
+    pub auto_base_log: DecompositionBaseLog,
+    pub auto_level: DecompositionLevelCount,
+    pub fft_type_auto: FftType,
+    pub ss_base_log: DecompositionBaseLog,
+    pub ss_level: DecompositionLevelCount,
+    pub cbs_base_log: DecompositionBaseLog,
+    pub cbs_level: DecompositionLevelCount,
+    pub log_lut_count: LutCountLog,
+    pub ciphertext_modulus: CiphertextModulus::<Scalar>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub struct HighPrecWWLpCBSParam<Scalar: UnsignedInteger> {
     lwe_dimension: LweDimension,
     lwe_modular_std_dev: StandardDev,
@@ -75,7 +119,41 @@ pub struct HighPrecWWLpCBSParam<Scalar: UnsignedInteger> {
     ciphertext_modulus: CiphertextModulus::<Scalar>,
 }
 
+/// Arguments of [`HighPrecWWLpCBSParam::try_new`] and [`HighPrecWWLpCBSParam::new_unchecked`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct HighPrecWWLpCBSParamFields<Scalar: UnsignedInteger> {
+    pub lwe_dimension: LweDimension,
+    pub lwe_modular_std_dev: StandardDev,
+    pub polynomial_size: PolynomialSize,
+    pub glwe_dimension: GlweDimension,
+    pub large_glwe_dimension: GlweDimension,
+    pub glwe_modular_std_dev: StandardDev,
+    pub large_glwe_modular_std_dev: StandardDev,
+    pub pbs_base_log: DecompositionBaseLog,
+    pub pbs_level: DecompositionLevelCount,
+    pub refresh_base_log: DecompositionBaseLog,
+    pub refresh_level: DecompositionLevelCount,
+    pub ks_base_log: DecompositionBaseLog,
+    pub ks_level: DecompositionLevelCount,
+    pub glwe_ds_to_large_base_log: DecompositionBaseLog,
+    pub glwe_ds_to_large_level: DecompositionLevelCount,
+    pub fft_type_to_large: FftType,
+    pub auto_base_log: DecompositionBaseLog,
+    pub auto_level: DecompositionLevelCount,
+    pub fft_type_auto: FftType,
+    pub glwe_ds_from_large_base_log: DecompositionBaseLog,
+    pub glwe_ds_from_large_level: DecompositionLevelCount,
+    pub fft_type_from_large: FftType,
+    pub ss_base_log: DecompositionBaseLog,
+    pub ss_level: DecompositionLevelCount,
+    pub cbs_base_log: DecompositionBaseLog,
+    pub cbs_level: DecompositionLevelCount,
+    pub log_lut_count: LutCountLog,
+    pub ciphertext_modulus: CiphertextModulus::<Scalar>,
+}
+
 impl<Scalar: UnsignedInteger> CBSParam<Scalar> {
+    /// Panics with the list of violated constraints if the parameters are invalid.
     pub fn new(
         lwe_dimension: LweDimension,
         lwe_modular_std_dev: StandardDev,
@@ -92,6 +170,50 @@ impl<Scalar: UnsignedInteger> CBSParam<Scalar> {
         cbs_level: DecompositionLevelCount,
         ciphertext_modulus: CiphertextModulus::<Scalar>,
     ) -> Self {
+        Self::try_new(CBSParamFields {
+            lwe_dimension,
+            lwe_modular_std_dev,
+            polynomial_size,
+            glwe_dimension,
+            glwe_modular_std_dev,
+            pbs_base_log,
+            pbs_level,
+            ks_base_log,
+            ks_level,
+            pfks_base_log,
+            pfks_level,
+            cbs_base_log,
+            cbs_level,
+            ciphertext_modulus,
+        }).unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    pub fn try_new(fields: CBSParamFields<Scalar>) -> Result<Self, ParamError> {
+        let param = Self::new_unchecked(fields);
+        param.validate()?;
+
+        Ok(param)
+    }
+
+    /// Skips the validation, e.g. for toy parameters to be checked by `validate_with_min_security_bits`.
+    pub fn new_unchecked(fields: CBSParamFields<Scalar>) -> Self {
+        let CBSParamFields {
+            lwe_dimension,
+            lwe_modular_std_dev,
+            polynomial_size,
+            glwe_dimension,
+            glwe_modular_std_dev,
+            pbs_base_log,
+            pbs_level,
+            ks_base_log,
+            ks_level,
+            pfks_base_log,
+            pfks_level,
+            cbs_base_log,
+            cbs_level,
+            ciphertext_modulus,
+        } = fields;
+
         CBSParam {
             lwe_dimension,
             lwe_modular_std_dev,
@@ -110,6 +232,34 @@ impl<Scalar: UnsignedInteger> CBSParam<Scalar> {
         }
     }
 
+    /// Validates with the default security threshold of `DEFAULT_MIN_SECURITY_BITS`.
+    pub fn validate(&self) -> Result<(), ParamError> {
+        self.validate_with_min_security_bits(DEFAULT_MIN_SECURITY_BITS)
+    }
+
+    /// A threshold of zero explicitly allows insecure parameters, e.g. toy parameters for experiments.
+    pub fn validate_with_min_security_bits(&self, min_security_bits: usize) -> Result<(), ParamError> {
+        let mut checker = ParamChecker::new(Scalar::BITS);
+
+        checker.power_of_two("polynomial_size", self.polynomial_size.0);
+        checker.decomposition("pbs", self.pbs_base_log.0, self.pbs_level.0);
+        checker.decomposition("ks", self.ks_base_log.0, self.ks_level.0);
+        checker.decomposition("pfks", self.pfks_base_log.0, self.pfks_level.0);
+        checker.cbs_decomposition(self.cbs_base_log.0, self.cbs_level.0, 0);
+
+        checker.security(|| self.security(), min_security_bits);
+
+        checker.finish()
+    }
+
+    /// Estimated security of the secret keys.
+    pub fn security(&self) -> SecurityReport {
+        SecurityReport::new(vec![
+            ("lwe", estimate_lwe_security(self.lwe_dimension.0, self.lwe_modular_std_dev, Scalar::BITS)),
+            ("glwe", estimate_glwe_security(self.glwe_dimension, self.polynomial_size, self.glwe_modular_std_dev, Scalar::BITS)),
+        ])
+    }
+
     pub fn lwe_dimension(&self) -> LweDimension {
         self.lwe_dimension
     }
@@ -168,6 +318,7 @@ impl<Scalar: UnsignedInteger> CBSParam<Scalar> {
 }
 
 impl<Scalar: UnsignedInteger> WWLpCBSParam<Scalar> {
+    /// Panics with the list of violated constraints if the parameters are invalid.
     pub fn new(
         lwe_dimension: LweDimension,
         lwe_modular_std_dev: StandardDev,
@@ -190,6 +341,62 @@ impl<Scalar: UnsignedInteger> WWLpCBSParam<Scalar> {
         log_lut_count: LutCountLog,
         ciphertext_modulus: CiphertextModulus::<Scalar>,
     ) -> Self {
+        Self::try_new(WWLpCBSParamFields {
+            lwe_dimension,
+            lwe_modular_std_dev,
+            polynomial_size,
+            glwe_dimension,
+            glwe_modular_std_dev,
+            pbs_base_log,
+            pbs_level,
+            refresh_base_log,
+            refresh_level,
+            ks_base_log,
+            ks_level,
+            auto_base_log,
+            auto_level,
+            fft_type_auto,
+            ss_base_log,
+            ss_level,
+            cbs_base_log,
+            cbs_level,
+            log_lut_count,
+            ciphertext_modulus,
+        }).unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    pub fn try_new(fields: WWLpCBSParamFields<Scalar>) -> Result<Self, ParamError> {
+        let param = Self::new_unchecked(fields);
+        param.validate()?;
+
+        Ok(param)
+    }
+
+    /// Skips the validation, e.g. for toy parameters to be checked by `validate_with_min_security_bits`.
+    pub fn new_unchecked(fields: WWLpCBSParamFields<Scalar>) -> Self {
+        let WWLpCBSParamFields {
+            lwe_dimension,
+            lwe_modular_std_dev,
+            polynomial_size,
+            glwe_dimension,
+            glwe_modular_std_dev,
+            pbs_base_log,
+            pbs_level,
+            refresh_base_log,
+            refresh_level,
+            ks_base_log,
+            ks_level,
+            auto_base_log,
+            auto_level,
+            fft_type_auto,
+            ss_base_log,
+            ss_level,
+            cbs_base_log,
+            cbs_level,
+            log_lut_count,
+            ciphertext_modulus,
+        } = fields;
+
         WWLpCBSParam {
             lwe_dimension,
             lwe_modular_std_dev,
@@ -214,6 +421,37 @@ impl<Scalar: UnsignedInteger> WWLpCBSParam<Scalar> {
         }
     }
 
+    /// Validates with the default security threshold of `DEFAULT_MIN_SECURITY_BITS`.
+    pub fn validate(&self) -> Result<(), ParamError> {
+        self.validate_with_min_security_bits(DEFAULT_MIN_SECURITY_BITS)
+    }
+
+    /// A threshold of zero explicitly allows insecure parameters, e.g. toy parameters for experiments.
+    pub fn validate_with_min_security_bits(&self, min_security_bits: usize) -> Result<(), ParamError> {
+        let mut checker = ParamChecker::new(Scalar::BITS);
+
+        checker.power_of_two("polynomial_size", self.polynomial_size.0);
+        checker.decomposition("pbs", self.pbs_base_log.0, self.pbs_level.0);
+        checker.decomposition("refresh", self.refresh_base_log.0, self.refresh_level.0);
+        checker.decomposition("ks", self.ks_base_log.0, self.ks_level.0);
+        checker.decomposition("auto", self.auto_base_log.0, self.auto_level.0);
+        checker.fft_split("auto", self.fft_type_auto, self.auto_base_log.0, self.polynomial_size.0);
+        checker.decomposition("ss", self.ss_base_log.0, self.ss_level.0);
+        checker.cbs_decomposition(self.cbs_base_log.0, self.cbs_level.0, self.log_lut_count.0);
+
+        checker.security(|| self.security(), min_security_bits);
+
+        checker.finish()
+    }
+
+    /// Estimated security of the secret keys.
+    pub fn security(&self) -> SecurityReport {
+        SecurityReport::new(vec![
+            ("lwe", estimate_lwe_security(self.lwe_dimension.0, self.lwe_modular_std_dev, Scalar::BITS)),
+            ("glwe", estimate_glwe_security(self.glwe_dimension, self.polynomial_size, self.glwe_modular_std_dev, Scalar::BITS)),
+        ])
+    }
+
     pub fn lwe_dimension(&self) -> LweDimension {
         self.lwe_dimension
     }
@@ -296,6 +534,7 @@ impl<Scalar: UnsignedInteger> WWLpCBSParam<Scalar> {
 }
 
 impl<Scalar: UnsignedInteger> HighPrecWWLpCBSParam<Scalar> {
+    /// Panics with the list of violated constraints if the parameters are invalid.
     pub fn new(
         lwe_dimension: LweDimension,
         lwe_modular_std_dev: StandardDev,
@@ -326,6 +565,78 @@ impl<Scalar: UnsignedInteger> HighPrecWWLpCBSParam<Scalar> {
         log_lut_count: LutCountLog,
         ciphertext_modulus: CiphertextModulus::<Scalar>,
     ) -> Self {
+        Self::try_new(HighPrecWWLpCBSParamFields {
+            lwe_dimension,
+            lwe_modular_std_dev,
+            polynomial_size,
+            glwe_dimension,
+            large_glwe_dimension,
+            glwe_modular_std_dev,
+            large_glwe_modular_std_dev,
+            pbs_base_log,
+            pbs_level,
+            refresh_base_log,
+            refresh_level,
+            ks_base_log,
+            ks_level,
+            glwe_ds_to_large_base_log,
+            glwe_ds_to_large_level,
+            fft_type_to_large,
+            auto_base_log,
+            auto_level,
+            fft_type_auto,
+            glwe_ds_from_large_base_log,
+            glwe_ds_from_large_level,
+            fft_type_from_large,
+            ss_base_log,
+            ss_level,
+            cbs_base_log,
+            cbs_level,
+            log_lut_count,
+            ciphertext_modulus,
+        }).unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    pub fn try_new(fields: HighPrecWWLpCBSParamFields<Scalar>) -> Result<Self, ParamError> {
+        let param = Self::new_unchecked(fields);
+        param.validate()?;
+
+        Ok(param)
+    }
+
+    /// Skips the validation, e.g. for toy parameters to be checked by `validate_with_min_security_bits`.
+    pub fn new_unchecked(fields: HighPrecWWLpCBSParamFields<Scalar>) -> Self {
+        let HighPrecWWLpCBSParamFields {
+            lwe_dimension,
+            lwe_modular_std_dev,
+            polynomial_size,
+            glwe_dimension,
+            large_glwe_dimension,
+            glwe_modular_std_dev,
+            large_glwe_modular_std_dev,
+            pbs_base_log,
+            pbs_level,
+            refresh_base_log,
+            refresh_level,
+            ks_base_log,
+            ks_level,
+            glwe_ds_to_large_base_log,
+            glwe_ds_to_large_level,
+            fft_type_to_large,
+            auto_base_log,
+            auto_level,
+            fft_type_auto,
+            glwe_ds_from_large_base_log,
+            glwe_ds_from_large_level,
+            fft_type_from_large,
+            ss_base_log,
+            ss_level,
+            cbs_base_log,
+            cbs_level,
+            log_lut_count,
+            ciphertext_modulus,
+        } = fields;
+
         HighPrecWWLpCBSParam {
             lwe_dimension,
             lwe_modular_std_dev,
@@ -358,6 +669,42 @@ impl<Scalar: UnsignedInteger> HighPrecWWLpCBSParam<Scalar> {
         }
     }
 
+    /// Validates with the default security threshold of `DEFAULT_MIN_SECURITY_BITS`.
+    pub fn validate(&self) -> Result<(), ParamError> {
+        self.validate_with_min_security_bits(DEFAULT_MIN_SECURITY_BITS)
+    }
+
+    /// A threshold of zero explicitly allows insecure parameters, e.g. toy parameters for experiments.
+    pub fn validate_with_min_security_bits(&self, min_security_bits: usize) -> Result<(), ParamError> {
+        let mut checker = ParamChecker::new(Scalar::BITS);
+
+        checker.power_of_two("polynomial_size", self.polynomial_size.0);
+        checker.decomposition("pbs", self.pbs_base_log.0, self.pbs_level.0);
+        checker.decomposition("refresh", self.refresh_base_log.0, self.refresh_level.0);
+        checker.decomposition("ks", self.ks_base_log.0, self.ks_level.0);
+        checker.decomposition("glwe_ds_to_large", self.glwe_ds_to_large_base_log.0, self.glwe_ds_to_large_level.0);
+        checker.exact_fft("glwe_ds_to_large", self.fft_type_to_large, self.glwe_ds_to_large_base_log.0, self.polynomial_size.0);
+        checker.decomposition("auto", self.auto_base_log.0, self.auto_level.0);
+        checker.fft_split("auto", self.fft_type_auto, self.auto_base_log.0, self.polynomial_size.0);
+        checker.decomposition("glwe_ds_from_large", self.glwe_ds_from_large_base_log.0, self.glwe_ds_from_large_level.0);
+        checker.exact_fft("glwe_ds_from_large", self.fft_type_from_large, self.glwe_ds_from_large_base_log.0, self.polynomial_size.0);
+        checker.decomposition("ss", self.ss_base_log.0, self.ss_level.0);
+        checker.cbs_decomposition(self.cbs_base_log.0, self.cbs_level.0, self.log_lut_count.0);
+
+        checker.security(|| self.security(), min_security_bits);
+
+        checker.finish()
+    }
+
+    /// Estimated security of the secret keys.
+    pub fn security(&self) -> SecurityReport {
+        SecurityReport::new(vec![
+            ("lwe", estimate_lwe_security(self.lwe_dimension.0, self.lwe_modular_std_dev, Scalar::BITS)),
+            ("glwe", estimate_glwe_security(self.glwe_dimension, self.polynomial_size, self.glwe_modular_std_dev, Scalar::BITS)),
+            ("large glwe", estimate_glwe_security(self.large_glwe_dimension, self.polynomial_size, self.large_glwe_modular_std_dev, Scalar::BITS)),
+        ])
+    }
+
     pub fn lwe_dimension(&self) -> LweDimension {
         self.lwe_dimension
     }
@@ -470,3 +817,10 @@ impl<Scalar: UnsignedInteger> HighPrecWWLpCBSParam<Scalar> {
         self.ciphertext_modulus
     }
 }
+
+impl_pbs_parameters!(CBSParam, WWLpCBSParam, HighPrecWWLpCBSParam);
+impl_lwe_keyswitch_parameters!(CBSParam, WWLpCBSParam, HighPrecWWLpCBSParam);
+impl_automorphism_parameters!(WWLpCBSParam, HighPrecWWLpCBSParam);
+impl_scheme_switching_parameters!(WWLpCBSParam, HighPrecWWLpCBSParam);
+impl_cbs_parameters!(CBSParam);
+impl_cbs_parameters!(WWLpCBSParam, HighPrecWWLpCBSParam; log_lut_count);
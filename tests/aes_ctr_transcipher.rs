@@ -0,0 +1,88 @@
+use std::time::Instant;
+
+use rand::Rng;
+use tfhe::core_crypto::{
+    prelude::*,
+    commons::math::random::Seed,
+};
+use aes::Aes128;
+use aes::cipher::{KeyInit, BlockEncrypt, generic_array::GenericArray};
+use patching_wwlp::{aes_ctr::*, aes_he::*, aes_ref::*, aes_instances::*, AesClientKey, AesParam};
+
+fn main() {
+    test_aes_ctr_transcipher(*AES_SET_2, 20);
+}
+
+fn test_aes_ctr_transcipher(param: AesParam<u64>, num_bytes: usize) {
+    println!(
+"==== AES-128-CTR transciphering by patched WWL+ circuit bootstrapping ====
+n: {}, N: {}, k: {}, l_glwe_ds: {}, B_glwe_ds: 2^{}
+l_pbs: {}, B_pbs: 2^{}, l_ggsw: {}, B_ggsw: 2^{}, LutCount: 2^{},
+l_auto: {}, B_auto: 2^{}, l_ss: {}, B_ss: 2^{}\n",
+        param.lwe_dimension().0, param.polynomial_size().0, param.glwe_dimension().0, param.glwe_ds_level().0, param.glwe_ds_base_log().0,
+        param.pbs_level().0, param.pbs_base_log().0, param.cbs_level().0, param.cbs_base_log().0, param.log_lut_count().0,
+        param.auto_level().0, param.auto_base_log().0, param.ss_level().0, param.ss_base_log().0,
+    );
+
+    // Set random generators
+    let mut boxed_seeder = new_seeder();
+    let seeder = boxed_seeder.as_mut();
+    let mut encryption_generator = EncryptionRandomGenerator::<ActivatedRandomGenerator>::new(seeder.seed(), seeder);
+
+    // Generate keys
+    let mut rng = rand::thread_rng();
+    let (client_key, server_key) = AesClientKey::generate(&param, Seed(rng.gen::<u128>()));
+    let lwe_sk = client_key.lwe_secret_key();
+
+    // ======== Client: AES-CTR by the aes crate ========
+    let mut key = [0u8; BLOCKSIZE_IN_BYTE];
+    for i in 0..BLOCKSIZE_IN_BYTE {
+        key[i] = rng.gen_range(0..=u8::MAX);
+    }
+    let plaintext: Vec<u8> = (0..num_bytes).map(|_| rng.gen_range(0..=u8::MAX)).collect();
+
+    // Random nonce with a counter about to wrap around, so that the increment carries into the nonce
+    let mut initial_counter = [0xFFu8; BLOCKSIZE_IN_BYTE];
+    for i in 0..12 {
+        initial_counter[i] = rng.gen_range(0..=u8::MAX);
+    }
+
+    let aes = Aes128::new_from_slice(&key).unwrap();
+    let ciphertext: Vec<u8> = plaintext.chunks(BLOCKSIZE_IN_BYTE).enumerate().flat_map(|(block_idx, plaintext_block)| {
+        let counter = u128::from_be_bytes(initial_counter).wrapping_add(block_idx as u128).to_be_bytes();
+        let mut keystream = GenericArray::clone_from_slice(&counter);
+        aes.encrypt_block(&mut keystream);
+        plaintext_block.iter().zip(keystream.iter()).map(|(p, k)| p ^ k).collect::<Vec<u8>>()
+    }).collect();
+
+    let he_master_key = encrypt_aes_master_key(
+        lwe_sk,
+        &key,
+        param.glwe_modular_std_dev(),
+        param.ciphertext_modulus(),
+        &mut encryption_generator,
+    );
+
+    // ======== Server: transciphering ========
+    let now = Instant::now();
+    let he_aes_ctr = HeAesCtr::from_he_master_key(&server_key, &he_master_key);
+    let time_key_expansion = now.elapsed();
+
+    let now = Instant::now();
+    let he_plaintext = he_aes_ctr.transcipher(initial_counter, &ciphertext);
+    let time_transcipher = now.elapsed();
+
+    // ======== Client: decryption of the LWE ciphertexts ========
+    let decrypted = decrypt_bytes(lwe_sk, &he_plaintext);
+    println!("Counter   : {initial_counter:>2x?}");
+    println!("Ciphertext: {ciphertext:>2x?}");
+    println!("Decrypted : {decrypted:>2x?}");
+    println!("Plaintext : {plaintext:>2x?}");
+    if decrypted != plaintext {
+        println!("Transciphering failure!");
+    }
+
+    println!("\n---- Evaluation Time ----");
+    println!("Key expansion : {} s", time_key_expansion.as_millis() as f64 / 1000f64);
+    println!("Transciphering: {} s ({} blocks)", time_transcipher.as_millis() as f64 / 1000f64, num_bytes.div_ceil(BLOCKSIZE_IN_BYTE));
+}
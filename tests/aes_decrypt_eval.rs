@@ -0,0 +1,134 @@
+use std::time::{Duration, Instant};
+
+use rand::Rng;
+use tfhe::core_crypto::{
+    prelude::*,
+    commons::math::random::Seed,
+};
+use patching_wwlp::{aes_he::*, aes_ref::*, aes_instances::*, AesClientKey, AesParam};
+
+fn main() {
+    test_aes_decrypt_by_patched_wwlp_cbs(*AES_SET_2);
+}
+
+fn test_aes_decrypt_by_patched_wwlp_cbs(param: AesParam<u64>) {
+    println!(
+"==== AES decryption by patched WWL+ circuit bootstrapping ====
+n: {}, N: {}, k: {}, l_glwe_ds: {}, B_glwe_ds: 2^{}
+l_pbs: {}, B_pbs: 2^{}, l_ggsw: {}, B_ggsw: 2^{}, LutCount: 2^{},
+l_auto: {}, B_auto: 2^{}, l_ss: {}, B_ss: 2^{}\n",
+        param.lwe_dimension().0, param.polynomial_size().0, param.glwe_dimension().0, param.glwe_ds_level().0, param.glwe_ds_base_log().0,
+        param.pbs_level().0, param.pbs_base_log().0, param.cbs_level().0, param.cbs_base_log().0, param.log_lut_count().0,
+        param.auto_level().0, param.auto_base_log().0, param.ss_level().0, param.ss_base_log().0,
+    );
+
+    // Set random generators
+    let mut boxed_seeder = new_seeder();
+    let seeder = boxed_seeder.as_mut();
+    let mut encryption_generator = EncryptionRandomGenerator::<ActivatedRandomGenerator>::new(seeder.seed(), seeder);
+
+    // Generate keys
+    let mut rng = rand::thread_rng();
+    let (client_key, server_key) = AesClientKey::generate(&param, Seed(rng.gen::<u128>()));
+    let lwe_sk = client_key.lwe_secret_key();
+    let lwe_sk_after_ks = client_key.lwe_secret_key_after_ks();
+
+    // ======== Plain ========
+    let mut key = [0u8; BLOCKSIZE_IN_BYTE];
+    for i in 0..BLOCKSIZE_IN_BYTE {
+        key[i] = rng.gen_range(0..=u8::MAX);
+    }
+
+    let aes = AesRef::new(&key);
+
+    let mut message = [0u8; BLOCKSIZE_IN_BYTE];
+    for i in 0..BLOCKSIZE_IN_BYTE {
+        message[i] = rng.gen_range(0..=u8::MAX);
+    }
+    let ciphertext = aes.encrypt_block(message);
+    let mut state = byte_array_to_mat(ciphertext);
+
+    // ======== HE ========
+    let he_round_keys = encrypt_aes_round_keys(
+        lwe_sk,
+        &key,
+        param.glwe_modular_std_dev(),
+        param.ciphertext_modulus(),
+        &mut encryption_generator,
+    );
+    let he_aes = HeAes::new(&server_key, he_round_keys);
+
+    let mut he_state = trivially_encrypt_aes_state(ciphertext, lwe_sk.lwe_dimension().to_lwe_size(), param.ciphertext_modulus());
+
+    let num_bytes_to_print = 2;
+    let mut time_lwe_ks = Duration::ZERO;
+    let mut time_inv_sub_bytes = Duration::ZERO;
+    let mut time_linear = Duration::ZERO;
+
+    println!("---- Error (bits) ----");
+    let mut now = Instant::now();
+    he_aes.decrypt_block_with_hook(&mut he_state, |r, step, he_state| {
+        let elapsed = now.elapsed();
+        match step {
+            HeAesStep::InvShiftRows => {
+                time_linear += elapsed;
+                aes.inv_shift_rows(&mut state);
+                println!("Round {r}");
+            }
+            HeAesStep::LweKeyswitch => {
+                time_lwe_ks += elapsed;
+                print_he_state_error("LWE ks     ", &he_state, state, lwe_sk_after_ks, num_bytes_to_print);
+            }
+            HeAesStep::InvSubBytes => {
+                time_inv_sub_bytes += elapsed;
+                aes.inv_sub_bytes(&mut state);
+                print_he_state_error("InvSubBytes", &he_state, state, lwe_sk, num_bytes_to_print);
+            }
+            HeAesStep::AddRoundKey => {
+                time_linear += elapsed;
+                aes.add_round_key(&mut state, r);
+                if r == 0 {
+                    print_he_state_error("Linear     ", &he_state, state, lwe_sk, num_bytes_to_print);
+                }
+            }
+            HeAesStep::InvMixColumns => {
+                time_linear += elapsed;
+                aes.inv_mix_columns(&mut state);
+                print_he_state_error("Linear     ", &he_state, state, lwe_sk, num_bytes_to_print);
+            }
+            _ => unreachable!(),
+        }
+        now = Instant::now();
+    });
+
+    let output = decrypt_aes_state(lwe_sk, &he_state);
+    println!("Output  : {output:>2x?}");
+    println!("Message : {message:>2x?}");
+    if output != message {
+        println!("Decryption failure!");
+    }
+
+    // Evaluation Time
+    println!("\n---- Evaluation Time ----");
+    println!("LWE KS     : {} s", time_lwe_ks.as_millis() as f64 / 1000f64);
+    println!("InvSubBytes: {} s", time_inv_sub_bytes.as_millis() as f64 / 1000f64);
+    println!("Linear     : {} ms", time_linear.as_micros() as f64 / 1000f64);
+
+    let time_total = time_lwe_ks + time_inv_sub_bytes + time_linear;
+    println!("Total      : {} s", time_total.as_millis() as f64 / 1000f64);
+}
+
+fn print_he_state_error(
+    step: &str,
+    he_state: &LweCiphertextListView<u64>,
+    state: StateByteMat,
+    lwe_sk: &LweSecretKeyOwned<u64>,
+    num_bytes_to_print: usize,
+) {
+    let (vec_err, max_err) = get_he_state_error(he_state, state, lwe_sk);
+    print!("  - {step}:");
+    for bit_err in vec_err.iter().take(BYTESIZE * num_bytes_to_print) {
+        print!(" {bit_err:>2}");
+    }
+    println!(" ... (max: {:.3})", (max_err as f64).log2());
+}
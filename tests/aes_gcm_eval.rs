@@ -0,0 +1,145 @@
+use std::time::Instant;
+
+use rand::Rng;
+use tfhe::core_crypto::{
+    prelude::*,
+    commons::math::random::Seed,
+};
+use patching_wwlp::{aes_gcm::*, aes_he::*, aes_ref::*, aes_instances::*, AesClientKey, AesParam};
+
+fn main() {
+    test_aes_gcm_eval(*AES_SET_2, 13, 20);
+}
+
+fn test_aes_gcm_eval(param: AesParam<u64>, aad_len: usize, num_bytes: usize) {
+    println!(
+"==== AES-128-GCM tag evaluation by patched WWL+ circuit bootstrapping ====
+n: {}, N: {}, k: {}, l_glwe_ds: {}, B_glwe_ds: 2^{}
+l_pbs: {}, B_pbs: 2^{}, l_ggsw: {}, B_ggsw: 2^{}, LutCount: 2^{},
+l_auto: {}, B_auto: 2^{}, l_ss: {}, B_ss: 2^{}\n",
+        param.lwe_dimension().0, param.polynomial_size().0, param.glwe_dimension().0, param.glwe_ds_level().0, param.glwe_ds_base_log().0,
+        param.pbs_level().0, param.pbs_base_log().0, param.cbs_level().0, param.cbs_base_log().0, param.log_lut_count().0,
+        param.auto_level().0, param.auto_base_log().0, param.ss_level().0, param.ss_base_log().0,
+    );
+
+    // Set random generators
+    let mut boxed_seeder = new_seeder();
+    let seeder = boxed_seeder.as_mut();
+    let mut encryption_generator = EncryptionRandomGenerator::<ActivatedRandomGenerator>::new(seeder.seed(), seeder);
+
+    // Generate keys
+    let mut rng = rand::thread_rng();
+    let (client_key, server_key) = AesClientKey::generate(&param, Seed(rng.gen::<u128>()));
+    let lwe_sk = client_key.lwe_secret_key();
+    let lwe_size = lwe_sk.lwe_dimension().to_lwe_size();
+
+    // ======== GF(2^128) multiplication of two encrypted blocks ========
+    println!("---- GF(2^128) multiplication ----");
+    let mut x = [0u8; BLOCKSIZE_IN_BYTE];
+    let mut y = [0u8; BLOCKSIZE_IN_BYTE];
+    for i in 0..BLOCKSIZE_IN_BYTE {
+        x[i] = rng.gen_range(0..=u8::MAX);
+        y[i] = rng.gen_range(0..=u8::MAX);
+    }
+    let correct_output = gf128_mul(x, y);
+
+    let mut he_x = trivially_encrypt_aes_state(x, lwe_size, param.ciphertext_modulus());
+    let mut he_y = trivially_encrypt_aes_state(y, lwe_size, param.ciphertext_modulus());
+    for (he_block, block) in [(&mut he_x, x), (&mut he_y, y)] {
+        for (bit_idx, mut he_bit) in he_block.iter_mut().enumerate() {
+            let bit = ((block[bit_idx / BYTESIZE] >> (bit_idx % BYTESIZE)) & 1) as u64;
+            encrypt_lwe_ciphertext(lwe_sk, &mut he_bit, Plaintext(bit << 63), param.glwe_modular_std_dev(), &mut encryption_generator);
+        }
+    }
+
+    let now = Instant::now();
+    let he_mul_y = HeGf128Mul::new(&server_key, &he_y);
+    let time_precomp = now.elapsed();
+
+    let now = Instant::now();
+    let he_output = he_mul_y.mul(&he_x);
+    let time_mul = now.elapsed();
+
+    let (_, max_err) = get_he_state_error(&he_output, byte_array_to_mat(correct_output), lwe_sk);
+    println!("max: {:.2}", (max_err as f64).log2());
+
+    let output = decrypt_aes_state(lwe_sk, &he_output);
+    println!("Output  : {output:>2x?}");
+    println!("Correct : {correct_output:>2x?}");
+    if output != correct_output {
+        println!("Multiplication failure!");
+    }
+    println!("Packing of Y x^k: {} s", time_precomp.as_millis() as f64 / 1000f64);
+    println!("Multiplication  : {} s", time_mul.as_millis() as f64 / 1000f64);
+
+    // ======== Client: AES-GCM by the reference implementation ========
+    let mut key = [0u8; BLOCKSIZE_IN_BYTE];
+    for i in 0..BLOCKSIZE_IN_BYTE {
+        key[i] = rng.gen_range(0..=u8::MAX);
+    }
+    let mut iv = [0u8; GCM_IV_LEN_IN_BYTE];
+    for i in 0..GCM_IV_LEN_IN_BYTE {
+        iv[i] = rng.gen_range(0..=u8::MAX);
+    }
+    let aad: Vec<u8> = (0..aad_len).map(|_| rng.gen_range(0..=u8::MAX)).collect();
+    let plaintext: Vec<u8> = (0..num_bytes).map(|_| rng.gen_range(0..=u8::MAX)).collect();
+
+    let (ciphertext, tag) = AesRef::new(&key).gcm_encrypt(&iv, &aad, &plaintext);
+
+    let he_master_key = encrypt_aes_master_key(
+        lwe_sk,
+        &key,
+        param.glwe_modular_std_dev(),
+        param.ciphertext_modulus(),
+        &mut encryption_generator,
+    );
+
+    // ======== Server: transciphering and tag ========
+    println!("\n---- AES-GCM ----");
+    let now = Instant::now();
+    let he_aes_gcm = HeAesGcm::from_he_master_key(&server_key, &he_master_key);
+    let time_setup = now.elapsed();
+
+    let now = Instant::now();
+    let he_plaintext = he_aes_gcm.transcipher(&iv, &ciphertext);
+    let time_transcipher = now.elapsed();
+
+    let now = Instant::now();
+    let he_tag = he_aes_gcm.tag(&iv, &aad, &ciphertext);
+    let time_tag = now.elapsed();
+
+    let mut forged_tag = tag;
+    forged_tag[rng.gen_range(0..BLOCKSIZE_IN_BYTE)] ^= 1 << rng.gen_range(0..BYTESIZE);
+    let he_valid = gcm_verify_tag(&he_tag, tag);
+    let he_forged = gcm_verify_tag(&he_tag, forged_tag);
+
+    // ======== Client: decryption of the LWE ciphertexts ========
+    let decrypted = decrypt_bytes(lwe_sk, &he_plaintext);
+    println!("Decrypted : {decrypted:>2x?}");
+    println!("Plaintext : {plaintext:>2x?}");
+    if decrypted != plaintext {
+        println!("Transciphering failure!");
+    }
+
+    let (_, max_err) = get_he_state_error(&he_tag, byte_array_to_mat(tag), lwe_sk);
+    println!("max: {:.2}", (max_err as f64).log2());
+
+    let output_tag = decrypt_aes_state(lwe_sk, &he_tag);
+    println!("Tag       : {output_tag:>2x?}");
+    println!("Correct   : {tag:>2x?}");
+    if output_tag != tag {
+        println!("Tag failure!");
+    }
+
+    let valid = decrypt_aes_state(lwe_sk, &he_valid) == [0u8; BLOCKSIZE_IN_BYTE];
+    let forged = decrypt_aes_state(lwe_sk, &he_forged) == [0u8; BLOCKSIZE_IN_BYTE];
+    println!("Valid tag : {valid}, forged tag: {forged}");
+    if !valid || forged {
+        println!("Verification failure!");
+    }
+
+    println!("\n---- Evaluation Time ----");
+    println!("Key expansion and H: {} s", time_setup.as_millis() as f64 / 1000f64);
+    println!("Transciphering     : {} s ({} blocks)", time_transcipher.as_millis() as f64 / 1000f64, num_bytes.div_ceil(BLOCKSIZE_IN_BYTE));
+    println!("Tag                : {} s ({} GHASH blocks)", time_tag.as_millis() as f64 / 1000f64, gcm_ghash_blocks(&aad, &ciphertext).len());
+}
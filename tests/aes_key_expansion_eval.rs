@@ -0,0 +1,94 @@
+use std::time::Instant;
+
+use rand::Rng;
+use tfhe::core_crypto::{
+    prelude::*,
+    commons::math::random::Seed,
+};
+use patching_wwlp::{aes_he::*, aes_ref::*, aes_instances::*, AesClientKey, AesParam};
+
+fn main() {
+    test_aes_key_expansion_by_patched_wwlp_cbs(*AES_SET_2, AesKeySize::Aes128, true);
+    println!();
+    test_aes_key_expansion_by_patched_wwlp_cbs(*AES192_SET_2, AesKeySize::Aes192, false);
+    println!();
+    test_aes_key_expansion_by_patched_wwlp_cbs(*AES256_SET_2, AesKeySize::Aes256, false);
+}
+
+fn test_aes_key_expansion_by_patched_wwlp_cbs(param: AesParam<u64>, key_size: AesKeySize, encrypt_block: bool) {
+    println!(
+"==== AES-{} key expansion by patched WWL+ circuit bootstrapping ====
+n: {}, N: {}, k: {}, l_glwe_ds: {}, B_glwe_ds: 2^{}
+l_pbs: {}, B_pbs: 2^{}, l_ggsw: {}, B_ggsw: 2^{}, LutCount: 2^{},
+l_auto: {}, B_auto: 2^{}, l_ss: {}, B_ss: 2^{}\n",
+        key_size.key_len_in_byte() * BYTESIZE,
+        param.lwe_dimension().0, param.polynomial_size().0, param.glwe_dimension().0, param.glwe_ds_level().0, param.glwe_ds_base_log().0,
+        param.pbs_level().0, param.pbs_base_log().0, param.cbs_level().0, param.cbs_base_log().0, param.log_lut_count().0,
+        param.auto_level().0, param.auto_base_log().0, param.ss_level().0, param.ss_base_log().0,
+    );
+
+    // Set random generators
+    let mut boxed_seeder = new_seeder();
+    let seeder = boxed_seeder.as_mut();
+    let mut encryption_generator = EncryptionRandomGenerator::<ActivatedRandomGenerator>::new(seeder.seed(), seeder);
+
+    // Generate keys
+    let mut rng = rand::thread_rng();
+    let (client_key, server_key) = AesClientKey::generate(&param, Seed(rng.gen::<u128>()));
+    let lwe_sk = client_key.lwe_secret_key();
+
+    // ======== Plain ========
+    let key: Vec<u8> = (0..key_size.key_len_in_byte()).map(|_| rng.gen_range(0..=u8::MAX)).collect();
+    let aes = AesRef::new(&key);
+    let round_keys = aes.get_round_keys();
+
+    // ======== HE ========
+    let he_master_key = encrypt_aes_master_key(
+        lwe_sk,
+        &key,
+        param.glwe_modular_std_dev(),
+        param.ciphertext_modulus(),
+        &mut encryption_generator,
+    );
+
+    let now = Instant::now();
+    let he_aes = HeAes::from_he_master_key(&server_key, &he_master_key);
+    let time_key_expansion = now.elapsed();
+
+    println!("---- Round key error (bits) ----");
+    let mut is_correct = true;
+    for (r, (he_round_key, round_key)) in he_aes.he_round_keys().iter().zip(round_keys.iter()).enumerate() {
+        let (_, max_err) = get_he_state_error(he_round_key, byte_array_to_mat(*round_key), lwe_sk);
+        let decrypted = decrypt_aes_state(lwe_sk, he_round_key);
+        println!("Round key {r:>2}: {:.2}{}", (max_err as f64).log2(), if decrypted == *round_key {""} else {" (incorrect)"});
+        is_correct &= decrypted == *round_key;
+    }
+    if !is_correct {
+        println!("Key expansion failure!");
+    }
+    println!("Key expansion: {} s", time_key_expansion.as_millis() as f64 / 1000f64);
+
+    if encrypt_block {
+        let mut message = [0u8; BLOCKSIZE_IN_BYTE];
+        for i in 0..BLOCKSIZE_IN_BYTE {
+            message[i] = rng.gen_range(0..=u8::MAX);
+        }
+        let correct_output = aes.encrypt_block(message);
+
+        let mut he_state = trivially_encrypt_aes_state(message, lwe_sk.lwe_dimension().to_lwe_size(), param.ciphertext_modulus());
+        let now = Instant::now();
+        he_aes.encrypt_block(&mut he_state);
+        let time_block = now.elapsed();
+
+        let (_, max_err) = get_he_state_error(&he_state, byte_array_to_mat(correct_output), lwe_sk);
+        let output = decrypt_aes_state(lwe_sk, &he_state);
+        println!("\n---- Block encryption by the expanded round keys ----");
+        println!("max: {:.2}", (max_err as f64).log2());
+        println!("Output  : {output:>2x?}");
+        println!("Correct : {correct_output:>2x?}");
+        if output != correct_output {
+            println!("Encryption failure!");
+        }
+        println!("Block encryption: {} s", time_block.as_millis() as f64 / 1000f64);
+    }
+}
@@ -0,0 +1,106 @@
+use std::time::Instant;
+
+use rand::Rng;
+use tfhe::core_crypto::{
+    prelude::*,
+    commons::math::random::Seed,
+};
+use patching_wwlp::{aes_he::*, aes_mac::*, aes_ref::*, aes_instances::*, AesClientKey, AesParam};
+
+fn main() {
+    test_aes_mac_eval(*AES_SET_2, 20, 32);
+}
+
+fn test_aes_mac_eval(param: AesParam<u64>, cmac_len: usize, cbc_mac_len: usize) {
+    println!(
+"==== AES-128 CBC-MAC and CMAC evaluation by patched WWL+ circuit bootstrapping ====
+n: {}, N: {}, k: {}, l_glwe_ds: {}, B_glwe_ds: 2^{}
+l_pbs: {}, B_pbs: 2^{}, l_ggsw: {}, B_ggsw: 2^{}, LutCount: 2^{},
+l_auto: {}, B_auto: 2^{}, l_ss: {}, B_ss: 2^{}\n",
+        param.lwe_dimension().0, param.polynomial_size().0, param.glwe_dimension().0, param.glwe_ds_level().0, param.glwe_ds_base_log().0,
+        param.pbs_level().0, param.pbs_base_log().0, param.cbs_level().0, param.cbs_base_log().0, param.log_lut_count().0,
+        param.auto_level().0, param.auto_base_log().0, param.ss_level().0, param.ss_base_log().0,
+    );
+
+    // Set random generators
+    let mut boxed_seeder = new_seeder();
+    let seeder = boxed_seeder.as_mut();
+    let mut encryption_generator = EncryptionRandomGenerator::<ActivatedRandomGenerator>::new(seeder.seed(), seeder);
+
+    // Generate keys
+    let mut rng = rand::thread_rng();
+    let (client_key, server_key) = AesClientKey::generate(&param, Seed(rng.gen::<u128>()));
+    let lwe_sk = client_key.lwe_secret_key();
+
+    // ======== Plain ========
+    let key: Vec<u8> = (0..BLOCKSIZE_IN_BYTE).map(|_| rng.gen_range(0..=u8::MAX)).collect();
+    let aes = AesRef::new(&key);
+
+    let cmac_message: Vec<u8> = (0..cmac_len).map(|_| rng.gen_range(0..=u8::MAX)).collect();
+    let cbc_mac_message: Vec<u8> = (0..cbc_mac_len).map(|_| rng.gen_range(0..=u8::MAX)).collect();
+
+    // ======== HE ========
+    let he_round_keys = encrypt_aes_round_keys(
+        lwe_sk,
+        &key,
+        param.glwe_modular_std_dev(),
+        param.ciphertext_modulus(),
+        &mut encryption_generator,
+    );
+    let he_aes_mac = HeAesMac::new(HeAes::new(&server_key, he_round_keys));
+
+    let he_cmac_message = encrypt_bytes(lwe_sk, &cmac_message, param.glwe_modular_std_dev(), param.ciphertext_modulus(), &mut encryption_generator);
+    let he_cbc_mac_message = encrypt_bytes(lwe_sk, &cbc_mac_message, param.glwe_modular_std_dev(), param.ciphertext_modulus(), &mut encryption_generator);
+
+    println!("---- CMAC subkeys ----");
+    let now = Instant::now();
+    let (he_k1, he_k2) = he_aes_mac.cmac_subkeys();
+    let time_subkeys = now.elapsed();
+
+    let (k1, k2) = aes.cmac_subkeys();
+    for (name, he_subkey, subkey) in [("K1", he_k1, k1), ("K2", he_k2, k2)] {
+        let output = decrypt_aes_state(lwe_sk, he_subkey);
+        println!("{name} Output : {output:>2x?}");
+        println!("{name} Correct: {subkey:>2x?}");
+        if output != subkey {
+            println!("Subkey failure!");
+        }
+    }
+
+    println!("\n---- CMAC ({cmac_len} bytes) ----");
+    let now = Instant::now();
+    let he_cmac = he_aes_mac.cmac(&he_cmac_message);
+    let time_cmac = now.elapsed();
+
+    let correct_output = aes.cmac(&cmac_message);
+    let (_, max_err) = get_he_state_error(&he_cmac, byte_array_to_mat(correct_output), lwe_sk);
+    println!("max: {:.2}", (max_err as f64).log2());
+
+    let output = decrypt_aes_state(lwe_sk, &he_cmac);
+    println!("Output  : {output:>2x?}");
+    println!("Correct : {correct_output:>2x?}");
+    if output != correct_output {
+        println!("CMAC failure!");
+    }
+
+    println!("\n---- CBC-MAC ({cbc_mac_len} bytes) ----");
+    let now = Instant::now();
+    let he_cbc_mac = he_aes_mac.cbc_mac(&he_cbc_mac_message);
+    let time_cbc_mac = now.elapsed();
+
+    let correct_output = aes.cbc_mac(&cbc_mac_message);
+    let (_, max_err) = get_he_state_error(&he_cbc_mac, byte_array_to_mat(correct_output), lwe_sk);
+    println!("max: {:.2}", (max_err as f64).log2());
+
+    let output = decrypt_aes_state(lwe_sk, &he_cbc_mac);
+    println!("Output  : {output:>2x?}");
+    println!("Correct : {correct_output:>2x?}");
+    if output != correct_output {
+        println!("CBC-MAC failure!");
+    }
+
+    println!("\n---- Evaluation Time ----");
+    println!("Subkeys: {} s", time_subkeys.as_millis() as f64 / 1000f64);
+    println!("CMAC   : {} s ({} blocks)", time_cmac.as_millis() as f64 / 1000f64, cmac_len.div_ceil(BLOCKSIZE_IN_BYTE).max(1));
+    println!("CBC-MAC: {} s ({} blocks)", time_cbc_mac.as_millis() as f64 / 1000f64, cbc_mac_len / BLOCKSIZE_IN_BYTE);
+}
@@ -1,19 +1,38 @@
 use patching_wwlp::aes_ref::*;
 use rand::{thread_rng, Rng};
-use aes::Aes128;
-use aes::cipher::{KeyInit, BlockEncrypt, generic_array::GenericArray};
+use aes::{Aes128, Aes192, Aes256};
+use aes::cipher::{KeyInit, BlockCipher, BlockDecrypt, BlockEncrypt, generic_array::GenericArray};
 
 fn main() {
+    for key_size in [AesKeySize::Aes128, AesKeySize::Aes192, AesKeySize::Aes256] {
+        println!("======== {key_size:?} ========");
+        let success = match key_size {
+            AesKeySize::Aes128 => test_aes_ref::<Aes128>(key_size),
+            AesKeySize::Aes192 => test_aes_ref::<Aes192>(key_size),
+            AesKeySize::Aes256 => test_aes_ref::<Aes256>(key_size),
+        };
+        if !success {
+            return;
+        }
+    }
+
+    println!("======== AES-GCM ========");
+    if !test_gcm_ref() {
+        return;
+    }
+
+    println!("======== AES-CMAC ========");
+    test_cmac_ref();
+}
+
+fn test_aes_ref<Cipher: KeyInit + BlockCipher + BlockEncrypt + BlockDecrypt>(key_size: AesKeySize) -> bool {
     for trial in 0..10 {
         println!("==== Trial {} ====", trial + 1);
         let mut rng = thread_rng();
-        let mut key = [0u8; BLOCKSIZE_IN_BYTE];
-        for i in 0..BLOCKSIZE_IN_BYTE {
-            key[i] = rng.gen_range(0..=u8::MAX);
-        }
+        let key = (0..key_size.key_len_in_byte()).map(|_| rng.gen_range(0..=u8::MAX)).collect::<Vec<u8>>();
 
-        let aes_ref = Aes128Ref::new(&key);
-        let aes = Aes128::new(&GenericArray::from(key));
+        let aes_ref = AesRef::new(&key);
+        let aes = Cipher::new_from_slice(&key).unwrap();
 
         let mut message = [0u8; BLOCKSIZE_IN_BYTE];
         for i in 0..BLOCKSIZE_IN_BYTE {
@@ -22,7 +41,7 @@ fn main() {
 
         let output_ref = aes_ref.encrypt_block(message);
 
-        let mut block = GenericArray::from(message);
+        let mut block = GenericArray::clone_from_slice(&message);
         aes.encrypt_block(&mut block);
 
         println!("Key      : {key:>2x?}");
@@ -33,9 +52,159 @@ fn main() {
         for i in 0..BLOCKSIZE_IN_BYTE {
             if output_ref[i] != block[i] {
                 println!("\nEncryption failure!");
-                return;
+                return false;
+            }
+        }
+
+        let decrypted_ref = aes_ref.decrypt_block(output_ref);
+        aes.decrypt_block(&mut block);
+        println!("Decrypted: {decrypted_ref:>2x?}");
+
+        for i in 0..BLOCKSIZE_IN_BYTE {
+            if decrypted_ref[i] != block[i] || decrypted_ref[i] != message[i] {
+                println!("\nDecryption failure!");
+                return false;
+            }
+        }
+
+        // CBC-MAC as the last block of the CBC encryption with the zero IV
+        let mac_message = (0..3 * BLOCKSIZE_IN_BYTE).map(|_| rng.gen_range(0..=u8::MAX)).collect::<Vec<u8>>();
+        let mut mac = GenericArray::clone_from_slice(&[0u8; BLOCKSIZE_IN_BYTE]);
+        for mac_block in mac_message.chunks_exact(BLOCKSIZE_IN_BYTE) {
+            for (m, b) in mac.iter_mut().zip(mac_block.iter()) {
+                *m ^= b;
             }
+            aes.encrypt_block(&mut mac);
+        }
+        if aes_ref.cbc_mac(&mac_message)[..] != mac[..] {
+            println!("\nCBC-MAC failure!");
+            return false;
+        }
+
+        if expand_key_by_terms(&key, key_size) != aes_ref.get_round_keys() {
+            println!("\nKey expansion by terms failure!");
+            return false;
+        }
+        println!();
+    }
+
+    true
+}
+
+/// Round keys as the XORs of `AesKeySize::key_expansion_terms`, as in the homomorphic key expansion.
+fn expand_key_by_terms(key: &[u8], key_size: AesKeySize) -> Vec<StateByteArray> {
+    let terms = key_size.key_expansion_terms();
+    let word_of_terms = |term_words: &Vec<[u8; 4]>, word_terms: &Vec<usize>| {
+        word_terms.iter().fold([0u8; 4], |acc, &term| {
+            [0, 1, 2, 3].map(|row| acc[row] ^ term_words[term][row])
+        })
+    };
+
+    let mut term_words = key.chunks_exact(4).map(|word| [word[0], word[1], word[2], word[3]]).collect::<Vec<_>>();
+    for sub_word in terms.sub_words.iter() {
+        let mut word = word_of_terms(&term_words, &terms.word_terms[sub_word.word]);
+        if sub_word.rcon.is_some() {
+            word.rotate_left(1);
+        }
+        word = word.map(|byte| AES128_SBOX[byte as usize]);
+        if let Some(rcon) = sub_word.rcon {
+            word[0] ^= rcon;
+        }
+        term_words.push(word);
+    }
+
+    terms.word_terms.chunks_exact(4).map(|round_key_terms| {
+        let mut round_key = [0u8; BLOCKSIZE_IN_BYTE];
+        for (col, word_terms) in round_key_terms.iter().enumerate() {
+            round_key[4*col..4*col + 4].copy_from_slice(&word_of_terms(&term_words, word_terms));
+        }
+        round_key
+    }).collect()
+}
+
+/// Test cases 2 to 4 of the GCM specification (McGrew and Viega).
+fn test_gcm_ref() -> bool {
+    let test_cases = [
+        (
+            "00000000000000000000000000000000",
+            "000000000000000000000000",
+            "",
+            "00000000000000000000000000000000",
+            "0388dace60b6a392f328c2b971b2fe78",
+            "ab6e47d42cec13bdf53a67b21257bddf",
+        ),
+        (
+            "feffe9928665731c6d6a8f9467308308",
+            "cafebabefacedbaddecaf888",
+            "",
+            "d9313225f88406e5a55909c5aff5269a86a7a9531534f7da2e4c303d8a318a721c3c0c95956809532fcf0e2449a6b525b16aedf5aa0de657ba637b391aafd255",
+            "42831ec2217774244b7221b784d0d49ce3aa212f2c02a4e035c17e2329aca12e21d514b25466931c7d8f6a5aac84aa051ba30b396a0aac973d58e091473f5985",
+            "4d5c2af327cd64a62cf35abd2ba6fab4",
+        ),
+        (
+            "feffe9928665731c6d6a8f9467308308",
+            "cafebabefacedbaddecaf888",
+            "feedfacedeadbeeffeedfacedeadbeefabaddad2",
+            "d9313225f88406e5a55909c5aff5269a86a7a9531534f7da2e4c303d8a318a721c3c0c95956809532fcf0e2449a6b525b16aedf5aa0de657ba637b39",
+            "42831ec2217774244b7221b784d0d49ce3aa212f2c02a4e035c17e2329aca12e21d514b25466931c7d8f6a5aac84aa051ba30b396a0aac973d58e091",
+            "5bc94fbc3221a5db94fae95ae7121a47",
+        ),
+    ];
+
+    for (case_idx, (key, iv, aad, plaintext, ciphertext, tag)) in test_cases.iter().enumerate() {
+        println!("==== Test Case {} ====", case_idx + 2);
+        let aes_ref = AesRef::new(&hex_to_bytes(key));
+        let mut gcm_iv = [0u8; GCM_IV_LEN_IN_BYTE];
+        gcm_iv.copy_from_slice(&hex_to_bytes(iv));
+
+        let (output_ciphertext, output_tag) = aes_ref.gcm_encrypt(&gcm_iv, &hex_to_bytes(aad), &hex_to_bytes(plaintext));
+        println!("Tag      : {output_tag:>2x?}");
+        println!("Correct  : {:>2x?}", hex_to_bytes(tag));
+
+        if output_ciphertext != hex_to_bytes(ciphertext) || output_tag.to_vec() != hex_to_bytes(tag) {
+            println!("\nGCM failure!");
+            return false;
         }
         println!();
     }
+
+    true
+}
+
+/// Subkeys and examples 1 to 4 of RFC 4493.
+fn test_cmac_ref() -> bool {
+    let aes_ref = AesRef::new(&hex_to_bytes("2b7e151628aed2a6abf7158809cf4f3c"));
+    let (k1, k2) = aes_ref.cmac_subkeys();
+    println!("K1       : {k1:>2x?}");
+    println!("K2       : {k2:>2x?}");
+    if k1.to_vec() != hex_to_bytes("fbeed618357133667c85e08f7236a8de") || k2.to_vec() != hex_to_bytes("f7ddac306ae266ccf90bc11ee46d513b") {
+        println!("\nCMAC subkey failure!");
+        return false;
+    }
+
+    let message = hex_to_bytes(
+        "6bc1bee22e409f96e93d7e117393172aae2d8a571e03ac9c9eb76fac45af8e5130c81c46a35ce411e5fbc1191a0a52eff69f2445df4f9b17ad2b417be66c3710"
+    );
+    for (len, mac) in [
+        (0, "bb1d6929e95937287fa37d129b756746"),
+        (16, "070a16b46b4d4144f79bdd9dd04a287c"),
+        (40, "dfa66747de9ae63030ca32611497c827"),
+        (64, "51f0bebf7e3b9d92fc49741779363cfe"),
+    ] {
+        let output = aes_ref.cmac(&message[..len]);
+        println!("==== Mlen = {len} ====");
+        println!("Mac      : {output:>2x?}");
+        println!("Correct  : {:>2x?}", hex_to_bytes(mac));
+
+        if output.to_vec() != hex_to_bytes(mac) {
+            println!("\nCMAC failure!");
+            return false;
+        }
+    }
+
+    true
+}
+
+fn hex_to_bytes(hex: &str) -> Vec<u8> {
+    (0..hex.len()).step_by(2).map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap()).collect()
 }
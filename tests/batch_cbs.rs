@@ -0,0 +1,185 @@
+use std::time::Instant;
+
+use rand::Rng;
+use tfhe::core_crypto::{
+    prelude::*,
+    fft_impl::fft64::{c64, crypto::ggsw::{FourierGgswCiphertextList, FourierGgswCiphertextView}},
+};
+use patching_wwlp::{circuit_bootstrap_lwe_ciphertext_by_trace_with_preprocessing, circuit_bootstrap_lwe_ciphertext_list_by_trace_with_preprocessing, gen_all_auto_keys, generate_scheme_switching_key, keygen_pbs, CbsAccumulator, WWLpCBSParam, wwlp_cbs_instance::*};
+
+type Scalar = u64;
+
+fn main() {
+    // wopbs_param_message_2_carry_2
+    println!("-------- wopbs_param_message_2_carry_2 --------");
+    test_batch_cbs(*WWLP_CBS_WOPBS_2_2, 128);
+}
+
+fn test_batch_cbs(
+    param: WWLpCBSParam<u64>,
+    num_lwe: usize,
+) {
+    let lwe_dimension = param.lwe_dimension();
+    let lwe_modular_std_dev = param.lwe_modular_std_dev();
+    let polynomial_size = param.polynomial_size();
+    let glwe_dimension = param.glwe_dimension();
+    let glwe_modular_std_dev = param.glwe_modular_std_dev();
+    let pbs_base_log = param.pbs_base_log();
+    let pbs_level = param.pbs_level();
+    let ks_base_log = param.ks_base_log();
+    let ks_level = param.ks_level();
+    let auto_base_log = param.auto_base_log();
+    let auto_level = param.auto_level();
+    let fft_type_auto = param.fft_type_auto();
+    let ss_base_log = param.ss_base_log();
+    let ss_level = param.ss_level();
+    let cbs_base_log = param.cbs_base_log();
+    let cbs_level = param.cbs_level();
+    let log_lut_count = param.log_lut_count();
+    let ciphertext_modulus = param.ciphertext_modulus();
+    let glwe_size = glwe_dimension.to_glwe_size();
+
+    // Set random generators and buffers
+    let mut boxed_seeder = new_seeder();
+    let seeder = boxed_seeder.as_mut();
+
+    let mut secret_generator = SecretRandomGenerator::<ActivatedRandomGenerator>::new(seeder.seed());
+    let mut encryption_generator = EncryptionRandomGenerator::<ActivatedRandomGenerator>::new(seeder.seed(), seeder);
+
+    // Generate keys
+    let (
+        _lwe_sk,
+        glwe_sk,
+        lwe_sk_after_ks,
+        bsk,
+        _ksk,
+    ) = keygen_pbs(
+        lwe_dimension,
+        glwe_dimension,
+        polynomial_size,
+        lwe_modular_std_dev,
+        glwe_modular_std_dev,
+        pbs_base_log,
+        pbs_level,
+        ks_base_log,
+        ks_level,
+        &mut secret_generator,
+        &mut encryption_generator,
+    );
+    let bsk = bsk.as_view();
+
+    let auto_keys = gen_all_auto_keys(
+        auto_base_log,
+        auto_level,
+        fft_type_auto,
+        &glwe_sk,
+        glwe_modular_std_dev,
+        &mut encryption_generator,
+    );
+
+    let ss_key = generate_scheme_switching_key(
+        &glwe_sk,
+        ss_base_log,
+        ss_level,
+        glwe_modular_std_dev,
+        ciphertext_modulus,
+        &mut encryption_generator,
+    );
+    let ss_key = ss_key.as_view();
+
+    let cbs_acc = CbsAccumulator::new(polynomial_size, glwe_size, cbs_base_log, cbs_level, log_lut_count);
+
+    // GLWE encryption of 1 to be multiplied by the output GGSW
+    let log_scale = Scalar::BITS - 2;
+    let mut pt_one = PlaintextList::new(Scalar::ZERO, PlaintextCount(polynomial_size.0));
+    *pt_one.get_mut(0).0 = Scalar::ONE << log_scale;
+    let mut glwe_one = GlweCiphertext::new(Scalar::ZERO, glwe_size, polynomial_size, ciphertext_modulus);
+    encrypt_glwe_ciphertext(&glwe_sk, &mut glwe_one, &pt_one, glwe_modular_std_dev, &mut encryption_generator);
+
+    let get_ext_prod_err = |fourier_ggsw: FourierGgswCiphertextView, msg: Scalar| -> Scalar {
+        let mut out = GlweCiphertext::new(Scalar::ZERO, glwe_size, polynomial_size, ciphertext_modulus);
+        add_external_product_assign(&mut out, &fourier_ggsw, &glwe_one);
+
+        let mut dec = PlaintextList::new(Scalar::ZERO, PlaintextCount(polynomial_size.0));
+        decrypt_glwe_ciphertext(&glwe_sk, &out, &mut dec);
+
+        let correct_val = msg << log_scale;
+        let decrypted = *dec.get(0).0;
+        std::cmp::min(decrypted.wrapping_sub(correct_val), correct_val.wrapping_sub(decrypted))
+    };
+
+    // Input bits
+    let mut rng = rand::thread_rng();
+    let msg_list = (0..num_lwe).map(|_| rng.gen_range(0..2) as Scalar).collect::<Vec<Scalar>>();
+
+    let mut lwe_list = LweCiphertextList::new(Scalar::ZERO, lwe_sk_after_ks.lwe_dimension().to_lwe_size(), LweCiphertextCount(num_lwe), ciphertext_modulus);
+    for (mut lwe, &msg) in lwe_list.iter_mut().zip(msg_list.iter()) {
+        encrypt_lwe_ciphertext(
+            &lwe_sk_after_ks,
+            &mut lwe,
+            Plaintext(msg << (Scalar::BITS - 1)),
+            lwe_modular_std_dev,
+            &mut encryption_generator,
+        );
+    }
+
+    // Sequential CBS
+    let now = Instant::now();
+    let fourier_ggsw_vec = lwe_list.iter().map(|lwe| {
+        circuit_bootstrap_lwe_ciphertext_by_trace_with_preprocessing(
+            lwe.as_view(),
+            bsk,
+            &auto_keys,
+            ss_key,
+            &cbs_acc,
+        )
+    }).collect::<Vec<_>>();
+    let time_single = now.elapsed();
+
+    let mut max_err_single = Scalar::ZERO;
+    for (fourier_ggsw, &msg) in fourier_ggsw_vec.iter().zip(msg_list.iter()) {
+        let max_err = get_ext_prod_err(fourier_ggsw.as_view(), msg);
+        max_err_single = std::cmp::max(max_err_single, max_err);
+    }
+
+    // Batch CBS
+    let mut fourier_ggsw_list = FourierGgswCiphertextList::new(
+        vec![c64::default();
+        num_lwe * polynomial_size.to_fourier_polynomial_size().0
+            * glwe_size.0
+            * glwe_size.0
+            * cbs_level.0
+        ],
+        num_lwe,
+        glwe_size,
+        polynomial_size,
+        cbs_base_log,
+        cbs_level,
+    );
+
+    let now = Instant::now();
+    circuit_bootstrap_lwe_ciphertext_list_by_trace_with_preprocessing(
+        &lwe_list,
+        &mut fourier_ggsw_list,
+        bsk,
+        &auto_keys,
+        ss_key,
+        &cbs_acc,
+    );
+    let time_batch = now.elapsed();
+
+    let mut max_err_batch = Scalar::ZERO;
+    for (fourier_ggsw, &msg) in fourier_ggsw_list.as_view().into_ggsw_iter().zip(msg_list.iter()) {
+        let max_err = get_ext_prod_err(fourier_ggsw, msg);
+        max_err_batch = std::cmp::max(max_err_batch, max_err);
+    }
+
+    println!(
+        "[Single] {} CBS: {} ms, ext prod err: (Max) {:.2} bits",
+        num_lwe, time_single.as_millis(), (max_err_single as f64).log2(),
+    );
+    println!(
+        "[Batch]  {} CBS: {} ms, ext prod err: (Max) {:.2} bits",
+        num_lwe, time_batch.as_millis(), (max_err_batch as f64).log2(),
+    );
+}
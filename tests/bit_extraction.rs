@@ -0,0 +1,176 @@
+use tfhe::core_crypto::prelude::*;
+use patching_wwlp::{circuit_bootstrap_lwe_ciphertext_by_trace_with_preprocessing, extract_bits_from_lwe_ciphertext, gen_all_auto_keys, generate_scheme_switching_key, keygen_pbs, recompose_lwe_ciphertext_from_bits, CbsAccumulator, WWLpCBSParam, wwlp_cbs_instance::*};
+
+type Scalar = u64;
+
+fn main() {
+    // wopbs_param_message_2_carry_2
+    println!("-------- wopbs_param_message_2_carry_2 --------");
+    test_bit_extraction(*WWLP_CBS_WOPBS_2_2, 4);
+}
+
+fn test_bit_extraction(
+    param: WWLpCBSParam<u64>,
+    num_bits: usize,
+) {
+    let lwe_dimension = param.lwe_dimension();
+    let lwe_modular_std_dev = param.lwe_modular_std_dev();
+    let polynomial_size = param.polynomial_size();
+    let glwe_dimension = param.glwe_dimension();
+    let glwe_modular_std_dev = param.glwe_modular_std_dev();
+    let pbs_base_log = param.pbs_base_log();
+    let pbs_level = param.pbs_level();
+    let ks_base_log = param.ks_base_log();
+    let ks_level = param.ks_level();
+    let auto_base_log = param.auto_base_log();
+    let auto_level = param.auto_level();
+    let fft_type_auto = param.fft_type_auto();
+    let ss_base_log = param.ss_base_log();
+    let ss_level = param.ss_level();
+    let cbs_base_log = param.cbs_base_log();
+    let cbs_level = param.cbs_level();
+    let log_lut_count = param.log_lut_count();
+    let ciphertext_modulus = param.ciphertext_modulus();
+    let glwe_size = glwe_dimension.to_glwe_size();
+
+    // Set random generators and buffers
+    let mut boxed_seeder = new_seeder();
+    let seeder = boxed_seeder.as_mut();
+
+    let mut secret_generator = SecretRandomGenerator::<ActivatedRandomGenerator>::new(seeder.seed());
+    let mut encryption_generator = EncryptionRandomGenerator::<ActivatedRandomGenerator>::new(seeder.seed(), seeder);
+
+    // Generate keys
+    let (
+        lwe_sk,
+        glwe_sk,
+        lwe_sk_after_ks,
+        bsk,
+        ksk,
+    ) = keygen_pbs(
+        lwe_dimension,
+        glwe_dimension,
+        polynomial_size,
+        lwe_modular_std_dev,
+        glwe_modular_std_dev,
+        pbs_base_log,
+        pbs_level,
+        ks_base_log,
+        ks_level,
+        &mut secret_generator,
+        &mut encryption_generator,
+    );
+    let bsk = bsk.as_view();
+
+    let auto_keys = gen_all_auto_keys(
+        auto_base_log,
+        auto_level,
+        fft_type_auto,
+        &glwe_sk,
+        glwe_modular_std_dev,
+        &mut encryption_generator,
+    );
+
+    let ss_key = generate_scheme_switching_key(
+        &glwe_sk,
+        ss_base_log,
+        ss_level,
+        glwe_modular_std_dev,
+        ciphertext_modulus,
+        &mut encryption_generator,
+    );
+    let ss_key = ss_key.as_view();
+
+    let cbs_acc = CbsAccumulator::new(polynomial_size, glwe_size, cbs_base_log, cbs_level, log_lut_count);
+
+    // GLWE encryption of 1 to be multiplied by the output GGSW
+    let log_scale = Scalar::BITS - 2;
+    let mut pt_one = PlaintextList::new(Scalar::ZERO, PlaintextCount(polynomial_size.0));
+    *pt_one.get_mut(0).0 = Scalar::ONE << log_scale;
+    let mut glwe_one = GlweCiphertext::new(Scalar::ZERO, glwe_size, polynomial_size, ciphertext_modulus);
+    encrypt_glwe_ciphertext(&glwe_sk, &mut glwe_one, &pt_one, glwe_modular_std_dev, &mut encryption_generator);
+
+    // One padding bit
+    let delta_log = DeltaLog(Scalar::BITS as usize - num_bits - 1);
+    let message_modulus = 1 << num_bits;
+
+    let mut num_bit_fail = 0;
+    let mut num_cbs_fail = 0;
+    let mut num_recomp_fail = 0;
+    let mut max_bit_err = Scalar::ZERO;
+    let mut max_recomp_err = Scalar::ZERO;
+    for msg in 0..message_modulus {
+        let lwe = allocate_and_encrypt_new_lwe_ciphertext(
+            &lwe_sk,
+            Plaintext((msg as Scalar) << delta_log.0),
+            glwe_modular_std_dev,
+            ciphertext_modulus,
+            &mut encryption_generator,
+        );
+
+        // Bit extraction
+        let mut lwe_bit_list = LweCiphertextList::new(
+            Scalar::ZERO,
+            lwe_sk_after_ks.lwe_dimension().to_lwe_size(),
+            LweCiphertextCount(num_bits),
+            ciphertext_modulus,
+        );
+        extract_bits_from_lwe_ciphertext(&lwe, &mut lwe_bit_list, bsk, &ksk, delta_log);
+
+        for (bit_idx, lwe_bit) in lwe_bit_list.iter().enumerate() {
+            let bit = ((msg >> bit_idx) & 1) as Scalar;
+            let correct_val = bit << (Scalar::BITS - 1);
+            let decrypted = decrypt_lwe_ciphertext(&lwe_sk_after_ks, &lwe_bit).0;
+            let abs_err = std::cmp::min(decrypted.wrapping_sub(correct_val), correct_val.wrapping_sub(decrypted));
+            max_bit_err = std::cmp::max(max_bit_err, abs_err);
+            if abs_err >= Scalar::ONE << (Scalar::BITS - 2) {
+                num_bit_fail += 1;
+            }
+
+            // Circuit bootstrapping of the extracted bit
+            let fourier_ggsw = circuit_bootstrap_lwe_ciphertext_by_trace_with_preprocessing(
+                lwe_bit.as_view(),
+                bsk,
+                &auto_keys,
+                ss_key,
+                &cbs_acc,
+            );
+
+            let mut out = GlweCiphertext::new(Scalar::ZERO, glwe_size, polynomial_size, ciphertext_modulus);
+            add_external_product_assign(&mut out, &fourier_ggsw, &glwe_one);
+
+            let mut dec = PlaintextList::new(Scalar::ZERO, PlaintextCount(polynomial_size.0));
+            decrypt_glwe_ciphertext(&glwe_sk, &out, &mut dec);
+            let rounded = (*dec.get(0).0).wrapping_add(Scalar::ONE << (log_scale - 1)) >> log_scale;
+            if rounded != bit {
+                num_cbs_fail += 1;
+            }
+        }
+
+        // Recomposition
+        let mut lwe_recomp = LweCiphertext::new(Scalar::ZERO, lwe.lwe_size(), ciphertext_modulus);
+        recompose_lwe_ciphertext_from_bits(&lwe_bit_list, &mut lwe_recomp, bsk, delta_log);
+
+        let correct_val = (msg as Scalar) << delta_log.0;
+        let decrypted = decrypt_lwe_ciphertext(&lwe_sk, &lwe_recomp).0;
+        let abs_err = std::cmp::min(decrypted.wrapping_sub(correct_val), correct_val.wrapping_sub(decrypted));
+        max_recomp_err = std::cmp::max(max_recomp_err, abs_err);
+        let rounded = decrypted.wrapping_add(Scalar::ONE << (delta_log.0 - 1)) >> delta_log.0;
+        if rounded != msg as Scalar {
+            num_recomp_fail += 1;
+        }
+    }
+
+    println!(
+        "Bit extraction: {} / {} failures, (Max) {:.2} bits",
+        num_bit_fail, num_bits * message_modulus, (max_bit_err as f64).log2(),
+    );
+    println!(
+        "CBS of extracted bits: {} / {} failures",
+        num_cbs_fail, num_bits * message_modulus,
+    );
+    println!(
+        "Recomposition: {} / {} failures, (Max) {:.2} bits",
+        num_recomp_fail, message_modulus, (max_recomp_err as f64).log2(),
+    );
+}
@@ -0,0 +1,217 @@
+use rand::Rng;
+use tfhe::core_crypto::{
+    prelude::*,
+    commons::math::random::Seed,
+};
+use patching_wwlp::{circuit_bootstrap_lwe_ciphertext_by_trace_with_preprocessing, circuit_bootstrap_lwe_ciphertext_by_trace_with_preprocessing_high_prec, keyswitch_lwe_ciphertext_by_glwe_keyswitch, lwe_msb_bit_to_ggsw_by_pfpks, AesClientKey, CbsClientKey, HighPrecWWLpCbsClientKey, WWLpCbsClientKey, F128, aes_instances::*, wwlp_cbs_instance::*};
+
+type Scalar = u64;
+const NUM_REPEAT: usize = 20;
+
+fn main() {
+    let seed = Seed(rand::thread_rng().gen::<u128>());
+
+    println!("-------- CBS key set (wopbs_param_message_2_carry_2) --------");
+    let param = *CBS_WOPBS_2_2;
+    let (client_key, server_key) = CbsClientKey::generate(&param, seed);
+    let (client_key_again, _) = CbsClientKey::generate(&param, seed);
+    println!(
+        "Same secret keys from the same seed: {}",
+        client_key.glwe_secret_key().as_ref() == client_key_again.glwe_secret_key().as_ref()
+            && client_key.lwe_secret_key_after_ks().as_ref() == client_key_again.lwe_secret_key_after_ks().as_ref(),
+    );
+    drop(client_key_again);
+
+    let glwe_size = param.glwe_dimension().to_glwe_size();
+    let polynomial_size = param.polynomial_size();
+    let cbs_base_log = param.cbs_base_log();
+    let cbs_level = param.cbs_level();
+    let ciphertext_modulus = param.ciphertext_modulus();
+
+    let mut boxed_seeder = new_seeder();
+    let seeder = boxed_seeder.as_mut();
+    let mut encryption_generator = EncryptionRandomGenerator::<ActivatedRandomGenerator>::new(seeder.seed(), seeder);
+
+    let mut rng = rand::thread_rng();
+    let mut num_fail = 0;
+    for _ in 0..NUM_REPEAT {
+        let msg = rng.gen_range(0..2) as Scalar;
+        let lwe = allocate_and_encrypt_new_lwe_ciphertext(
+            client_key.lwe_secret_key_after_ks(),
+            Plaintext(msg << (Scalar::BITS - 1)),
+            param.lwe_modular_std_dev(),
+            ciphertext_modulus,
+            &mut encryption_generator,
+        );
+
+        let mut ggsw = GgswCiphertext::new(Scalar::ZERO, glwe_size, polynomial_size, cbs_base_log, cbs_level, ciphertext_modulus);
+        lwe_msb_bit_to_ggsw_by_pfpks(
+            &lwe,
+            &mut ggsw,
+            server_key.fourier_bsk(),
+            &server_key.pfpksk_list().as_view(),
+            server_key.cbs_acc(),
+        );
+        let mut fourier_ggsw = FourierGgswCiphertext::new(glwe_size, polynomial_size, cbs_base_log, cbs_level);
+        convert_standard_ggsw_ciphertext_to_fourier(&ggsw, &mut fourier_ggsw);
+
+        if !check_cbs_output(&fourier_ggsw, client_key.glwe_secret_key(), msg, param.glwe_modular_std_dev(), &mut encryption_generator) {
+            num_fail += 1;
+        }
+    }
+    println!("CBS failures: {num_fail}/{NUM_REPEAT}\n");
+
+    println!("-------- Patched WWL+ CBS key set (wopbs_param_message_2_carry_2) --------");
+    let param = *WWLP_CBS_WOPBS_2_2;
+    let (client_key, server_key) = WWLpCbsClientKey::generate(&param, seed);
+    let (client_key_again, _) = WWLpCbsClientKey::generate(&param, seed);
+    println!(
+        "Same secret keys from the same seed: {}",
+        client_key.glwe_secret_key().as_ref() == client_key_again.glwe_secret_key().as_ref()
+            && client_key.lwe_secret_key_after_ks().as_ref() == client_key_again.lwe_secret_key_after_ks().as_ref(),
+    );
+    drop(client_key_again);
+
+    let mut num_fail = 0;
+    for _ in 0..NUM_REPEAT {
+        let msg = rng.gen_range(0..2) as Scalar;
+        let lwe = allocate_and_encrypt_new_lwe_ciphertext(
+            client_key.lwe_secret_key_after_ks(),
+            Plaintext(msg << (Scalar::BITS - 1)),
+            param.lwe_modular_std_dev(),
+            ciphertext_modulus,
+            &mut encryption_generator,
+        );
+
+        let fourier_ggsw = circuit_bootstrap_lwe_ciphertext_by_trace_with_preprocessing(
+            lwe.as_view(),
+            server_key.fourier_bsk(),
+            server_key.auto_keys(),
+            server_key.ss_key(),
+            server_key.cbs_acc(),
+        );
+
+        if !check_cbs_output(&fourier_ggsw, client_key.glwe_secret_key(), msg, param.glwe_modular_std_dev(), &mut encryption_generator) {
+            num_fail += 1;
+        }
+    }
+    println!("CBS failures: {num_fail}/{NUM_REPEAT}\n");
+
+    println!("-------- High-precision WWL+ CBS key set (wopbs_param_message_3_carry_3) --------");
+    let param = *HIGHPREC_WWLP_CBS_WOPBS_3_3;
+    let (client_key, server_key) = HighPrecWWLpCbsClientKey::generate::<F128>(&param, seed);
+    let (client_key_again, _) = HighPrecWWLpCbsClientKey::generate::<F128>(&param, seed);
+    println!(
+        "Same secret keys from the same seed: {}",
+        client_key.glwe_secret_key().as_ref() == client_key_again.glwe_secret_key().as_ref()
+            && client_key.lwe_secret_key_after_ks().as_ref() == client_key_again.lwe_secret_key_after_ks().as_ref()
+            && client_key.large_glwe_secret_key().as_ref() == client_key_again.large_glwe_secret_key().as_ref(),
+    );
+    drop(client_key_again);
+
+    let mut num_fail = 0;
+    for _ in 0..NUM_REPEAT {
+        let msg = rng.gen_range(0..2) as Scalar;
+        let lwe = allocate_and_encrypt_new_lwe_ciphertext(
+            client_key.lwe_secret_key_after_ks(),
+            Plaintext(msg << (Scalar::BITS - 1)),
+            param.lwe_modular_std_dev(),
+            param.ciphertext_modulus(),
+            &mut encryption_generator,
+        );
+
+        let fourier_ggsw = circuit_bootstrap_lwe_ciphertext_by_trace_with_preprocessing_high_prec(
+            lwe.as_view(),
+            server_key.fourier_bsk(),
+            server_key.glwe_ksk_to_large(),
+            server_key.glwe_ksk_from_large(),
+            server_key.auto_keys(),
+            server_key.ss_key(),
+            server_key.cbs_acc(),
+        );
+
+        if !check_cbs_output(&fourier_ggsw, client_key.glwe_secret_key(), msg, param.glwe_modular_std_dev(), &mut encryption_generator) {
+            num_fail += 1;
+        }
+    }
+    println!("CBS failures: {num_fail}/{NUM_REPEAT}\n");
+
+    println!("-------- AES key set (AES_SET_1) --------");
+    let param = *AES_SET_1;
+    let (client_key, server_key) = AesClientKey::generate(&param, seed);
+    let (client_key_again, _) = AesClientKey::generate(&param, seed);
+    println!(
+        "Same secret keys from the same seed: {}",
+        client_key.glwe_secret_key().as_ref() == client_key_again.glwe_secret_key().as_ref()
+            && client_key.lwe_secret_key_after_ks().as_ref() == client_key_again.lwe_secret_key_after_ks().as_ref(),
+    );
+    drop(client_key_again);
+
+    // The input bit is encrypted under the bootstrapping output key as in the AES evaluation,
+    // and keyswitched by the GLWE keyswitching key before the CBS.
+    let glwe_ksk = server_key.glwe_ksk();
+    let lwe_size_after_ks = glwe_ksk.output_glwe_size().to_glwe_dimension()
+        .to_equivalent_lwe_dimension(glwe_ksk.polynomial_size())
+        .to_lwe_size();
+
+    let mut num_fail = 0;
+    for _ in 0..NUM_REPEAT {
+        let msg = rng.gen_range(0..2) as Scalar;
+        let lwe = allocate_and_encrypt_new_lwe_ciphertext(
+            client_key.lwe_secret_key(),
+            Plaintext(msg << (Scalar::BITS - 1)),
+            param.glwe_modular_std_dev(),
+            param.ciphertext_modulus(),
+            &mut encryption_generator,
+        );
+        let mut lwe_ks = LweCiphertext::new(Scalar::ZERO, lwe_size_after_ks, param.ciphertext_modulus());
+        keyswitch_lwe_ciphertext_by_glwe_keyswitch(&lwe, &mut lwe_ks, glwe_ksk);
+
+        let fourier_ggsw = circuit_bootstrap_lwe_ciphertext_by_trace_with_preprocessing(
+            lwe_ks.as_view(),
+            server_key.fourier_bsk(),
+            server_key.auto_keys(),
+            server_key.ss_key(),
+            server_key.cbs_acc(),
+        );
+
+        if !check_cbs_output(&fourier_ggsw, client_key.glwe_secret_key(), msg, param.glwe_modular_std_dev(), &mut encryption_generator) {
+            num_fail += 1;
+        }
+    }
+    println!("CBS failures: {num_fail}/{NUM_REPEAT}");
+}
+
+fn check_cbs_output<C, K, G>(
+    fourier_ggsw: &FourierGgswCiphertext<C>,
+    glwe_sk: &GlweSecretKey<K>,
+    msg: Scalar,
+    glwe_modular_std_dev: StandardDev,
+    encryption_generator: &mut EncryptionRandomGenerator<G>,
+) -> bool
+where
+    C: Container<Element=tfhe::core_crypto::fft_impl::fft64::c64>,
+    K: Container<Element=Scalar>,
+    G: ByteRandomGenerator,
+{
+    let glwe_size = fourier_ggsw.glwe_size();
+    let polynomial_size = fourier_ggsw.polynomial_size();
+    let ciphertext_modulus = CiphertextModulus::<Scalar>::new_native();
+
+    let pt = PlaintextList::from_container((0..polynomial_size.0).map(|i| {
+        ((i % 2) as Scalar) << (Scalar::BITS - 1)
+    }).collect::<Vec<Scalar>>());
+    let mut glwe = GlweCiphertext::new(Scalar::ZERO, glwe_size, polynomial_size, ciphertext_modulus);
+    encrypt_glwe_ciphertext(glwe_sk, &mut glwe, &pt, glwe_modular_std_dev, encryption_generator);
+
+    let mut out = GlweCiphertext::new(Scalar::ZERO, glwe_size, polynomial_size, ciphertext_modulus);
+    add_external_product_assign(&mut out, fourier_ggsw, &glwe);
+
+    let mut dec = PlaintextList::new(Scalar::ZERO, PlaintextCount(polynomial_size.0));
+    decrypt_glwe_ciphertext(glwe_sk, &out, &mut dec);
+
+    let decomposer = SignedDecomposer::new(DecompositionBaseLog(1), DecompositionLevelCount(1));
+    dec.iter().zip(pt.iter()).all(|(d, p)| {
+        decomposer.closest_representable(*d.0) == msg * *p.0
+    })
+}
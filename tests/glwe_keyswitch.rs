@@ -1,10 +1,11 @@
 use std::time::Instant;
 
 use tfhe::core_crypto::prelude::*;
-use patching_wwlp::{fourier_glwe_keyswitch::*, get_glwe_l2_err, glwe_keyswitch::*, utils::get_glwe_max_err};
+use patching_wwlp::{fourier_glwe_keyswitch::*, get_glwe_l2_err, glwe_keyswitch::*, utils::get_glwe_max_err, F128};
 
 type Scalar = u64;
 const FFT_TYPE: FftType = FftType::Split(40);
+const FFT_TYPE_128: FftType = FftType::F128;
 
 fn main() {
     let polynomial_size = PolynomialSize(2048);
@@ -96,7 +97,7 @@ fn test_glwe_keyswitch(
         ciphertext_modulus,
         &mut encryption_generator,
     );
-    let mut fourier_glwe_ksk = FourierGlweKeyswitchKey::new(
+    let mut fourier_glwe_ksk: FourierGlweKeyswitchKeyOwned = FourierGlweKeyswitchKey::new(
         large_glwe_size,
         glwe_size,
         polynomial_size,
@@ -106,6 +107,16 @@ fn test_glwe_keyswitch(
     );
     convert_standard_glwe_keyswitch_key_to_fourier(&standard_glwe_ksk, &mut fourier_glwe_ksk);
 
+    let mut fourier128_glwe_ksk: FourierGlweKeyswitchKeyOwned<F128> = FourierGlweKeyswitchKey::new(
+        large_glwe_size,
+        glwe_size,
+        polynomial_size,
+        decomp_base_log_to_small,
+        decomp_level_count_to_small,
+        FFT_TYPE_128,
+    );
+    convert_standard_glwe_keyswitch_key_to_fourier(&standard_glwe_ksk, &mut fourier128_glwe_ksk);
+
 
     let mut output = GlweCiphertext::new(Scalar::ZERO, glwe_size, polynomial_size, ciphertext_modulus);
 
@@ -161,6 +172,25 @@ fn test_glwe_keyswitch(
         (max_err as f64).log2(),
         l2_err.log2(),
     );
+
+    let now = Instant::now();
+    for _ in 0..num_repeat {
+        keyswitch_glwe_ciphertext(
+            &fourier128_glwe_ksk,
+            &large_ct,
+            &mut output,
+        );
+    }
+    let time_to_small_fourier128 = now.elapsed();
+
+    let max_err = get_glwe_max_err(&glwe_sk, &output, &pt);
+    let l2_err = get_glwe_l2_err(&glwe_sk, &output, &pt);
+    println!(
+        "[Fourier128] GLWE KS large -> small: {} ms, (Max) {:.2} bits (l2) {:.2} bits",
+        time_to_small_fourier128.as_millis() as f64 / num_repeat as f64,
+        (max_err as f64).log2(),
+        l2_err.log2(),
+    );
     println!();
 
     // Test Glwe Keyswitching: Small -> Large
@@ -177,7 +207,7 @@ fn test_glwe_keyswitch(
         ciphertext_modulus,
         &mut encryption_generator,
     );
-    let mut fourier_glwe_ksk = FourierGlweKeyswitchKey::new(
+    let mut fourier_glwe_ksk: FourierGlweKeyswitchKeyOwned = FourierGlweKeyswitchKey::new(
         glwe_size,
         large_glwe_size,
         polynomial_size,
@@ -187,6 +217,16 @@ fn test_glwe_keyswitch(
     );
     convert_standard_glwe_keyswitch_key_to_fourier(&standard_glwe_ksk, &mut fourier_glwe_ksk);
 
+    let mut fourier128_glwe_ksk: FourierGlweKeyswitchKeyOwned<F128> = FourierGlweKeyswitchKey::new(
+        glwe_size,
+        large_glwe_size,
+        polynomial_size,
+        decomp_base_log_to_large,
+        decomp_level_count_to_large,
+        FFT_TYPE_128,
+    );
+    convert_standard_glwe_keyswitch_key_to_fourier(&standard_glwe_ksk, &mut fourier128_glwe_ksk);
+
     let mut output = GlweCiphertext::new(Scalar::ZERO, large_glwe_size, polynomial_size, ciphertext_modulus);
 
     let now = Instant::now();
@@ -226,4 +266,23 @@ fn test_glwe_keyswitch(
         (max_err as f64).log2(),
         l2_err.log2(),
     );
+
+    let now = Instant::now();
+    for _ in 0..num_repeat {
+        keyswitch_glwe_ciphertext(
+            &fourier128_glwe_ksk,
+            &ct,
+            &mut output,
+        );
+    }
+    let time_to_large_fourier128 = now.elapsed();
+
+    let max_err = get_glwe_max_err(&large_glwe_sk, &output, &pt);
+    let l2_err = get_glwe_l2_err(&large_glwe_sk, &output, &pt);
+    println!(
+        "[Fourier128] GLWE KS small -> large: {} ms, (Max) {:.2} bits (l2) {:.2} bits",
+        time_to_large_fourier128.as_millis() as f64 / num_repeat as f64,
+        (max_err as f64).log2(),
+        l2_err.log2(),
+    );
 }
@@ -0,0 +1,106 @@
+use rand::Rng;
+use tfhe::core_crypto::prelude::*;
+use patching_wwlp::{circuit_bootstrap_lwe_ciphertext_by_trace_with_preprocessing_high_prec, keygen_high_prec_wwlp_cbs, CbsAccumulator, HighPrecWWLpCBSParam, HighPrecWWLpCbsKeys, F128, wwlp_cbs_instance::*};
+
+type Scalar = u64;
+
+fn main() {
+    // wopbs_param_message_3_carry_3
+    println!("-------- wopbs_param_message_3_carry_3 --------");
+    test_high_prec_cbs(*HIGHPREC_WWLP_CBS_WOPBS_3_3, 100);
+
+    // wopbs_param_message_4_carry_4
+    println!("-------- wopbs_param_message_4_carry_4 --------");
+    test_high_prec_cbs(*HIGHPREC_WWLP_CBS_WOPBS_4_4, 100);
+}
+
+fn test_high_prec_cbs(
+    param: HighPrecWWLpCBSParam<u64>,
+    num_repeat: usize,
+) {
+    let lwe_modular_std_dev = param.lwe_modular_std_dev();
+    let polynomial_size = param.polynomial_size();
+    let glwe_dimension = param.glwe_dimension();
+    let glwe_modular_std_dev = param.glwe_modular_std_dev();
+    let cbs_base_log = param.cbs_base_log();
+    let cbs_level = param.cbs_level();
+    let log_lut_count = param.log_lut_count();
+    let ciphertext_modulus = param.ciphertext_modulus();
+    let glwe_size = glwe_dimension.to_glwe_size();
+
+    // Set random generators and buffers
+    let mut boxed_seeder = new_seeder();
+    let seeder = boxed_seeder.as_mut();
+
+    let mut secret_generator = SecretRandomGenerator::<ActivatedRandomGenerator>::new(seeder.seed());
+    let mut encryption_generator = EncryptionRandomGenerator::<ActivatedRandomGenerator>::new(seeder.seed(), seeder);
+
+    // Generate keys
+    let HighPrecWWLpCbsKeys {
+        glwe_secret_key: glwe_sk,
+        lwe_secret_key_after_ks: lwe_sk_after_ks,
+        fourier_bsk: bsk,
+        glwe_ksk_to_large,
+        glwe_ksk_from_large,
+        auto_keys,
+        ss_key,
+        ..
+    } = keygen_high_prec_wwlp_cbs::<_, F128, _>(&param, &mut secret_generator, &mut encryption_generator);
+    let bsk = bsk.as_view();
+    let ss_key = ss_key.as_view();
+
+    let cbs_acc = CbsAccumulator::new(polynomial_size, glwe_size, cbs_base_log, cbs_level, log_lut_count);
+
+    // GLWE encryption of 1 to be multiplied by the output GGSW
+    let log_scale = Scalar::BITS - 2;
+    let mut pt_one = PlaintextList::new(Scalar::ZERO, PlaintextCount(polynomial_size.0));
+    *pt_one.get_mut(0).0 = Scalar::ONE << log_scale;
+    let mut glwe_one = GlweCiphertext::new(Scalar::ZERO, glwe_size, polynomial_size, ciphertext_modulus);
+    encrypt_glwe_ciphertext(&glwe_sk, &mut glwe_one, &pt_one, glwe_modular_std_dev, &mut encryption_generator);
+
+    let mut rng = rand::thread_rng();
+    let mut num_fail = 0;
+    let mut max_err = Scalar::ZERO;
+    for _ in 0..num_repeat {
+        let msg = rng.gen_range(0..2) as Scalar;
+
+        let lwe = allocate_and_encrypt_new_lwe_ciphertext(
+            &lwe_sk_after_ks,
+            Plaintext(msg << (Scalar::BITS - 1)),
+            lwe_modular_std_dev,
+            ciphertext_modulus,
+            &mut encryption_generator,
+        );
+
+        let fourier_ggsw = circuit_bootstrap_lwe_ciphertext_by_trace_with_preprocessing_high_prec(
+            lwe.as_view(),
+            bsk,
+            &glwe_ksk_to_large,
+            &glwe_ksk_from_large,
+            &auto_keys,
+            ss_key,
+            &cbs_acc,
+        );
+
+        let mut out = GlweCiphertext::new(Scalar::ZERO, glwe_size, polynomial_size, ciphertext_modulus);
+        add_external_product_assign(&mut out, &fourier_ggsw, &glwe_one);
+
+        let mut dec = PlaintextList::new(Scalar::ZERO, PlaintextCount(polynomial_size.0));
+        decrypt_glwe_ciphertext(&glwe_sk, &out, &mut dec);
+
+        let correct_val = msg << log_scale;
+        let decrypted = *dec.get(0).0;
+        let rounded = decrypted.wrapping_add(Scalar::ONE << (log_scale - 1)) >> log_scale;
+        if rounded != msg {
+            num_fail += 1;
+        }
+
+        let abs_err = std::cmp::min(decrypted.wrapping_sub(correct_val), correct_val.wrapping_sub(decrypted));
+        max_err = std::cmp::max(max_err, abs_err);
+    }
+
+    println!(
+        "High prec CBS: {} / {} failures, ext prod err: (Max) {:.2} bits",
+        num_fail, num_repeat, (max_err as f64).log2(),
+    );
+}
@@ -0,0 +1,147 @@
+use rand::Rng;
+use tfhe::core_crypto::prelude::*;
+use patching_wwlp::{circuit_bootstrap_lwe_ciphertext_by_trace_with_preprocessing, gen_all_auto_keys, generate_scheme_switching_key, keygen_pbs, lut_eval_by_cmux_tree, CbsAccumulator, WWLpCBSParam, wwlp_cbs_instance::*};
+
+type Scalar = u64;
+
+fn main() {
+    // wopbs_param_message_2_carry_2
+    println!("-------- wopbs_param_message_2_carry_2 --------");
+    // Horizontal packing
+    test_lut_eval(*WWLP_CBS_WOPBS_2_2, 4, 3, 16);
+    // Vertical packing
+    test_lut_eval(*WWLP_CBS_WOPBS_2_2, 12, 2, 4);
+}
+
+fn test_lut_eval(
+    param: WWLpCBSParam<u64>,
+    input_bit_count: usize,
+    output_bit_count: usize,
+    num_repeat: usize,
+) {
+    let lwe_dimension = param.lwe_dimension();
+    let lwe_modular_std_dev = param.lwe_modular_std_dev();
+    let polynomial_size = param.polynomial_size();
+    let glwe_dimension = param.glwe_dimension();
+    let glwe_modular_std_dev = param.glwe_modular_std_dev();
+    let pbs_base_log = param.pbs_base_log();
+    let pbs_level = param.pbs_level();
+    let ks_base_log = param.ks_base_log();
+    let ks_level = param.ks_level();
+    let auto_base_log = param.auto_base_log();
+    let auto_level = param.auto_level();
+    let fft_type_auto = param.fft_type_auto();
+    let ss_base_log = param.ss_base_log();
+    let ss_level = param.ss_level();
+    let cbs_base_log = param.cbs_base_log();
+    let cbs_level = param.cbs_level();
+    let log_lut_count = param.log_lut_count();
+    let ciphertext_modulus = param.ciphertext_modulus();
+    let glwe_size = glwe_dimension.to_glwe_size();
+
+    println!(
+        "LUT: {} bits -> {} bits, N = {}",
+        input_bit_count, output_bit_count, polynomial_size.0,
+    );
+
+    // Set random generators and buffers
+    let mut boxed_seeder = new_seeder();
+    let seeder = boxed_seeder.as_mut();
+
+    let mut secret_generator = SecretRandomGenerator::<ActivatedRandomGenerator>::new(seeder.seed());
+    let mut encryption_generator = EncryptionRandomGenerator::<ActivatedRandomGenerator>::new(seeder.seed(), seeder);
+
+    // Generate keys
+    let (
+        lwe_sk,
+        glwe_sk,
+        lwe_sk_after_ks,
+        bsk,
+        _ksk,
+    ) = keygen_pbs(
+        lwe_dimension,
+        glwe_dimension,
+        polynomial_size,
+        lwe_modular_std_dev,
+        glwe_modular_std_dev,
+        pbs_base_log,
+        pbs_level,
+        ks_base_log,
+        ks_level,
+        &mut secret_generator,
+        &mut encryption_generator,
+    );
+    let bsk = bsk.as_view();
+
+    let auto_keys = gen_all_auto_keys(
+        auto_base_log,
+        auto_level,
+        fft_type_auto,
+        &glwe_sk,
+        glwe_modular_std_dev,
+        &mut encryption_generator,
+    );
+
+    let ss_key = generate_scheme_switching_key(
+        &glwe_sk,
+        ss_base_log,
+        ss_level,
+        glwe_modular_std_dev,
+        ciphertext_modulus,
+        &mut encryption_generator,
+    );
+    let ss_key = ss_key.as_view();
+
+    let cbs_acc = CbsAccumulator::new(polynomial_size, glwe_size, cbs_base_log, cbs_level, log_lut_count);
+
+    let mut rng = rand::thread_rng();
+    let table = (0..(1 << input_bit_count)).map(|_| rng.gen_range(0..(1 << output_bit_count))).collect::<Vec<usize>>();
+
+    let mut num_fail = 0;
+    let mut max_err = Scalar::ZERO;
+    for _ in 0..num_repeat {
+        let msg = rng.gen_range(0..(1 << input_bit_count)) as usize;
+
+        let fourier_ggsw_bit_list = (0..input_bit_count).map(|i| {
+            let bit = ((msg >> i) & 1) as Scalar;
+            let lwe = allocate_and_encrypt_new_lwe_ciphertext(
+                &lwe_sk_after_ks,
+                Plaintext(bit << (Scalar::BITS - 1)),
+                lwe_modular_std_dev,
+                ciphertext_modulus,
+                &mut encryption_generator,
+            );
+
+            circuit_bootstrap_lwe_ciphertext_by_trace_with_preprocessing(
+                lwe.as_view(),
+                bsk,
+                &auto_keys,
+                ss_key,
+                &cbs_acc,
+            )
+        }).collect::<Vec<_>>();
+
+        let mut lwe_out_list = LweCiphertextList::new(
+            Scalar::ZERO,
+            lwe_sk.lwe_dimension().to_lwe_size(),
+            LweCiphertextCount(output_bit_count),
+            ciphertext_modulus,
+        );
+        lut_eval_by_cmux_tree(&fourier_ggsw_bit_list, &table, &mut lwe_out_list);
+
+        for (bit_idx, lwe_out) in lwe_out_list.iter().enumerate() {
+            let correct_val = (((table[msg] >> bit_idx) & 1) as Scalar) << (Scalar::BITS - 1);
+            let decrypted = decrypt_lwe_ciphertext(&lwe_sk, &lwe_out).0;
+            let abs_err = std::cmp::min(decrypted.wrapping_sub(correct_val), correct_val.wrapping_sub(decrypted));
+            max_err = std::cmp::max(max_err, abs_err);
+            if abs_err >= Scalar::ONE << (Scalar::BITS - 2) {
+                num_fail += 1;
+            }
+        }
+    }
+
+    println!(
+        "{} / {} failures, (Max) {:.2} bits\n",
+        num_fail, num_repeat * output_bit_count, (max_err as f64).log2(),
+    );
+}
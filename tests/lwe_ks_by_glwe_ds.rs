@@ -1,6 +1,6 @@
 use std::time::Instant;
 
-use patching_wwlp::{allocate_and_generate_new_glwe_keyswitch_key, convert_standard_glwe_keyswitch_key_to_fourier, get_val_and_abs_err, keyswitch_lwe_ciphertext_by_glwe_keyswitch, FftType, FourierGlweKeyswitchKey};
+use patching_wwlp::{allocate_and_generate_new_glwe_keyswitch_key, convert_standard_glwe_keyswitch_key_to_fourier, get_val_and_abs_err, keyswitch_lwe_ciphertext_by_glwe_keyswitch, FftType, FourierGlweKeyswitchKey, FourierGlweKeyswitchKeyOwned};
 use tfhe::core_crypto::prelude::*;
 
 type Scalar = u64;
@@ -61,7 +61,7 @@ fn main() {
         ciphertext_modulus,
         &mut encryption_generator,
     );
-    let mut fourier_glwe_ksk = FourierGlweKeyswitchKey::new(
+    let mut fourier_glwe_ksk: FourierGlweKeyswitchKeyOwned = FourierGlweKeyswitchKey::new(
         src_glwe_size,
         dst_glwe_size,
         common_polynomial_size,
@@ -1,6 +1,6 @@
 use std::time::Instant;
 
-use patching_wwlp::{allocate_and_generate_new_glwe_keyswitch_key, convert_lwe_to_glwe_const, convert_standard_glwe_keyswitch_key_to_fourier, gen_all_auto_keys, get_glwe_l2_err, get_glwe_max_err, get_val_and_abs_err, glwe_preprocessing_assign, keyswitch_glwe_ciphertext, trace_assign, FftType, FourierGlweKeyswitchKey};
+use patching_wwlp::{allocate_and_generate_new_glwe_keyswitch_key, convert_lwe_to_glwe_const, convert_standard_glwe_keyswitch_key_to_fourier, gen_all_auto_keys, get_glwe_l2_err, get_glwe_max_err, get_val_and_abs_err, glwe_preprocessing_assign, keyswitch_glwe_ciphertext, trace_assign, FftType, FourierGlweKeyswitchKey, FourierGlweKeyswitchKeyOwned};
 use tfhe::core_crypto::prelude::*;
 
 type Scalar = u64;
@@ -96,7 +96,7 @@ B_auto: 2^{}, l_auto: {}, fft_type_auto: {:?}",
         ciphertext_modulus,
         &mut encryption_generator,
     );
-    let mut fourier_glwe_ksk_to_large = FourierGlweKeyswitchKey::new(
+    let mut fourier_glwe_ksk_to_large: FourierGlweKeyswitchKeyOwned = FourierGlweKeyswitchKey::new(
         glwe_size,
         large_glwe_size,
         polynomial_size,
@@ -115,7 +115,7 @@ B_auto: 2^{}, l_auto: {}, fft_type_auto: {:?}",
         ciphertext_modulus,
         &mut encryption_generator,
     );
-    let mut fourier_glwe_ksk_from_large = FourierGlweKeyswitchKey::new(
+    let mut fourier_glwe_ksk_from_large: FourierGlweKeyswitchKeyOwned = FourierGlweKeyswitchKey::new(
         large_glwe_size,
         glwe_size,
         polynomial_size,
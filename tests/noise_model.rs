@@ -0,0 +1,440 @@
+use rand::Rng;
+use tfhe::core_crypto::{
+    prelude::*,
+    commons::math::random::Seed,
+};
+use patching_wwlp::{aes_instances::*, circuit_bootstrap_lwe_ciphertext_by_trace, get_glwe_l2_err, lut_eval_by_cmux_tree, lwe_msb_bit_to_glev_by_trace, lwe_msb_bit_to_lev, noise::*, switch_scheme, wwlp_cbs_instance::*, he_add_round_key, he_mix_columns, he_shift_rows, he_sub_bytes_by_patched_wwlp_cbs, keyswitch_lwe_ciphertext_by_glwe_keyswitch, AesClientKey, AesKeySize, AesParam, CbsAccumulator, WWLpCbsClientKey, WWLpCbsMode, BLOCKSIZE_IN_BIT, NUM_ROUNDS};
+
+type Scalar = u64;
+const NUM_REPEAT: usize = 100;
+const NUM_REPEAT_CMUX_TREE: usize = 20;
+const CMUX_TREE_DEPTH: usize = 8;
+/// Largest gap in log2 between the sampled and the predicted variances.
+const LOG2_VAR_TOLERANCE: f64 = 1.0;
+
+fn main() {
+    println!("-------- Predicted CBS noise --------");
+    let q = modulus::<Scalar>();
+    for (name, param) in [
+        ("CBS_WOPBS_2_2", *CBS_WOPBS_2_2),
+        ("CBS_WOPBS_3_3", *CBS_WOPBS_3_3),
+        ("CBS_WOPBS_4_4", *CBS_WOPBS_4_4),
+    ] {
+        println!("{name}: lev {:.2}, ggsw {:.2}", log2_std(var_lev(&param)), log2_std(var_pfks_cbs(&param)));
+    }
+
+    let param = *WWLP_CBS_WOPBS_2_2;
+    let var_in = var_cbs_input(&param, param.refresh_base_log(), param.refresh_level());
+    println!(
+        "WWLP_CBS_WOPBS_2_2: input {:.2}, lev {:.2}, ggsw {:.2} (original: {:.2}), log2 fail prob {:.2}",
+        log2_std(var_in),
+        log2_std(var_lev(&param)),
+        log2_std(var_wwlp_cbs(&param, WWLpCbsMode::Patched)),
+        log2_std(var_wwlp_cbs(&param, WWLpCbsMode::Original)),
+        log2_cbs_fail_prob(&param, var_in),
+    );
+
+    for (name, param) in [
+        ("HIGHPREC_WWLP_CBS_WOPBS_3_3", *HIGHPREC_WWLP_CBS_WOPBS_3_3),
+        ("HIGHPREC_WWLP_CBS_WOPBS_4_4", *HIGHPREC_WWLP_CBS_WOPBS_4_4),
+    ] {
+        let var_in = var_cbs_input(&param, param.refresh_base_log(), param.refresh_level());
+        println!(
+            "{name}: input {:.2}, lev {:.2}, ggsw {:.2}, log2 fail prob {:.2}",
+            log2_std(var_in),
+            log2_std(var_lev(&param)),
+            log2_std(var_high_prec_wwlp_cbs(&param)),
+            log2_cbs_fail_prob(&param, var_in),
+        );
+    }
+
+    println!("\n-------- Predicted AES noise --------");
+    for (name, param) in [
+        ("AES_SET_1", *AES_SET_1),
+        ("AES_SET_2", *AES_SET_2),
+        ("AES_SET_3", *AES_SET_3),
+        ("AES192_SET_1", *AES192_SET_1),
+        ("AES192_SET_2", *AES192_SET_2),
+        ("AES192_SET_3", *AES192_SET_3),
+        ("AES256_SET_1", *AES256_SET_1),
+        ("AES256_SET_2", *AES256_SET_2),
+        ("AES256_SET_3", *AES256_SET_3),
+    ] {
+        println!(
+            "{name}: ggsw {:.2}, sbox output {:.2}, round input {:.2}, log2 fail prob {:.2} / {:.2} / {:.2} for AES-128/192/256 (round: {:.2})",
+            log2_std(var_wwlp_cbs(&param, WWLpCbsMode::Patched)),
+            log2_std(var_aes_sbox_output(&param)),
+            log2_std(var_aes_round_input(&param)),
+            log2_aes_fail_prob(&param, AesKeySize::Aes128),
+            log2_aes_fail_prob(&param, AesKeySize::Aes192),
+            log2_aes_fail_prob(&param, AesKeySize::Aes256),
+            log2_aes_round_fail_prob(&param),
+        );
+    }
+
+    // The AES-192/256 sets keep the failure probability of AES-128 with the corresponding AES set
+    for (name, base_param, param, key_size) in [
+        ("AES192_SET_1", *AES_SET_1, *AES192_SET_1, AesKeySize::Aes192),
+        ("AES192_SET_2", *AES_SET_2, *AES192_SET_2, AesKeySize::Aes192),
+        ("AES192_SET_3", *AES_SET_3, *AES192_SET_3, AesKeySize::Aes192),
+        ("AES256_SET_1", *AES_SET_1, *AES256_SET_1, AesKeySize::Aes256),
+        ("AES256_SET_2", *AES_SET_2, *AES256_SET_2, AesKeySize::Aes256),
+        ("AES256_SET_3", *AES_SET_3, *AES256_SET_3, AesKeySize::Aes256),
+    ] {
+        assert!(
+            log2_aes_fail_prob(&param, key_size) <= log2_aes_fail_prob(&base_param, AesKeySize::Aes128),
+            "{name} fails more often than AES-128",
+        );
+    }
+
+    println!("\n-------- Predicted AES decryption noise --------");
+    for (name, param, key_size) in [
+        ("AES_SET_1", *AES_SET_1, AesKeySize::Aes128),
+        ("AES_SET_2", *AES_SET_2, AesKeySize::Aes128),
+        ("AES_SET_3", *AES_SET_3, AesKeySize::Aes128),
+        ("AES192_SET_1", *AES192_SET_1, AesKeySize::Aes192),
+        ("AES192_SET_2", *AES192_SET_2, AesKeySize::Aes192),
+        ("AES192_SET_3", *AES192_SET_3, AesKeySize::Aes192),
+        ("AES256_SET_1", *AES256_SET_1, AesKeySize::Aes256),
+        ("AES256_SET_2", *AES256_SET_2, AesKeySize::Aes256),
+        ("AES256_SET_3", *AES256_SET_3, AesKeySize::Aes256),
+    ] {
+        println!(
+            "{name} ({key_size:?}): round input {:.2} (encryption: {:.2}), log2 fail prob {:.2} (encryption: {:.2})",
+            log2_std(var_aes_decryption_round_input(&param)),
+            log2_std(var_aes_round_input(&param)),
+            log2_aes_decryption_fail_prob(&param, key_size),
+            log2_aes_fail_prob(&param, key_size),
+        );
+        assert!(log2_aes_decryption_fail_prob(&param, key_size) < -64.0, "{name} is too noisy for the decryption");
+    }
+
+    println!("\n-------- Predicted AES noise with the homomorphic key expansion --------");
+    for (name, param, key_size) in [
+        ("AES_SET_1", *AES_SET_1, AesKeySize::Aes128),
+        ("AES_SET_2", *AES_SET_2, AesKeySize::Aes128),
+        ("AES_SET_3", *AES_SET_3, AesKeySize::Aes128),
+        ("AES192_SET_2", *AES192_SET_2, AesKeySize::Aes192),
+        ("AES256_SET_2", *AES256_SET_2, AesKeySize::Aes256),
+    ] {
+        println!(
+            "{name} ({key_size:?}): round key {:.2} (fresh: {:.2}), {} + {} CBS, log2 fail prob {:.2} (fresh round keys: {:.2})",
+            log2_std(var_aes_he_round_key(&param, key_size)),
+            log2_std(var_aes_fresh_round_key(&param)),
+            aes_num_cbs(key_size),
+            aes_key_expansion_num_cbs(key_size),
+            log2_aes_fail_prob_with_he_key_expansion(&param, key_size),
+            log2_aes_fail_prob(&param, key_size),
+        );
+    }
+
+    println!("\n-------- Predicted GHASH noise --------");
+    for (name, param) in [
+        ("AES_SET_1", *AES_SET_1),
+        ("AES_SET_2", *AES_SET_2),
+        ("AES_SET_3", *AES_SET_3),
+    ] {
+        println!(
+            "{name}: H x^k {:.2} ({} terms), product {:.2}, log2 fail prob per block {:.2}",
+            log2_std(var_ghash_h_power(&param)),
+            ghash_max_h_terms(),
+            log2_std(var_ghash_output(&param)),
+            log2_ghash_block_fail_prob(&param),
+        );
+    }
+
+    println!("\n-------- Sampled vs predicted WWL+ CBS noise (WWLP_CBS_WOPBS_2_2) --------");
+    let param = *WWLP_CBS_WOPBS_2_2;
+    let (client_key, server_key) = WWLpCbsClientKey::generate(&param, Seed(rand::thread_rng().gen::<u128>()));
+
+    let glwe_sk = client_key.glwe_secret_key();
+    let lwe_sk = glwe_sk.clone().into_lwe_secret_key();
+    let glwe_size = param.glwe_dimension().to_glwe_size();
+    let polynomial_size = param.polynomial_size();
+    let cbs_base_log = param.cbs_base_log();
+    let cbs_level = param.cbs_level();
+    let ciphertext_modulus = param.ciphertext_modulus();
+    let lwe_size = lwe_sk.lwe_dimension().to_lwe_size();
+
+    let mut boxed_seeder = new_seeder();
+    let seeder = boxed_seeder.as_mut();
+    let mut encryption_generator = EncryptionRandomGenerator::<ActivatedRandomGenerator>::new(seeder.seed(), seeder);
+
+    let orig_wwlp_cbs_acc = CbsAccumulator::new_original_wwlp(polynomial_size, glwe_size, cbs_base_log, cbs_level, param.log_lut_count());
+
+    let mut rng = rand::thread_rng();
+    for (mode, cbs_acc) in [
+        (WWLpCbsMode::Patched, server_key.cbs_acc()),
+        (WWLpCbsMode::Original, &orig_wwlp_cbs_acc),
+    ] {
+        let mut lev_err_list = vec![];
+        let mut glev_err_list = vec![];
+        let mut ggsw_err_list = vec![];
+        for _ in 0..NUM_REPEAT {
+            let msg = rng.gen_range(0..2) as Scalar;
+            let lwe = allocate_and_encrypt_new_lwe_ciphertext(
+                client_key.lwe_secret_key_after_ks(),
+                Plaintext(msg << (Scalar::BITS - 1)),
+                param.lwe_modular_std_dev(),
+                ciphertext_modulus,
+                &mut encryption_generator,
+            );
+
+            let mut lev = LweCiphertextList::new(Scalar::ZERO, lwe_size, LweCiphertextCount(cbs_level.0), ciphertext_modulus);
+            lwe_msb_bit_to_lev(&lwe, &mut lev, server_key.fourier_bsk(), cbs_acc);
+            for (k, lwe_lev) in lev.iter().enumerate() {
+                let log_scale = Scalar::BITS as usize - (k + 1) * cbs_base_log.0 - cbs_acc.log_scale_offset();
+                lev_err_list.push(get_sampled_lwe_var(&lwe_sk, &lwe_lev, msg << log_scale));
+            }
+
+            let mut glev = GlweCiphertextList::new(Scalar::ZERO, glwe_size, polynomial_size, GlweCiphertextCount(cbs_level.0), ciphertext_modulus);
+            let glev_mut_view = GlweCiphertextListMutView::from_container(glev.as_mut(), glwe_size, polynomial_size, ciphertext_modulus);
+            lwe_msb_bit_to_glev_by_trace(lwe.as_view(), glev_mut_view, server_key.fourier_bsk(), server_key.auto_keys(), cbs_acc, mode);
+
+            for (k, glwe) in glev.iter().enumerate() {
+                let log_scale = Scalar::BITS as usize - (k + 1) * cbs_base_log.0;
+                glev_err_list.push(get_sampled_const_var(glwe_sk, &glwe, msg << log_scale));
+            }
+
+            let mut ggsw = GgswCiphertext::new(Scalar::ZERO, glwe_size, polynomial_size, cbs_base_log, cbs_level, ciphertext_modulus);
+            switch_scheme(&glev, &mut ggsw, server_key.ss_key());
+
+            let glwe_sk_poly_list = glwe_sk.as_polynomial_list();
+            for (k, ggsw_level_matrix) in ggsw.iter().enumerate() {
+                let log_scale = Scalar::BITS as usize - (k + 1) * cbs_base_log.0;
+                for (i, glwe) in ggsw_level_matrix.as_glwe_list().iter().enumerate() {
+                    let correct_val_list = if i < param.glwe_dimension().0 {
+                        let sk_i = glwe_sk_poly_list.get(i);
+                        PlaintextList::from_container(sk_i.iter().map(|val| {
+                            (msg * *val).wrapping_neg() << log_scale
+                        }).collect::<Vec<Scalar>>())
+                    } else {
+                        PlaintextList::from_container((0..polynomial_size.0).map(|j| {
+                            if j == 0 {msg << log_scale} else {Scalar::ZERO}
+                        }).collect::<Vec<Scalar>>())
+                    };
+                    ggsw_err_list.push(get_sampled_var(glwe_sk, &glwe, &correct_val_list));
+                }
+            }
+        }
+
+        let var_glev = var_trace(
+            polynomial_size.0,
+            param.glwe_dimension().0,
+            var_lev(&param),
+            var_automorphism(&param),
+            mode,
+        );
+        println!(
+            "[{mode:?}] lev: sampled {:.2}, predicted {:.2} / glev (constant term): sampled {:.2}, predicted {:.2} / ggsw: sampled {:.2}, predicted {:.2}",
+            log2_std(mean(&lev_err_list)),
+            log2_std(var_lev(&param)),
+            log2_std(mean(&glev_err_list)),
+            log2_std(var_glev),
+            log2_std(mean(&ggsw_err_list)),
+            log2_std(var_wwlp_cbs(&param, mode)),
+        );
+        assert_sampled_var(&format!("[{mode:?}] lev"), mean(&lev_err_list), var_lev(&param));
+        assert_sampled_var(&format!("[{mode:?}] glev"), mean(&glev_err_list), var_glev);
+        assert_sampled_var(&format!("[{mode:?}] ggsw"), mean(&ggsw_err_list), var_wwlp_cbs(&param, mode));
+    }
+
+    println!("\n-------- Sampled vs predicted CMux tree noise (WWLP_CBS_WOPBS_2_2, depth {CMUX_TREE_DEPTH}) --------");
+    let table_size = 1 << CMUX_TREE_DEPTH;
+    let mut cmux_tree_err_list = vec![];
+    for _ in 0..NUM_REPEAT_CMUX_TREE {
+        let idx = rng.gen_range(0..table_size);
+        let fourier_ggsw_bit_list = (0..CMUX_TREE_DEPTH).map(|i| {
+            let lwe = allocate_and_encrypt_new_lwe_ciphertext(
+                client_key.lwe_secret_key_after_ks(),
+                Plaintext((((idx >> i) & 1) as Scalar) << (Scalar::BITS - 1)),
+                param.lwe_modular_std_dev(),
+                ciphertext_modulus,
+                &mut encryption_generator,
+            );
+            circuit_bootstrap_lwe_ciphertext_by_trace(
+                lwe.as_view(),
+                server_key.fourier_bsk(),
+                server_key.auto_keys(),
+                server_key.ss_key(),
+                server_key.cbs_acc(),
+                WWLpCbsMode::Patched,
+            )
+        }).collect::<Vec<_>>();
+
+        let table = (0..table_size).map(|_| rng.gen_range(0..=u8::MAX) as usize).collect::<Vec<usize>>();
+        let mut lwe_out_list = LweCiphertextList::new(Scalar::ZERO, lwe_size, LweCiphertextCount(u8::BITS as usize), ciphertext_modulus);
+        lut_eval_by_cmux_tree(&fourier_ggsw_bit_list, &table, &mut lwe_out_list);
+
+        for (j, lwe_out) in lwe_out_list.iter().enumerate() {
+            let correct_val = (((table[idx] >> j) & 1) as Scalar) << (Scalar::BITS - 1);
+            cmux_tree_err_list.push(get_sampled_lwe_var(&lwe_sk, &lwe_out, correct_val));
+        }
+    }
+
+    let var_cmux_tree_out = var_cmux_tree(&param, var_wwlp_cbs(&param, WWLpCbsMode::Patched), CMUX_TREE_DEPTH);
+    println!(
+        "cmux tree output: sampled {:.2}, predicted {:.2}",
+        log2_std(mean(&cmux_tree_err_list)),
+        log2_std(var_cmux_tree_out),
+    );
+    assert_sampled_var("cmux tree", mean(&cmux_tree_err_list), var_cmux_tree_out);
+
+    println!("\n-------- Sampled vs predicted AES round noise (AES_SET_1) --------");
+    let param = *AES_SET_1;
+    let (client_key, server_key) = AesClientKey::generate(&param, Seed(rng.gen::<u128>()));
+    let lwe_sk = client_key.lwe_secret_key();
+    let lwe_sk_after_ks = client_key.lwe_secret_key_after_ks();
+
+    // The noise does not depend on the encrypted values, so the message and the round keys are random bits
+    let mut he_state = encrypt_random_bits(lwe_sk, &param, &mut rng, &mut encryption_generator);
+    let mut he_state_ks = LweCiphertextList::new(
+        Scalar::ZERO,
+        lwe_sk_after_ks.lwe_dimension().to_lwe_size(),
+        LweCiphertextCount(BLOCKSIZE_IN_BIT),
+        param.ciphertext_modulus(),
+    );
+
+    // The input of the first round is a fresh encryption, so the round input is sampled from the second round on
+    let mut sbox_err_list = vec![];
+    let mut round_input_err_list = vec![];
+    for r in 0..NUM_ROUNDS {
+        let he_round_key = encrypt_random_bits(lwe_sk, &param, &mut rng, &mut encryption_generator);
+        he_add_round_key(&mut he_state, &he_round_key);
+        for (lwe, mut lwe_ks) in he_state.iter().zip(he_state_ks.iter_mut()) {
+            keyswitch_lwe_ciphertext_by_glwe_keyswitch(&lwe, &mut lwe_ks, server_key.glwe_ksk());
+        }
+        if r > 0 {
+            round_input_err_list.extend(he_state_ks.iter().map(|lwe| get_sampled_bit_var(lwe_sk_after_ks, &lwe)));
+        }
+
+        he_sub_bytes_by_patched_wwlp_cbs(
+            &he_state_ks,
+            &mut he_state,
+            server_key.fourier_bsk(),
+            server_key.auto_keys(),
+            server_key.ss_key(),
+            server_key.cbs_acc(),
+        );
+        sbox_err_list.extend(he_state.iter().map(|lwe| get_sampled_bit_var(lwe_sk, &lwe)));
+        he_shift_rows(&mut he_state);
+        he_mix_columns(&mut he_state);
+    }
+
+    // The round input is an upper bound: the keyswitching error is doubled, and its decomposition digits are
+    // bounded by B/2 instead of having the variance B^2/12 of uniform digits, i.e. 6 times the average error
+    let var_round_input = var_aes_round_input(&param);
+    let var_round_input_average = var_round_input - 2.0 * var_aes_glwe_ds(&param) * (1.0 - 1.0 / 6.0);
+    println!(
+        "sbox output: sampled {:.2}, predicted {:.2} / round input: sampled {:.2}, predicted {:.2} (average {:.2})",
+        log2_std(mean(&sbox_err_list)),
+        log2_std(var_aes_sbox_output(&param)),
+        log2_std(mean(&round_input_err_list)),
+        log2_std(var_round_input),
+        log2_std(var_round_input_average),
+    );
+    assert_sampled_var("aes sbox output", mean(&sbox_err_list), var_aes_sbox_output(&param));
+    assert!(mean(&round_input_err_list) <= var_round_input, "aes round input: sampled variance exceeds the predicted bound");
+    assert_sampled_var("aes round input", mean(&round_input_err_list), var_round_input_average);
+
+    println!("(log2 of the standard deviation over Z_q with q = 2^{})", q.log2());
+}
+
+fn get_sampled_var<KeyCont, InputCont, PtCont>(
+    glwe_sk: &GlweSecretKey<KeyCont>,
+    glwe: &GlweCiphertext<InputCont>,
+    correct_val_list: &PlaintextList<PtCont>,
+) -> f64
+where
+    KeyCont: Container<Element=Scalar>,
+    InputCont: Container<Element=Scalar>,
+    PtCont: Container<Element=Scalar>,
+{
+    let l2_err = get_glwe_l2_err(glwe_sk, glwe, correct_val_list);
+    l2_err * l2_err / glwe.polynomial_size().0 as f64
+}
+
+fn get_sampled_const_var<KeyCont, InputCont>(
+    glwe_sk: &GlweSecretKey<KeyCont>,
+    glwe: &GlweCiphertext<InputCont>,
+    correct_val: Scalar,
+) -> f64
+where
+    KeyCont: Container<Element=Scalar>,
+    InputCont: Container<Element=Scalar>,
+{
+    let mut dec = PlaintextList::new(Scalar::ZERO, PlaintextCount(glwe.polynomial_size().0));
+    decrypt_glwe_ciphertext(glwe_sk, glwe, &mut dec);
+
+    let err = dec.as_ref()[0].wrapping_sub(correct_val) as i64 as f64;
+    err * err
+}
+
+fn get_sampled_lwe_var<KeyCont, InputCont>(
+    lwe_sk: &LweSecretKey<KeyCont>,
+    lwe: &LweCiphertext<InputCont>,
+    correct_val: Scalar,
+) -> f64
+where
+    KeyCont: Container<Element=Scalar>,
+    InputCont: Container<Element=Scalar>,
+{
+    let err = decrypt_lwe_ciphertext(lwe_sk, lwe).0.wrapping_sub(correct_val) as i64 as f64;
+    err * err
+}
+
+/// Variance of an LWE encryption of a bit b * 2^(BITS - 1), whose value is decoded by rounding.
+fn get_sampled_bit_var<KeyCont, InputCont>(
+    lwe_sk: &LweSecretKey<KeyCont>,
+    lwe: &LweCiphertext<InputCont>,
+) -> f64
+where
+    KeyCont: Container<Element=Scalar>,
+    InputCont: Container<Element=Scalar>,
+{
+    let decrypted = decrypt_lwe_ciphertext(lwe_sk, lwe).0;
+    let correct_val = decrypted.wrapping_add(1 << (Scalar::BITS - 2)) & (1 << (Scalar::BITS - 1));
+    let err = decrypted.wrapping_sub(correct_val) as i64 as f64;
+    err * err
+}
+
+fn encrypt_random_bits<KeyCont, Gen>(
+    lwe_sk: &LweSecretKey<KeyCont>,
+    param: &AesParam<Scalar>,
+    rng: &mut impl Rng,
+    encryption_generator: &mut EncryptionRandomGenerator<Gen>,
+) -> LweCiphertextListOwned<Scalar>
+where
+    KeyCont: Container<Element=Scalar>,
+    Gen: ByteRandomGenerator,
+{
+    let bits = PlaintextList::from_container(
+        (0..BLOCKSIZE_IN_BIT).map(|_| rng.gen_range(0..2) << (Scalar::BITS - 1)).collect::<Vec<Scalar>>()
+    );
+    let mut lwe_list = LweCiphertextList::new(
+        Scalar::ZERO,
+        lwe_sk.lwe_dimension().to_lwe_size(),
+        LweCiphertextCount(BLOCKSIZE_IN_BIT),
+        param.ciphertext_modulus(),
+    );
+    encrypt_lwe_ciphertext_list(lwe_sk, &mut lwe_list, &bits, param.glwe_modular_std_dev(), encryption_generator);
+    lwe_list
+}
+
+fn assert_sampled_var(name: &str, sampled_var: f64, predicted_var: f64) {
+    let gap = sampled_var.log2() - predicted_var.log2();
+    assert!(
+        gap.abs() <= LOG2_VAR_TOLERANCE,
+        "{name}: sampled variance 2^{:.2} is not within 2^{LOG2_VAR_TOLERANCE} of the predicted variance 2^{:.2}",
+        sampled_var.log2(),
+        predicted_var.log2(),
+    );
+}
+
+fn mean(list: &[f64]) -> f64 {
+    list.iter().sum::<f64>() / list.len() as f64
+}
+
+fn log2_std(var: f64) -> f64 {
+    var.log2() / 2.0
+}
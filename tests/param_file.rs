@@ -0,0 +1,91 @@
+use std::fmt::Debug;
+use patching_wwlp::{aes_instances::*, auto_conv_instance::*, wwlp_cbs_instance::*, param_file::*, AesParam, AutoConvParam, WWLpCBSParam};
+
+fn main() {
+    println!("-------- Round trip of the parameter instances --------");
+    round_trip("CBS_WOPBS_2_2", &*CBS_WOPBS_2_2);
+    round_trip("CBS_WOPBS_3_3", &*CBS_WOPBS_3_3);
+    round_trip("CBS_WOPBS_4_4", &*CBS_WOPBS_4_4);
+    round_trip("WWLP_CBS_WOPBS_2_2", &*WWLP_CBS_WOPBS_2_2);
+    round_trip("HIGHPREC_WWLP_CBS_WOPBS_3_3", &*HIGHPREC_WWLP_CBS_WOPBS_3_3);
+    round_trip("HIGHPREC_WWLP_CBS_WOPBS_4_4", &*HIGHPREC_WWLP_CBS_WOPBS_4_4);
+    round_trip("AES_SET_1", &*AES_SET_1);
+    round_trip("AES_SET_2", &*AES_SET_2);
+    round_trip("AES_SET_3", &*AES_SET_3);
+    round_trip("AES192_SET_1", &*AES192_SET_1);
+    round_trip("AES192_SET_2", &*AES192_SET_2);
+    round_trip("AES192_SET_3", &*AES192_SET_3);
+    round_trip("AES256_SET_1", &*AES256_SET_1);
+    round_trip("AES256_SET_2", &*AES256_SET_2);
+    round_trip("AES256_SET_3", &*AES256_SET_3);
+    round_trip("PKSK_PARAM_2_2", &*PKSK_PARAM_2_2);
+    round_trip("PKSK_PARAM_3_3", &*PKSK_PARAM_3_3);
+    round_trip("PKSK_PARAM_4_4", &*PKSK_PARAM_4_4);
+    round_trip("AUTO_PARAM_2_2_VANILLA", &*AUTO_PARAM_2_2_VANILLA);
+    round_trip("AUTO_PARAM_2_2_LEV_3", &*AUTO_PARAM_2_2_LEV_3);
+    round_trip("AUTO_PARAM_3_3_LEV_4", &*AUTO_PARAM_3_3_LEV_4);
+    round_trip("AUTO_PARAM_3_3_LEV_5", &*AUTO_PARAM_3_3_LEV_5);
+    round_trip("AUTO_PARAM_4_4_LEV_3", &*AUTO_PARAM_4_4_LEV_3);
+    round_trip("AUTO_PARAM_4_4_LEV_4", &*AUTO_PARAM_4_4_LEV_4);
+
+    println!("\n-------- Parameter file with several sets --------");
+    let text = [
+        AES_SET_1.to_param_string(Some("AES_SET_1")),
+        AES_SET_2.to_param_string(Some("AES_SET_2")),
+        AUTO_PARAM_2_2_LEV_3.to_param_string(None),
+    ].join("\n");
+    println!("{text}");
+
+    let path = std::env::temp_dir().join("patching_wwlp_params.txt");
+    std::fs::write(&path, &text).unwrap();
+    print_loaded("AES_SET_2", AesParam::<u64>::load(&path, Some("AES_SET_2")).map(|param| param == *AES_SET_2));
+    print_loaded("AutoConvParam", AutoConvParam::<u64>::load(&path, None).map(|param| param == *AUTO_PARAM_2_2_LEV_3));
+    print_loaded("unnamed AesParam", AesParam::<u64>::load(&path, None).map(|_| true));
+    print_loaded("WWLpCBSParam", WWLpCBSParam::<u64>::load(&path, None).map(|_| true));
+    std::fs::remove_file(&path).unwrap();
+
+    println!("\n-------- Invalid parameter files --------");
+    let text = AUTO_PARAM_2_2_LEV_3.to_param_string(None);
+    for (description, text) in [
+        ("missing key", text.replace("auto_level = 3\n", "")),
+        ("unknown key", text.replace("auto_level = 3\n", "auto_level = 3\nks_level = 5\n")),
+        ("duplicate key", text.replace("auto_level = 3\n", "auto_level = 3\nauto_level = 4\n")),
+        ("invalid fft type", text.replace("split(42)", "split(x)")),
+        ("invalid std dev", text.replace("glwe_modular_std_dev = ", "glwe_modular_std_dev = -")),
+        ("syntax error", text.replace("auto_level = 3", "auto_level 3")),
+        ("constraint violation", text.replace("polynomial_size = 2048", "polynomial_size = 2000")),
+    ] {
+        match AutoConvParam::<u64>::from_param_str(&text, None) {
+            Ok(_) => println!("{description}: accepted"),
+            Err(err) => println!("{description}: {err}"),
+        }
+    }
+
+    println!("\n-------- Insecure parameter file --------");
+    let text = text.replace("polynomial_size = 2048", "polynomial_size = 512");
+    match AutoConvParam::<u64>::from_param_str(&text, None) {
+        Ok(_) => println!("default security threshold: accepted"),
+        Err(err) => println!("default security threshold: {err}"),
+    }
+    match AutoConvParam::<u64>::from_param_str_with_min_security_bits(&text, None, 0) {
+        Ok(_) => println!("no security threshold: accepted"),
+        Err(err) => println!("no security threshold: {err}"),
+    }
+}
+
+fn round_trip<P: ParamFileFormat + PartialEq + Debug>(name: &str, param: &P) {
+    let text = param.to_param_string(Some(name));
+    match P::from_param_str(&text, Some(name)) {
+        Ok(parsed) if parsed == *param && parsed.to_param_string(Some(name)) == text => println!("{name}: ok"),
+        Ok(parsed) => println!("{name}: mismatch\n{text}{parsed:?}"),
+        Err(err) => println!("{name}: {err}"),
+    }
+}
+
+fn print_loaded(name: &str, result: Result<bool, ParamFileError>) {
+    match result {
+        Ok(true) => println!("{name}: loaded"),
+        Ok(false) => println!("{name}: loaded with different values"),
+        Err(err) => println!("{name}: {err}"),
+    }
+}
@@ -0,0 +1,69 @@
+use patching_wwlp::{aes_instances::*, wwlp_cbs_instance::*, evaluate_aes_param, evaluate_wwlp_cbs_param, optimize_aes_param, optimize_wwlp_cbs_param, AesKeySize, KeyParam};
+
+/// Number of input bits of the LUT evaluated after the CBS for wopbs_param_message_2_carry_2.
+const LUT_DEPTH: usize = 4;
+
+fn main() {
+    println!("-------- WWLP_CBS_WOPBS_2_2 --------");
+    let param = *WWLP_CBS_WOPBS_2_2;
+    let current = evaluate_wwlp_cbs_param(&param, LUT_DEPTH);
+    println!("[current] {current}");
+
+    let key = KeyParam::new(
+        param.lwe_dimension(),
+        param.lwe_modular_std_dev(),
+        param.polynomial_size(),
+        param.glwe_dimension(),
+        param.glwe_modular_std_dev(),
+        param.ciphertext_modulus(),
+    );
+    match optimize_wwlp_cbs_param(&key, LUT_DEPTH, current.log2_fail_prob()) {
+        Some(optimized) => {
+            let p = optimized.param();
+            println!("[optimized] {optimized}");
+            println!(
+                "B_pbs: 2^{}, l_pbs: {}, B_refresh: 2^{}, l_refresh: {}, B_ks: 2^{}, l_ks: {}, B_auto: 2^{}, l_auto: {}, fft_type_auto: {:?}, B_ss: 2^{}, l_ss: {}, B_cbs: 2^{}, l_cbs: {}, log_lut_count: {}",
+                p.pbs_base_log().0, p.pbs_level().0, p.refresh_base_log().0, p.refresh_level().0, p.ks_base_log().0, p.ks_level().0,
+                p.auto_base_log().0, p.auto_level().0, p.fft_type_auto(), p.ss_base_log().0, p.ss_level().0,
+                p.cbs_base_log().0, p.cbs_level().0, p.log_lut_count().0,
+            );
+        }
+        None => println!("[optimized] no parameters meet the target"),
+    }
+
+    for (name, param) in [
+        ("AES_SET_1", *AES_SET_1),
+        ("AES_SET_2", *AES_SET_2),
+        ("AES_SET_3", *AES_SET_3),
+    ] {
+        println!("\n-------- {name} --------");
+        let current = evaluate_aes_param(&param, AesKeySize::Aes128);
+        println!("[current] {current}");
+
+        let key = KeyParam::new(
+            param.lwe_dimension(),
+            param.lwe_modular_std_dev(),
+            param.polynomial_size(),
+            param.glwe_dimension(),
+            param.glwe_modular_std_dev(),
+            param.ciphertext_modulus(),
+        );
+
+        // The longer key sizes are optimized for the failure probability of AES-128 over their extra rounds.
+        for key_size in [AesKeySize::Aes128, AesKeySize::Aes192, AesKeySize::Aes256] {
+            match optimize_aes_param(&key, param.common_polynomial_size(), key_size, current.log2_fail_prob()) {
+                Some(optimized) => {
+                    let p = optimized.param();
+                    println!("[optimized {key_size:?}] {optimized}");
+                    println!(
+                        "B_pbs: 2^{}, l_pbs: {}, B_ds: 2^{}, l_ds: {}, fft_type_ds: {:?}, B_auto: 2^{}, l_auto: {}, fft_type_auto: {:?}, B_ss: 2^{}, l_ss: {}, B_cbs: 2^{}, l_cbs: {}, log_lut_count: {}",
+                        p.pbs_base_log().0, p.pbs_level().0, p.glwe_ds_base_log().0, p.glwe_ds_level().0, p.fft_type_ds(),
+                        p.auto_base_log().0, p.auto_level().0, p.fft_type_auto(), p.ss_base_log().0, p.ss_level().0,
+                        p.cbs_base_log().0, p.cbs_level().0, p.log_lut_count().0,
+                    );
+                }
+                None => println!("[optimized {key_size:?}] no parameters meet the target"),
+            }
+        }
+    }
+}
@@ -0,0 +1,135 @@
+use tfhe::core_crypto::prelude::*;
+use patching_wwlp::{auto_conv_instance::*, aes_instances::*, wwlp_cbs_instance::*, AesParam, AesParamFields, FftType, HighPrecWWLpCBSParam, HighPrecWWLpCBSParamFields, ParamError, ParamViolation, WWLpCBSParam, WWLpCBSParamFields};
+
+fn main() {
+    println!("-------- Parameter instances --------");
+    print_result("CBS_WOPBS_2_2", CBS_WOPBS_2_2.validate());
+    print_result("CBS_WOPBS_3_3", CBS_WOPBS_3_3.validate());
+    print_result("CBS_WOPBS_4_4", CBS_WOPBS_4_4.validate());
+    print_result("WWLP_CBS_WOPBS_2_2", WWLP_CBS_WOPBS_2_2.validate());
+    print_result("HIGHPREC_WWLP_CBS_WOPBS_3_3", HIGHPREC_WWLP_CBS_WOPBS_3_3.validate());
+    print_result("HIGHPREC_WWLP_CBS_WOPBS_4_4", HIGHPREC_WWLP_CBS_WOPBS_4_4.validate());
+    print_result("AES_SET_1", AES_SET_1.validate());
+    print_result("AES_SET_2", AES_SET_2.validate());
+    print_result("AES_SET_3", AES_SET_3.validate());
+    print_result("AES192_SET_1", AES192_SET_1.validate());
+    print_result("AES192_SET_2", AES192_SET_2.validate());
+    print_result("AES192_SET_3", AES192_SET_3.validate());
+    print_result("AES256_SET_1", AES256_SET_1.validate());
+    print_result("AES256_SET_2", AES256_SET_2.validate());
+    print_result("AES256_SET_3", AES256_SET_3.validate());
+    print_result("PKSK_PARAM_2_2", PKSK_PARAM_2_2.validate());
+    print_result("PKSK_PARAM_3_3", PKSK_PARAM_3_3.validate());
+    print_result("PKSK_PARAM_4_4", PKSK_PARAM_4_4.validate());
+    print_result("AUTO_PARAM_2_2_VANILLA", AUTO_PARAM_2_2_VANILLA.validate());
+    print_result("AUTO_PARAM_2_2_LEV_3", AUTO_PARAM_2_2_LEV_3.validate());
+    print_result("AUTO_PARAM_3_3_LEV_4", AUTO_PARAM_3_3_LEV_4.validate());
+    print_result("AUTO_PARAM_3_3_LEV_5", AUTO_PARAM_3_3_LEV_5.validate());
+    print_result("AUTO_PARAM_4_4_LEV_3", AUTO_PARAM_4_4_LEV_3.validate());
+    print_result("AUTO_PARAM_4_4_LEV_4", AUTO_PARAM_4_4_LEV_4.validate());
+
+    println!("\n-------- Invalid parameters --------");
+    let invalid = WWLpCBSParam::<u64>::try_new(WWLpCBSParamFields {
+        lwe_dimension: LweDimension(769),
+        lwe_modular_std_dev: StandardDev(0.0000043131554647504185),
+        polynomial_size: PolynomialSize(2000), // not a power of two
+        glwe_dimension: GlweDimension(1),
+        glwe_modular_std_dev: StandardDev(0.00000000000000029403601535432533),
+        pbs_base_log: DecompositionBaseLog(15),
+        pbs_level: DecompositionLevelCount(5), // 75 bits
+        refresh_base_log: DecompositionBaseLog(23),
+        refresh_level: DecompositionLevelCount(1),
+        ks_base_log: DecompositionBaseLog(6),
+        ks_level: DecompositionLevelCount(2),
+        auto_base_log: DecompositionBaseLog(20),
+        auto_level: DecompositionLevelCount(3),
+        fft_type_auto: FftType::Split(30), // 34 + 20 + 11 bits
+        ss_base_log: DecompositionBaseLog(8),
+        ss_level: DecompositionLevelCount(6),
+        cbs_base_log: DecompositionBaseLog(5),
+        cbs_level: DecompositionLevelCount(6), // two accumulators of four levels
+        log_lut_count: LutCountLog(2),
+        ciphertext_modulus: CiphertextModulus::<u64>::new_native(),
+    });
+    let err = invalid.expect_err("invalid parameters were accepted");
+    println!("{err}");
+    let violations = err.violations();
+    assert!(violations.iter().any(|v| matches!(v, ParamViolation::NotPowerOfTwo { name: "polynomial_size", value: 2000 })));
+    assert!(violations.iter().any(|v| matches!(v, ParamViolation::InvalidDecomposition { name: "pbs", base_log: 15, level: 5, .. })));
+    assert!(violations.iter().any(|v| matches!(v, ParamViolation::FftSplitOverflow { name: "auto", .. })));
+    assert!(violations.iter().any(|v| matches!(v, ParamViolation::LutCountMismatch { cbs_level: 6, log_lut_count: 2 })));
+    assert!(violations.iter().any(|v| matches!(v, ParamViolation::InsufficientSecurity { name: "glwe", .. })));
+
+    let invalid = AesParam::<u64>::try_new(AesParamFields {
+        lwe_dimension: LweDimension(769), // not divisible by the common polynomial size
+        lwe_modular_std_dev: StandardDev(0.00000702047462940120),
+        polynomial_size: PolynomialSize(2048),
+        glwe_dimension: GlweDimension(1),
+        glwe_modular_std_dev: StandardDev(0.00000000000000029403601535432533),
+        pbs_base_log: DecompositionBaseLog(15),
+        pbs_level: DecompositionLevelCount(2),
+        glwe_ds_base_log: DecompositionBaseLog(4),
+        glwe_ds_level: DecompositionLevelCount(3),
+        common_polynomial_size: PolynomialSize(256),
+        fft_type_ds: FftType::Vanilla,
+        auto_base_log: DecompositionBaseLog(7),
+        auto_level: DecompositionLevelCount(7),
+        fft_type_auto: FftType::Split(37),
+        ss_base_log: DecompositionBaseLog(8),
+        ss_level: DecompositionLevelCount(6),
+        cbs_base_log: DecompositionBaseLog(5),
+        cbs_level: DecompositionLevelCount(3),
+        log_lut_count: LutCountLog(2),
+        ciphertext_modulus: CiphertextModulus::<u64>::new_native(),
+    });
+    let err = invalid.expect_err("invalid parameters were accepted");
+    println!("{err}");
+    assert_eq!(
+        err.violations(),
+        &[ParamViolation::NotDivisible { name: "lwe_dimension", value: 769, divisor: 256 }],
+    );
+
+    let invalid = HighPrecWWLpCBSParam::<u64>::try_new(HighPrecWWLpCBSParamFields {
+        lwe_dimension: LweDimension(873),
+        lwe_modular_std_dev: StandardDev(0.0000006428797112843789),
+        polynomial_size: PolynomialSize(2048),
+        glwe_dimension: GlweDimension(1),
+        large_glwe_dimension: GlweDimension(2),
+        glwe_modular_std_dev: StandardDev(0.00000000000000029403601535432533),
+        large_glwe_modular_std_dev: StandardDev(0.0000000000000000002168404344971009),
+        pbs_base_log: DecompositionBaseLog(9),
+        pbs_level: DecompositionLevelCount(4),
+        refresh_base_log: DecompositionBaseLog(23),
+        refresh_level: DecompositionLevelCount(1),
+        ks_base_log: DecompositionBaseLog(10),
+        ks_level: DecompositionLevelCount(1),
+        glwe_ds_to_large_base_log: DecompositionBaseLog(15),
+        glwe_ds_to_large_level: DecompositionLevelCount(3),
+        fft_type_to_large: FftType::Vanilla, // not exact on the f64 backend
+        auto_base_log: DecompositionBaseLog(6),
+        auto_level: DecompositionLevelCount(10),
+        fft_type_auto: FftType::Split(36),
+        glwe_ds_from_large_base_log: DecompositionBaseLog(5),
+        glwe_ds_from_large_level: DecompositionLevelCount(10),
+        fft_type_from_large: FftType::F128,
+        ss_base_log: DecompositionBaseLog(6),
+        ss_level: DecompositionLevelCount(9),
+        cbs_base_log: DecompositionBaseLog(6),
+        cbs_level: DecompositionLevelCount(3),
+        log_lut_count: LutCountLog(2),
+        ciphertext_modulus: CiphertextModulus::<u64>::new_native(),
+    });
+    let err = invalid.expect_err("invalid parameters were accepted");
+    println!("{err}");
+    assert_eq!(
+        err.violations(),
+        &[ParamViolation::InexactFft { name: "glwe_ds_to_large" }],
+    );
+}
+
+fn print_result(name: &str, result: Result<(), ParamError>) {
+    match result {
+        Ok(()) => println!("{name}: ok"),
+        Err(err) => panic!("{name}: {err}"),
+    }
+}
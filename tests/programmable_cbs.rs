@@ -0,0 +1,152 @@
+use tfhe::core_crypto::prelude::*;
+use patching_wwlp::{gen_all_auto_keys, CbsAccumulator, generate_scheme_switching_key, keygen_pbs, programmable_circuit_bootstrap_lwe_ciphertext_by_trace_with_preprocessing, WWLpCBSParam, wwlp_cbs_instance::*};
+
+type Scalar = u64;
+
+fn main() {
+    // wopbs_param_message_2_carry_2
+    println!("-------- wopbs_param_message_2_carry_2 --------");
+    test_programmable_cbs(*WWLP_CBS_WOPBS_2_2, 16);
+}
+
+fn test_programmable_cbs(
+    param: WWLpCBSParam<u64>,
+    message_modulus: usize,
+) {
+    let lwe_dimension = param.lwe_dimension();
+    let lwe_modular_std_dev = param.lwe_modular_std_dev();
+    let polynomial_size = param.polynomial_size();
+    let glwe_dimension = param.glwe_dimension();
+    let glwe_modular_std_dev = param.glwe_modular_std_dev();
+    let pbs_base_log = param.pbs_base_log();
+    let pbs_level = param.pbs_level();
+    let ks_base_log = param.ks_base_log();
+    let ks_level = param.ks_level();
+    let auto_base_log = param.auto_base_log();
+    let auto_level = param.auto_level();
+    let fft_type_auto = param.fft_type_auto();
+    let ss_base_log = param.ss_base_log();
+    let ss_level = param.ss_level();
+    let cbs_base_log = param.cbs_base_log();
+    let cbs_level = param.cbs_level();
+    let log_lut_count = param.log_lut_count();
+    let ciphertext_modulus = param.ciphertext_modulus();
+    let glwe_size = glwe_dimension.to_glwe_size();
+
+    // Set random generators and buffers
+    let mut boxed_seeder = new_seeder();
+    let seeder = boxed_seeder.as_mut();
+
+    let mut secret_generator = SecretRandomGenerator::<ActivatedRandomGenerator>::new(seeder.seed());
+    let mut encryption_generator = EncryptionRandomGenerator::<ActivatedRandomGenerator>::new(seeder.seed(), seeder);
+
+    // Generate keys
+    let (
+        _lwe_sk,
+        glwe_sk,
+        lwe_sk_after_ks,
+        bsk,
+        _ksk,
+    ) = keygen_pbs(
+        lwe_dimension,
+        glwe_dimension,
+        polynomial_size,
+        lwe_modular_std_dev,
+        glwe_modular_std_dev,
+        pbs_base_log,
+        pbs_level,
+        ks_base_log,
+        ks_level,
+        &mut secret_generator,
+        &mut encryption_generator,
+    );
+    let bsk = bsk.as_view();
+
+    let auto_keys = gen_all_auto_keys(
+        auto_base_log,
+        auto_level,
+        fft_type_auto,
+        &glwe_sk,
+        glwe_modular_std_dev,
+        &mut encryption_generator,
+    );
+
+    let ss_key = generate_scheme_switching_key(
+        &glwe_sk,
+        ss_base_log,
+        ss_level,
+        glwe_modular_std_dev,
+        ciphertext_modulus,
+        &mut encryption_generator,
+    );
+    let ss_key = ss_key.as_view();
+
+    // GLWE encryption of 1 to be multiplied by the output GGSW
+    let log_scale = Scalar::BITS - 2;
+    let mut pt_one = PlaintextList::new(Scalar::ZERO, PlaintextCount(polynomial_size.0));
+    *pt_one.get_mut(0).0 = Scalar::ONE << log_scale;
+    let mut glwe_one = GlweCiphertext::new(Scalar::ZERO, glwe_size, polynomial_size, ciphertext_modulus);
+    encrypt_glwe_ciphertext(&glwe_sk, &mut glwe_one, &pt_one, glwe_modular_std_dev, &mut encryption_generator);
+
+    let log_message_modulus = message_modulus.ilog2() as usize;
+    let threshold = message_modulus / 2 - 1;
+    let mut func_list: Vec<(String, Box<dyn Fn(usize) -> bool>)> = vec![];
+    for j in 0..log_message_modulus {
+        func_list.push((format!("bit {j} of m"), Box::new(move |m: usize| (m >> j) & 1 == 1)));
+    }
+    func_list.push((format!("m > {threshold}"), Box::new(move |m: usize| m > threshold)));
+
+    let delta = (Scalar::ONE << (Scalar::BITS - 1)) / message_modulus as Scalar;
+    for (name, f) in func_list.iter() {
+        let cbs_acc = CbsAccumulator::new_boolean_function(
+            polynomial_size,
+            glwe_size,
+            cbs_base_log,
+            cbs_level,
+            log_lut_count,
+            message_modulus,
+            f,
+        );
+        let mut num_fail = 0;
+        let mut max_err = Scalar::ZERO;
+
+        for msg in 0..message_modulus {
+            let lwe = allocate_and_encrypt_new_lwe_ciphertext(
+                &lwe_sk_after_ks,
+                Plaintext(msg as Scalar * delta),
+                lwe_modular_std_dev,
+                ciphertext_modulus,
+                &mut encryption_generator,
+            );
+
+            let fourier_ggsw = programmable_circuit_bootstrap_lwe_ciphertext_by_trace_with_preprocessing(
+                lwe.as_view(),
+                bsk,
+                &auto_keys,
+                ss_key,
+                &cbs_acc,
+            );
+
+            let mut out = GlweCiphertext::new(Scalar::ZERO, glwe_size, polynomial_size, ciphertext_modulus);
+            add_external_product_assign(&mut out, &fourier_ggsw, &glwe_one);
+
+            let mut dec = PlaintextList::new(Scalar::ZERO, PlaintextCount(polynomial_size.0));
+            decrypt_glwe_ciphertext(&glwe_sk, &out, &mut dec);
+
+            let correct_val = (f(msg) as Scalar) << log_scale;
+            let decrypted = *dec.get(0).0;
+            let rounded = decrypted.wrapping_add(Scalar::ONE << (log_scale - 1)) >> log_scale;
+            if rounded != f(msg) as Scalar {
+                num_fail += 1;
+            }
+
+            let abs_err = std::cmp::min(decrypted.wrapping_sub(correct_val), correct_val.wrapping_sub(decrypted));
+            max_err = std::cmp::max(max_err, abs_err);
+        }
+
+        println!(
+            "f(m) = {}: {} / {} failures, ext prod err: (Max) {:.2} bits",
+            name, num_fail, message_modulus, (max_err as f64).log2(),
+        );
+    }
+}
@@ -1,7 +1,7 @@
 use rand::Rng;
 use tfhe::core_crypto::prelude::*;
 use patching_wwlp::{
-    automorphism::gen_all_auto_keys, byte_array_to_mat, generate_scheme_switching_key, get_he_state_error, he_add_round_key, he_mix_columns, he_shift_rows, he_sub_bytes_by_patched_wwlp_cbs, keygen_pbs_with_glwe_ds, keyswitch_lwe_ciphertext_by_glwe_keyswitch, Aes128Ref, aes_params::*, aes_instances::*, BLOCKSIZE_IN_BIT, BLOCKSIZE_IN_BYTE, BYTESIZE, NUM_ROUNDS
+    automorphism::gen_all_auto_keys, byte_array_to_mat, generate_scheme_switching_key, get_he_state_error, he_add_round_key, he_mix_columns, he_shift_rows, he_sub_bytes_by_patched_wwlp_cbs, keygen_pbs_with_glwe_ds, keyswitch_lwe_ciphertext_by_glwe_keyswitch, AesRef, CbsAccumulator, aes_params::*, aes_instances::*, BLOCKSIZE_IN_BIT, BLOCKSIZE_IN_BYTE, BYTESIZE, NUM_ROUNDS
 };
 
 fn main() {
@@ -88,6 +88,8 @@ B_ss: 2^{}, l_ss: {}, log_lut_count: {}\n",
     );
     let ss_key = ss_key.as_view();
 
+    let cbs_acc = CbsAccumulator::new(polynomial_size, glwe_size, cbs_base_log, cbs_level, log_lut_count);
+
     let auto_keys = gen_all_auto_keys(
         auto_base_log,
         auto_level,
@@ -109,7 +111,7 @@ B_ss: 2^{}, l_ss: {}, log_lut_count: {}\n",
         key[i] = rng.gen_range(0..=u8::MAX);
     }
 
-    let aes = Aes128Ref::new(&key);
+    let aes = AesRef::new(&key);
     let round_keys = aes.get_round_keys();
 
     let mut message = [0u8; BLOCKSIZE_IN_BYTE];
@@ -190,9 +192,7 @@ B_ss: 2^{}, l_ss: {}, log_lut_count: {}\n",
             fourier_bsk,
             &auto_keys,
             ss_key,
-            cbs_base_log,
-            cbs_level,
-            log_lut_count,
+            &cbs_acc,
         );
 
         aes.sub_bytes(&mut state);
@@ -238,9 +238,7 @@ B_ss: 2^{}, l_ss: {}, log_lut_count: {}\n",
         fourier_bsk,
         &auto_keys,
         ss_key,
-        cbs_base_log,
-        cbs_level,
-        log_lut_count,
+        &cbs_acc,
     );
 
     aes.sub_bytes(&mut state);
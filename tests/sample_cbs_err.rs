@@ -1,6 +1,6 @@
 use rand::Rng;
 use tfhe::core_crypto::prelude::*;
-use patching_wwlp::{allocate_and_generate_new_glwe_keyswitch_key, convert_lwe_to_glwe_by_trace_with_preprocessing, convert_lwe_to_glwe_by_trace_with_preprocessing_high_prec, convert_standard_glwe_keyswitch_key_to_fourier, gen_all_auto_keys, generate_scheme_switching_key, get_glwe_l2_err, get_glwe_max_err, keygen_pbs, lwe_msb_bit_to_lev, switch_scheme, FourierGlweKeyswitchKey, HighPrecWWLpCBSParam, WWLpCBSParam, wwlp_cbs_instance::*};
+use patching_wwlp::{allocate_and_generate_new_glwe_keyswitch_key, convert_lwe_to_glwe_by_trace_with_preprocessing, convert_standard_glwe_keyswitch_key_to_fourier, gen_all_auto_keys, generate_scheme_switching_key, get_glwe_l2_err, get_glwe_max_err, keygen_pbs, lwe_msb_bit_to_glev_by_trace, lwe_msb_bit_to_glev_by_trace_with_preprocessing_high_prec, lwe_msb_bit_to_lev, switch_scheme, CbsAccumulator, FourierGlweKeyswitchKey, FourierGlweKeyswitchKeyOwned, HighPrecWWLpCBSParam, WWLpCBSParam, WWLpCbsMode, wwlp_cbs_instance::*, F128};
 
 type Scalar = u64;
 const NUM_REPEAT: usize = 1000;
@@ -140,6 +140,15 @@ B_auto: 2^{}, l_auto: {}, fft_type: {:?}, B_ss: 2^{}, l_ss: {}\n",
     let mut wwlp_ep_l_infty_err_list = vec![];
     let mut wwlp_ep_l2_err_list = vec![];
 
+    let mut orig_wwlp_cbs_l_infty_err_list = vec![];
+    let mut orig_wwlp_cbs_l2_err_list = vec![];
+    let mut orig_wwlp_ep_l_infty_err_list = vec![];
+    let mut orig_wwlp_ep_l2_err_list = vec![];
+
+    let cbs_acc = CbsAccumulator::new(polynomial_size, glwe_size, cbs_base_log, cbs_level, LutCountLog(0));
+    let wwlp_cbs_acc = CbsAccumulator::new(polynomial_size, glwe_size, cbs_base_log, cbs_level, LutCountLog(3));
+    let orig_wwlp_cbs_acc = CbsAccumulator::new_original_wwlp(polynomial_size, glwe_size, cbs_base_log, cbs_level, LutCountLog(3));
+
     let mut rng = rand::thread_rng();
 
     for _ in 0..num_repeat {
@@ -155,7 +164,7 @@ B_auto: 2^{}, l_auto: {}, fft_type: {:?}, B_ss: 2^{}, l_ss: {}\n",
 
         /* Original CBS */
         let mut lev = LweCiphertextList::new(Scalar::ZERO, lwe_sk.lwe_dimension().to_lwe_size(), LweCiphertextCount(cbs_level.0), ciphertext_modulus);
-        lwe_msb_bit_to_lev(&lwe, &mut lev, bsk, cbs_base_log, cbs_level, LutCountLog(0));
+        lwe_msb_bit_to_lev(&lwe, &mut lev, bsk, &cbs_acc);
 
         let mut max_l_infty_err = Scalar::ZERO;
         let mut max_l2_err = f64::default();
@@ -230,7 +239,7 @@ B_auto: 2^{}, l_auto: {}, fft_type: {:?}, B_ss: 2^{}, l_ss: {}\n",
 
         /* WWL+ CBS */
         let mut lev = LweCiphertextList::new(Scalar::ZERO, lwe_sk.lwe_dimension().to_lwe_size(), LweCiphertextCount(cbs_level.0), ciphertext_modulus);
-        lwe_msb_bit_to_lev(&lwe, &mut lev, bsk, cbs_base_log, cbs_level, LutCountLog(3));
+        lwe_msb_bit_to_lev(&lwe, &mut lev, bsk, &wwlp_cbs_acc);
 
         let mut glev = GlweCiphertextList::new(Scalar::ZERO, glwe_size, polynomial_size, GlweCiphertextCount(cbs_level.0), ciphertext_modulus);
         for (lwe, mut glwe) in lev.iter().zip(glev.iter_mut()) {
@@ -296,6 +305,72 @@ B_auto: 2^{}, l_auto: {}, fft_type: {:?}, B_ss: 2^{}, l_ss: {}\n",
 
         wwlp_ep_l_infty_err_list.push(ep_max_err);
         wwlp_ep_l2_err_list.push(ep_l2_err);
+
+
+        /* Original WWL+ CBS */
+        let mut glev = GlweCiphertextList::new(Scalar::ZERO, glwe_size, polynomial_size, GlweCiphertextCount(cbs_level.0), ciphertext_modulus);
+        let glev_mut_view = GlweCiphertextListMutView::from_container(glev.as_mut(), glwe_size, polynomial_size, ciphertext_modulus);
+        lwe_msb_bit_to_glev_by_trace(lwe.as_view(), glev_mut_view, bsk, &auto_keys, &orig_wwlp_cbs_acc, WWLpCbsMode::Original);
+
+        let mut ggsw = GgswCiphertext::new(Scalar::ZERO, glwe_size, polynomial_size, cbs_base_log, cbs_level, ciphertext_modulus);
+        switch_scheme(&glev, &mut ggsw, ss_key);
+
+        let mut max_l_infty_err = Scalar::ZERO;
+        let mut max_l2_err = f64::default();
+
+        for (k, ggsw_level_matrix) in ggsw.iter().enumerate() {
+            let level = k + 1;
+            let log_scale = Scalar::BITS as usize - level * cbs_base_log.0;
+
+            for (i, glwe) in ggsw_level_matrix.as_glwe_list().iter().enumerate() {
+                let correct_val_list = if msg == 0 {
+                    PlaintextList::new(Scalar::ZERO, PlaintextCount(polynomial_size.0))
+                } else {
+                    if i < glwe_dimension.0 {
+                        let sk_i = glwe_sk_poly_list.get(i);
+                        PlaintextList::from_container((0..polynomial_size.0).map(|j| {
+                            let val = *sk_i.as_ref().get(j).unwrap();
+                            val.wrapping_neg() << log_scale
+                        }).collect::<Vec<Scalar>>())
+                    } else {
+                        PlaintextList::from_container((0..polynomial_size.0).map(|j| {
+                            if j == 0 {Scalar::ONE << log_scale} else {Scalar::ZERO}
+                        }).collect::<Vec<Scalar>>())
+                    }
+                };
+
+                let l_infty_err = get_glwe_max_err(&glwe_sk, &glwe, &correct_val_list);
+                let l2_err = get_glwe_l2_err(&glwe_sk, &glwe, &correct_val_list);
+
+                max_l_infty_err = std::cmp::max(max_l_infty_err, l_infty_err);
+                max_l2_err = if max_l2_err < l2_err {l2_err} else {max_l2_err};
+            }
+        }
+
+        orig_wwlp_cbs_l_infty_err_list.push(max_l_infty_err);
+        orig_wwlp_cbs_l2_err_list.push(max_l2_err);
+
+        let mut fourier_ggsw = FourierGgswCiphertext::new(glwe_size, polynomial_size, cbs_base_log, cbs_level);
+        convert_standard_ggsw_ciphertext_to_fourier(&ggsw, &mut fourier_ggsw);
+
+        let pt = PlaintextList::new(Scalar::ZERO, PlaintextCount(polynomial_size.0));
+        let mut glwe = GlweCiphertext::new(Scalar::ZERO, glwe_size, polynomial_size, ciphertext_modulus);
+        encrypt_glwe_ciphertext(
+            &glwe_sk,
+            &mut glwe,
+            &pt,
+            glwe_modular_std_dev,
+            &mut encryption_generator,
+        );
+        let mut out = GlweCiphertext::new(Scalar::ZERO, glwe_size, polynomial_size, ciphertext_modulus);
+
+        add_external_product_assign(&mut out, &fourier_ggsw, &glwe);
+
+        let ep_max_err = get_glwe_max_err(&glwe_sk, &out, &pt);
+        let ep_l2_err = get_glwe_l2_err(&glwe_sk, &out, &pt);
+
+        orig_wwlp_ep_l_infty_err_list.push(ep_max_err);
+        orig_wwlp_ep_l2_err_list.push(ep_l2_err);
     }
 
     println!("Original cbs error");
@@ -383,6 +458,49 @@ B_auto: 2^{}, l_auto: {}, fft_type: {:?}, B_ss: 2^{}, l_ss: {}\n",
     let avg_err = (avg_err as f64) / num_repeat as f64;
     let max_err = max_err as f64;
     println!("-       l2 norm: (Avg) {:.2} bits (Max) {:.2} bits\n", avg_err.log2(), max_err.log2());
+
+    println!("Original WWL+ cbs error");
+    println!("CBS output error");
+    let mut avg_err = Scalar::ZERO;
+    let mut max_err = Scalar::ZERO;
+    for err in orig_wwlp_cbs_l_infty_err_list.iter() {
+        avg_err += err;
+        max_err = std::cmp::max(max_err, *err);
+    }
+    let avg_err = (avg_err as f64) / num_repeat as f64;
+    let max_err = max_err as f64;
+    println!("- infinity norm: (Avg) {:.2} bits (Max) {:.2} bits", avg_err.log2(), max_err.log2());
+
+    let mut avg_err = 0f64;
+    let mut max_err = 0f64;
+    for err in orig_wwlp_cbs_l2_err_list.iter() {
+        avg_err += err;
+        max_err = if max_err < *err {*err} else {max_err};
+    }
+    let avg_err = (avg_err as f64) / num_repeat as f64;
+    let max_err = max_err as f64;
+    println!("-       l2 norm: (Avg) {:.2} bits (Max) {:.2} bits\n", avg_err.log2(), max_err.log2());
+
+    println!("External product error");
+    let mut avg_err = Scalar::ZERO;
+    let mut max_err = Scalar::ZERO;
+    for err in orig_wwlp_ep_l_infty_err_list.iter() {
+        avg_err += err;
+        max_err = std::cmp::max(max_err, *err);
+    }
+    let avg_err = (avg_err as f64) / num_repeat as f64;
+    let max_err = max_err as f64;
+    println!("- infinity norm: (Avg) {:.2} bits (Max) {:.2} bits", avg_err.log2(), max_err.log2());
+
+    let mut avg_err = 0f64;
+    let mut max_err = 0f64;
+    for err in orig_wwlp_ep_l2_err_list.iter() {
+        avg_err += err;
+        max_err = if max_err < *err {*err} else {max_err};
+    }
+    let avg_err = (avg_err as f64) / num_repeat as f64;
+    let max_err = max_err as f64;
+    println!("-       l2 norm: (Avg) {:.2} bits (Max) {:.2} bits\n", avg_err.log2(), max_err.log2());
 }
 
 
@@ -488,7 +606,7 @@ B_ss: 2^{}, l_ss: {}\n",
         ciphertext_modulus,
         &mut encryption_generator,
     );
-    let mut fourier_glwe_dsk_to_large = FourierGlweKeyswitchKey::new(
+    let mut fourier_glwe_dsk_to_large: FourierGlweKeyswitchKeyOwned<F128> = FourierGlweKeyswitchKey::new(
         glwe_size,
         large_glwe_size,
         polynomial_size,
@@ -507,7 +625,7 @@ B_ss: 2^{}, l_ss: {}\n",
         ciphertext_modulus,
         &mut encryption_generator,
     );
-    let mut fourier_glwe_dsk_from_large = FourierGlweKeyswitchKey::new(
+    let mut fourier_glwe_dsk_from_large: FourierGlweKeyswitchKeyOwned<F128> = FourierGlweKeyswitchKey::new(
         large_glwe_size,
         glwe_size,
         polynomial_size,
@@ -546,6 +664,9 @@ B_ss: 2^{}, l_ss: {}\n",
     let mut wwlp_ep_l_infty_err_list = vec![];
     let mut wwlp_ep_l2_err_list = vec![];
 
+    let cbs_acc = CbsAccumulator::new(polynomial_size, glwe_size, cbs_base_log, cbs_level, LutCountLog(0));
+    let wwlp_cbs_acc = CbsAccumulator::new(polynomial_size, glwe_size, cbs_base_log, cbs_level, LutCountLog(3));
+
     let mut rng = rand::thread_rng();
 
     for _ in 0..num_repeat {
@@ -561,7 +682,7 @@ B_ss: 2^{}, l_ss: {}\n",
 
         /* Original CBS */
         let mut lev = LweCiphertextList::new(Scalar::ZERO, lwe_sk.lwe_dimension().to_lwe_size(), LweCiphertextCount(cbs_level.0), ciphertext_modulus);
-        lwe_msb_bit_to_lev(&lwe, &mut lev, bsk, cbs_base_log, cbs_level, LutCountLog(0));
+        lwe_msb_bit_to_lev(&lwe, &mut lev, bsk, &cbs_acc);
 
         let mut max_l_infty_err = Scalar::ZERO;
         let mut max_l2_err = f64::default();
@@ -635,13 +756,9 @@ B_ss: 2^{}, l_ss: {}\n",
 
 
         /* High Prec WWL+ CBS */
-        let mut lev = LweCiphertextList::new(Scalar::ZERO, lwe_sk.lwe_dimension().to_lwe_size(), LweCiphertextCount(cbs_level.0), ciphertext_modulus);
-        lwe_msb_bit_to_lev(&lwe, &mut lev, bsk, cbs_base_log, cbs_level, LutCountLog(3));
-
         let mut glev = GlweCiphertextList::new(Scalar::ZERO, glwe_size, polynomial_size, GlweCiphertextCount(cbs_level.0), ciphertext_modulus);
-        for (lwe, mut glwe) in lev.iter().zip(glev.iter_mut()) {
-            convert_lwe_to_glwe_by_trace_with_preprocessing_high_prec(&lwe, &mut glwe, &fourier_glwe_dsk_to_large, &fourier_glwe_dsk_from_large, &auto_keys);
-        }
+        let glev_mut_view = GlweCiphertextListMutView::from_container(glev.as_mut(), glwe_size, polynomial_size, ciphertext_modulus);
+        lwe_msb_bit_to_glev_by_trace_with_preprocessing_high_prec(lwe.as_view(), glev_mut_view, bsk, &fourier_glwe_dsk_to_large, &fourier_glwe_dsk_from_large, &auto_keys, &wwlp_cbs_acc);
 
         let mut ggsw = GgswCiphertext::new(Scalar::ZERO, glwe_size, polynomial_size, cbs_base_log, cbs_level, ciphertext_modulus);
         switch_scheme(&glev, &mut ggsw, ss_key);
@@ -1,4 +1,4 @@
-use patching_wwlp::{allocate_and_generate_new_glwe_keyswitch_key, convert_lwe_to_glwe_by_trace_with_preprocessing, convert_lwe_to_glwe_by_trace_with_preprocessing_high_prec, convert_standard_glwe_keyswitch_key_to_fourier, gen_all_auto_keys, generate_scheme_switching_key, get_glwe_l2_err, get_glwe_max_err, switch_scheme, wwlp_cbs_instance::*, FftType, FourierGlweKeyswitchKey};
+use patching_wwlp::{allocate_and_generate_new_glwe_keyswitch_key, convert_lwe_to_glwe_by_trace_with_preprocessing, convert_lwe_to_glwe_by_trace_with_preprocessing_high_prec, convert_standard_glwe_keyswitch_key_to_fourier, gen_all_auto_keys, generate_scheme_switching_key, get_glwe_l2_err, get_glwe_max_err, switch_scheme, wwlp_cbs_instance::*, FftType, F128, FourierGlweKeyswitchKey, FourierGlweKeyswitchKeyOwned};
 use rand::Rng;
 use tfhe::core_crypto::prelude::*;
 
@@ -293,7 +293,7 @@ B_ss: 2^{}, l_ss: {}",
         ciphertext_modulus,
         &mut encryption_generator,
     );
-    let mut fourier_glwe_dsk_to_large = FourierGlweKeyswitchKey::new(
+    let mut fourier_glwe_dsk_to_large: FourierGlweKeyswitchKeyOwned<F128> = FourierGlweKeyswitchKey::new(
         glwe_size,
         large_glwe_size,
         polynomial_size,
@@ -312,7 +312,7 @@ B_ss: 2^{}, l_ss: {}",
         ciphertext_modulus,
         &mut encryption_generator,
     );
-    let mut fourier_glwe_dsk_from_large = FourierGlweKeyswitchKey::new(
+    let mut fourier_glwe_dsk_from_large: FourierGlweKeyswitchKeyOwned<F128> = FourierGlweKeyswitchKey::new(
         large_glwe_size,
         glwe_size,
         polynomial_size,
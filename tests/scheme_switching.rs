@@ -1,8 +1,5 @@
-use patching_wwlp::{gen_all_auto_keys, generate_scheme_switching_key, get_glwe_l2_err, get_glwe_max_err, switch_scheme, trace_assign, FftType};
-use tfhe::core_crypto::{
-    prelude::*,
-    fft_impl::fft64::crypto::ggsw::FourierGgswCiphertextListView,
-};
+use patching_wwlp::{gen_all_auto_keys, generate_scheme_switching_key, generate_scheme_switching_key_f128, get_glwe_l2_err, get_glwe_max_err, switch_scheme, trace_assign, FftBackend, FftType, FourierSchemeSwitchingKeyView};
+use tfhe::core_crypto::prelude::*;
 
 type Scalar = u64;
 
@@ -127,17 +124,30 @@ fn main() {
     }
 
     test_scheme_switching_err(&glwe_sk, &glev, ggsw_base_log, ggsw_level, ss_key, &correct_val_list, &correct_val_list);
+
+    println!("\n\n-------- GLWE input with trace error, f128 scheme switching key --------");
+    let ss_key_fft128 = generate_scheme_switching_key_f128(
+        &glwe_sk,
+        ss_base_log,
+        ss_level,
+        glwe_modular_std_dev,
+        ciphertext_modulus,
+        &mut encryption_generator,
+    );
+
+    test_scheme_switching_err(&glwe_sk, &glev, ggsw_base_log, ggsw_level, ss_key_fft128.as_view(), &correct_val_list, &correct_val_list);
 }
 
-fn test_scheme_switching_err<KeyCont, InputCont, BeforeCont, AfterCont>(
+fn test_scheme_switching_err<F, KeyCont, InputCont, BeforeCont, AfterCont>(
     glwe_secret_key: &GlweSecretKey<KeyCont>,
     glev: &GlweCiphertextList<InputCont>,
     ggsw_base_log: DecompositionBaseLog,
     ggsw_level: DecompositionLevelCount,
-    ss_key: FourierGgswCiphertextListView,
+    ss_key: FourierSchemeSwitchingKeyView<F>,
     correct_val_before: &PlaintextList<BeforeCont>,
     correct_val_after: &PlaintextList<AfterCont>,
 ) where
+    F: FftBackend,
     KeyCont: Container<Element = Scalar>,
     InputCont: Container<Element = Scalar>,
     BeforeCont: Container<Element = Scalar>,
@@ -0,0 +1,58 @@
+use tfhe::core_crypto::prelude::*;
+use patching_wwlp::{aes_instances::*, auto_conv_instance::*, wwlp_cbs_instance::*, security::*, AutoConvParam, FftType, ParamViolation};
+
+fn main() {
+    println!("-------- Estimated security (threshold: {DEFAULT_MIN_SECURITY_BITS} bits) --------");
+    for (name, report) in [
+        ("CBS_WOPBS_2_2", CBS_WOPBS_2_2.security()),
+        ("CBS_WOPBS_3_3", CBS_WOPBS_3_3.security()),
+        ("CBS_WOPBS_4_4", CBS_WOPBS_4_4.security()),
+        ("WWLP_CBS_WOPBS_2_2", WWLP_CBS_WOPBS_2_2.security()),
+        ("HIGHPREC_WWLP_CBS_WOPBS_3_3", HIGHPREC_WWLP_CBS_WOPBS_3_3.security()),
+        ("HIGHPREC_WWLP_CBS_WOPBS_4_4", HIGHPREC_WWLP_CBS_WOPBS_4_4.security()),
+        ("AES_SET_1", AES_SET_1.security()),
+        ("AES_SET_2", AES_SET_2.security()),
+        ("AES_SET_3", AES_SET_3.security()),
+        ("PKSK_PARAM_2_2", PKSK_PARAM_2_2.security()),
+        ("AUTO_PARAM_2_2_VANILLA", AUTO_PARAM_2_2_VANILLA.security()),
+        ("AUTO_PARAM_2_2_LEV_3", AUTO_PARAM_2_2_LEV_3.security()),
+        ("PKSK_PARAM_3_3", PKSK_PARAM_3_3.security()),
+        ("AUTO_PARAM_3_3_LEV_4", AUTO_PARAM_3_3_LEV_4.security()),
+        ("AUTO_PARAM_3_3_LEV_5", AUTO_PARAM_3_3_LEV_5.security()),
+        ("PKSK_PARAM_4_4", PKSK_PARAM_4_4.security()),
+        ("AUTO_PARAM_4_4_LEV_3", AUTO_PARAM_4_4_LEV_3.security()),
+        ("AUTO_PARAM_4_4_LEV_4", AUTO_PARAM_4_4_LEV_4.security()),
+    ] {
+        println!("{name}: {report}");
+        assert!(report.bits() >= DEFAULT_MIN_SECURITY_BITS as f64, "{name} is below the security threshold");
+    }
+
+    // Regression values of the estimator
+    assert_bits(CBS_WOPBS_2_2.security().parts(), "lwe", 132.7);
+    assert_bits(CBS_WOPBS_2_2.security().parts(), "glwe", 129.7);
+    assert_bits(AES_SET_1.security().parts(), "lwe", 137.6);
+    assert_bits(HIGHPREC_WWLP_CBS_WOPBS_3_3.security().parts(), "large glwe", 230.9);
+
+    println!("\n-------- Insecure parameters --------");
+    let toy_param = AutoConvParam::<u64>::new_unchecked(
+        PolynomialSize(512),
+        GlweDimension(1),
+        StandardDev(0.00000000000000029403601535432533),
+        DecompositionBaseLog(12),
+        DecompositionLevelCount(3),
+        FftType::Vanilla,
+        CiphertextModulus::<u64>::new_native(),
+    );
+    println!("{}", toy_param.security());
+    assert_bits(toy_param.security().parts(), "glwe", 43.4);
+
+    let err = toy_param.validate().expect_err("insecure parameters were accepted");
+    println!("[default] rejected: {err}");
+    assert!(err.violations().iter().all(|v| matches!(v, ParamViolation::InsufficientSecurity { min_bits: DEFAULT_MIN_SECURITY_BITS, .. })));
+    assert!(toy_param.validate_with_min_security_bits(0).is_ok());
+}
+
+fn assert_bits(parts: &[(&'static str, LweSecurity)], name: &str, expected_bits: f64) {
+    let (_, security) = parts.iter().find(|(part_name, _)| *part_name == name).unwrap();
+    assert!((security.bits() - expected_bits).abs() < 0.1, "{name}: {:.1} bits, expected {expected_bits}", security.bits());
+}
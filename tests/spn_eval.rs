@@ -0,0 +1,121 @@
+use std::time::{Duration, Instant};
+
+use rand::Rng;
+use tfhe::core_crypto::{
+    prelude::*,
+    commons::math::random::Seed,
+};
+use patching_wwlp::{spn_ciphers::*, spn_he::*, spn_ref::*, aes_instances::*, AesClientKey, AesParam};
+
+fn main() {
+    let param = *AES_SET_2;
+    test_spn_eval(param, "PRESENT-80", &Present80::new(), PRESENT80_KEY_LEN_IN_BYTE);
+    println!();
+    test_spn_eval(param, "SKINNY-64-64", &Skinny64_64::new(), SKINNY64_64_KEY_LEN_IN_BYTE);
+    println!();
+    let lowmc = LowMc::new_128_128_10_20();
+    test_spn_eval(param, "LowMC-128-128-10-20", &lowmc, lowmc.key_size() / 8);
+}
+
+fn test_spn_eval<C: SpnCipher>(param: AesParam<u64>, name: &str, cipher: &C, key_len_in_byte: usize) {
+    println!(
+"==== {name} evaluation by patched WWL+ circuit bootstrapping ====
+n: {}, N: {}, k: {}, l_glwe_ds: {}, B_glwe_ds: 2^{}
+l_pbs: {}, B_pbs: 2^{}, l_ggsw: {}, B_ggsw: 2^{}, LutCount: 2^{},
+l_auto: {}, B_auto: 2^{}, l_ss: {}, B_ss: 2^{}\n",
+        param.lwe_dimension().0, param.polynomial_size().0, param.glwe_dimension().0, param.glwe_ds_level().0, param.glwe_ds_base_log().0,
+        param.pbs_level().0, param.pbs_base_log().0, param.cbs_level().0, param.cbs_base_log().0, param.log_lut_count().0,
+        param.auto_level().0, param.auto_base_log().0, param.ss_level().0, param.ss_base_log().0,
+    );
+
+    // Set random generators
+    let mut boxed_seeder = new_seeder();
+    let seeder = boxed_seeder.as_mut();
+    let mut encryption_generator = EncryptionRandomGenerator::<ActivatedRandomGenerator>::new(seeder.seed(), seeder);
+
+    // Generate keys
+    let mut rng = rand::thread_rng();
+    let (client_key, server_key) = AesClientKey::generate(&param, Seed(rng.gen::<u128>()));
+    let lwe_sk = client_key.lwe_secret_key();
+    let lwe_sk_after_ks = client_key.lwe_secret_key_after_ks();
+
+    // ======== Plain ========
+    let key: Vec<u8> = (0..key_len_in_byte).map(|_| rng.gen_range(0..=u8::MAX)).collect();
+    let message: Vec<u8> = (0..cipher.block_size() / 8).map(|_| rng.gen_range(0..=u8::MAX)).collect();
+    let round_keys = cipher.round_keys(&key);
+    let correct_output = spn_encrypt_block(cipher, &key, &message);
+
+    // ======== HE ========
+    let he_round_keys = encrypt_spn_round_keys(
+        lwe_sk,
+        cipher,
+        &key,
+        param.glwe_modular_std_dev(),
+        param.ciphertext_modulus(),
+        &mut encryption_generator,
+    );
+    let he_spn = HeSpn::new(&server_key, cipher, he_round_keys);
+
+    let mut state = cipher.block_to_bits(&message);
+    let mut he_state = trivially_encrypt_spn_bits(&state, lwe_sk.lwe_dimension().to_lwe_size(), param.ciphertext_modulus());
+
+    let mut time_lwe_ks = Duration::ZERO;
+    let mut time_sbox = Duration::ZERO;
+    let mut time_linear = Duration::ZERO;
+    let mut max_err_ks = 0u64;
+    let mut max_err_sbox = 0u64;
+    let mut max_err_linear = 0u64;
+
+    println!("---- Error (bits) ----");
+    let mut now = Instant::now();
+    he_spn.encrypt_block_with_hook(&mut he_state, |r, step, he_state| {
+        let elapsed = now.elapsed();
+        match step {
+            HeSpnStep::LweKeyswitch => {
+                time_lwe_ks += elapsed;
+                max_err_ks = max_err_ks.max(get_he_spn_state_error(&he_state, &state, lwe_sk_after_ks));
+            }
+            HeSpnStep::SubstitutionLayer => {
+                time_sbox += elapsed;
+                state = spn_sbox_layer(cipher, &state);
+                max_err_sbox = max_err_sbox.max(get_he_spn_state_error(&he_state, &state, lwe_sk));
+            }
+            HeSpnStep::LinearLayer => {
+                time_linear += elapsed;
+                state = spn_linear_layer(cipher, r - 1, &state);
+            }
+            HeSpnStep::AddRoundConstant => {
+                time_linear += elapsed;
+                state.iter_mut().zip(cipher.round_constant(r - 1)).for_each(|(b, c)| *b ^= c);
+            }
+            HeSpnStep::AddRoundKey => {
+                time_linear += elapsed;
+                state.iter_mut().zip(round_keys[r].iter()).for_each(|(b, k)| *b ^= k);
+                if r > 0 {
+                    max_err_linear = max_err_linear.max(get_he_spn_state_error(&he_state, &state, lwe_sk));
+                }
+            }
+        }
+        now = Instant::now();
+    });
+    println!("LWE ks : {:.2}", (max_err_ks as f64).log2());
+    println!("S-box  : {:.2}", (max_err_sbox as f64).log2());
+    println!("Linear : {:.2}", (max_err_linear as f64).log2());
+
+    let max_err = get_he_spn_state_error(&he_state, &cipher.block_to_bits(&correct_output), lwe_sk);
+    println!("max: {:.2}", (max_err as f64).log2());
+
+    let output = cipher.bits_to_block(&decrypt_spn_bits(lwe_sk, &he_state));
+    println!("Output  : {output:>2x?}");
+    println!("Correct : {correct_output:>2x?}");
+    if output != correct_output {
+        println!("Decryption failure!");
+    }
+
+    // Evaluation Time
+    println!("\n---- Evaluation Time ----");
+    println!("LWE KS : {} s", time_lwe_ks.as_millis() as f64 / 1000f64);
+    println!("S-box  : {} s", time_sbox.as_millis() as f64 / 1000f64);
+    println!("Linear : {} s", time_linear.as_millis() as f64 / 1000f64);
+    println!("Total  : {} s", (time_lwe_ks + time_sbox + time_linear).as_millis() as f64 / 1000f64);
+}
@@ -0,0 +1,52 @@
+use patching_wwlp::{spn_ref::*, spn_ciphers::*};
+
+fn main() {
+    println!("======== PRESENT-80 ========");
+    let present = Present80::new();
+    let success = test_spn_kat(&present, &[
+        ("00000000000000000000", "0000000000000000", "5579c1387b228445"),
+        ("ffffffffffffffffffff", "0000000000000000", "e72c46c0f5945049"),
+        ("00000000000000000000", "ffffffffffffffff", "a112ffc72f68417b"),
+        ("ffffffffffffffffffff", "ffffffffffffffff", "3333dcd3213210d2"),
+    ]);
+    if !success {
+        return;
+    }
+
+    println!("======== SKINNY-64-64 ========");
+    let skinny = Skinny64_64::new();
+    let success = test_spn_kat(&skinny, &[
+        ("f5269826fc681238", "06034f957724d19d", "bb39dfb2429b8ac7"),
+    ]);
+    if !success {
+        return;
+    }
+
+    // Picnic-L1 test vector
+    println!("======== LowMC-128-128-10-20 ========");
+    let lowmc = LowMc::new_128_128_10_20();
+    test_spn_kat(&lowmc, &[
+        ("80000000000000000000000000000000", "abff0000000000000000000000000000", "0e30720b9f64d5c2a7771c8c238d8f70"),
+    ]);
+}
+
+fn test_spn_kat<C: SpnCipher>(cipher: &C, test_vectors: &[(&str, &str, &str)]) -> bool {
+    for (key, plaintext, ciphertext) in test_vectors.iter() {
+        let output = spn_encrypt_block(cipher, &hex_to_bytes(key), &hex_to_bytes(plaintext));
+        println!("Key      : {key}");
+        println!("Output   : {:>2x?}", output);
+        println!("Correct  : {:>2x?}", hex_to_bytes(ciphertext));
+
+        if output != hex_to_bytes(ciphertext) {
+            println!("\nEncryption failure!");
+            return false;
+        }
+        println!();
+    }
+
+    true
+}
+
+fn hex_to_bytes(hex: &str) -> Vec<u8> {
+    (0..hex.len()).step_by(2).map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap()).collect()
+}
@@ -0,0 +1,108 @@
+use std::time::Instant;
+
+use rand::Rng;
+use tfhe::core_crypto::{
+    prelude::*,
+    commons::math::random::Seed,
+};
+use patching_wwlp::{aes_he::*, spn_he::*, trivium_he::*, trivium_ref::*, aes_instances::*, AesClientKey, AesParam};
+
+fn main() {
+    test_trivium_eval(*AES_SET_2, false, 16);
+    println!();
+    test_trivium_eval(*AES_SET_2, true, 16);
+}
+
+fn test_trivium_eval(param: AesParam<u64>, is_kreyvium: bool, num_bytes: usize) {
+    println!(
+"==== {} evaluation by patched WWL+ circuit bootstrapping ====
+n: {}, N: {}, k: {}, l_glwe_ds: {}, B_glwe_ds: 2^{}
+l_pbs: {}, B_pbs: 2^{}, l_ggsw: {}, B_ggsw: 2^{}, LutCount: 2^{},
+l_auto: {}, B_auto: 2^{}, l_ss: {}, B_ss: 2^{}\n",
+        if is_kreyvium {"Kreyvium"} else {"Trivium"},
+        param.lwe_dimension().0, param.polynomial_size().0, param.glwe_dimension().0, param.glwe_ds_level().0, param.glwe_ds_base_log().0,
+        param.pbs_level().0, param.pbs_base_log().0, param.cbs_level().0, param.cbs_base_log().0, param.log_lut_count().0,
+        param.auto_level().0, param.auto_base_log().0, param.ss_level().0, param.ss_base_log().0,
+    );
+
+    // Set random generators
+    let mut boxed_seeder = new_seeder();
+    let seeder = boxed_seeder.as_mut();
+    let mut encryption_generator = EncryptionRandomGenerator::<ActivatedRandomGenerator>::new(seeder.seed(), seeder);
+
+    // Generate keys
+    let mut rng = rand::thread_rng();
+    let (client_key, server_key) = AesClientKey::generate(&param, Seed(rng.gen::<u128>()));
+    let lwe_sk = client_key.lwe_secret_key();
+
+    // ======== Plain ========
+    // Test vectors of eSTREAM and of the Kreyvium reference implementation
+    let (key, iv, kat_keystream) = if is_kreyvium {
+        ("0053a6f94c9ff24598eb000000000000", "0d74db42a91077de45ac000000000000", "d1f0303482061111")
+    } else {
+        ("0053a6f94c9ff24598eb", "0d74db42a91077de45ac", "f4cd954a717f26a7")
+    };
+    let (key, iv, kat_keystream) = (hex_to_bytes(key), hex_to_bytes(iv), hex_to_bytes(kat_keystream));
+    let plaintext: Vec<u8> = (0..num_bytes).map(|_| rng.gen_range(0..=u8::MAX)).collect();
+
+    let keystream = if is_kreyvium {
+        KreyviumRef::new(&key, &iv).keystream_bits(8 * num_bytes)
+    } else {
+        TriviumRef::new(&key, &iv).keystream_bits(8 * num_bytes)
+    };
+    let ciphertext = plaintext.iter().enumerate()
+        .map(|(b, &byte)| (0..8).fold(byte, |acc, i| acc ^ (keystream[8*b + i] << i)))
+        .collect::<Vec<u8>>();
+
+    // ======== HE ========
+    let he_key = encrypt_bytes(lwe_sk, &key, param.glwe_modular_std_dev(), param.ciphertext_modulus(), &mut encryption_generator);
+
+    println!("---- Initialization ----");
+    let now = Instant::now();
+    let (mut he_trivium, mut he_kreyvium) = if is_kreyvium {
+        (None, Some(HeKreyvium::new(&server_key, &he_key, &iv)))
+    } else {
+        (Some(HeTrivium::new(&server_key, &he_key, &iv)), None)
+    };
+    let time_init = now.elapsed();
+
+    println!("---- Keystream ({} bits) ----", 8 * num_bytes);
+    let now = Instant::now();
+    let he_keystream = match (he_trivium.as_mut(), he_kreyvium.as_mut()) {
+        (Some(he_trivium), _) => he_trivium.keystream(8 * num_bytes),
+        (_, Some(he_kreyvium)) => he_kreyvium.keystream(8 * num_bytes),
+        _ => unreachable!(),
+    };
+    let time_keystream = now.elapsed();
+
+    let max_err = get_he_spn_state_error(&he_keystream, &keystream, lwe_sk);
+    println!("max: {:.2}", (max_err as f64).log2());
+
+    let output = decrypt_spn_bits(lwe_sk, &he_keystream);
+    if output != keystream {
+        println!("Keystream failure!");
+    }
+    let output = decrypt_bytes(lwe_sk, &he_keystream);
+    println!("Keystream: {:>2x?}", &output[..kat_keystream.len()]);
+    println!("Correct  : {kat_keystream:>2x?}");
+    if output[..kat_keystream.len()] != kat_keystream {
+        println!("Test vector failure!");
+    }
+
+    let mut he_plaintext = he_keystream;
+    he_add_public_bits(&mut he_plaintext, &ciphertext.iter().flat_map(|&byte| (0..8).map(move |i| (byte >> i) & 1)).collect::<Vec<u8>>());
+    let output = decrypt_bytes(lwe_sk, &he_plaintext);
+    println!("Output  : {output:>2x?}");
+    println!("Correct : {plaintext:>2x?}");
+    if output != plaintext {
+        println!("Transciphering failure!");
+    }
+
+    println!("\n---- Evaluation Time ----");
+    println!("Init     : {} s", time_init.as_millis() as f64 / 1000f64);
+    println!("Keystream: {} s ({} bits)", time_keystream.as_millis() as f64 / 1000f64, 8 * num_bytes);
+}
+
+fn hex_to_bytes(hex: &str) -> Vec<u8> {
+    (0..hex.len()).step_by(2).map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap()).collect()
+}
@@ -0,0 +1,61 @@
+use patching_wwlp::trivium_ref::*;
+
+fn main() {
+    println!("======== Trivium ========");
+    if !test_trivium_ref() {
+        return;
+    }
+
+    println!("======== Kreyvium ========");
+    test_kreyvium_ref();
+}
+
+/// eSTREAM test vectors, whose keystream bytes are the first 64 bits.
+fn test_trivium_ref() -> bool {
+    for (key, iv, keystream) in [
+        ("80000000000000000000", "00000000000000000000", "38eb86ff730d7a9c"),
+        ("0053a6f94c9ff24598eb", "0d74db42a91077de45ac", "f4cd954a717f26a7"),
+    ] {
+        let output = TriviumRef::new(&hex_to_bytes(key), &hex_to_bytes(iv)).keystream_bytes(8);
+        println!("Key      : {key}");
+        println!("IV       : {iv}");
+        println!("Output   : {output:>2x?}");
+        println!("Correct  : {:>2x?}", hex_to_bytes(keystream));
+
+        if output != hex_to_bytes(keystream) {
+            println!("\nKeystream failure!");
+            return false;
+        }
+        println!();
+    }
+
+    true
+}
+
+/// Test vectors of the Kreyvium reference implementation, whose keystream bytes are the first 64 bits.
+fn test_kreyvium_ref() -> bool {
+    for (key, iv, keystream) in [
+        ("00000000000000000000000000000000", "00000000000000000000000000000000", "26dcf1f4bc0f1922"),
+        ("01000000000000000000000000000000", "00000000000000000000000000000000", "4fd421d4da3d2c8a"),
+        ("00000000000000000000000000000000", "01000000000000000000000000000000", "c9217ba0d762aca1"),
+        ("0053a6f94c9ff24598eb000000000000", "0d74db42a91077de45ac000000000000", "d1f0303482061111"),
+    ] {
+        let output = KreyviumRef::new(&hex_to_bytes(key), &hex_to_bytes(iv)).keystream_bytes(8);
+        println!("Key      : {key}");
+        println!("IV       : {iv}");
+        println!("Output   : {output:>2x?}");
+        println!("Correct  : {:>2x?}", hex_to_bytes(keystream));
+
+        if output != hex_to_bytes(keystream) {
+            println!("\nKeystream failure!");
+            return false;
+        }
+        println!();
+    }
+
+    true
+}
+
+fn hex_to_bytes(hex: &str) -> Vec<u8> {
+    (0..hex.len()).step_by(2).map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap()).collect()
+}